@@ -22,6 +22,12 @@ global_asm!(include_str!(env!("APP_ASM")));
 const USER_STACK_SIZE: usize = 4096;
 // 最大应用数量
 const MAX_APP_NUM: usize = 16;
+// 时间片长度，按 syscall::clock_freq() 换算为 tick
+#[cfg(not(feature = "coop"))]
+const TIMER_SLICE: TimeSpec = TimeSpec {
+    tv_sec: 0,
+    tv_nsec: 1_250_000,
+};
 
 // 用户栈（为每个应用静态分配）
 static mut USER_STACKS: [[u8; USER_STACK_SIZE]; MAX_APP_NUM] = [[0u8; USER_STACK_SIZE]; MAX_APP_NUM];
@@ -131,7 +137,7 @@ extern "C" fn rust_main() -> ! {
         // 7. 设置时间片定时器（非 coop 模式）
         #[cfg(not(feature = "coop"))]
         {
-            sbi_rt::set_timer(time::read64() + 12500);
+            sbi_rt::set_timer(time::read64() + TIMER_SLICE.to_ticks());
         }
 
         // 8. 执行任务
@@ -273,16 +279,8 @@ impl syscall::Scheduling for SyscallContext {
 impl syscall::Clock for SyscallContext {
     fn clock_gettime(&self, _caller: Caller, clock_id: usize, tp: *mut TimeSpec) -> isize {
         if clock_id == ClockId::CLOCK_MONOTONIC.0 {
-            // 获取当前时间
-            let time_val = time::read64();
-            // 假设时钟频率为 10MHz（QEMU 默认）
-            const CLOCK_FREQ: u64 = 10_000_000;
-            let tv_sec = time_val / CLOCK_FREQ;
-            let tv_nsec = (time_val % CLOCK_FREQ) * 1_000_000_000 / CLOCK_FREQ;
-            let timespec = TimeSpec {
-                tv_sec: tv_sec as usize,
-                tv_nsec: tv_nsec as usize,
-            };
+            // 获取当前时间，按 syscall::clock_freq() 换算
+            let timespec = TimeSpec::from_ticks(time::read64());
             unsafe {
                 *tp = timespec;
             }
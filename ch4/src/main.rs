@@ -8,12 +8,12 @@ use core::panic::PanicInfo;
 use core::ptr::NonNull;
 
 use kernel_context::foreign::{ForeignPortal, MultislotPortal};
-use kernel_context::LocalContext;
+use kernel_context::{trap, LocalContext};
 use kernel_vm::page_table::{Pte, Sv39, VAddr, VmFlags, PPN, VPN};
 use kernel_vm::{AddressSpace, PageManager};
 use linker::{AppMeta, KernelLayout, KernelRegionTitle};
 use rcore_console::{init_console, log, print, println, set_log_level, test_log, Console};
-use riscv::register::{scause, satp, stval};
+use riscv::register::{satp, stval};
 use sbi_rt::{legacy, NoReason, Shutdown, SystemFailure};
 use syscall::{
     Caller, ClockId, SyscallId, SyscallResult, TimeSpec, STDDEBUG, STDOUT,
@@ -222,6 +222,8 @@ fn kernel_space(
             KernelRegionTitle::Text => "VRX",
             KernelRegionTitle::Rodata => "VR",
             KernelRegionTitle::Data => "VRW",
+            KernelRegionTitle::Tls => "VRW",
+            KernelRegionTitle::Bss => "VRW",
             KernelRegionTitle::Boot => "VRW",
         };
         let flags = VmFlags::build_from_str(flags_str);
@@ -505,48 +507,43 @@ extern "C" fn rust_main() -> ! {
         satp::write(kernel_satp);
         unsafe { core::arch::asm!("sfence.vma zero, zero"); }
 
-        let trap_cause = scause::read();
-        match trap_cause.cause() {
-            scause::Trap::Exception(scause::Exception::UserEnvCall) => {
-                let id = SyscallId::from(proc.context.a(7));
-                let args = [
-                    proc.context.a(0),
-                    proc.context.a(1),
-                    proc.context.a(2),
-                    proc.context.a(3),
-                    proc.context.a(4),
-                    proc.context.a(5),
-                ];
-                let result = syscall::handle(caller, id, args);
-
-                match result {
+        let keep = trap::dispatch(
+            trap::decode(),
+            &mut proc.context,
+            |ctx| {
+                let id = SyscallId::from(ctx.a(7));
+                let args = [ctx.a(0), ctx.a(1), ctx.a(2), ctx.a(3), ctx.a(4), ctx.a(5)];
+                match syscall::handle(caller, id, args) {
                     SyscallResult::Done(ret) => {
                         if id == SyscallId::EXIT {
-                            processes.remove(current);
+                            false
                         } else {
-                            *proc.context.a_mut(0) = ret as usize;
-                            proc.context.move_next();
+                            *ctx.a_mut(0) = ret as usize;
+                            ctx.move_next();
+                            true
                         }
                     }
                     SyscallResult::Unsupported(_) => {
                         log::error!("Unsupported syscall {:?}", id);
-                        processes.remove(current);
+                        false
                     }
                 }
-            }
-            scause::Trap::Exception(scause::Exception::Breakpoint) => {
-                // Skip the ebreak instruction and continue
-                proc.context.move_next();
-            }
-            _ => {
+            },
+            // 本章没有时钟中断，只有断点会走到这里：继续运行当前进程
+            |_| true,
+            |cause, ctx| {
                 log::error!(
                     "Trap {:?} stval={:#x} pc={:#x}",
-                    trap_cause.cause(),
+                    cause,
                     stval::read(),
-                    proc.context.pc()
+                    ctx.pc()
                 );
-                processes.remove(current);
-            }
+                log::debug!("{ctx:?}");
+                false
+            },
+        );
+        if !keep {
+            processes.remove(current);
         }
         unsafe { CURRENT_SPACE = None };
     }
@@ -629,11 +626,7 @@ impl syscall::Scheduling for SyscallHost {
 impl syscall::Clock for SyscallHost {
     fn clock_gettime(&self, _caller: Caller, clock_id: usize, tp: *mut TimeSpec) -> isize {
         if clock_id == ClockId::CLOCK_MONOTONIC.0 {
-            let time_val = riscv::register::time::read64();
-            const CLOCK_FREQ: u64 = 10_000_000;
-            let tv_sec = (time_val / CLOCK_FREQ) as usize;
-            let tv_nsec = ((time_val % CLOCK_FREQ) * 1_000_000_000 / CLOCK_FREQ) as usize;
-            let spec = TimeSpec { tv_sec, tv_nsec };
+            let spec = TimeSpec::from_ticks(riscv::register::time::read64());
             let space = unsafe { CURRENT_SPACE.and_then(|p| p.as_ref()) };
             if let Some(space) = space {
                 let vaddr = VAddr::<Sv39>::new(tp as usize);
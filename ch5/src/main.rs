@@ -3,19 +3,19 @@
 
 extern crate alloc;
 
-use alloc::collections::{BTreeMap, VecDeque};
 use core::arch::global_asm;
 use core::panic::PanicInfo;
 use core::ptr::NonNull;
 
 use kernel_context::foreign::{ForeignPortal, MultislotPortal};
 use kernel_context::foreign::{ForeignContext, SlotKey};
+use kernel_context::trap;
 use kernel_vm::page_table::{Pte, Sv39, VAddr, VmFlags, PPN, VPN};
 use kernel_vm::{AddressSpace, PageManager};
 use linker::{AppMeta, KernelLayout, KernelRegionTitle};
 use rcore_console::{init_console, log, print, println, set_log_level, test_log, Console};
-use rcore_task_manage::{Manage, PManager, ProcId, Schedule};
-use riscv::register::{scause, satp, stval};
+use rcore_task_manage::{MapScheduler, PManager, ProcId, WaitResult};
+use riscv::register::{satp, stval};
 use sbi_rt::{legacy, NoReason, Shutdown, SystemFailure};
 use syscall::{
     Caller, ClockId, SyscallId, SyscallResult, TimeSpec, STDDEBUG, STDIN, STDOUT,
@@ -201,6 +201,8 @@ fn kernel_space(
             KernelRegionTitle::Text => "VRX",
             KernelRegionTitle::Rodata => "VR",
             KernelRegionTitle::Data => "VRW",
+            KernelRegionTitle::Tls => "VRW",
+            KernelRegionTitle::Bss => "VRW",
             KernelRegionTitle::Boot => "VRW",
         };
         let flags = VmFlags::build_from_str(flags_str);
@@ -238,52 +240,7 @@ impl Process {
     }
 }
 
-struct ProcManager {
-    store: BTreeMap<ProcId, Process>,
-    ready: VecDeque<ProcId>,
-}
-
-impl ProcManager {
-    fn new() -> Self {
-        Self {
-            store: BTreeMap::new(),
-            ready: VecDeque::new(),
-        }
-    }
-}
-
-impl Manage<Process, ProcId> for ProcManager {
-    fn insert(&mut self, id: ProcId, item: Process) {
-        self.store.insert(id, item);
-    }
-
-    fn delete(&mut self, id: ProcId) {
-        self.store.remove(&id);
-    }
-
-    fn get_mut(&mut self, id: ProcId) -> Option<&mut Process> {
-        self.store.get_mut(&id)
-    }
-}
-
-impl Schedule<ProcId> for ProcManager {
-    fn add(&mut self, id: ProcId) {
-        self.ready.push_back(id);
-    }
-
-    fn fetch(&mut self) -> Option<ProcId> {
-        self.ready.pop_front()
-    }
-}
-
-fn alloc_pid_nonzero() -> ProcId {
-    loop {
-        let pid = ProcId::new();
-        if pid.get_usize() != 0 {
-            return pid;
-        }
-    }
-}
+type ProcManager = MapScheduler<Process, ProcId>;
 
 fn app_name_at(app_names: *const u8, index: usize) -> Option<&'static str> {
     let mut ptr = app_names;
@@ -550,7 +507,7 @@ impl syscall::Process for SyscallContext {
         *child_ctx.sp_mut() = parent.context.context.sp();
         child_ctx.move_next();
 
-        let child_pid = alloc_pid_nonzero();
+        let child_pid = ProcId::new_nonreserved();
         *child_ctx.a_mut(0) = 0;
         let child = Process {
             pid: child_pid,
@@ -633,8 +590,8 @@ impl syscall::Process for SyscallContext {
             ProcId::from_usize(pid as usize)
         };
         match processor.wait(child_pid) {
-            Some((pid, -1)) if pid.get_usize() == usize::MAX - 1 => -2,
-            Some((reaped_pid, code)) => {
+            WaitResult::Alive => -2,
+            WaitResult::Exited(reaped_pid, code) => {
                 let space = unsafe { CURRENT_SPACE.and_then(|p| p.as_ref()) };
                 if let (Some(space), Some(ptr)) = (space, NonNull::new(exit_code_ptr)) {
                     let vaddr = VAddr::<Sv39>::new(exit_code_ptr as usize);
@@ -645,7 +602,7 @@ impl syscall::Process for SyscallContext {
                 }
                 reaped_pid.get_usize() as isize
             }
-            None => -1,
+            WaitResult::NoChild => -1,
         }
     }
 
@@ -665,11 +622,7 @@ impl syscall::Scheduling for SyscallContext {
 impl syscall::Clock for SyscallContext {
     fn clock_gettime(&self, caller: Caller, clock_id: usize, tp: *mut TimeSpec) -> isize {
         if clock_id == ClockId::CLOCK_MONOTONIC.0 {
-            let time_val = riscv::register::time::read64();
-            const CLOCK_FREQ: u64 = 10_000_000;
-            let tv_sec = (time_val / CLOCK_FREQ) as usize;
-            let tv_nsec = ((time_val % CLOCK_FREQ) * 1_000_000_000 / CLOCK_FREQ) as usize;
-            let spec = TimeSpec { tv_sec, tv_nsec };
+            let spec = TimeSpec::from_ticks(riscv::register::time::read64());
             let space = unsafe { CURRENT_SPACE.and_then(|p| p.as_ref()) };
             if let Some(space) = space {
                 let vaddr = VAddr::<Sv39>::new(tp as usize);
@@ -834,53 +787,47 @@ extern "C" fn rust_main() -> ! {
         satp::write(kernel_satp);
         unsafe { core::arch::asm!("sfence.vma zero, zero"); }
 
-        let trap_cause = scause::read();
-        match trap_cause.cause() {
-            scause::Trap::Exception(scause::Exception::UserEnvCall) => {
-                let id = SyscallId::from(proc.context.context.a(7));
-                let args = [
-                    proc.context.context.a(0),
-                    proc.context.context.a(1),
-                    proc.context.context.a(2),
-                    proc.context.context.a(3),
-                    proc.context.context.a(4),
-                    proc.context.context.a(5),
-                ];
+        trap::dispatch(
+            trap::decode(),
+            &mut proc.context.context,
+            |ctx| {
+                let id = SyscallId::from(ctx.a(7));
+                let args = [ctx.a(0), ctx.a(1), ctx.a(2), ctx.a(3), ctx.a(4), ctx.a(5)];
                 let result = syscall::handle(caller, id, args);
 
+                let processor = unsafe { PROCESSOR.as_mut().unwrap() };
                 match result {
                     SyscallResult::Done(ret) => {
                         if id == SyscallId::EXIT {
-                            let processor = unsafe { PROCESSOR.as_mut().unwrap() };
                             processor.make_current_exited(ret);
                         } else {
-                            *proc.context.context.a_mut(0) = ret as usize;
-                            proc.context.context.move_next();
-                            let processor = unsafe { PROCESSOR.as_mut().unwrap() };
+                            *ctx.a_mut(0) = ret as usize;
+                            ctx.move_next();
                             processor.make_current_suspend();
                         }
                     }
                     SyscallResult::Unsupported(_) => {
                         log::error!("Unsupported syscall {:?}", id);
-                        let processor = unsafe { PROCESSOR.as_mut().unwrap() };
                         processor.make_current_exited(-2);
                     }
                 }
-            }
-            scause::Trap::Exception(scause::Exception::Breakpoint) => {
-                proc.context.context.move_next();
-            }
-            _ => {
+            },
+            |_| {
+                let processor = unsafe { PROCESSOR.as_mut().unwrap() };
+                processor.make_current_suspend();
+            },
+            |cause, ctx| {
                 log::error!(
                     "Trap {:?} stval={:#x} pc={:#x}",
-                    trap_cause.cause(),
+                    cause,
                     stval::read(),
-                    proc.context.context.pc()
+                    ctx.pc()
                 );
+                log::debug!("{ctx:?}");
                 let processor = unsafe { PROCESSOR.as_mut().unwrap() };
                 processor.make_current_exited(-3);
-            }
-        }
+            },
+        );
         unsafe { CURRENT_SPACE = None };
         unsafe { CURRENT_PID = None };
     }
@@ -4,7 +4,7 @@
 extern crate alloc;
 
 use alloc::alloc::{alloc, alloc_zeroed, dealloc, handle_alloc_error};
-use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::string::String;
 use alloc::sync::Arc;
 use alloc::vec;
@@ -15,18 +15,18 @@ use core::ptr::NonNull;
 
 use easy_fs::{BlockDevice, EasyFileSystem, FSManager, FileHandle, Inode, OpenFlags};
 use kernel_context::foreign::{ForeignContext, MultislotPortal};
+use kernel_context::trap;
 use kernel_vm::page_table::{Pte, Sv39, VAddr, VmFlags, PPN, VPN};
 use kernel_vm::{AddressSpace, PageManager};
 use linker::{KernelLayout, KernelRegionTitle};
 use rcore_console::{init_console, log, print, println, set_log_level, test_log, Console};
-use rcore_task_manage::{Manage, PManager, ProcId, Schedule};
-use riscv::register::{scause, satp, stval};
+use rcore_task_manage::{MapScheduler, PManager, ProcId, WaitResult};
+use riscv::register::{satp, stval};
 use sbi_rt::{legacy, NoReason, Shutdown, SystemFailure};
 use spin::{Lazy, Mutex};
 use syscall::{
     Caller, ClockId, SyscallId, SyscallResult, TimeSpec, STDDEBUG, STDIN, STDOUT,
 };
-use virtio_drivers::{Hal, VirtIOBlk, VirtIOHeader};
 use xmas_elf::header::{Machine, Type as ElfType};
 use xmas_elf::program::Type as ProgramType;
 use xmas_elf::ElfFile;
@@ -216,6 +216,8 @@ fn kernel_space(
             KernelRegionTitle::Text => "VRX",
             KernelRegionTitle::Rodata => "VR",
             KernelRegionTitle::Data => "VRW",
+            KernelRegionTitle::Tls => "VRW",
+            KernelRegionTitle::Bss => "VRW",
             KernelRegionTitle::Boot => "VRW",
         };
         let range = VPN::new(start)..VPN::new(end);
@@ -250,81 +252,29 @@ fn kernel_space(
 
 pub mod virtio_block {
     use super::*;
+    use ::virtio_block::VirtIOBlock;
 
-    struct VirtioHal;
-
-    impl Hal for VirtioHal {
-        fn dma_alloc(pages: usize) -> usize {
-            let layout = core::alloc::Layout::from_size_align(pages * PAGE_SIZE, PAGE_SIZE).unwrap();
-            let ptr = unsafe { alloc_zeroed(layout) };
-            if ptr.is_null() {
-                return 0;
-            }
-            let paddr = Self::virt_to_phys(ptr as usize);
-            if paddr == 0 {
-                unsafe { dealloc(ptr, layout) };
-            }
-            paddr
-        }
-
-        fn dma_dealloc(paddr: usize, pages: usize) -> i32 {
-            let vaddr = Self::phys_to_virt(paddr);
-            if vaddr == 0 {
-                return -1;
-            }
-            let layout = core::alloc::Layout::from_size_align(pages * PAGE_SIZE, PAGE_SIZE).unwrap();
-            unsafe { dealloc(vaddr as *mut u8, layout) };
+    /// 内核地址空间恒等映射，已映射的地址即物理地址，未映射时返回 0
+    fn virt_to_phys(vaddr: usize) -> usize {
+        let space = unsafe { KERNEL_SPACE.as_ref() };
+        let Some(space) = space else {
+            return 0;
+        };
+        let addr = VAddr::<Sv39>::new(vaddr);
+        if space
+            .translate::<u8>(addr, VmFlags::build_from_str("R"))
+            .is_some()
+        {
+            addr.val()
+        } else {
             0
         }
-
-        fn phys_to_virt(paddr: usize) -> usize {
-            paddr
-        }
-
-        fn virt_to_phys(vaddr: usize) -> usize {
-            let space = unsafe { KERNEL_SPACE.as_ref() };
-            let Some(space) = space else {
-                return 0;
-            };
-            let addr = VAddr::<Sv39>::new(vaddr);
-            if space
-                .translate::<u8>(addr, VmFlags::build_from_str("R"))
-                .is_some()
-            {
-                addr.val()
-            } else {
-                0
-            }
-        }
-    }
-
-    struct VirtIOBlock(Mutex<VirtIOBlk<'static, VirtioHal>>);
-
-    impl VirtIOBlock {
-        fn new() -> Self {
-            let header = unsafe { &mut *(VIRTIO0 as *mut VirtIOHeader) };
-            let blk = VirtIOBlk::<VirtioHal>::new(header).expect("failed to init virtio-blk");
-            Self(Mutex::new(blk))
-        }
-    }
-
-    impl BlockDevice for VirtIOBlock {
-        fn read_block(&self, block_id: usize, buf: &mut [u8]) {
-            self.0
-                .lock()
-                .read_block(block_id, buf)
-                .expect("virtio read block failed");
-        }
-
-        fn write_block(&self, block_id: usize, buf: &[u8]) {
-            self.0
-                .lock()
-                .write_block(block_id, buf)
-                .expect("virtio write block failed");
-        }
     }
 
-    pub static BLOCK_DEVICE: Lazy<Arc<dyn BlockDevice>> = Lazy::new(|| Arc::new(VirtIOBlock::new()));
+    pub static BLOCK_DEVICE: Lazy<Arc<dyn BlockDevice>> = Lazy::new(|| {
+        ::virtio_block::init(virt_to_phys);
+        Arc::new(unsafe { VirtIOBlock::new(VIRTIO0) })
+    });
 }
 
 pub mod fs {
@@ -343,31 +293,32 @@ pub mod fs {
     impl FSManager for FileSystem {
         fn open(&self, path: &str, flags: OpenFlags) -> Option<Arc<FileHandle>> {
             let (readable, writable) = flags.read_write();
+            let handle = |inode| {
+                let mut handle = FileHandle::new(readable, writable, inode);
+                handle.set_append(flags.contains(OpenFlags::APPEND));
+                Arc::new(handle)
+            };
 
             if path == "/" || path == "." || path.is_empty() {
-                return Some(Arc::new(FileHandle::new(
-                    readable,
-                    writable,
-                    Arc::clone(&self.root),
-                )));
+                return Some(handle(Arc::clone(&self.root)));
             }
 
             if flags.contains(OpenFlags::CREATE) {
                 if let Some(inode) = self.root.find(path) {
-                    inode.clear();
-                    return Some(Arc::new(FileHandle::new(readable, writable, inode)));
+                    // 追加打开时保留原内容
+                    if !flags.contains(OpenFlags::APPEND) {
+                        inode.clear();
+                    }
+                    return Some(handle(inode));
                 }
-                return self
-                    .root
-                    .create(path)
-                    .map(|inode| Arc::new(FileHandle::new(readable, writable, inode)));
+                return self.root.create(path).map(handle);
             }
 
             self.root.find(path).map(|inode| {
                 if flags.contains(OpenFlags::TRUNC) {
                     inode.clear();
                 }
-                Arc::new(FileHandle::new(readable, writable, inode))
+                handle(inode)
             })
         }
 
@@ -378,12 +329,30 @@ pub mod fs {
             self.root.find(path)
         }
 
-        fn link(&self, _src: &str, _dst: &str) -> isize {
-            -1
+        fn link(&self, src: &str, dst: &str) -> isize {
+            // 只有根目录一层，路径即根目录下的文件名
+            if self.root.link(src, dst) {
+                0
+            } else {
+                -1
+            }
         }
 
-        fn unlink(&self, _path: &str) -> isize {
-            -1
+        fn unlink(&self, path: &str) -> isize {
+            if self.root.unlink(path) {
+                0
+            } else {
+                -1
+            }
+        }
+
+        fn rename(&self, old_path: &str, new_path: &str) -> isize {
+            // 只有根目录一层，跨目录重命名即根目录内重命名
+            if self.root.rename(old_path, new_path) {
+                0
+            } else {
+                -1
+            }
         }
 
         fn readdir(&self, path: &str) -> Option<Vec<String>> {
@@ -425,6 +394,7 @@ fn duplicate_file_handle(file: &FileHandle) -> FileHandle {
         None => FileHandle::empty(file.readable(), file.writable()),
     };
     cloned.offset = file.offset;
+    cloned.set_append(file.append());
     cloned
 }
 
@@ -591,7 +561,7 @@ impl Process {
         let mut child_ctx = self.context.context.clone();
         *child_ctx.a_mut(0) = 0;
 
-        let child_pid = alloc_pid_nonzero();
+        let child_pid = ProcId::new_nonreserved();
 
         Some(Self {
             pid: child_pid,
@@ -644,52 +614,7 @@ impl Process {
     }
 }
 
-struct ProcManager {
-    store: BTreeMap<ProcId, Process>,
-    ready: VecDeque<ProcId>,
-}
-
-impl ProcManager {
-    fn new() -> Self {
-        Self {
-            store: BTreeMap::new(),
-            ready: VecDeque::new(),
-        }
-    }
-}
-
-impl Manage<Process, ProcId> for ProcManager {
-    fn insert(&mut self, id: ProcId, item: Process) {
-        self.store.insert(id, item);
-    }
-
-    fn delete(&mut self, id: ProcId) {
-        self.store.remove(&id);
-    }
-
-    fn get_mut(&mut self, id: ProcId) -> Option<&mut Process> {
-        self.store.get_mut(&id)
-    }
-}
-
-impl Schedule<ProcId> for ProcManager {
-    fn add(&mut self, id: ProcId) {
-        self.ready.push_back(id);
-    }
-
-    fn fetch(&mut self) -> Option<ProcId> {
-        self.ready.pop_front()
-    }
-}
-
-fn alloc_pid_nonzero() -> ProcId {
-    loop {
-        let pid = ProcId::new();
-        if pid.get_usize() != 0 {
-            return pid;
-        }
-    }
-}
+type ProcManager = MapScheduler<Process, ProcId>;
 
 fn current_space() -> Option<&'static AddressSpace<Sv39, Sv39Manager>> {
     unsafe { CURRENT_SPACE.and_then(|p| p.as_ref()) }
@@ -705,12 +630,14 @@ fn read_user_bytes(
     ptr: *const u8,
     len: usize,
 ) -> Option<Vec<u8>> {
+    let segments = space.translate_range(
+        VAddr::<Sv39>::new(ptr as usize),
+        len,
+        VmFlags::build_from_str("R"),
+    )?;
     let mut out = Vec::with_capacity(len);
-    let flags = VmFlags::build_from_str("R");
-    for i in 0..len {
-        let vaddr = VAddr::<Sv39>::new(ptr as usize + i);
-        let src = space.translate::<u8>(vaddr, flags)?;
-        out.push(unsafe { *src.as_ptr() });
+    for (src, chunk) in segments {
+        out.extend_from_slice(unsafe { core::slice::from_raw_parts(src.as_ptr(), chunk) });
     }
     Some(out)
 }
@@ -720,14 +647,17 @@ fn write_user_bytes(
     ptr: *mut u8,
     data: &[u8],
 ) -> bool {
-    let flags = VmFlags::build_from_str("W");
-    for (i, byte) in data.iter().copied().enumerate() {
-        let vaddr = VAddr::<Sv39>::new(ptr as usize + i);
-        let dst = match space.translate::<u8>(vaddr, flags) {
-            Some(p) => p,
-            None => return false,
-        };
-        unsafe { *dst.as_ptr() = byte };
+    let Some(segments) = space.translate_range(
+        VAddr::<Sv39>::new(ptr as usize),
+        data.len(),
+        VmFlags::build_from_str("W"),
+    ) else {
+        return false;
+    };
+    let mut copied = 0;
+    for (dst, chunk) in segments {
+        unsafe { core::ptr::copy_nonoverlapping(data[copied..].as_ptr(), dst.as_ptr(), chunk) };
+        copied += chunk;
     }
     true
 }
@@ -801,8 +731,10 @@ impl syscall::IO for SyscallContext {
             return -1;
         };
 
-        let written = inode.write_at(file.offset, &data);
-        file.offset += written;
+        // 追加打开时每次写入都落在当前文件末尾
+        let offset = if file.append() { inode.size() } else { file.offset };
+        let written = inode.write_at(offset, &data);
+        file.offset = offset + written;
         written as isize
     }
 
@@ -966,8 +898,8 @@ impl syscall::Process for SyscallContext {
             return -1;
         };
         match processor.wait(child_pid) {
-            Some((sentinel, -1)) if sentinel.get_usize() == usize::MAX - 1 => -2,
-            Some((reaped_pid, code)) => {
+            WaitResult::Alive => -2,
+            WaitResult::Exited(reaped_pid, code) => {
                 if !exit_code_ptr.is_null() {
                     let Some(space) = current_space() else {
                         return -1;
@@ -979,7 +911,7 @@ impl syscall::Process for SyscallContext {
                 }
                 reaped_pid.get_usize() as isize
             }
-            None => -1,
+            WaitResult::NoChild => -1,
         }
     }
 
@@ -1004,12 +936,7 @@ impl syscall::Clock for SyscallContext {
             return -1;
         }
 
-        let ticks = riscv::register::time::read64();
-        const CLOCK_FREQ: u64 = 10_000_000;
-        let ts = TimeSpec {
-            tv_sec: (ticks / CLOCK_FREQ) as usize,
-            tv_nsec: ((ticks % CLOCK_FREQ) * 1_000_000_000 / CLOCK_FREQ) as usize,
-        };
+        let ts = TimeSpec::from_ticks(riscv::register::time::read64());
 
         let Some(space) = current_space() else {
             return -1;
@@ -1153,49 +1080,45 @@ extern "C" fn rust_main() -> ! {
         satp::write(kernel_satp);
         unsafe { core::arch::asm!("sfence.vma zero, zero") };
 
-        let trap_cause = scause::read();
-        match trap_cause.cause() {
-            scause::Trap::Exception(scause::Exception::UserEnvCall) => {
-                proc.context.context.move_next();
-
-                let id = SyscallId::from(proc.context.context.a(7));
-                let args = [
-                    proc.context.context.a(0),
-                    proc.context.context.a(1),
-                    proc.context.context.a(2),
-                    proc.context.context.a(3),
-                    proc.context.context.a(4),
-                    proc.context.context.a(5),
-                ];
+        trap::dispatch(
+            trap::decode(),
+            &mut proc.context.context,
+            |ctx| {
+                ctx.move_next();
+
+                let id = SyscallId::from(ctx.a(7));
+                let args = [ctx.a(0), ctx.a(1), ctx.a(2), ctx.a(3), ctx.a(4), ctx.a(5)];
 
+                let processor = unsafe { PROCESSOR.as_mut().unwrap() };
                 match syscall::handle(caller, id, args) {
                     SyscallResult::Done(ret) => {
                         if id == SyscallId::EXIT {
-                            let processor = unsafe { PROCESSOR.as_mut().unwrap() };
                             processor.make_current_exited(ret);
                         } else {
-                            *proc.context.context.a_mut(0) = ret as usize;
-                            let processor = unsafe { PROCESSOR.as_mut().unwrap() };
+                            *ctx.a_mut(0) = ret as usize;
                             processor.make_current_suspend();
                         }
                     }
                     SyscallResult::Unsupported(_) => {
-                        let processor = unsafe { PROCESSOR.as_mut().unwrap() };
                         processor.make_current_exited(-2);
                     }
                 }
-            }
-            _ => {
+            },
+            |_| {
+                let processor = unsafe { PROCESSOR.as_mut().unwrap() };
+                processor.make_current_suspend();
+            },
+            |cause, ctx| {
                 log::error!(
                     "trap {:?} stval={:#x} sepc={:#x}",
-                    trap_cause.cause(),
+                    cause,
                     stval::read(),
-                    proc.context.context.pc()
+                    ctx.pc()
                 );
                 let processor = unsafe { PROCESSOR.as_mut().unwrap() };
                 processor.make_current_exited(-3);
-            }
-        }
+            },
+        );
 
         unsafe {
             CURRENT_SPACE = None;
@@ -13,26 +13,37 @@ use alloc::vec::Vec;
 use core::arch::global_asm;
 use core::panic::PanicInfo;
 use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use current::CurrentTask;
 
-use easy_fs::{BlockDevice, EasyFileSystem, FSManager, FileHandle, Inode, OpenFlags};
+use easy_fs::{
+    BlockDevice, DiskInodeType, EasyFileSystem, FSManager, FileHandle, FsStat, Inode, OpenFlags,
+    PipeEnd, PipeError, ReadPoll, SeekFrom, UserBuffer, PIPE_BUFFER_SIZE,
+};
 use kernel_context::foreign::{ForeignContext, MultislotPortal};
+use kernel_context::trap;
 use kernel_vm::page_table::{Pte, Sv39, VAddr, VmFlags, PPN, VPN};
-use kernel_vm::{AddressSpace, PageManager};
+use kernel_vm::{AddressSpace, FaultHandler, FaultKind, FaultOutcome, PageManager};
 use linker::{KernelLayout, KernelRegionTitle};
 use rcore_console::{init_console, log, print, println, set_log_level, test_log, Console};
-use rcore_task_manage::{Manage, PThreadManager, ProcId, Schedule, ThreadId};
-use riscv::register::{scause, satp, sie, stval};
+use rcore_task_manage::{
+    AlarmTable, CurrentSlot, MapScheduler, PThreadManager, PrioritySchedule, ProcId, ThreadId,
+    WaitResult,
+};
+use riscv::register::{satp, sie, stval};
 use sbi_rt::{legacy, set_timer, NoReason, Shutdown, SystemFailure};
 use spin::{Lazy, Mutex as SpinMutex};
 use sync::{
     Condvar as SyncCondvar, Mutex as SyncMutexTrait, MutexBlocking as SyncMutexBlocking,
-    Semaphore as SyncSemaphore,
+    Semaphore as SyncSemaphore, SleepQueue,
 };
 use syscall::{
-    Caller, ClockId, SyscallId, SyscallResult, TimeSpec, STDDEBUG, STDIN, STDOUT,
+    Caller, ClockId, PollFd, SigInfo, SyscallId, SyscallResult, TimeSpec, TimeVal, Tms, MADV_DONTNEED,
+    MAP_ANONYMOUS, MAP_PRIVATE, MAP_SHARED, POLLIN, POLLNVAL, POLLOUT, PROT_EXEC, PROT_WRITE, PR_GET_NAME,
+    PR_SET_NAME, PRIO_MAX, PRIO_MIN, RLIMIT_AS, RLIM_INFINITY, SEEK_CUR, SEEK_END, SEEK_SET, SI_USER, TASK_COMM_LEN,
+    TIMER_ABSTIME,
 };
-use signal::SignalNo;
-use virtio_drivers::{Hal, VirtIOBlk, VirtIOHeader};
+use signal::{MaskHow, SignalNo};
 use xmas_elf::header::{Machine, Type as ElfType};
 use xmas_elf::program::Type as ProgramType;
 use xmas_elf::ElfFile;
@@ -91,22 +102,56 @@ const PHYS_MEM_START: usize = 0x8000_0000;
 const MEMORY: usize = 64 * 1024 * 1024;
 const PAGE_SIZE: usize = 4096;
 const USER_STACK_PAGES: usize = 2;
+/// 每个线程栈槽位的虚拟页数，栈从 `USER_STACK_PAGES` 页起在槽位内按需向下增长
+const USER_STACK_SLOT_PAGES: usize = 16;
 const PORTAL_CODE_SIZE: usize = 256;
 const PORTAL_VPN: usize = (1 << 27) - 1;
 const TOP_OF_USER_STACK_VPN: usize = PORTAL_VPN;
+/// 文件映射从此页号向下分配，上方留给各线程的用户栈
+const MMAP_TOP_VPN: usize = TOP_OF_USER_STACK_VPN - 0x1000;
 const VIRTIO0: usize = 0x1000_1000;
+/// QEMU virt 平台的 goldfish RTC，`TIME_LOW`/`TIME_HIGH` 给出自 Unix 纪元起的纳秒数
+const GOLDFISH_RTC: usize = 0x10_1000;
 const USER_CSTR_MAX: usize = 4096;
-const TIMER_SLICE_TICKS: u64 = 100_000;
+/// 文件描述符上限，`dup2` 的目标描述符不能达到它（EBADF）
+const FD_LIMIT: usize = 1024;
+/// 时间片长度，按 `syscall::clock_freq()` 换算为 tick
+const TIMER_SLICE: TimeSpec = TimeSpec {
+    tv_sec: 0,
+    tv_nsec: 10_000_000,
+};
 const BLOCKED_RETURN: isize = isize::MIN;
+/// 位置无关可执行文件（ET_DYN）的装载基址
+#[cfg(feature = "pie")]
+const PIE_LOAD_BASE: usize = 0x4000_0000;
+/// `coop` 模式下默认的系统调用预算
+const COOP_DEFAULT_BUDGET: usize = 1000;
 
 pub const MMIO: &[(usize, usize)] = &[(VIRTIO0, 0x1000)];
 
 static mut KERNEL_SPACE: Option<AddressSpace<Sv39, Sv39Manager>> = None;
 pub static mut PROCESSOR: Option<PThreadManager<Process, Thread, ThreadManager, ProcManager>> =
     None;
-static mut CURRENT_SPACE: Option<*const AddressSpace<Sv39, Sv39Manager>> = None;
-static mut CURRENT_PID: Option<ProcId> = None;
-static mut CURRENT_TID: Option<ThreadId> = None;
+/// 睡眠中的线程及其唤醒时刻（绝对 tick）
+static SLEEPERS: SpinMutex<SleepQueue> = SpinMutex::new(SleepQueue::new());
+/// poll 探测 stdin 时预读的字符，read 时优先取出
+static STDIN_BUFFER: SpinMutex<VecDeque<u8>> = SpinMutex::new(VecDeque::new());
+/// 各进程 `alarm` 设置的到期时刻（绝对 tick），到期时向进程发送 SIGALRM
+static ALARMS: SpinMutex<AlarmTable> = SpinMutex::new(AlarmTable::new());
+/// 因管道空或满而阻塞、或在 `ppoll` 中等待的线程，任一管道有读写或关闭时全部唤醒重试
+static PIPE_WAITERS: SpinMutex<Vec<ThreadId>> = SpinMutex::new(Vec::new());
+/// `coop` 模式的软看门狗：线程连续这么多次系统调用都没有让出时强制挂起，0 表示关闭
+static COOP_BUDGET: AtomicUsize = AtomicUsize::new(0);
+/// 已退出、尚待父进程回收的进程留下的 (用户态, 内核态) 总时间，含其已回收的子进程
+static EXITED_TIMES: SpinMutex<BTreeMap<ProcId, (usize, usize)>> = SpinMutex::new(BTreeMap::new());
+
+/// fork 后写时复制共享的物理页号到其余引用数的映射，不在表中的页只被一个地址空间引用
+static SHARED_PAGES: SpinMutex<BTreeMap<usize, usize>> = SpinMutex::new(BTreeMap::new());
+
+/// 设置 `coop` 模式下的系统调用预算，0 关闭看门狗
+pub fn set_coop_budget(n: usize) {
+    COOP_BUDGET.store(n, Ordering::Relaxed);
+}
 
 struct SbiConsole;
 
@@ -190,6 +235,17 @@ impl PageManager<Sv39> for Sv39Manager {
         if ppn.val() == self.root_ppn.val() {
             return 0;
         }
+        // 还被其他地址空间共享的页只去掉一个引用
+        if len == 1 {
+            let mut shared = SHARED_PAGES.lock();
+            if let Some(refs) = shared.get_mut(&ppn.val()) {
+                *refs -= 1;
+                if *refs == 0 {
+                    shared.remove(&ppn.val());
+                }
+                return 0;
+            }
+        }
         let ptr = (ppn.val() << 12) as *mut u8;
         let layout = core::alloc::Layout::from_size_align(len * PAGE_SIZE, PAGE_SIZE).unwrap();
         unsafe { dealloc(ptr, layout) };
@@ -201,6 +257,16 @@ impl PageManager<Sv39> for Sv39Manager {
         ppn.val() == self.root_ppn.val() || self.in_heap(ppn)
     }
 
+    fn share(&mut self, pte: Pte<Sv39>, len: usize) {
+        let mut shared = SHARED_PAGES.lock();
+        for ppn in pte.ppn().val()..pte.ppn().val() + len {
+            *shared.entry(ppn).or_insert(0) += 1;
+        }
+    }
+
+    // Sv39 页表项的 RSW 低位
+    const COW_FLAG: usize = 1 << 8;
+
     fn drop_root(&mut self) {
         let ptr = self.root_ptr.as_ptr() as *mut u8;
         let layout = core::alloc::Layout::from_size_align(PAGE_SIZE, PAGE_SIZE).unwrap();
@@ -208,6 +274,26 @@ impl PageManager<Sv39> for Sv39Manager {
     }
 }
 
+/// 用户地址空间的缺页策略：写时复制页上的写缺页分出私有副本，其余只认 TLB 未刷新造成的伪缺页
+struct UserFaultHandler;
+
+impl FaultHandler<Sv39, Sv39Manager> for UserFaultHandler {
+    fn handle(
+        space: &mut AddressSpace<Sv39, Sv39Manager>,
+        addr: VAddr<Sv39>,
+        kind: FaultKind,
+    ) -> FaultOutcome {
+        if kind == FaultKind::Store && space.handle_cow_fault(addr) == FaultOutcome::Mapped {
+            return FaultOutcome::Mapped;
+        }
+        if space.translate::<u8>(addr, kind.required_flags()).is_some() {
+            FaultOutcome::Mapped
+        } else {
+            FaultOutcome::Signal
+        }
+    }
+}
+
 fn kernel_space(
     layout: &KernelLayout,
     heap_ppn_start: PPN<Sv39>,
@@ -226,6 +312,8 @@ fn kernel_space(
             KernelRegionTitle::Text => "VRX",
             KernelRegionTitle::Rodata => "VR",
             KernelRegionTitle::Data => "VRW",
+            KernelRegionTitle::Tls => "VRW",
+            KernelRegionTitle::Bss => "VRW",
             KernelRegionTitle::Boot => "VRW",
         };
         let range = VPN::new(start)..VPN::new(end);
@@ -242,11 +330,8 @@ fn kernel_space(
     space.map_extern(portal_range, portal_ppn, VmFlags::build_from_str("VRWX"));
 
     for (base, len) in MMIO.iter().copied() {
-        let start = base >> 12;
-        let end = (base + len + PAGE_SIZE - 1) >> 12;
-        if end > start {
-            let range = VPN::new(start)..VPN::new(end);
-            space.map_extern(range, PPN::new(start), VmFlags::build_from_str("VRW"));
+        if space.map_mmio(base, len, VmFlags::build_from_str("VRW")).is_none() {
+            log::warn!("MMIO region {:#x}..{:#x} overlaps kernel mapping", base, base + len);
         }
     }
 
@@ -260,124 +345,90 @@ fn kernel_space(
 
 pub mod virtio_block {
     use super::*;
+    use ::virtio_block::VirtIOBlock;
 
-    struct VirtioHal;
-
-    impl Hal for VirtioHal {
-        fn dma_alloc(pages: usize) -> usize {
-            let layout = core::alloc::Layout::from_size_align(pages * PAGE_SIZE, PAGE_SIZE).unwrap();
-            let ptr = unsafe { alloc_zeroed(layout) };
-            if ptr.is_null() {
-                return 0;
-            }
-            let paddr = Self::virt_to_phys(ptr as usize);
-            if paddr == 0 {
-                unsafe { dealloc(ptr, layout) };
-            }
-            paddr
-        }
-
-        fn dma_dealloc(paddr: usize, pages: usize) -> i32 {
-            let vaddr = Self::phys_to_virt(paddr);
-            if vaddr == 0 {
-                return -1;
-            }
-            let layout = core::alloc::Layout::from_size_align(pages * PAGE_SIZE, PAGE_SIZE).unwrap();
-            unsafe { dealloc(vaddr as *mut u8, layout) };
+    /// 内核地址空间恒等映射，已映射的地址即物理地址，未映射时返回 0
+    fn virt_to_phys(vaddr: usize) -> usize {
+        let space = unsafe { KERNEL_SPACE.as_ref() };
+        let Some(space) = space else {
+            return 0;
+        };
+        let addr = VAddr::<Sv39>::new(vaddr);
+        if space
+            .translate::<u8>(addr, VmFlags::build_from_str("R"))
+            .is_some()
+        {
+            addr.val()
+        } else {
             0
         }
-
-        fn phys_to_virt(paddr: usize) -> usize {
-            paddr
-        }
-
-        fn virt_to_phys(vaddr: usize) -> usize {
-            let space = unsafe { KERNEL_SPACE.as_ref() };
-            let Some(space) = space else {
-                return 0;
-            };
-            let addr = VAddr::<Sv39>::new(vaddr);
-            if space
-                .translate::<u8>(addr, VmFlags::build_from_str("R"))
-                .is_some()
-            {
-                addr.val()
-            } else {
-                0
-            }
-        }
-    }
-
-    struct VirtIOBlock(SpinMutex<VirtIOBlk<'static, VirtioHal>>);
-
-    impl VirtIOBlock {
-        fn new() -> Self {
-            let header = unsafe { &mut *(VIRTIO0 as *mut VirtIOHeader) };
-            let blk = VirtIOBlk::<VirtioHal>::new(header).expect("failed to init virtio-blk");
-            Self(SpinMutex::new(blk))
-        }
-    }
-
-    impl BlockDevice for VirtIOBlock {
-        fn read_block(&self, block_id: usize, buf: &mut [u8]) {
-            self.0
-                .lock()
-                .read_block(block_id, buf)
-                .expect("virtio read block failed");
-        }
-
-        fn write_block(&self, block_id: usize, buf: &[u8]) {
-            self.0
-                .lock()
-                .write_block(block_id, buf)
-                .expect("virtio write block failed");
-        }
     }
 
-    pub static BLOCK_DEVICE: Lazy<Arc<dyn BlockDevice>> = Lazy::new(|| Arc::new(VirtIOBlock::new()));
+    pub static BLOCK_DEVICE: Lazy<Arc<dyn BlockDevice>> = Lazy::new(|| {
+        ::virtio_block::init(virt_to_phys);
+        Arc::new(unsafe { VirtIOBlock::new(VIRTIO0) })
+    });
 }
 
 pub mod fs {
     use super::*;
 
     pub struct FileSystem {
+        efs: Arc<SpinMutex<EasyFileSystem>>,
         root: Arc<Inode>,
     }
 
     impl FileSystem {
-        fn new(root: Inode) -> Self {
-            Self { root: Arc::new(root) }
+        fn new(efs: Arc<SpinMutex<EasyFileSystem>>) -> Self {
+            let root = EasyFileSystem::root_inode(&efs);
+            Self {
+                efs,
+                root: Arc::new(root),
+            }
+        }
+
+        /// 查询 `path` 所在文件系统的容量；只有一个文件系统，`path` 只需存在
+        pub fn stat_fs(&self, path: &str) -> Option<FsStat> {
+            if !(path == "/" || path == "." || path.is_empty()) {
+                self.root.find(path)?;
+            }
+            Some(self.efs.lock().stat_fs())
         }
     }
 
     impl FSManager for FileSystem {
         fn open(&self, path: &str, flags: OpenFlags) -> Option<Arc<FileHandle>> {
             let (readable, writable) = flags.read_write();
+            let handle = |inode| {
+                let mut handle = FileHandle::new(readable, writable, inode);
+                handle.set_append(flags.contains(OpenFlags::APPEND));
+                Arc::new(handle)
+            };
 
             if path == "/" || path == "." || path.is_empty() {
-                return Some(Arc::new(FileHandle::new(
-                    readable,
-                    writable,
-                    Arc::clone(&self.root),
-                )));
+                return Some(handle(Arc::clone(&self.root)));
             }
 
             if flags.contains(OpenFlags::CREATE) {
+                let type_ = flags.create_type();
                 if let Some(inode) = self.root.find(path) {
-                    inode.clear();
-                    return Some(Arc::new(FileHandle::new(readable, writable, inode)));
+                    // 已存在的条目类型必须与请求一致，且只截断普通文件；追加打开时保留原内容
+                    if inode.inode_type() != type_ {
+                        return None;
+                    }
+                    if type_ == DiskInodeType::File && !flags.contains(OpenFlags::APPEND) {
+                        inode.clear();
+                    }
+                    return Some(handle(inode));
                 }
-                return self
-                    .root
-                    .create(path)
-                    .map(|inode| Arc::new(FileHandle::new(readable, writable, inode)));
+                return self.root.create_typed(path, type_).map(handle);
             }
 
             self.root.find(path).map(|inode| {
                 if flags.contains(OpenFlags::TRUNC) {
                     inode.clear();
                 }
-                Arc::new(FileHandle::new(readable, writable, inode))
+                handle(inode)
             })
         }
 
@@ -388,12 +439,30 @@ pub mod fs {
             self.root.find(path)
         }
 
-        fn link(&self, _src: &str, _dst: &str) -> isize {
-            -1
+        fn link(&self, src: &str, dst: &str) -> isize {
+            // 只有根目录一层，路径即根目录下的文件名
+            if self.root.link(src, dst) {
+                0
+            } else {
+                -1
+            }
         }
 
-        fn unlink(&self, _path: &str) -> isize {
-            -1
+        fn unlink(&self, path: &str) -> isize {
+            if self.root.unlink(path) {
+                0
+            } else {
+                -1
+            }
+        }
+
+        fn rename(&self, old_path: &str, new_path: &str) -> isize {
+            // 只有根目录一层，跨目录重命名即根目录内重命名
+            if self.root.rename(old_path, new_path) {
+                0
+            } else {
+                -1
+            }
         }
 
         fn readdir(&self, path: &str) -> Option<Vec<String>> {
@@ -405,9 +474,7 @@ pub mod fs {
     }
 
     pub static FS: Lazy<FileSystem> = Lazy::new(|| {
-        let efs = EasyFileSystem::open(Arc::clone(&virtio_block::BLOCK_DEVICE));
-        let root = EasyFileSystem::root_inode(&efs);
-        FileSystem::new(root)
+        FileSystem::new(EasyFileSystem::open(Arc::clone(&virtio_block::BLOCK_DEVICE)))
     });
 
     pub fn read_all(file: Arc<FileHandle>) -> Vec<u8> {
@@ -429,12 +496,61 @@ pub mod fs {
     }
 }
 
+/// 当前正在运行的任务（地址空间、进程、线程）
+///
+/// 目前只有单个 hart，用一个全局槽位保存；引入 SMP 后改为按 hart 索引。
+/// 只有调度循环会写入，所有 `unsafe` 都集中在这里。
+pub mod current {
+    use super::*;
+
+    static CURRENT: CurrentSlot<AddressSpace<Sv39, Sv39Manager>> = CurrentSlot::new();
+
+    pub struct CurrentTask;
+
+    impl CurrentTask {
+        /// 切换到 `tid` 运行前记录当前任务
+        pub fn set(space: &mut AddressSpace<Sv39, Sv39Manager>, pid: ProcId, tid: ThreadId) {
+            // 调度循环在任务让出后、回收进程前调用 clear，期间地址空间保持有效
+            unsafe { CURRENT.set(space, pid, tid) };
+        }
+
+        /// 任务让出 CPU 后清空
+        pub fn clear() {
+            CURRENT.clear();
+        }
+
+        /// 当前任务的地址空间
+        ///
+        /// 只在处理当前任务的陷入期间使用：调度循环在任务让出后才 clear，进程也只在
+        /// clear 之后回收，系统调用修改进程时不会移动或释放它的地址空间。
+        /// 可变是为了在访问用户缓冲区前补齐缺页，调用方不应同时经由进程持有它的引用。
+        pub fn space() -> Option<&'static mut AddressSpace<Sv39, Sv39Manager>> {
+            unsafe { CURRENT.space_mut() }
+        }
+
+        /// 当前进程 ID
+        pub fn pid() -> Option<ProcId> {
+            CURRENT.pid()
+        }
+
+        /// 当前线程 ID
+        pub fn tid() -> Option<ThreadId> {
+            CURRENT.tid()
+        }
+    }
+}
+
 fn duplicate_file_handle(file: &FileHandle) -> FileHandle {
+    if let Some(end) = file.pipe() {
+        return FileHandle::from_pipe(end.clone());
+    }
     let mut cloned = match file.inode.as_ref() {
         Some(inode) => FileHandle::new(file.readable(), file.writable(), Arc::clone(inode)),
         None => FileHandle::empty(file.readable(), file.writable()),
     };
     cloned.offset = file.offset;
+    cloned.set_read_mode(file.read_mode());
+    cloned.set_append(file.append());
     cloned
 }
 
@@ -463,6 +579,69 @@ pub struct Thread {
     pub tid: ThreadId,
     pub pid: ProcId,
     pub context: ForeignContext,
+    /// `tgkill` 定向到本线程、尚未投递的信号（按位）
+    pub pending: usize,
+    /// 上次让出以来的系统调用次数，用于 `coop` 模式的预算
+    syscalls_since_yield: usize,
+    /// 累计在用户态运行的 tick 数
+    utime: usize,
+    /// 累计在内核中为本线程处理陷入的 tick 数
+    stime: usize,
+    /// 线程名，以 0 结尾；默认取所属进程的程序名，可由 `prctl(PR_SET_NAME)` 修改
+    name: [u8; TASK_COMM_LEN],
+    /// 调度优先级，数值越大越先被调度；新线程取 [`DEFAULT_PRIORITY`]
+    priority: u8,
+    /// 阻塞在 `ppoll` 中时为本次调用的截止时刻（tick，`u64::MAX` 表示不限时）
+    poll_deadline: Option<u64>,
+    /// 限时阻塞在条件变量或信号量上时为所等待的对象
+    sync_wait: Option<SyncWait>,
+}
+
+/// 限时等待的同步对象，到期时由 [`wake_expired_sleepers`] 调用它的 `expire`
+enum SyncWait {
+    Condvar(Arc<SyncCondvar>),
+    Semaphore(Arc<SyncSemaphore>),
+}
+
+/// 未调用 `set_priority` 的线程的调度优先级，同优先级的线程按先进先出轮转
+const DEFAULT_PRIORITY: u8 = 16;
+
+/// 把名字截断为至多 `TASK_COMM_LEN - 1` 字节并补 0
+fn comm_name(name: &[u8]) -> [u8; TASK_COMM_LEN] {
+    let mut comm = [0u8; TASK_COMM_LEN];
+    let len = name.len().min(TASK_COMM_LEN - 1);
+    comm[..len].copy_from_slice(&name[..len]);
+    comm
+}
+
+impl Thread {
+    /// 线程名的字符串形式，用于日志
+    fn name_str(&self) -> &str {
+        let len = self
+            .name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(TASK_COMM_LEN);
+        core::str::from_utf8(&self.name[..len]).unwrap_or("?")
+    }
+
+    /// 记录一次已完成的系统调用，返回是否应挂起当前线程。
+    ///
+    /// 非 `coop` 模式每次系统调用后都轮转；`coop` 模式只在让出或预算耗尽时挂起。
+    fn charge_syscall(&mut self, id: SyscallId) -> bool {
+        if id == SyscallId::SCHED_YIELD || !cfg!(feature = "coop") {
+            self.syscalls_since_yield = 0;
+            return true;
+        }
+        self.syscalls_since_yield += 1;
+        let budget = COOP_BUDGET.load(Ordering::Relaxed);
+        if budget != 0 && self.syscalls_since_yield >= budget {
+            log::debug!("tid {} used up coop budget, forcing suspend", self.tid.get_usize());
+            self.syscalls_since_yield = 0;
+            return true;
+        }
+        false
+    }
 }
 
 pub struct Process {
@@ -472,45 +651,250 @@ pub struct Process {
     pub signal: Box<dyn signal::Signal>,
     thread_stacks: BTreeMap<ThreadId, usize>,
     waittid_waiters: BTreeMap<ThreadId, Vec<ThreadId>>,
-    condvar_wait_mutex: BTreeMap<ThreadId, usize>,
+    /// 阻塞在 sigtimedwait 上的线程：(线程, 等待的信号集合, 用户态 SigInfo 地址)
+    sigwaiters: Vec<(ThreadId, usize, usize)>,
     semaphores: Vec<Arc<SyncSemaphore>>,
     mutexes: Vec<Option<Arc<dyn SyncMutexTrait>>>,
     condvars: Vec<Arc<SyncCondvar>>,
+    /// 地址空间大小上限（字节），默认 `RLIM_INFINITY`
+    as_limit: usize,
+    /// 已退出线程的累计时间，以及已回收子进程的累计时间（tick）
+    times: Tms,
+    /// 用户 id 与组 id，fork 与 exec 都保留，目前不用于权限检查
+    uid: usize,
+    gid: usize,
+    /// 程序名，新线程的默认线程名；exec 时更新
+    name: [u8; TASK_COMM_LEN],
+    /// vfork 创建的子进程在 exec 或 exit 之前借用父进程的地址空间，
+    /// 这里记录父进程与阻塞在 vfork 上的父线程
+    vfork_parent: Option<(ProcId, ThreadId)>,
+    /// 堆的起始地址，紧接在最高的 ELF 段之后
+    heap_base: usize,
+    /// 当前堆顶（program break），`[heap_base, heap_top)` 所在的页已映射
+    heap_top: usize,
+    /// 下一段文件映射的结束页号，每次映射后向下移动
+    mmap_base: usize,
+    /// `mmap` 建立的文件映射
+    file_mappings: Vec<FileMapping>,
+    /// `mmap` 建立的匿名映射：(起始地址, 长度)
+    anon_mappings: Vec<(usize, usize)>,
+}
+
+/// `mmap` 建立的一段文件映射
+#[derive(Clone)]
+struct FileMapping {
+    /// 起始虚拟地址，页对齐
+    start: usize,
+    /// 映射长度，按页取整
+    len: usize,
+    inode: Arc<Inode>,
+    /// 映射起点对应的文件偏移
+    offset: usize,
+    /// 建立映射时区间内有效的文件字节数，写回不超过这个长度，不会扩大文件
+    file_len: usize,
+    /// `MAP_SHARED` 时写回文件，`MAP_PRIVATE` 只在建立时拷入内容
+    shared: bool,
+}
+
+impl FileMapping {
+    /// 把 `[addr, end)` 中属于本映射的内容写回文件，私有映射不做任何事
+    ///
+    /// 不跟踪页表的脏位，重叠范围内的页全部写回。
+    fn write_back(&self, space: &AddressSpace<Sv39, Sv39Manager>, addr: usize, end: usize) {
+        if !self.shared {
+            return;
+        }
+        let mut va = addr.max(self.start);
+        let to = end.min(self.start + self.file_len);
+        while va < to {
+            let chunk = (PAGE_SIZE - va % PAGE_SIZE).min(to - va);
+            let flags = VmFlags::build_from_str("V");
+            if let Some(ptr) = space.translate::<u8>(VAddr::new(va), flags) {
+                let data = unsafe { core::slice::from_raw_parts(ptr.as_ptr(), chunk) };
+                self.inode.write_at(self.offset + (va - self.start), data);
+            }
+            va += chunk;
+        }
+    }
+}
+
+/// 栈槽位 `slot` 的栈顶页号
+fn stack_top_vpn(slot: usize) -> Option<usize> {
+    TOP_OF_USER_STACK_VPN.checked_sub(USER_STACK_SLOT_PAGES.checked_mul(slot)?)
 }
 
+/// 在栈槽位 `slot` 顶部登记按需分配的线程栈，返回初始栈指针
+///
+/// 槽位被之前退出的线程用过时沿用那时的栈，不重复登记。
 fn map_thread_stack(space: &mut AddressSpace<Sv39, Sv39Manager>, slot: usize) -> Option<usize> {
-    let pages = USER_STACK_PAGES.checked_mul(slot + 1)?;
-    let stack_vpn = TOP_OF_USER_STACK_VPN.checked_sub(pages)?;
-    let stack_range = VPN::new(stack_vpn)..VPN::new(stack_vpn + USER_STACK_PAGES);
-    space.map(stack_range, &[], 0, VmFlags::build_from_str("VRWU"));
-    Some(
-        VAddr::<Sv39>::new((stack_vpn + USER_STACK_PAGES) << 12)
-            .val()
-            .wrapping_sub(16),
-    )
+    let top = stack_top_vpn(slot)?;
+    let stack_vpn = top.checked_sub(USER_STACK_PAGES)?;
+    if !space.areas.iter().any(|area| area.end.val() == top) {
+        let stack_range = VPN::new(stack_vpn)..VPN::new(top);
+        space.map_lazy(stack_range, VmFlags::build_from_str("VRWU"));
+    }
+    Some(VAddr::<Sv39>::new(top << 12).val().wrapping_sub(16))
+}
+
+/// `mmap` 的 `prot` 对应的页表标志；RISC-V 的叶子页表项不能没有任何权限，因此总是可读
+fn mmap_flags(prot: usize) -> VmFlags<Sv39> {
+    match (prot & PROT_WRITE != 0, prot & PROT_EXEC != 0) {
+        (true, true) => VmFlags::build_from_str("VRWXU"),
+        (false, true) => VmFlags::build_from_str("VRXU"),
+        (true, false) => VmFlags::build_from_str("VRWU"),
+        _ => VmFlags::build_from_str("VRU"),
+    }
+}
+
+/// 堆的起始地址：最高的已映射区间之后的第一页
+fn heap_base_of(space: &AddressSpace<Sv39, Sv39Manager>) -> usize {
+    let end_vpn = space.areas.iter().map(|area| area.end.val()).max();
+    end_vpn.unwrap_or(0) << 12
+}
+
+/// ELF 中 PT_TLS 段描述的线程局部存储模板
+struct TlsTemplate {
+    /// `.tdata` 初始内容，其余 `mem_size - init.len()` 字节（`.tbss`）为 0
+    init: Vec<u8>,
+    mem_size: usize,
+    align: usize,
+}
+
+/// 在主线程栈顶划出 TLS 块并写入模板，返回新的 `(sp, tp)`
+///
+/// RISC-V 采用 TLS variant I，`tp` 直接指向 TLS 块起始处。
+fn place_main_tls(
+    space: &mut AddressSpace<Sv39, Sv39Manager>,
+    stack_top: usize,
+    tls: &TlsTemplate,
+) -> Option<(usize, usize)> {
+    let align = tls.align.max(16);
+    let tp = stack_top.checked_sub(tls.mem_size)? & !(align - 1);
+    // TLS 块最多占用一半的栈空间
+    if stack_top - tp > USER_STACK_PAGES * PAGE_SIZE / 2 {
+        return None;
+    }
+    if !write_user_bytes(space, tp as *mut u8, &tls.init) {
+        return None;
+    }
+    Some((tp - 16, tp))
+}
+
+/// 对装载到 `base` 的 PIE 应用 `DT_RELA` 中的 `R_RISCV_RELATIVE` 重定位
+///
+/// 没有 `PT_DYNAMIC` 或 `DT_RELA` 时无需处理；遇到其他重定位类型（需要符号表）时拒绝装载。
+#[cfg(feature = "pie")]
+fn apply_pie_relocations(
+    elf: &ElfFile,
+    elf_data: &[u8],
+    space: &mut AddressSpace<Sv39, Sv39Manager>,
+    base: usize,
+) -> Option<()> {
+    const DT_NULL: u64 = 0;
+    const DT_RELA: u64 = 7;
+    const DT_RELASZ: u64 = 8;
+    const DT_RELAENT: u64 = 9;
+    const R_RISCV_NONE: u32 = 0;
+    const R_RISCV_RELATIVE: u32 = 3;
+    const RELA_SIZE: usize = 24;
+
+    let Some(dynamic) = elf
+        .program_iter()
+        .find(|ph| matches!(ph.get_type(), Ok(ProgramType::Dynamic)))
+    else {
+        return Some(());
+    };
+    let offset = dynamic.offset() as usize;
+    let entries = elf_data.get(offset..offset.checked_add(dynamic.file_size() as usize)?)?;
+    let (mut rela, mut relasz, mut relaent) = (None, 0, RELA_SIZE);
+    for entry in entries.chunks_exact(16) {
+        let tag = u64::from_le_bytes(entry[..8].try_into().ok()?);
+        let val = u64::from_le_bytes(entry[8..].try_into().ok()?) as usize;
+        match tag {
+            DT_NULL => break,
+            DT_RELA => rela = Some(val),
+            DT_RELASZ => relasz = val,
+            DT_RELAENT => relaent = val,
+            _ => {}
+        }
+    }
+    let Some(rela) = rela else {
+        return Some(());
+    };
+    if relaent != RELA_SIZE {
+        return None;
+    }
+
+    // 重定位表位于已装载的只读段中，直接从新地址空间读取
+    let table = read_user_bytes(space, base.checked_add(rela)? as *const u8, relasz)?;
+    for entry in table.chunks_exact(RELA_SIZE) {
+        let r_offset = u64::from_le_bytes(entry[..8].try_into().ok()?) as usize;
+        let r_info = u64::from_le_bytes(entry[8..16].try_into().ok()?);
+        let r_addend = i64::from_le_bytes(entry[16..].try_into().ok()?);
+        match r_info as u32 {
+            R_RISCV_NONE => {}
+            R_RISCV_RELATIVE => {
+                if r_offset % 8 != 0 {
+                    return None;
+                }
+                // 目标可能在 RELRO 等用户只读页中，按内核视角直接写入
+                let dst = space.translate::<u64>(
+                    VAddr::<Sv39>::new(base.checked_add(r_offset)?),
+                    VmFlags::build_from_str("R"),
+                )?;
+                unsafe { *dst.as_ptr() = (base as u64).wrapping_add(r_addend as u64) };
+            }
+            other => {
+                log::warn!("unsupported PIE relocation type {other}");
+                return None;
+            }
+        }
+    }
+    Some(())
 }
 
 fn load_user_space_from_elf(
     elf_data: &[u8],
     kernel_space: &AddressSpace<Sv39, Sv39Manager>,
-) -> Option<(AddressSpace<Sv39, Sv39Manager>, usize)> {
+) -> Option<(AddressSpace<Sv39, Sv39Manager>, usize, Option<TlsTemplate>)> {
     let elf = ElfFile::new(elf_data).ok()?;
-    if elf.header.pt2.type_().as_type() != ElfType::Executable {
-        return None;
-    }
+    // ET_EXEC 按链接地址装载；启用 `pie` 时 ET_DYN 整体平移到 PIE_LOAD_BASE
+    let base = match elf.header.pt2.type_().as_type() {
+        ElfType::Executable => 0,
+        #[cfg(feature = "pie")]
+        ElfType::SharedObject => PIE_LOAD_BASE,
+        _ => return None,
+    };
     if elf.header.pt2.machine().as_machine() != Machine::RISC_V {
         return None;
     }
 
     let mut space = AddressSpace::<Sv39, Sv39Manager>::new();
-    let entry = elf.header.pt2.entry_point() as usize;
+    space.set_fault_handler::<UserFaultHandler>();
+    let entry = base.checked_add(elf.header.pt2.entry_point() as usize)?;
 
+    let mut tls = None;
     let mut page_flags: BTreeMap<usize, (bool, bool, bool)> = BTreeMap::new();
     for ph in elf.program_iter() {
-        if ph.get_type().ok()? != ProgramType::Load {
+        let ph_type = ph.get_type().ok()?;
+        if ph_type == ProgramType::Tls {
+            let offset = ph.offset() as usize;
+            let filesz = ph.file_size() as usize;
+            let align = (ph.align() as usize).max(1);
+            if !align.is_power_of_two() {
+                return None;
+            }
+            tls = Some(TlsTemplate {
+                init: elf_data.get(offset..offset.checked_add(filesz)?)?.to_vec(),
+                mem_size: ph.mem_size() as usize,
+                align,
+            });
+            continue;
+        }
+        if ph_type != ProgramType::Load {
             continue;
         }
-        let vaddr = ph.virtual_addr() as usize;
+        let vaddr = base.checked_add(ph.virtual_addr() as usize)?;
         let memsz = ph.mem_size() as usize;
         if memsz == 0 {
             continue;
@@ -532,7 +916,7 @@ fn load_user_space_from_elf(
             continue;
         }
 
-        let vaddr = ph.virtual_addr() as usize;
+        let vaddr = base.checked_add(ph.virtual_addr() as usize)?;
         let offset = ph.offset() as usize;
         let filesz = ph.file_size() as usize;
         let memsz = ph.mem_size() as usize;
@@ -587,8 +971,13 @@ fn load_user_space_from_elf(
         }
     }
 
+    #[cfg(feature = "pie")]
+    if base != 0 {
+        apply_pie_relocations(&elf, elf_data, &mut space, base)?;
+    }
+
     space.copy_leaf_pte_from(kernel_space, VPN::new(PORTAL_VPN));
-    Some((space, entry))
+    Some((space, entry, tls))
 }
 
 impl Process {
@@ -601,17 +990,32 @@ impl Process {
         kernel_space: &AddressSpace<Sv39, Sv39Manager>,
         pid: ProcId,
         main_tid: ThreadId,
+        name: &str,
     ) -> Option<(Self, Thread)> {
-        let (mut space, entry) = load_user_space_from_elf(elf_data, kernel_space)?;
-        let stack_top = map_thread_stack(&mut space, 0)?;
+        let (mut space, entry, tls) = load_user_space_from_elf(elf_data, kernel_space)?;
+        let heap_base = heap_base_of(&space);
+        let mut stack_top = map_thread_stack(&mut space, 0)?;
+        let mut tp = 0;
+        if let Some(tls) = &tls {
+            (stack_top, tp) = place_main_tls(&mut space, stack_top, tls)?;
+        }
         let satp = (8 << 60) | space.root_ppn().val();
 
         let mut ctx = kernel_context::LocalContext::user(entry);
         *ctx.sp_mut() = stack_top;
+        *ctx.tp_mut() = tp;
         let main_thread = Thread {
             tid: main_tid,
             pid,
             context: ForeignContext { context: ctx, satp },
+            pending: 0,
+            syscalls_since_yield: 0,
+            utime: 0,
+            stime: 0,
+            name: comm_name(name.as_bytes()),
+            priority: DEFAULT_PRIORITY,
+            poll_deadline: None,
+            sync_wait: None,
         };
 
         let mut thread_stacks = BTreeMap::new();
@@ -624,31 +1028,77 @@ impl Process {
             signal: Box::new(signal_impl::SignalImpl::new()),
             thread_stacks,
             waittid_waiters: BTreeMap::new(),
-            condvar_wait_mutex: BTreeMap::new(),
+            sigwaiters: Vec::new(),
             semaphores: Vec::new(),
             mutexes: Vec::new(),
             condvars: Vec::new(),
+            as_limit: RLIM_INFINITY,
+            times: Tms::default(),
+            uid: 0,
+            gid: 0,
+            name: comm_name(name.as_bytes()),
+            vfork_parent: None,
+            heap_base,
+            heap_top: heap_base,
+            mmap_base: MMAP_TOP_VPN,
+            file_mappings: Vec::new(),
+            anon_mappings: Vec::new(),
         };
         Some((process, main_thread))
     }
 
     fn fork(&mut self, kernel_space: &AddressSpace<Sv39, Sv39Manager>) -> Option<Self> {
+        // vfork 子进程自己的地址空间是空的，不能再复制
+        if self.vfork_parent.is_some() {
+            return None;
+        }
+        // 父子共享物理页直到某一方写入；父进程返回用户态前会整体刷新 TLB
         let mut child_space = AddressSpace::<Sv39, Sv39Manager>::new();
-        self.space.cloneself(&mut child_space);
+        self.space.cow_clone(&mut child_space);
         child_space.copy_leaf_pte_from(kernel_space, VPN::new(PORTAL_VPN));
+        Some(self.child_with_space(child_space))
+    }
 
-        Some(Self {
-            pid: alloc_pid_nonzero(),
-            space: child_space,
+    /// 创建共享本进程地址空间的 vfork 子进程，`parent_tid` 是阻塞等待的父线程
+    ///
+    /// 子进程的 `space` 只是占位用的空地址空间，exec 时被新程序替换；
+    /// 在此之前子进程运行在父进程的页表上，调度时用父进程的地址空间访问用户内存。
+    fn vfork(&mut self, parent_tid: ThreadId) -> Option<Self> {
+        if self.vfork_parent.is_some() {
+            return None;
+        }
+        let mut child = self.child_with_space(AddressSpace::new());
+        child.vfork_parent = Some((self.pid, parent_tid));
+        child.file_mappings.clear();
+        child.anon_mappings.clear();
+        Some(child)
+    }
+
+    fn child_with_space(&self, space: AddressSpace<Sv39, Sv39Manager>) -> Self {
+        Self {
+            pid: ProcId::alloc(),
+            space,
             fd_table: clone_fd_table(&self.fd_table),
             signal: self.signal.from_fork(),
             thread_stacks: BTreeMap::new(),
             waittid_waiters: BTreeMap::new(),
-            condvar_wait_mutex: BTreeMap::new(),
+            sigwaiters: Vec::new(),
             semaphores: Vec::new(),
             mutexes: Vec::new(),
             condvars: Vec::new(),
-        })
+            as_limit: self.as_limit,
+            times: Tms::default(),
+            uid: self.uid,
+            gid: self.gid,
+            name: self.name,
+            vfork_parent: None,
+            heap_base: self.heap_base,
+            heap_top: self.heap_top,
+            // 页面由 cow_clone 写时复制，共享映射在 fork 后不再共享页面，各自写回
+            mmap_base: self.mmap_base,
+            file_mappings: self.file_mappings.clone(),
+            anon_mappings: self.anon_mappings.clone(),
+        }
     }
 
     fn exec(
@@ -656,29 +1106,148 @@ impl Process {
         current_tid: ThreadId,
         elf_data: &[u8],
         kernel_space: &AddressSpace<Sv39, Sv39Manager>,
+        path: &str,
     ) -> Option<ForeignContext> {
-        let (mut new_space, entry) = load_user_space_from_elf(elf_data, kernel_space)?;
-        let stack_top = map_thread_stack(&mut new_space, 0)?;
+        let (mut new_space, entry, tls) = load_user_space_from_elf(elf_data, kernel_space)?;
+        let heap_base = heap_base_of(&new_space);
+        let mut stack_top = map_thread_stack(&mut new_space, 0)?;
+        let mut tp = 0;
+        if let Some(tls) = &tls {
+            (stack_top, tp) = place_main_tls(&mut new_space, stack_top, tls)?;
+        }
         let satp = (8 << 60) | new_space.root_ppn().val();
 
+        self.sync_file_mappings(0, usize::MAX);
+        self.file_mappings.clear();
+        self.anon_mappings.clear();
+        self.mmap_base = MMAP_TOP_VPN;
+        self.heap_base = heap_base;
+        self.heap_top = heap_base;
         let mut old_space = core::mem::replace(&mut self.space, new_space);
         old_space.free_allocated_pages_and_root(Some(VPN::new(PORTAL_VPN)));
 
         self.thread_stacks.clear();
         self.thread_stacks.insert(current_tid, 0);
         self.waittid_waiters.clear();
-        self.condvar_wait_mutex.clear();
+        self.sigwaiters.clear();
         self.semaphores.clear();
         self.mutexes.clear();
         self.condvars.clear();
         self.signal.clear();
+        let program = path.rsplit('/').next().unwrap_or(path);
+        self.name = comm_name(program.as_bytes());
 
         let mut context = kernel_context::LocalContext::user(entry);
         *context.sp_mut() = stack_top;
+        *context.tp_mut() = tp;
         Some(ForeignContext { context, satp })
     }
 
+    /// 再映射 `extra_pages` 页后地址空间是否仍不超过 RLIMIT_AS
+    fn can_map_pages(&self, extra_pages: usize) -> bool {
+        self.space
+            .mapped_pages()
+            .checked_add(extra_pages)
+            .and_then(|pages| pages.checked_mul(PAGE_SIZE))
+            .is_some_and(|bytes| bytes <= self.as_limit)
+    }
+
+    /// 把堆顶调整到 `new_top`，返回新的堆顶
+    ///
+    /// 堆按页伸缩；`new_top` 低于 `heap_base`、超出 RLIMIT_AS 或与其他映射重叠时失败。
+    /// vfork 子进程还借用着父进程的地址空间，不能调整堆。
+    fn set_brk(&mut self, new_top: usize) -> Option<usize> {
+        if new_top < self.heap_base || self.vfork_parent.is_some() {
+            return None;
+        }
+        let old_end = self.heap_top.div_ceil(PAGE_SIZE);
+        let new_end = new_top.checked_add(PAGE_SIZE - 1)? / PAGE_SIZE;
+        if new_end > old_end && !self.can_map_pages(new_end - old_end) {
+            return None;
+        }
+        let flags = VmFlags::build_from_str("VRWU");
+        if !self
+            .space
+            .resize_heap(VPN::new(old_end), VPN::new(new_end), flags)
+        {
+            return None;
+        }
+        self.heap_top = new_top;
+        Some(new_top)
+    }
+
+    /// 在 `mmap_base` 下方分配 `pages` 页，拷入 `inode` 从 `offset` 开始的内容并映射，返回起始地址
+    fn mmap_file(
+        &mut self,
+        inode: Arc<Inode>,
+        offset: usize,
+        pages: usize,
+        prot: usize,
+        shared: bool,
+    ) -> Option<usize> {
+        let start_vpn = self.reserve_mmap_pages(pages)?;
+        let mut data = vec![0u8; pages * PAGE_SIZE];
+        let file_len = inode.read_at(offset, &mut data);
+        self.space.map(
+            VPN::new(start_vpn)..VPN::new(start_vpn + pages),
+            &data[..file_len],
+            0,
+            mmap_flags(prot),
+        );
+        let start = start_vpn << 12;
+        self.file_mappings.push(FileMapping {
+            start,
+            len: pages * PAGE_SIZE,
+            inode,
+            offset,
+            file_len,
+            shared,
+        });
+        Some(start)
+    }
+
+    /// 在 `mmap_base` 下方分配 `pages` 页全零的匿名映射，返回起始地址
+    fn mmap_anon(&mut self, pages: usize, prot: usize) -> Option<usize> {
+        let start_vpn = self.reserve_mmap_pages(pages)?;
+        let range = VPN::new(start_vpn)..VPN::new(start_vpn + pages);
+        self.space.map(range, &[], 0, mmap_flags(prot));
+        let start = start_vpn << 12;
+        self.anon_mappings.push((start, pages * PAGE_SIZE));
+        Some(start)
+    }
+
+    /// 从 `mmap_base` 向下划出 `pages` 页，返回起始页号
+    ///
+    /// 超出 RLIMIT_AS 或与已有区间重叠时失败，此时 `mmap_base` 不变。
+    fn reserve_mmap_pages(&mut self, pages: usize) -> Option<usize> {
+        if !self.can_map_pages(pages) {
+            return None;
+        }
+        let end_vpn = self.mmap_base;
+        let start_vpn = end_vpn.checked_sub(pages)?;
+        let overlaps = self
+            .space
+            .areas
+            .iter()
+            .any(|area| area.start.val() < end_vpn && start_vpn < area.end.val());
+        if overlaps {
+            return None;
+        }
+        self.mmap_base = start_vpn;
+        Some(start_vpn)
+    }
+
+    /// 把与 `[addr, end)` 重叠的共享文件映射写回文件
+    fn sync_file_mappings(&self, addr: usize, end: usize) {
+        for mapping in &self.file_mappings {
+            mapping.write_back(&self.space, addr, end);
+        }
+    }
+
     fn alloc_thread_stack(&mut self, tid: ThreadId) -> Option<usize> {
+        if !self.can_map_pages(USER_STACK_PAGES) {
+            return None;
+        }
         let mut slot = 0usize;
         while self.thread_stacks.values().any(|s| *s == slot) {
             slot += 1;
@@ -692,10 +1261,60 @@ impl Process {
         self.thread_stacks.get(&tid).copied()
     }
 
+    /// `addr` 落在某个线程栈的栈底之下、仍在其栈槽位内时，把该栈向下扩展到包含 `addr`
+    ///
+    /// 扩展的部分仍按需分配，超出 RLIMIT_AS 时不扩展；返回是否扩展。
+    fn grow_stack_to(&mut self, addr: usize) -> bool {
+        let vpn = addr >> 12;
+        let top = self.thread_stacks.values().find_map(|&slot| {
+            let top = stack_top_vpn(slot)?;
+            let limit = top.checked_sub(USER_STACK_SLOT_PAGES)?;
+            (limit <= vpn && vpn < top).then_some(top)
+        });
+        let Some(top) = top else {
+            return false;
+        };
+        let bottom = self
+            .space
+            .areas
+            .iter()
+            .find(|area| area.end.val() == top)
+            .map(|area| area.start.val());
+        let Some(extra) = bottom.and_then(|bottom| bottom.checked_sub(vpn)) else {
+            return false;
+        };
+        extra > 0 && self.can_map_pages(extra) && self.space.grow_stack(VPN::new(top), extra)
+    }
+
     fn remove_thread_stack(&mut self, tid: ThreadId) {
         self.thread_stacks.remove(&tid);
-        self.condvar_wait_mutex.remove(&tid);
         self.waittid_waiters.remove(&tid);
+        self.cancel_sigwaiter(tid);
+    }
+
+    /// 把 `tid` 移出 sigtimedwait 等待者，返回它之前是否在等待
+    fn cancel_sigwaiter(&mut self, tid: ThreadId) -> bool {
+        let len = self.sigwaiters.len();
+        self.sigwaiters.retain(|&(waiter, _, _)| waiter != tid);
+        self.sigwaiters.len() != len
+    }
+
+    /// 取出第一个在等待 `signum` 的 sigtimedwait 线程，`tid` 非空时只匹配该线程
+    ///
+    /// 返回线程与它的 SigInfo 地址并取消它的超时；信号本身仍需调用方从待处理集合中取出。
+    fn take_sigwaiter(
+        &mut self,
+        signum: SignalNo,
+        tid: Option<ThreadId>,
+    ) -> Option<(ThreadId, usize)> {
+        let bit = 1usize << signum as usize;
+        let idx = self
+            .sigwaiters
+            .iter()
+            .position(|&(waiter, set, _)| set & bit != 0 && tid.map_or(true, |t| t == waiter))?;
+        let (waiter, _, info) = self.sigwaiters.remove(idx);
+        SLEEPERS.lock().cancel(waiter);
+        Some((waiter, info))
     }
 
     fn add_waittid_waiter(&mut self, target: ThreadId, waiter: ThreadId) {
@@ -734,96 +1353,162 @@ impl Process {
         self.fd_table[fd] = None;
         0
     }
-}
 
-struct ProcManager {
-    store: BTreeMap<ProcId, Process>,
-}
+    /// 把 `fd` 的句柄放到最小的空闲描述符上，两者共享同一个 `FileHandle`
+    fn dup_fd(&mut self, fd: usize) -> isize {
+        let Some(file) = self.get_fd(fd) else {
+            return -1;
+        };
+        self.alloc_fd(file) as isize
+    }
 
-impl ProcManager {
-    fn new() -> Self {
-        Self {
-            store: BTreeMap::new(),
+    /// 把 `old_fd` 的句柄放到 `new_fd` 上，`new_fd` 原有的句柄被关闭
+    ///
+    /// `new_fd` 不小于 [`FD_LIMIT`] 时失败，避免按用户给的值扩大描述符表。
+    fn dup2_fd(&mut self, old_fd: usize, new_fd: usize) -> isize {
+        if new_fd >= FD_LIMIT {
+            return -1;
         }
+        let Some(file) = self.get_fd(old_fd) else {
+            return -1;
+        };
+        if new_fd >= self.fd_table.len() {
+            self.fd_table.resize(new_fd + 1, None);
+        }
+        self.fd_table[new_fd] = Some(file);
+        new_fd as isize
     }
 }
 
-impl Manage<Process, ProcId> for ProcManager {
-    fn insert(&mut self, id: ProcId, item: Process) {
-        self.store.insert(id, item);
-    }
-
-    fn delete(&mut self, id: ProcId) {
-        self.store.remove(&id);
-    }
+type ProcManager = MapScheduler<Process, ProcId>;
+type ThreadManager = MapScheduler<Thread, ThreadId, PrioritySchedule<ThreadId>>;
 
-    fn get_mut(&mut self, id: ProcId) -> Option<&mut Process> {
-        self.store.get_mut(&id)
-    }
+fn current_space() -> Option<&'static mut AddressSpace<Sv39, Sv39Manager>> {
+    CurrentTask::space()
 }
 
-struct ThreadManager {
-    store: BTreeMap<ThreadId, Thread>,
-    ready: VecDeque<ThreadId>,
+fn current_process_mut() -> Option<&'static mut Process> {
+    unsafe { PROCESSOR.as_mut() }?.get_current_proc()
 }
 
-impl ThreadManager {
-    fn new() -> Self {
-        Self {
-            store: BTreeMap::new(),
-            ready: VecDeque::new(),
-        }
+fn wake_thread_with_ret(tid: ThreadId, ret: isize) {
+    let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
+        return;
+    };
+    if let Some(thread) = processor.get_task(tid) {
+        // 限时等待的线程被提前唤醒时撤销超时；条件变量的等待者可能在超时后
+        // 才等到互斥锁，此时由条件变量告知这次等待已超时
+        let ret = match thread.sync_wait.take() {
+            Some(wait) => {
+                SLEEPERS.lock().cancel(tid);
+                match wait {
+                    SyncWait::Condvar(condvar) if condvar.take_timeout(tid) => -1,
+                    _ => ret,
+                }
+            }
+            None => ret,
+        };
+        *thread.context.context.a_mut(0) = ret as usize;
+        processor.re_enque(tid);
     }
 }
 
-impl Manage<Thread, ThreadId> for ThreadManager {
-    fn insert(&mut self, id: ThreadId, item: Thread) {
-        self.store.insert(id, item);
-    }
-
-    fn delete(&mut self, id: ThreadId) {
-        self.store.remove(&id);
-    }
-
-    fn get_mut(&mut self, id: ThreadId) -> Option<&mut Thread> {
-        self.store.get_mut(&id)
-    }
+/// 让当前线程阻塞在管道上
+///
+/// 把 pc 退回到 `ecall`，线程被唤醒后重新执行这次 read/write。
+fn block_on_pipe() -> isize {
+    let Some(tid) = CurrentTask::tid() else {
+        return -1;
+    };
+    let Some(thread) = unsafe { PROCESSOR.as_mut() }.and_then(|p| p.get_task(tid)) else {
+        return -1;
+    };
+    *thread.context.context.pc_mut() -= 4;
+    PIPE_WAITERS.lock().push(tid);
+    BLOCKED_RETURN
 }
 
-impl Schedule<ThreadId> for ThreadManager {
-    fn add(&mut self, id: ThreadId) {
-        self.ready.push_back(id);
-    }
-
-    fn fetch(&mut self) -> Option<ThreadId> {
-        self.ready.pop_front()
+/// 唤醒所有阻塞在管道上的线程
+fn wake_pipe_waiters() {
+    let waiters = core::mem::take(&mut *PIPE_WAITERS.lock());
+    let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
+        return;
+    };
+    for tid in waiters {
+        if processor.get_task(tid).is_some() {
+            // 带超时的 ppoll 同时登记在睡眠队列上，撤销以免到期时重复入队
+            SLEEPERS.lock().cancel(tid);
+            processor.re_enque(tid);
+        }
     }
 }
 
-fn alloc_pid_nonzero() -> ProcId {
-    loop {
-        let pid = ProcId::new();
-        if pid.get_usize() != 0 {
-            return pid;
+/// 取出所有到期时刻不晚于 `now` 的闹钟，向对应进程发送 SIGALRM
+fn fire_expired_alarms(now: u64) {
+    let expired = ALARMS.lock().expire(now);
+    let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
+        return;
+    };
+    for pid in expired {
+        if let Some(proc) = processor.get_proc(pid) {
+            send_proc_signal(proc, SignalNo::SIGALRM);
         }
     }
 }
 
-fn current_space() -> Option<&'static AddressSpace<Sv39, Sv39Manager>> {
-    unsafe { CURRENT_SPACE.and_then(|p| p.as_ref()) }
-}
-
-fn current_process_mut() -> Option<&'static mut Process> {
-    unsafe { PROCESSOR.as_mut() }?.get_current_proc()
+/// 从 RTC 读取当前墙上时间，换算为 `time` 寄存器为 0 时的时刻交给 `syscall`
+fn init_boot_realtime() {
+    // 先读低 32 位，设备会锁存对应的高 32 位
+    let low = unsafe { core::ptr::read_volatile(GOLDFISH_RTC as *const u32) };
+    let high = unsafe { core::ptr::read_volatile((GOLDFISH_RTC + 4) as *const u32) };
+    let now = ((high as u64) << 32) | low as u64;
+    let elapsed = TimeSpec::from_ticks(riscv::register::time::read64()).as_nanos();
+    syscall::set_boot_realtime(TimeSpec::from_nanos(now.saturating_sub(elapsed)));
 }
 
-fn wake_thread_with_ret(tid: ThreadId, ret: isize) {
+/// 唤醒所有唤醒时刻不晚于 `now` 的睡眠线程
+///
+/// 阻塞在 `ppoll` 中的线程会重新执行这次调用，不改写返回值，并从管道等待队列中撤下。
+fn wake_expired_sleepers(now: u64) {
+    let expired = SLEEPERS.lock().expire(now);
     let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
         return;
     };
-    if let Some(thread) = processor.get_task(tid) {
-        *thread.context.context.a_mut(0) = ret as usize;
-        processor.re_enque(tid);
+    for tid in expired {
+        match processor.get_task(tid) {
+            Some(thread) if thread.poll_deadline.is_some() => {
+                PIPE_WAITERS.lock().retain(|&waiter| waiter != tid);
+                processor.re_enque(tid);
+            }
+            // 让同步对象移出所有到期的等待者；超时返回值由 wake_thread_with_ret 决定
+            Some(Thread {
+                sync_wait: Some(SyncWait::Condvar(condvar)),
+                ..
+            }) => {
+                let condvar = Arc::clone(condvar);
+                for ready in condvar.expire(now) {
+                    wake_thread_with_ret(ready, 0);
+                }
+            }
+            Some(Thread {
+                sync_wait: Some(SyncWait::Semaphore(sem)),
+                ..
+            }) => {
+                let sem = Arc::clone(sem);
+                for expired in sem.expire(now) {
+                    wake_thread_with_ret(expired, -1);
+                }
+            }
+            Some(thread) => {
+                // sigtimedwait 超时返回 -1，nanosleep 等睡眠到期返回 0
+                let pid = thread.pid;
+                let timed_out = processor
+                    .get_proc(pid)
+                    .is_some_and(|proc| proc.cancel_sigwaiter(tid));
+                wake_thread_with_ret(tid, if timed_out { -1 } else { 0 });
+            }
+            None => {}
+        }
     }
 }
 
@@ -853,7 +1538,25 @@ fn handle_current_signals(pid: ProcId, tid: ThreadId) -> signal::SignalResult {
     let Some(thread_ptr) = processor.get_task(tid).map(|t| t as *mut Thread) else {
         return signal::SignalResult::ProcessKilled(-3);
     };
-    unsafe { (*proc_ptr).signal.handle_signals(&mut (*thread_ptr).context.context) }
+    let (proc, thread) = unsafe { (&mut *proc_ptr, &mut *thread_ptr) };
+    let result = proc.signal.handle_signals(&mut thread.context.context);
+    if !matches!(result, signal::SignalResult::NoSignal) {
+        return result;
+    }
+    // 进程级没有可投递的信号时，再尝试投递定向到本线程的信号
+    let mut pending = thread.pending;
+    while pending != 0 {
+        let bit = pending.trailing_zeros() as usize;
+        pending &= !(1 << bit);
+        if let Some(result) = proc
+            .signal
+            .deliver_signal(SignalNo::from(bit), &mut thread.context.context)
+        {
+            thread.pending &= !(1 << bit);
+            return result;
+        }
+    }
+    signal::SignalResult::NoSignal
 }
 
 fn exit_current_thread(pid: ProcId, tid: ThreadId, exit_code: isize) {
@@ -861,46 +1564,146 @@ fn exit_current_thread(pid: ProcId, tid: ThreadId, exit_code: isize) {
     let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
         return;
     };
+    let (utime, stime) = processor
+        .get_task(tid)
+        .map_or((0, 0), |thread| (thread.utime, thread.stime));
+    let last_thread = processor.thread_count(pid) == 1;
+    let mut vfork_parent = None;
     if let Some(proc) = processor.get_proc(pid) {
         proc.remove_thread_stack(tid);
+        // 线程的时间并入进程；最后一个线程退出时留给父进程 waitpid 回收
+        proc.times.tms_utime += utime;
+        proc.times.tms_stime += stime;
+        if last_thread {
+            let t = proc.times;
+            EXITED_TIMES.lock().insert(
+                pid,
+                (t.tms_utime + t.tms_cutime, t.tms_stime + t.tms_cstime),
+            );
+        }
+        if last_thread {
+            vfork_parent = proc.vfork_parent.take();
+            proc.sync_file_mappings(0, usize::MAX);
+            // 关闭所有文件描述符，对端阻塞的管道读写得以看到 EOF 或断开
+            proc.fd_table.clear();
+        }
+    }
+    if last_thread {
+        wake_pipe_waiters();
+        ALARMS.lock().cancel(pid);
+        // 进程删除后无法再 waittid，归还本线程和未被回收的线程 ID
+        ThreadId::free(tid);
+        for dead in processor.dead_threads(pid) {
+            ThreadId::free(dead);
+        }
+    }
+    // vfork 子进程退出，释放阻塞的父线程
+    if let Some((_, parent_tid)) = vfork_parent {
+        wake_thread_with_ret(parent_tid, pid.get_usize() as isize);
+    }
+    // 会话首进程退出，孤儿进程组的成员收到 SIGHUP
+    for pid in processor.make_current_exited(exit_code) {
+        if let Some(proc) = processor.get_proc(pid) {
+            send_proc_signal(proc, SignalNo::SIGHUP);
+        }
+    }
+}
+
+/// 向进程发送信号
+fn send_proc_signal(target: &mut Process, signum: SignalNo) {
+    target.signal.add_signal(signum);
+    // 有线程在 sigwaitinfo 等待该信号时直接交给它，不再走异步处理
+    if let Some((waiter, info)) = target.take_sigwaiter(signum, None) {
+        target.signal.dequeue_from_set(1 << signum as usize);
+        write_user_siginfo(&mut target.space, info, signum);
+        wake_thread_with_ret(waiter, signum as isize);
     }
-    processor.make_current_exited(exit_code);
 }
 
 fn read_user_bytes(
-    space: &AddressSpace<Sv39, Sv39Manager>,
+    space: &mut AddressSpace<Sv39, Sv39Manager>,
     ptr: *const u8,
     len: usize,
 ) -> Option<Vec<u8>> {
+    if !space.populate(VAddr::new(ptr as usize), len, FaultKind::Load) {
+        return None;
+    }
+    let segments = space.translate_range(
+        VAddr::<Sv39>::new(ptr as usize),
+        len,
+        VmFlags::build_from_str("R"),
+    )?;
     let mut out = Vec::with_capacity(len);
-    let flags = VmFlags::build_from_str("R");
-    for i in 0..len {
-        let vaddr = VAddr::<Sv39>::new(ptr as usize + i);
-        let src = space.translate::<u8>(vaddr, flags)?;
-        out.push(unsafe { *src.as_ptr() });
+    for (src, chunk) in segments {
+        out.extend_from_slice(unsafe { core::slice::from_raw_parts(src.as_ptr(), chunk) });
     }
     Some(out)
 }
 
+/// 读取用户给出的相对超时，换算为以 tick 计的截止时刻
+fn read_user_deadline(
+    space: &mut AddressSpace<Sv39, Sv39Manager>,
+    timeout: *const TimeSpec,
+) -> Option<u64> {
+    let size = core::mem::size_of::<TimeSpec>();
+    let raw = read_user_bytes(space, timeout.cast::<u8>(), size)?;
+    let timeout = unsafe { core::ptr::read_unaligned(raw.as_ptr().cast::<TimeSpec>()) };
+    Some(riscv::register::time::read64().saturating_add(timeout.to_ticks()))
+}
+
+/// 把用户缓冲区按页切分为若干内核可访问的切片，交给文件直接读写，省去中间的 `Vec`
+fn user_buffer(
+    space: &mut AddressSpace<Sv39, Sv39Manager>,
+    ptr: *const u8,
+    len: usize,
+    flags: &str,
+) -> Option<UserBuffer> {
+    // 内核要写入时按写访问补齐，写时复制页先分出私有副本
+    let kind = if flags.contains('W') {
+        FaultKind::Store
+    } else {
+        FaultKind::Load
+    };
+    if !space.populate(VAddr::new(ptr as usize), len, kind) {
+        return None;
+    }
+    let segments = space.translate_range(
+        VAddr::<Sv39>::new(ptr as usize),
+        len,
+        VmFlags::build_from_str(flags),
+    )?;
+    let buffers = segments
+        .into_iter()
+        .map(|(ptr, chunk)| unsafe { core::slice::from_raw_parts_mut(ptr.as_ptr(), chunk) })
+        .collect();
+    Some(UserBuffer::new(buffers))
+}
+
 fn write_user_bytes(
-    space: &AddressSpace<Sv39, Sv39Manager>,
+    space: &mut AddressSpace<Sv39, Sv39Manager>,
     ptr: *mut u8,
     data: &[u8],
 ) -> bool {
-    let flags = VmFlags::build_from_str("W");
-    for (i, byte) in data.iter().copied().enumerate() {
-        let vaddr = VAddr::<Sv39>::new(ptr as usize + i);
-        let dst = match space.translate::<u8>(vaddr, flags) {
-            Some(p) => p,
-            None => return false,
-        };
-        unsafe { *dst.as_ptr() = byte };
+    if !space.populate(VAddr::new(ptr as usize), data.len(), FaultKind::Store) {
+        return false;
+    }
+    let Some(segments) = space.translate_range(
+        VAddr::<Sv39>::new(ptr as usize),
+        data.len(),
+        VmFlags::build_from_str("W"),
+    ) else {
+        return false;
+    };
+    let mut copied = 0;
+    for (dst, chunk) in segments {
+        unsafe { core::ptr::copy_nonoverlapping(data[copied..].as_ptr(), dst.as_ptr(), chunk) };
+        copied += chunk;
     }
     true
 }
 
 fn read_user_signal_action(
-    space: &AddressSpace<Sv39, Sv39Manager>,
+    space: &mut AddressSpace<Sv39, Sv39Manager>,
     ptr: *const syscall::SignalAction,
 ) -> Option<syscall::SignalAction> {
     const SIGNAL_ACTION_SIZE: usize = core::mem::size_of::<syscall::SignalAction>();
@@ -913,10 +1716,55 @@ fn read_user_signal_action(
     Some(unsafe { core::ptr::read_unaligned(raw.as_ptr().cast::<syscall::SignalAction>()) })
 }
 
-fn write_user_signal_action(
-    space: &AddressSpace<Sv39, Sv39Manager>,
-    ptr: *mut syscall::SignalAction,
-    action: &syscall::SignalAction,
+/// stdin 是否有可读字符；缓冲为空时向 SBI 预读一个字符
+fn stdin_ready() -> bool {
+    let mut buf = STDIN_BUFFER.lock();
+    if buf.is_empty() {
+        #[allow(deprecated)]
+        let ch = legacy::console_getchar();
+        if ch != usize::MAX {
+            buf.push_back(ch as u8);
+        }
+    }
+    !buf.is_empty()
+}
+
+/// 计算单个 fd 的就绪事件，并返回是否在等待控制台输入
+///
+/// 控制台输入没有中断通知，只能由调用方定期重新检查。
+fn poll_revents(proc: &Process, pfd: &PollFd) -> (i16, bool) {
+    if pfd.fd < 0 {
+        return (0, false);
+    }
+    let Some(handle) = proc.get_fd(pfd.fd as usize) else {
+        return (POLLNVAL, false);
+    };
+    let (readable, writable, console) = {
+        let handle = handle.lock();
+        match handle.pipe() {
+            // 管道端在读写不会阻塞时就绪
+            Some(PipeEnd::Read(reader)) => (reader.is_ready(), false, false),
+            Some(PipeEnd::Write(writer)) => (false, writer.is_ready(), false),
+            None => {
+                let console = handle.inode.is_none();
+                (handle.readable(), handle.writable(), console)
+            }
+        }
+    };
+    let mut revents = 0;
+    if pfd.events & POLLIN != 0 && readable && (!console || stdin_ready()) {
+        revents |= POLLIN;
+    }
+    if pfd.events & POLLOUT != 0 && writable {
+        revents |= POLLOUT;
+    }
+    (revents, pfd.events & POLLIN != 0 && readable && console)
+}
+
+fn write_user_signal_action(
+    space: &mut AddressSpace<Sv39, Sv39Manager>,
+    ptr: *mut syscall::SignalAction,
+    action: &syscall::SignalAction,
 ) -> bool {
     const SIGNAL_ACTION_SIZE: usize = core::mem::size_of::<syscall::SignalAction>();
     let bytes =
@@ -924,19 +1772,77 @@ fn write_user_signal_action(
     write_user_bytes(space, ptr.cast::<u8>(), bytes)
 }
 
-fn read_user_cstr(space: &AddressSpace<Sv39, Sv39Manager>, ptr: *const u8) -> Option<String> {
+/// 向用户态 `info` 写入 `signum` 对应的 SigInfo，`info` 为 0 时不写
+fn write_user_siginfo(
+    space: &mut AddressSpace<Sv39, Sv39Manager>,
+    info: usize,
+    signum: SignalNo,
+) -> bool {
+    if info == 0 {
+        return true;
+    }
+    let siginfo = SigInfo {
+        si_signo: signum as i32,
+        si_errno: 0,
+        si_code: SI_USER,
+    };
+    let bytes = unsafe {
+        core::slice::from_raw_parts(
+            (&siginfo as *const SigInfo).cast::<u8>(),
+            core::mem::size_of::<SigInfo>(),
+        )
+    };
+    write_user_bytes(space, info as *mut u8, bytes)
+}
+
+/// 读取用户态 C 字符串失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CstrError {
+    /// 前 `max` 个字节内没有 `\0`
+    TooLong,
+    /// 地址未映射或不可读
+    Fault,
+    /// 内容不是合法 UTF-8
+    InvalidUtf8,
+}
+
+impl CstrError {
+    /// 对应的系统调用返回值（负的 errno）
+    fn errno(self) -> isize {
+        match self {
+            CstrError::TooLong => -36,     // ENAMETOOLONG
+            CstrError::Fault => -14,       // EFAULT
+            CstrError::InvalidUtf8 => -22, // EINVAL
+        }
+    }
+}
+
+/// 读取以 `\0` 结尾的用户态字符串，最多检查 `max` 个字节（含结尾的 `\0`）
+fn read_cstr_max(
+    space: &mut AddressSpace<Sv39, Sv39Manager>,
+    ptr: *const u8,
+    max: usize,
+) -> Result<String, CstrError> {
     let flags = VmFlags::build_from_str("R");
     let mut buf = Vec::new();
-    for i in 0..USER_CSTR_MAX {
+    for i in 0..max {
         let vaddr = VAddr::<Sv39>::new(ptr as usize + i);
-        let src = space.translate::<u8>(vaddr, flags)?;
+        // 每进入一页先补齐缺页
+        if (i == 0 || vaddr.offset() == 0) && !space.populate(vaddr, 1, FaultKind::Load) {
+            return Err(CstrError::Fault);
+        }
+        let src = space.translate::<u8>(vaddr, flags).ok_or(CstrError::Fault)?;
         let b = unsafe { *src.as_ptr() };
         if b == 0 {
-            return String::from_utf8(buf).ok();
+            return String::from_utf8(buf).map_err(|_| CstrError::InvalidUtf8);
         }
         buf.push(b);
     }
-    None
+    Err(CstrError::TooLong)
+}
+
+fn read_user_cstr(space: &mut AddressSpace<Sv39, Sv39Manager>, ptr: *const u8) -> Option<String> {
+    read_cstr_max(space, ptr, USER_CSTR_MAX).ok()
 }
 
 fn print_available_apps() {
@@ -961,26 +1867,6 @@ impl syscall::IO for SyscallContext {
             return -1;
         };
 
-        if fd == STDOUT || fd == STDDEBUG {
-            let Some(handle) = current_process_mut().and_then(|p| p.get_fd(fd)) else {
-                return -1;
-            };
-            if !handle.lock().writable() {
-                return -1;
-            }
-            let Some(data) = read_user_bytes(space, buf, count) else {
-                return -1;
-            };
-            for byte in data.iter().copied() {
-                print!("{}", byte as char);
-            }
-            return count as isize;
-        }
-
-        let Some(data) = read_user_bytes(space, buf, count) else {
-            return -1;
-        };
-
         let Some(file) = current_process_mut().and_then(|p| p.get_fd(fd)) else {
             return -1;
         };
@@ -989,13 +1875,36 @@ impl syscall::IO for SyscallContext {
         if !file.writable() {
             return -1;
         }
-        let Some(inode) = file.inode.as_ref() else {
+        if let Some(PipeEnd::Write(writer)) = file.pipe() {
+            // 一次最多写满管道缓冲区，不按用户给出的长度分配内核缓冲
+            let count = count.min(PIPE_BUFFER_SIZE);
+            let Some(data) = read_user_bytes(space, buf, count) else {
+                return -1;
+            };
+            return match writer.write(&data) {
+                Ok(written) => {
+                    wake_pipe_waiters();
+                    written as isize
+                }
+                Err(PipeError::WouldBlock) => block_on_pipe(),
+                Err(PipeError::BrokenPipe) => -1,
+            };
+        }
+        let Some(user_buf) = user_buffer(space, buf, count, "R") else {
             return -1;
         };
+        // 没有 Inode 的句柄是控制台，dup2 可以把它放到任意描述符上
+        if file.inode.is_none() {
+            for segment in user_buf.segments() {
+                for byte in segment.iter().copied() {
+                    print!("{}", byte as char);
+                }
+            }
+            return count as isize;
+        }
 
-        let written = inode.write_at(file.offset, &data);
-        file.offset += written;
-        written as isize
+        // 逐段直接写入文件；追加打开时每次写入都落在当前文件末尾
+        file.write(user_buf) as isize
     }
 
     fn read(&self, _caller: Caller, fd: usize, buf: *mut u8, count: usize) -> isize {
@@ -1007,79 +1916,299 @@ impl syscall::IO for SyscallContext {
             return -1;
         };
 
-        if fd == STDIN {
-            let Some(handle) = current_process_mut().and_then(|p| p.get_fd(fd)) else {
-                return -1;
-            };
-            if !handle.lock().readable() {
-                return -1;
-            }
+        let Some(file) = current_process_mut().and_then(|p| p.get_fd(fd)) else {
+            return -1;
+        };
 
-            let mut in_buf = Vec::with_capacity(count);
-            while in_buf.len() < count {
+        let mut file = file.lock();
+        if !file.readable() {
+            return -1;
+        }
+        // 管道为空且仍有写端时阻塞，写端全部关闭后读到 EOF
+        if let Some(PipeEnd::Read(reader)) = file.pipe() {
+            // 一次最多读出整个管道缓冲区，不按用户给出的长度分配内核缓冲
+            let mut out = vec![0u8; count.min(PIPE_BUFFER_SIZE)];
+            let read_len = match reader.read(&mut out) {
+                Ok(read_len) => read_len,
+                Err(_) => return block_on_pipe(),
+            };
+            wake_pipe_waiters();
+            return if write_user_bytes(space, buf, &out[..read_len]) {
+                read_len as isize
+            } else {
+                -1
+            };
+        }
+        if file.inode.is_none() {
+            // 控制台：先取走之前缓冲的输入，再轮询 SBI 控制台；控制台不会报告 EOF
+            let mode = file.read_mode();
+            let mut in_buf = vec![0u8; count];
+            let len = mode.fill(&mut in_buf, || {
+                if let Some(ch) = STDIN_BUFFER.lock().pop_front() {
+                    return ReadPoll::Byte(ch);
+                }
                 #[allow(deprecated)]
                 let ch = legacy::console_getchar();
                 if ch == usize::MAX {
-                    if in_buf.is_empty() {
-                        continue;
-                    }
-                    break;
+                    ReadPoll::Empty
+                } else {
+                    ReadPoll::Byte(ch as u8)
                 }
-                in_buf.push(ch as u8);
-            }
-            if write_user_bytes(space, buf, &in_buf) {
-                return in_buf.len() as isize;
+            });
+            if write_user_bytes(space, buf, &in_buf[..len]) {
+                return len as isize;
             }
             return -1;
         }
 
-        let Some(file) = current_process_mut().and_then(|p| p.get_fd(fd)) else {
+        let Some(user_buf) = user_buffer(space, buf, count, "W") else {
             return -1;
         };
+        // 逐段直接从文件读入用户页
+        file.read(user_buf) as isize
+    }
 
-        let mut file = file.lock();
-        if !file.readable() {
+    fn open(&self, _caller: Caller, path: *const u8, flags: u32) -> isize {
+        let Some(space) = current_space() else {
             return -1;
-        }
-        let Some(inode) = file.inode.as_ref() else {
+        };
+        let path = match read_cstr_max(space, path, USER_CSTR_MAX) {
+            Ok(path) => path,
+            Err(e) => return e.errno(),
+        };
+        let flags = OpenFlags::from_bits_truncate(flags);
+        let Some(file) = fs::FS.open(path.as_str(), flags) else {
+            return -1;
+        };
+
+        let kernel_file = Arc::new(SpinMutex::new(duplicate_file_handle(&file)));
+        let Some(proc) = current_process_mut() else {
             return -1;
         };
+        proc.alloc_fd(kernel_file) as isize
+    }
+
+    fn close(&self, _caller: Caller, fd: usize) -> isize {
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        let ret = proc.close_fd(fd);
+        // 关闭的可能是管道的最后一个读端或写端
+        wake_pipe_waiters();
+        ret
+    }
+
+    fn lseek(&self, _caller: Caller, fd: usize, offset: isize, whence: usize) -> isize {
+        let pos = match whence {
+            SEEK_SET if offset >= 0 => SeekFrom::Start(offset as usize),
+            SEEK_CUR => SeekFrom::Current(offset),
+            SEEK_END => SeekFrom::End(offset),
+            _ => return -1,
+        };
+        let Some(file) = current_process_mut().and_then(|p| p.get_fd(fd)) else {
+            return -1;
+        };
+        file.lock().seek(pos).map_or(-1, |offset| offset as isize)
+    }
+
+    fn dup(&self, _caller: Caller, fd: usize) -> isize {
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        proc.dup_fd(fd)
+    }
 
-        let mut out = vec![0u8; count];
-        let read_len = inode.read_at(file.offset, &mut out);
-        file.offset += read_len;
+    fn dup2(&self, _caller: Caller, oldfd: usize, newfd: usize) -> isize {
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        let ret = proc.dup2_fd(oldfd, newfd);
+        // 被覆盖的可能是管道的最后一个读端或写端
+        wake_pipe_waiters();
+        ret
+    }
 
-        if write_user_bytes(space, buf, &out[..read_len]) {
-            read_len as isize
+    fn getdents(&self, _caller: Caller, fd: usize, buf: *mut u8, len: usize) -> isize {
+        let Some(space) = current_space() else {
+            return -1;
+        };
+        let Some(file) = current_process_mut().and_then(|p| p.get_fd(fd)) else {
+            return -1;
+        };
+        let max = len / syscall::Dirent::SIZE;
+        if max == 0 {
+            return -1;
+        }
+        // 游标保存在句柄的 offset 中；缓冲区按实际读出的目录项数分配，不按用户给出的长度
+        let Some(entries) = file.lock().read_dirents(max) else {
+            return -1;
+        };
+        let mut out = Vec::with_capacity(entries.len() * syscall::Dirent::SIZE);
+        for (entry, inode_type) in &entries {
+            let d_type = match inode_type {
+                DiskInodeType::File => syscall::DT_REG,
+                DiskInodeType::Directory => syscall::DT_DIR,
+            };
+            let dirent = syscall::Dirent::new(entry.inode_number() as u64, d_type, entry.name());
+            out.extend_from_slice(dirent.as_bytes());
+        }
+        if write_user_bytes(space, buf, &out) {
+            out.len() as isize
         } else {
             -1
         }
     }
 
-    fn open(&self, _caller: Caller, path: *const u8, flags: u32) -> isize {
+    fn pipe(&self, _caller: Caller, fds: *mut usize) -> isize {
         let Some(space) = current_space() else {
             return -1;
         };
-        let Some(path) = read_user_cstr(space, path) else {
+        let Some(proc) = current_process_mut() else {
             return -1;
         };
-        let flags = OpenFlags::from_bits_truncate(flags);
-        let Some(file) = fs::FS.open(path.as_str(), flags) else {
+        let (reader, writer) = easy_fs::pipe();
+        let read_end = FileHandle::from_pipe(PipeEnd::Read(reader));
+        let write_end = FileHandle::from_pipe(PipeEnd::Write(writer));
+        let read_fd = proc.alloc_fd(Arc::new(SpinMutex::new(read_end)));
+        let write_fd = proc.alloc_fd(Arc::new(SpinMutex::new(write_end)));
+        let bytes: Vec<u8> = [read_fd, write_fd]
+            .iter()
+            .flat_map(|fd| fd.to_ne_bytes())
+            .collect();
+        if !write_user_bytes(space, fds.cast::<u8>(), &bytes) {
+            proc.close_fd(read_fd);
+            proc.close_fd(write_fd);
+            return -1;
+        }
+        0
+    }
+
+    fn ppoll(
+        &self,
+        _caller: Caller,
+        fds: *mut PollFd,
+        nfds: usize,
+        timeout: *const TimeSpec,
+        sigmask: *const usize,
+        _sigsetsize: usize,
+    ) -> isize {
+        const POLL_FD_SIZE: usize = core::mem::size_of::<PollFd>();
+        // 不支持在等待期间临时替换信号掩码
+        if !sigmask.is_null() {
+            return -1;
+        }
+        let Some(space) = current_space() else {
             return -1;
         };
+        let Some(tid) = CurrentTask::tid() else {
+            return -1;
+        };
+        let now = riscv::register::time::read64();
+        // 被唤醒后重新执行本次调用时，沿用首次进入时算出的截止时刻
+        let resumed = unsafe { PROCESSOR.as_mut() }
+            .and_then(|p| p.get_task(tid))
+            .and_then(|thread| thread.poll_deadline.take());
+        let deadline = match resumed {
+            Some(deadline) => deadline,
+            None if timeout.is_null() => u64::MAX,
+            None => {
+                let size = core::mem::size_of::<TimeSpec>();
+                let Some(raw) = read_user_bytes(space, timeout.cast::<u8>(), size) else {
+                    return -1;
+                };
+                let timeout = unsafe { core::ptr::read_unaligned(raw.as_ptr().cast::<TimeSpec>()) };
+                now.saturating_add(timeout.to_ticks())
+            }
+        };
 
-        let kernel_file = Arc::new(SpinMutex::new(duplicate_file_handle(&file)));
         let Some(proc) = current_process_mut() else {
             return -1;
         };
-        proc.alloc_fd(kernel_file) as isize
+        let Some(len) = nfds.checked_mul(POLL_FD_SIZE) else {
+            return -1;
+        };
+        let Some(mut raw) = read_user_bytes(space, fds.cast::<u8>(), len) else {
+            return -1;
+        };
+        let mut ready = 0;
+        let mut console = false;
+        for chunk in raw.chunks_exact_mut(POLL_FD_SIZE) {
+            let mut pfd = unsafe { core::ptr::read_unaligned(chunk.as_ptr().cast::<PollFd>()) };
+            let (revents, waits_console) = poll_revents(proc, &pfd);
+            pfd.revents = revents;
+            console |= waits_console;
+            if pfd.revents != 0 {
+                ready += 1;
+            }
+            unsafe { core::ptr::write_unaligned(chunk.as_mut_ptr().cast::<PollFd>(), pfd) };
+        }
+        if ready > 0 || now >= deadline {
+            return if write_user_bytes(space, fds.cast::<u8>(), &raw) {
+                ready
+            } else {
+                -1
+            };
+        }
+
+        // 暂无就绪项：挂到管道的等待队列上，被唤醒后重新执行本次调用
+        let Some(thread) = unsafe { PROCESSOR.as_mut() }.and_then(|p| p.get_task(tid)) else {
+            return -1;
+        };
+        thread.poll_deadline = Some(deadline);
+        *thread.context.context.pc_mut() -= 4;
+        PIPE_WAITERS.lock().push(tid);
+        // 超时由睡眠队列唤醒；等待控制台输入时每个时间片重新检查一次
+        let wake_at = if console {
+            deadline.min(now.saturating_add(TIMER_SLICE.to_ticks()))
+        } else {
+            deadline
+        };
+        if wake_at != u64::MAX {
+            SLEEPERS.lock().push(tid, wake_at);
+        }
+        BLOCKED_RETURN
     }
 
-    fn close(&self, _caller: Caller, fd: usize) -> isize {
-        let Some(proc) = current_process_mut() else {
+    fn rename(&self, _caller: Caller, old_path: *const u8, new_path: *const u8) -> isize {
+        let Some(space) = current_space() else {
+            return -1;
+        };
+        let (Some(old_path), Some(new_path)) =
+            (read_user_cstr(space, old_path), read_user_cstr(space, new_path))
+        else {
+            return -1;
+        };
+        fs::FS.rename(old_path.as_str(), new_path.as_str())
+    }
+
+    fn statfs(&self, _caller: Caller, path: *const u8, buf: *mut syscall::FsStat) -> isize {
+        let Some(space) = current_space() else {
+            return -1;
+        };
+        let path = match read_cstr_max(space, path, USER_CSTR_MAX) {
+            Ok(path) => path,
+            Err(e) => return e.errno(),
+        };
+        let Some(stat) = fs::FS.stat_fs(path.as_str()) else {
             return -1;
         };
-        proc.close_fd(fd)
+        let stat = syscall::FsStat {
+            total_blocks: stat.total_blocks as u64,
+            free_blocks: stat.free_blocks as u64,
+            total_inodes: stat.total_inodes as u64,
+            free_inodes: stat.free_inodes as u64,
+        };
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                (&stat as *const syscall::FsStat).cast::<u8>(),
+                core::mem::size_of::<syscall::FsStat>(),
+            )
+        };
+        if write_user_bytes(space, buf.cast::<u8>(), bytes) {
+            0
+        } else {
+            -1
+        }
     }
 }
 
@@ -1091,11 +2220,9 @@ impl syscall::Process for SyscallContext {
         let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
             return -1;
         };
-        let parent_pid = unsafe { CURRENT_PID.unwrap_or(ProcId::from_usize(usize::MAX)) };
-        let parent_tid = unsafe { CURRENT_TID.unwrap_or(ThreadId::from_usize(usize::MAX)) };
-        if parent_pid.get_usize() == usize::MAX || parent_tid.get_usize() == usize::MAX {
+        let (Some(parent_pid), Some(parent_tid)) = (CurrentTask::pid(), CurrentTask::tid()) else {
             return -1;
-        }
+        };
 
         let (mut child_proc, parent_stack_slot) = {
             let Some(parent_proc) = processor.get_proc(parent_pid) else {
@@ -1108,16 +2235,16 @@ impl syscall::Process for SyscallContext {
             (child_proc, stack_slot)
         };
 
-        let mut child_ctx = {
+        let (mut child_ctx, child_name) = {
             let Some(parent_thread) = processor.get_task(parent_tid) else {
                 return -1;
             };
-            parent_thread.context.context.clone()
+            (parent_thread.context.context.clone(), parent_thread.name)
         };
         *child_ctx.a_mut(0) = 0;
 
         let child_pid = child_proc.pid;
-        let child_tid = ThreadId::new();
+        let child_tid = ThreadId::alloc();
         child_proc.thread_stacks.insert(child_tid, parent_stack_slot);
         let child_thread = Thread {
             tid: child_tid,
@@ -1126,6 +2253,14 @@ impl syscall::Process for SyscallContext {
                 context: child_ctx,
                 satp: child_proc.satp(),
             },
+            pending: 0,
+            syscalls_since_yield: 0,
+            utime: 0,
+            stime: 0,
+            name: child_name,
+            priority: DEFAULT_PRIORITY,
+            poll_deadline: None,
+            sync_wait: None,
         };
 
         processor.add_proc(child_pid, child_proc, parent_pid);
@@ -1133,12 +2268,67 @@ impl syscall::Process for SyscallContext {
         child_pid.get_usize() as isize
     }
 
+    fn vfork(&self, _caller: Caller) -> isize {
+        let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
+            return -1;
+        };
+        let (Some(parent_pid), Some(parent_tid)) = (CurrentTask::pid(), CurrentTask::tid()) else {
+            return -1;
+        };
+
+        let (mut child_proc, parent_stack_slot, parent_satp) = {
+            let Some(parent_proc) = processor.get_proc(parent_pid) else {
+                return -1;
+            };
+            let Some(child_proc) = parent_proc.vfork(parent_tid) else {
+                return -1;
+            };
+            let stack_slot = parent_proc.stack_slot_of(parent_tid).unwrap_or(0);
+            (child_proc, stack_slot, parent_proc.satp())
+        };
+
+        let (mut child_ctx, child_name) = {
+            let Some(parent_thread) = processor.get_task(parent_tid) else {
+                return -1;
+            };
+            (parent_thread.context.context.clone(), parent_thread.name)
+        };
+        *child_ctx.a_mut(0) = 0;
+
+        let child_pid = child_proc.pid;
+        let child_tid = ThreadId::alloc();
+        child_proc.thread_stacks.insert(child_tid, parent_stack_slot);
+        // 不复制任何页面：子进程直接在父进程的页表和栈上运行
+        let child_thread = Thread {
+            tid: child_tid,
+            pid: child_pid,
+            context: ForeignContext {
+                context: child_ctx,
+                satp: parent_satp,
+            },
+            pending: 0,
+            syscalls_since_yield: 0,
+            utime: 0,
+            stime: 0,
+            name: child_name,
+            priority: DEFAULT_PRIORITY,
+            poll_deadline: None,
+            sync_wait: None,
+        };
+
+        processor.add_proc(child_pid, child_proc, parent_pid);
+        processor.add(child_tid, child_thread, child_pid);
+        // 父线程阻塞到子进程 exec 或 exit，届时由它们写回子进程 pid
+        BLOCKED_RETURN
+    }
+
     fn exec(&self, _caller: Caller, path: *const u8) -> isize {
         let Some(space) = current_space() else {
             return -1;
         };
-        let Some(path) = read_user_cstr(space, path) else {
-            return -1;
+        let path = match read_cstr_max(space, path, USER_CSTR_MAX) {
+            Ok(path) => path,
+            Err(e) => return e.errno(),
         };
 
         let Some(file) = fs::FS.open(path.as_str(), OpenFlags::RDONLY) else {
@@ -1154,22 +2344,31 @@ impl syscall::Process for SyscallContext {
         let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
             return -1;
         };
-        let pid = unsafe { CURRENT_PID.unwrap_or(ProcId::from_usize(usize::MAX)) };
-        let tid = unsafe { CURRENT_TID.unwrap_or(ThreadId::from_usize(usize::MAX)) };
-        if pid.get_usize() == usize::MAX || tid.get_usize() == usize::MAX {
+        let (Some(pid), Some(tid)) = (CurrentTask::pid(), CurrentTask::tid()) else {
             return -1;
-        }
+        };
 
         let Some(new_context) = ({
             let Some(proc) = processor.get_proc(pid) else {
                 return -1;
             };
-            proc.exec(tid, &elf_data, kernel_space)
+            proc.exec(tid, &elf_data, kernel_space, path.as_str())
         }) else {
             return -1;
         };
+        let (name, vfork_parent) = match processor.get_proc(pid) {
+            Some(proc) => (Some(proc.name), proc.vfork_parent.take()),
+            None => (None, None),
+        };
+        // vfork 子进程已换上自己的地址空间，父进程可以继续运行
+        if let Some((_, parent_tid)) = vfork_parent {
+            wake_thread_with_ret(parent_tid, pid.get_usize() as isize);
+        }
         if let Some(thread) = processor.get_task(tid) {
             thread.context = new_context;
+            if let Some(name) = name {
+                thread.name = name;
+            }
             0
         } else {
             -1
@@ -1199,8 +2398,18 @@ impl syscall::Process for SyscallContext {
             return -1;
         };
         match processor.wait(child_pid) {
-            Some((sentinel, -1)) if sentinel.get_usize() == usize::MAX - 1 => -2,
-            Some((reaped_pid, code)) => {
+            WaitResult::Alive => -2,
+            WaitResult::Exited(reaped_pid, code) => {
+                if let Some((utime, stime)) = EXITED_TIMES.lock().remove(&reaped_pid) {
+                    if let Some(process) = current_process_mut() {
+                        process.times.tms_cutime += utime;
+                        process.times.tms_cstime += stime;
+                    }
+                }
+                // 以它为组长的进程组或会话还在时，pid 暂不复用
+                if !processor.id_in_use(reaped_pid) {
+                    ProcId::free(reaped_pid);
+                }
                 if !exit_code_ptr.is_null() {
                     let Some(space) = current_space() else {
                         return -1;
@@ -1212,59 +2421,227 @@ impl syscall::Process for SyscallContext {
                 }
                 reaped_pid.get_usize() as isize
             }
-            None => -1,
+            WaitResult::NoChild => -1,
         }
     }
 
     fn getpid(&self, _caller: Caller) -> isize {
-        unsafe { CURRENT_PID.map(|p| p.get_usize() as isize).unwrap_or(-1) }
+        CurrentTask::pid().map(|p| p.get_usize() as isize).unwrap_or(-1)
     }
-}
 
-impl syscall::Thread for SyscallContext {
-    fn thread_create(&self, _caller: Caller, entry: usize, arg: usize) -> isize {
-        let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
-            return -1;
-        };
-        let pid = unsafe { CURRENT_PID.unwrap_or(ProcId::from_usize(usize::MAX)) };
-        if pid.get_usize() == usize::MAX {
+    fn setrlimit(&self, _caller: Caller, resource: usize, limit: usize) -> isize {
+        // 目前只支持 RLIMIT_AS；超限的映射请求失败而不是分配
+        if resource != RLIMIT_AS {
             return -1;
         }
-        let tid = ThreadId::new();
-
-        let (satp, stack_top) = {
-            let Some(proc) = processor.get_proc(pid) else {
-                return -1;
-            };
-            let Some(stack_top) = proc.alloc_thread_stack(tid) else {
-                return -1;
-            };
-            (proc.satp(), stack_top)
+        let Some(process) = current_process_mut() else {
+            return -1;
         };
+        process.as_limit = limit;
+        0
+    }
 
-        let mut context = kernel_context::LocalContext::user(entry);
-        *context.sp_mut() = stack_top;
-        *context.a_mut(0) = arg;
-        let thread = Thread {
+    fn getuid(&self, _caller: Caller) -> isize {
+        current_process_mut().map_or(-1, |process| process.uid as isize)
+    }
+
+    fn getgid(&self, _caller: Caller) -> isize {
+        current_process_mut().map_or(-1, |process| process.gid as isize)
+    }
+
+    fn setuid(&self, _caller: Caller, uid: usize) -> isize {
+        let Some(process) = current_process_mut() else {
+            return -1;
+        };
+        process.uid = uid;
+        0
+    }
+
+    fn setgid(&self, _caller: Caller, gid: usize) -> isize {
+        let Some(process) = current_process_mut() else {
+            return -1;
+        };
+        process.gid = gid;
+        0
+    }
+
+    fn setpgid(&self, _caller: Caller, pid: usize, pgid: usize) -> isize {
+        let (Some(processor), Some(current)) = (unsafe { PROCESSOR.as_mut() }, CurrentTask::pid())
+        else {
+            return -1;
+        };
+        let pid = if pid == 0 { current } else { ProcId::from_usize(pid) };
+        let pgid = if pgid == 0 { pid } else { ProcId::from_usize(pgid) };
+        // 只能操作自己或同一会话中的进程
+        if processor.getsid(pid) != processor.getsid(current) {
+            return -1;
+        }
+        if processor.setpgid(pid, pgid) {
+            0
+        } else {
+            -1
+        }
+    }
+
+    fn getpgid(&self, _caller: Caller, pid: usize) -> isize {
+        let (Some(processor), Some(current)) = (unsafe { PROCESSOR.as_mut() }, CurrentTask::pid())
+        else {
+            return -1;
+        };
+        let pid = if pid == 0 { current } else { ProcId::from_usize(pid) };
+        processor.getpgid(pid).map_or(-1, |pgid| pgid.get_usize() as isize)
+    }
+
+    fn getsid(&self, _caller: Caller, pid: usize) -> isize {
+        let (Some(processor), Some(current)) = (unsafe { PROCESSOR.as_mut() }, CurrentTask::pid())
+        else {
+            return -1;
+        };
+        let pid = if pid == 0 { current } else { ProcId::from_usize(pid) };
+        processor.getsid(pid).map_or(-1, |sid| sid.get_usize() as isize)
+    }
+
+    fn setsid(&self, _caller: Caller) -> isize {
+        let (Some(processor), Some(current)) = (unsafe { PROCESSOR.as_mut() }, CurrentTask::pid())
+        else {
+            return -1;
+        };
+        processor.setsid(current).map_or(-1, |sid| sid.get_usize() as isize)
+    }
+
+    fn times(&self, _caller: Caller, tms: *mut Tms) -> isize {
+        let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
+            return -1;
+        };
+        let Some(pid) = CurrentTask::pid() else {
+            return -1;
+        };
+        let Some(space) = current_space() else {
+            return -1;
+        };
+        // 存活线程的时间加上已退出线程并入进程的时间
+        let tids = processor.get_thread(pid).cloned().unwrap_or_default();
+        let (mut utime, mut stime) = (0, 0);
+        for tid in tids {
+            if let Some(thread) = processor.get_task(tid) {
+                utime += thread.utime;
+                stime += thread.stime;
+            }
+        }
+        let Some(proc) = processor.get_proc(pid) else {
+            return -1;
+        };
+        let value = Tms {
+            tms_utime: proc.times.tms_utime + utime,
+            tms_stime: proc.times.tms_stime + stime,
+            tms_cutime: proc.times.tms_cutime,
+            tms_cstime: proc.times.tms_cstime,
+        };
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                (&value as *const Tms).cast::<u8>(),
+                core::mem::size_of::<Tms>(),
+            )
+        };
+        if !write_user_bytes(space, tms.cast::<u8>(), bytes) {
+            return -1;
+        }
+        0
+    }
+}
+
+impl syscall::Thread for SyscallContext {
+    fn thread_create(&self, _caller: Caller, entry: usize, arg: usize, tls: usize) -> isize {
+        let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
+            return -1;
+        };
+        let Some(pid) = CurrentTask::pid() else {
+            return -1;
+        };
+
+        let (tid, satp, stack_top, name) = {
+            let Some(proc) = processor.get_proc(pid) else {
+                return -1;
+            };
+            // vfork 子进程在 exec 之前没有自己的地址空间
+            if proc.vfork_parent.is_some() {
+                return -1;
+            }
+            let tid = ThreadId::alloc();
+            let Some(stack_top) = proc.alloc_thread_stack(tid) else {
+                ThreadId::free(tid);
+                return -1;
+            };
+            (tid, proc.satp(), stack_top, proc.name)
+        };
+
+        let mut context = kernel_context::LocalContext::user(entry);
+        *context.sp_mut() = stack_top;
+        *context.a_mut(0) = arg;
+        *context.tp_mut() = tls;
+        let thread = Thread {
             tid,
             pid,
             context: ForeignContext { context, satp },
+            pending: 0,
+            syscalls_since_yield: 0,
+            utime: 0,
+            stime: 0,
+            name,
+            priority: DEFAULT_PRIORITY,
+            poll_deadline: None,
+            sync_wait: None,
         };
         processor.add(tid, thread, pid);
         tid.get_usize() as isize
     }
 
     fn gettid(&self, _caller: Caller) -> isize {
-        unsafe { CURRENT_TID.map(|t| t.get_usize() as isize).unwrap_or(-1) }
+        CurrentTask::tid().map(|t| t.get_usize() as isize).unwrap_or(-1)
+    }
+
+    fn prctl(&self, _caller: Caller, option: usize, arg2: usize) -> isize {
+        let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
+            return -1;
+        };
+        let (Some(tid), Some(space)) = (CurrentTask::tid(), current_space()) else {
+            return -1;
+        };
+        let Some(thread) = processor.get_task(tid) else {
+            return -1;
+        };
+        match option {
+            PR_SET_NAME => {
+                // 遇到 0 或读满 15 字节为止，更长的名字被截断
+                let mut name = Vec::new();
+                for i in 0..TASK_COMM_LEN - 1 {
+                    let Some(byte) = read_user_bytes(space, (arg2 + i) as *const u8, 1) else {
+                        return -1;
+                    };
+                    if byte[0] == 0 {
+                        break;
+                    }
+                    name.push(byte[0]);
+                }
+                thread.name = comm_name(&name);
+                0
+            }
+            PR_GET_NAME => {
+                if write_user_bytes(space, arg2 as *mut u8, &thread.name) {
+                    0
+                } else {
+                    -1
+                }
+            }
+            _ => -1,
+        }
     }
 
     fn waittid(&self, _caller: Caller, tid: usize) -> isize {
         let target_tid = ThreadId::from_usize(tid);
-        let self_tid = unsafe { CURRENT_TID.unwrap_or(ThreadId::from_usize(usize::MAX)) };
-        let self_pid = unsafe { CURRENT_PID.unwrap_or(ProcId::from_usize(usize::MAX)) };
-        if self_tid.get_usize() == usize::MAX || self_pid.get_usize() == usize::MAX {
+        let (Some(self_tid), Some(self_pid)) = (CurrentTask::tid(), CurrentTask::pid()) else {
             return -1;
-        }
+        };
         if target_tid == self_tid {
             return -1;
         }
@@ -1281,7 +2658,10 @@ impl syscall::Thread for SyscallContext {
                     -1
                 }
             }
-            Some(code) => code,
+            Some(code) => {
+                ThreadId::free(target_tid);
+                code
+            }
             None => -1,
         }
     }
@@ -1314,10 +2694,9 @@ impl syscall::SyncMutex for SyscallContext {
     }
 
     fn semaphore_down(&self, _caller: Caller, sem_id: usize) -> isize {
-        let tid = unsafe { CURRENT_TID.unwrap_or(ThreadId::from_usize(usize::MAX)) };
-        if tid.get_usize() == usize::MAX {
+        let Some(tid) = CurrentTask::tid() else {
             return -1;
-        }
+        };
         let sem = {
             let Some(proc) = current_process_mut() else {
                 return -1;
@@ -1335,149 +2714,445 @@ impl syscall::SyncMutex for SyscallContext {
         }
     }
 
-    fn mutex_create(&self, _caller: Caller, blocking: bool) -> isize {
+    fn semaphore_down_timeout(
+        &self,
+        _caller: Caller,
+        sem_id: usize,
+        timeout: *const TimeSpec,
+    ) -> isize {
+        let Some(tid) = CurrentTask::tid() else {
+            return -1;
+        };
+        let Some(deadline) = current_space().and_then(|space| read_user_deadline(space, timeout))
+        else {
+            return -1;
+        };
+        let sem = {
+            let Some(proc) = current_process_mut() else {
+                return -1;
+            };
+            let Some(sem) = proc.semaphores.get(sem_id) else {
+                return -1;
+            };
+            Arc::clone(sem)
+        };
+        if sem.down_timeout(tid, deadline) {
+            return 0;
+        }
+        let Some(thread) = unsafe { PROCESSOR.as_mut() }.and_then(|p| p.get_task(tid)) else {
+            return -1;
+        };
+        thread.sync_wait = Some(SyncWait::Semaphore(sem));
+        SLEEPERS.lock().push(tid, deadline);
+        BLOCKED_RETURN
+    }
+
+    fn semaphore_trydown(&self, _caller: Caller, sem_id: usize) -> isize {
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        let Some(sem) = proc.semaphores.get(sem_id) else {
+            return -1;
+        };
+        if sem.try_down() {
+            0
+        } else {
+            -1
+        }
+    }
+
+    fn mutex_create(&self, _caller: Caller, blocking: bool) -> isize {
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        if blocking {
+            proc.mutexes.push(Some(
+                Arc::new(SyncMutexBlocking::new()) as Arc<dyn SyncMutexTrait>,
+            ));
+        } else {
+            proc.mutexes.push(None);
+        }
+        (proc.mutexes.len() - 1) as isize
+    }
+
+    fn mutex_lock(&self, _caller: Caller, mutex_id: usize) -> isize {
+        let Some(tid) = CurrentTask::tid() else {
+            return -1;
+        };
+        let mutex = {
+            let Some(proc) = current_process_mut() else {
+                return -1;
+            };
+            let Some(mutex) = proc.mutexes.get(mutex_id).and_then(|m| m.as_ref()) else {
+                return -1;
+            };
+            Arc::clone(mutex)
+        };
+        // 持有者重复加锁会永远等待自己，直接报错
+        if mutex.owner() == Some(tid) {
+            return -1;
+        }
+        let lock_ok = mutex.lock(tid);
+        if lock_ok {
+            0
+        } else {
+            BLOCKED_RETURN
+        }
+    }
+
+    fn mutex_trylock(&self, _caller: Caller, mutex_id: usize) -> isize {
+        let Some(tid) = CurrentTask::tid() else {
+            return -1;
+        };
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        let Some(mutex) = proc.mutexes.get(mutex_id).and_then(|m| m.as_ref()) else {
+            return -1;
+        };
+        if mutex.try_lock(tid) {
+            0
+        } else {
+            -1
+        }
+    }
+
+    fn mutex_unlock(&self, _caller: Caller, mutex_id: usize) -> isize {
+        let Some(tid) = CurrentTask::tid() else {
+            return -1;
+        };
+        let mutex = {
+            let Some(proc) = current_process_mut() else {
+                return -1;
+            };
+            let Some(mutex) = proc.mutexes.get(mutex_id).and_then(|m| m.as_ref()) else {
+                return -1;
+            };
+            Arc::clone(mutex)
+        };
+        // 只有持有者可以解锁，未加锁的互斥锁也在这里被拒绝
+        if mutex.owner() != Some(tid) {
+            return -1;
+        }
+        let wake_tid = mutex.unlock();
+        if let Some(tid) = wake_tid {
+            wake_thread_with_ret(tid, 0);
+        }
+        0
+    }
+
+    fn condvar_create(&self, _caller: Caller) -> isize {
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        proc.condvars.push(Arc::new(SyncCondvar::new()));
+        (proc.condvars.len() - 1) as isize
+    }
+
+    fn condvar_signal(&self, _caller: Caller, condvar_id: usize) -> isize {
+        let condvar = {
+            let Some(proc) = current_process_mut() else {
+                return -1;
+            };
+            let Some(condvar) = proc.condvars.get(condvar_id) else {
+                return -1;
+            };
+            Arc::clone(condvar)
+        };
+        // 等待者已由条件变量替它重新 lock；锁被占用时它留在互斥锁队列里
+        if let Some(tid) = condvar.signal() {
+            wake_thread_with_ret(tid, 0);
+        }
+        0
+    }
+
+    fn condvar_wait(&self, _caller: Caller, condvar_id: usize, mutex_id: usize) -> isize {
+        let Some(tid) = CurrentTask::tid() else {
+            return -1;
+        };
+        let (condvar, mutex) = {
+            let Some(proc) = current_process_mut() else {
+                return -1;
+            };
+            let Some(condvar) = proc.condvars.get(condvar_id) else {
+                return -1;
+            };
+            let Some(mutex) = proc.mutexes.get(mutex_id).and_then(|m| m.as_ref()) else {
+                return -1;
+            };
+            (Arc::clone(condvar), Arc::clone(mutex))
+        };
+        // 等待前必须持有互斥锁，否则无锁可释放
+        if mutex.owner() != Some(tid) {
+            return -1;
+        }
+        let ticket = condvar.wait(tid, mutex);
+        if let Some(tid) = ticket.handoff {
+            wake_thread_with_ret(tid, 0);
+        }
+        BLOCKED_RETURN
+    }
+
+    fn condvar_wait_timeout(
+        &self,
+        _caller: Caller,
+        condvar_id: usize,
+        mutex_id: usize,
+        timeout: *const TimeSpec,
+    ) -> isize {
+        let Some(tid) = CurrentTask::tid() else {
+            return -1;
+        };
+        let Some(deadline) = current_space().and_then(|space| read_user_deadline(space, timeout))
+        else {
+            return -1;
+        };
+        let (condvar, mutex) = {
+            let Some(proc) = current_process_mut() else {
+                return -1;
+            };
+            let Some(condvar) = proc.condvars.get(condvar_id) else {
+                return -1;
+            };
+            let Some(mutex) = proc.mutexes.get(mutex_id).and_then(|m| m.as_ref()) else {
+                return -1;
+            };
+            (Arc::clone(condvar), Arc::clone(mutex))
+        };
+        if mutex.owner() != Some(tid) {
+            return -1;
+        }
+        let Some(thread) = unsafe { PROCESSOR.as_mut() }.and_then(|p| p.get_task(tid)) else {
+            return -1;
+        };
+        thread.sync_wait = Some(SyncWait::Condvar(Arc::clone(&condvar)));
+        SLEEPERS.lock().push(tid, deadline);
+        let ticket = condvar.wait_timeout(tid, mutex, deadline);
+        if let Some(tid) = ticket.handoff {
+            wake_thread_with_ret(tid, 0);
+        }
+        BLOCKED_RETURN
+    }
+}
+
+impl syscall::Memory for SyscallContext {
+    fn mmap(
+        &self,
+        _caller: Caller,
+        _addr: usize,
+        len: usize,
+        prot: usize,
+        flags: usize,
+        fd: isize,
+        offset: usize,
+    ) -> isize {
+        // `addr` 仅作提示，由内核选择位置
+        if flags == MAP_PRIVATE | MAP_ANONYMOUS {
+            if len == 0 || len % PAGE_SIZE != 0 {
+                return -1;
+            }
+            let Some(proc) = current_process_mut() else {
+                return -1;
+            };
+            return match proc.mmap_anon(len / PAGE_SIZE, prot) {
+                Some(start) => start as isize,
+                None => -1,
+            };
+        }
+        if len == 0 || fd < 0 || offset % PAGE_SIZE != 0 {
+            return -1;
+        }
+        let shared = match flags {
+            MAP_SHARED => true,
+            MAP_PRIVATE => false,
+            _ => return -1,
+        };
+        let Some(pages) = len.checked_add(PAGE_SIZE - 1).map(|l| l / PAGE_SIZE) else {
+            return -1;
+        };
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        let Some(file) = proc.get_fd(fd as usize) else {
+            return -1;
+        };
+        let (inode, readable, writable) = {
+            let file = file.lock();
+            (file.inode.clone(), file.readable(), file.writable())
+        };
+        let Some(inode) = inode else {
+            return -1;
+        };
+        // 共享的可写映射会写回文件，要求文件以可写方式打开
+        if !readable || (shared && prot & PROT_WRITE != 0 && !writable) {
+            return -1;
+        }
+        match proc.mmap_file(inode, offset, pages, prot, shared) {
+            Some(start) => start as isize,
+            None => -1,
+        }
+    }
+
+    fn munmap(&self, _caller: Caller, addr: usize, len: usize) -> isize {
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        let Some(end) = addr.checked_add(len).and_then(|e| e.checked_add(PAGE_SIZE - 1)) else {
+            return -1;
+        };
+        let end = end / PAGE_SIZE * PAGE_SIZE;
+        // 只支持整段解除一次 mmap 建立的映射
+        if let Some(idx) = proc
+            .file_mappings
+            .iter()
+            .position(|m| m.start == addr && m.start + m.len == end)
+        {
+            let mapping = proc.file_mappings.remove(idx);
+            mapping.write_back(&proc.space, addr, end);
+        } else if let Some(idx) = proc
+            .anon_mappings
+            .iter()
+            .position(|&(start, len)| start == addr && start + len == end)
+        {
+            proc.anon_mappings.remove(idx);
+        } else {
+            return -1;
+        }
+        if proc.space.unmap(VPN::new(addr >> 12)..VPN::new(end >> 12)) {
+            0
+        } else {
+            -1
+        }
+    }
+
+    fn msync(&self, _caller: Caller, addr: usize, len: usize, _flags: usize) -> isize {
+        if addr % PAGE_SIZE != 0 {
+            return -1;
+        }
+        let Some(end) = addr.checked_add(len) else {
+            return -1;
+        };
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        // MS_ASYNC 也立即写回
+        proc.sync_file_mappings(addr, end);
+        0
+    }
+
+    fn madvise(&self, _caller: Caller, addr: usize, len: usize, advice: usize) -> isize {
+        if addr % PAGE_SIZE != 0 {
+            return -1;
+        }
+        let Some(end) = addr.checked_add(len).and_then(|e| e.checked_add(PAGE_SIZE - 1)) else {
+            return -1;
+        };
+        if advice != MADV_DONTNEED {
+            // 其他建议目前不影响行为，直接接受
+            return 0;
+        }
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        let end = end / PAGE_SIZE * PAGE_SIZE;
+        // 文件映射丢弃后应重新读取文件内容，按需清零会得到错误的数据，因此拒绝
+        let file_backed = proc
+            .file_mappings
+            .iter()
+            .any(|m| m.start < end && addr < m.start + m.len);
+        if file_backed {
+            return -1;
+        }
+        // 归还区间内的物理页，之后访问时缺页按需分配清零的页
+        proc.space.discard(VPN::new(addr >> 12)..VPN::new(end >> 12));
+        0
+    }
+
+    fn brk(&self, _caller: Caller, new_top: usize) -> isize {
         let Some(proc) = current_process_mut() else {
             return -1;
         };
-        if blocking {
-            proc.mutexes.push(Some(
-                Arc::new(SyncMutexBlocking::new()) as Arc<dyn SyncMutexTrait>,
-            ));
-        } else {
-            proc.mutexes.push(None);
+        if new_top == 0 {
+            return proc.heap_top as isize;
         }
-        (proc.mutexes.len() - 1) as isize
+        proc.set_brk(new_top).map_or(-1, |top| top as isize)
     }
 
-    fn mutex_lock(&self, _caller: Caller, mutex_id: usize) -> isize {
-        let tid = unsafe { CURRENT_TID.unwrap_or(ThreadId::from_usize(usize::MAX)) };
-        if tid.get_usize() == usize::MAX {
+    fn sbrk(&self, _caller: Caller, delta: isize) -> isize {
+        let Some(proc) = current_process_mut() else {
             return -1;
-        }
-        let mutex = {
-            let Some(proc) = current_process_mut() else {
-                return -1;
-            };
-            let Some(mutex) = proc.mutexes.get(mutex_id).and_then(|m| m.as_ref()) else {
-                return -1;
-            };
-            Arc::clone(mutex)
         };
-        let lock_ok = mutex.lock(tid);
-        if lock_ok {
-            0
-        } else {
-            BLOCKED_RETURN
+        let old_top = proc.heap_top;
+        let Some(new_top) = old_top.checked_add_signed(delta) else {
+            return -1;
+        };
+        match proc.set_brk(new_top) {
+            Some(_) => old_top as isize,
+            None => -1,
         }
     }
+}
 
-    fn mutex_unlock(&self, _caller: Caller, mutex_id: usize) -> isize {
-        let mutex = {
-            let Some(proc) = current_process_mut() else {
-                return -1;
-            };
-            let Some(mutex) = proc.mutexes.get(mutex_id).and_then(|m| m.as_ref()) else {
-                return -1;
-            };
-            Arc::clone(mutex)
+impl syscall::Scheduling for SyscallContext {
+    fn sched_yield(&self, _caller: Caller) -> isize {
+        let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
+            return -1;
         };
-        let wake_tid = mutex.unlock();
-        if let Some(tid) = wake_tid {
-            wake_thread_with_ret(tid, 0);
-        }
+        processor.make_current_suspend();
         0
     }
 
-    fn condvar_create(&self, _caller: Caller) -> isize {
-        let Some(proc) = current_process_mut() else {
+    fn getcpu(&self, _caller: Caller, cpu: *mut usize, node: *mut usize) -> isize {
+        let Some(space) = current_space() else {
             return -1;
         };
-        proc.condvars.push(Arc::new(SyncCondvar::new()));
-        (proc.condvars.len() - 1) as isize
-    }
-
-    fn condvar_signal(&self, _caller: Caller, condvar_id: usize) -> isize {
-        let (waiting_tid, maybe_mutex_id) = {
-            let Some(proc) = current_process_mut() else {
-                return -1;
-            };
-            let Some(condvar) = proc.condvars.get(condvar_id) else {
-                return -1;
-            };
-            let tid = condvar.signal();
-            let mutex_id = tid.and_then(|id| proc.condvar_wait_mutex.remove(&id));
-            (tid, mutex_id)
-        };
-        if let Some(tid) = waiting_tid {
-            if let Some(mutex_id) = maybe_mutex_id {
-                let mutex = {
-                    let Some(proc) = current_process_mut() else {
-                        return -1;
-                    };
-                    let Some(mutex) = proc.mutexes.get(mutex_id).and_then(|m| m.as_ref()) else {
-                        return -1;
-                    };
-                    Arc::clone(mutex)
-                };
-                let lock_result = mutex.lock(tid);
-                if lock_result {
-                    wake_thread_with_ret(tid, 0);
-                }
-            } else {
-                wake_thread_with_ret(tid, 0);
-            }
+        // 内核态的 tp 保存当前 hart 编号，陷入时由 execute 恢复
+        let hart_id = kernel_context::hart::current_id();
+        if !cpu.is_null() && !write_user_bytes(space, cpu.cast::<u8>(), &hart_id.to_ne_bytes()) {
+            return -1;
+        }
+        if !node.is_null() && !write_user_bytes(space, node.cast::<u8>(), &0usize.to_ne_bytes()) {
+            return -1;
         }
         0
     }
 
-    fn condvar_wait(&self, _caller: Caller, condvar_id: usize, mutex_id: usize) -> isize {
-        let tid = unsafe { CURRENT_TID.unwrap_or(ThreadId::from_usize(usize::MAX)) };
-        if tid.get_usize() == usize::MAX {
+    fn set_priority(&self, _caller: Caller, prio: isize) -> isize {
+        if !(PRIO_MIN..=PRIO_MAX).contains(&prio) {
             return -1;
         }
-        let (condvar, mutex) = {
-            let Some(proc) = current_process_mut() else {
-                return -1;
-            };
-            let Some(condvar) = proc.condvars.get(condvar_id) else {
-                return -1;
-            };
-            let Some(mutex) = proc.mutexes.get(mutex_id).and_then(|m| m.as_ref()) else {
-                return -1;
-            };
-            proc.condvar_wait_mutex.insert(tid, mutex_id);
-            (Arc::clone(condvar), Arc::clone(mutex))
+        let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
+            return -1;
         };
-        let _ = condvar.wait_no_sched(tid);
-        let wake_tid = mutex.unlock();
-        if let Some(tid) = wake_tid {
-            wake_thread_with_ret(tid, 0);
-        }
-        BLOCKED_RETURN
+        let Some(tid) = CurrentTask::tid() else {
+            return -1;
+        };
+        let Some(thread) = processor.get_task(tid) else {
+            return -1;
+        };
+        thread.priority = prio as u8;
+        // 当前线程不在就绪队列中，新优先级在它下次被挂起入队时生效
+        processor.set_priority(tid, prio as u8);
+        0
     }
-}
 
-impl syscall::Scheduling for SyscallContext {
-    fn sched_yield(&self, _caller: Caller) -> isize {
+    fn get_priority(&self, _caller: Caller) -> isize {
         let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
             return -1;
         };
-        processor.make_current_suspend();
-        0
+        CurrentTask::tid()
+            .and_then(|tid| processor.get_task(tid))
+            .map_or(-1, |thread| thread.priority as isize)
     }
 }
 
 impl syscall::Clock for SyscallContext {
     fn clock_gettime(&self, _caller: Caller, clock_id: usize, tp: *mut TimeSpec) -> isize {
-        if clock_id != ClockId::CLOCK_MONOTONIC.0 {
-            return -1;
-        }
-
         let ticks = riscv::register::time::read64();
-        const CLOCK_FREQ: u64 = 10_000_000;
-        let ts = TimeSpec {
-            tv_sec: (ticks / CLOCK_FREQ) as usize,
-            tv_nsec: ((ticks % CLOCK_FREQ) * 1_000_000_000 / CLOCK_FREQ) as usize,
+        let ts = match ClockId(clock_id) {
+            ClockId::CLOCK_MONOTONIC => TimeSpec::from_ticks(ticks),
+            ClockId::CLOCK_REALTIME => syscall::realtime_from_ticks(ticks),
+            _ => return -1,
         };
 
         let Some(space) = current_space() else {
@@ -1496,6 +3171,83 @@ impl syscall::Clock for SyscallContext {
             -1
         }
     }
+
+    fn gettimeofday(&self, _caller: Caller, tv: *mut TimeVal) -> isize {
+        let Some(space) = current_space() else {
+            return -1;
+        };
+        let now = syscall::realtime_from_ticks(riscv::register::time::read64());
+        let tv_val = TimeVal::from(now);
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                (&tv_val as *const TimeVal).cast::<u8>(),
+                core::mem::size_of::<TimeVal>(),
+            )
+        };
+        if write_user_bytes(space, tv.cast::<u8>(), bytes) {
+            0
+        } else {
+            -1
+        }
+    }
+
+    fn clock_nanosleep(
+        &self,
+        _caller: Caller,
+        clock_id: usize,
+        flags: usize,
+        req: *const TimeSpec,
+    ) -> isize {
+        if clock_id != ClockId::CLOCK_MONOTONIC.0 {
+            return -1;
+        }
+        let Some(tid) = CurrentTask::tid() else {
+            return -1;
+        };
+        let Some(space) = current_space() else {
+            return -1;
+        };
+        let Some(raw) = read_user_bytes(space, req.cast::<u8>(), core::mem::size_of::<TimeSpec>())
+        else {
+            return -1;
+        };
+        let req = unsafe { core::ptr::read_unaligned(raw.as_ptr().cast::<TimeSpec>()) };
+
+        let now = riscv::register::time::read64();
+        // 绝对时间直接换算成 tick，不受睡前被打断的影响；相对时长从现在起算
+        let deadline = if flags & TIMER_ABSTIME != 0 {
+            req.to_ticks()
+        } else {
+            now.saturating_add(req.to_ticks())
+        };
+        if deadline <= now {
+            return 0;
+        }
+        SLEEPERS.lock().push(tid, deadline);
+        BLOCKED_RETURN
+    }
+
+    fn nanosleep(&self, caller: Caller, req: *const TimeSpec) -> isize {
+        self.clock_nanosleep(caller, ClockId::CLOCK_MONOTONIC.0, 0, req)
+    }
+
+    fn alarm(&self, _caller: Caller, seconds: usize) -> isize {
+        let Some(pid) = CurrentTask::pid() else {
+            return -1;
+        };
+        let now = riscv::register::time::read64();
+        let deadline = (seconds != 0).then(|| {
+            let delay = TimeSpec {
+                tv_sec: seconds,
+                tv_nsec: 0,
+            };
+            now.saturating_add(delay.to_ticks())
+        });
+        let remaining = ALARMS
+            .lock()
+            .replace(pid, deadline, now, syscall::clock_freq());
+        remaining as isize
+    }
 }
 
 impl syscall::Signal for SyscallContext {
@@ -1514,7 +3266,40 @@ impl syscall::Signal for SyscallContext {
         let Some(target) = processor.get_proc(target_pid) else {
             return -1;
         };
-        target.signal.add_signal(signum);
+        send_proc_signal(target, signum);
+        0
+    }
+
+    fn tgkill(&self, _caller: Caller, pid: isize, tid: isize, signum: u8) -> isize {
+        if pid < 0 || tid < 0 {
+            return -1;
+        }
+        let signum = SignalNo::from(signum as usize);
+        if signum as usize == 0 || signum as usize > signal::MAX_SIG {
+            return -1;
+        }
+        let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
+            return -1;
+        };
+        let tid = ThreadId::from_usize(tid as usize);
+        let Some(thread) = processor.get_task(tid) else {
+            return -1;
+        };
+        // 线程必须属于 pid 指定的进程
+        if thread.pid != ProcId::from_usize(pid as usize) {
+            return -1;
+        }
+        // 目标线程正在 sigwaitinfo 等待该信号时直接交给它
+        if let Some(proc) = processor.get_proc(ProcId::from_usize(pid as usize)) {
+            if let Some((waiter, info)) = proc.take_sigwaiter(signum, Some(tid)) {
+                write_user_siginfo(&mut proc.space, info, signum);
+                wake_thread_with_ret(waiter, signum as isize);
+                return 0;
+            }
+        }
+        if let Some(thread) = processor.get_task(tid) {
+            thread.pending |= 1 << signum as usize;
+        }
         0
     }
 
@@ -1565,19 +3350,109 @@ impl syscall::Signal for SyscallContext {
         0
     }
 
-    fn sigprocmask(&self, _caller: Caller, mask: usize) -> isize {
+    fn sigprocmask(&self, _caller: Caller, how: usize, set: usize, oldset: *mut usize) -> isize {
+        let Some(how) = MaskHow::from_usize(how) else {
+            return -1;
+        };
+        let Some(space) = current_space() else {
+            return -1;
+        };
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        let old = proc.signal.modify_mask(how, set);
+        if !oldset.is_null() && !write_user_bytes(space, oldset.cast::<u8>(), &old.to_ne_bytes()) {
+            return -1;
+        }
+        0
+    }
+
+    fn sigpending(&self, _caller: Caller, set: *mut usize) -> isize {
+        let Some(space) = current_space() else {
+            return -1;
+        };
         let Some(proc) = current_process_mut() else {
             return -1;
         };
-        proc.signal.update_mask(mask) as isize
+        let pending = proc.signal.pending();
+        if !write_user_bytes(space, set.cast::<u8>(), &pending.to_ne_bytes()) {
+            return -1;
+        }
+        0
     }
 
-    fn sigreturn(&self, _caller: Caller) -> isize {
-        let pid = unsafe { CURRENT_PID.unwrap_or(ProcId::from_usize(usize::MAX)) };
-        let tid = unsafe { CURRENT_TID.unwrap_or(ThreadId::from_usize(usize::MAX)) };
-        if pid.get_usize() == usize::MAX || tid.get_usize() == usize::MAX {
+    fn sigtimedwait(
+        &self,
+        _caller: Caller,
+        set: *const usize,
+        info: *mut SigInfo,
+        timeout: *const TimeSpec,
+        sigsetsize: usize,
+    ) -> isize {
+        if sigsetsize != core::mem::size_of::<usize>() {
+            return -1;
+        }
+        let Some(tid) = CurrentTask::tid() else {
+            return -1;
+        };
+        let Some(space) = current_space() else {
+            return -1;
+        };
+        let Some(raw) = read_user_bytes(space, set.cast::<u8>(), sigsetsize) else {
+            return -1;
+        };
+        let set = usize::from_ne_bytes(raw.as_slice().try_into().unwrap());
+        let deadline = if timeout.is_null() {
+            None
+        } else {
+            let size = core::mem::size_of::<TimeSpec>();
+            let Some(raw) = read_user_bytes(space, timeout.cast::<u8>(), size) else {
+                return -1;
+            };
+            let timeout = unsafe { core::ptr::read_unaligned(raw.as_ptr().cast::<TimeSpec>()) };
+            Some(riscv::register::time::read64().saturating_add(timeout.to_ticks()))
+        };
+        let Some(proc) = current_process_mut() else {
+            return -1;
+        };
+        // SIGKILL 与 SIGSTOP 不能被同步等待
+        let set = set & !(1 << SignalNo::SIGKILL as usize) & !(1 << SignalNo::SIGSTOP as usize);
+        if set == 0 {
             return -1;
         }
+        // 已有待处理的信号时直接取出（先看发给本线程的），否则阻塞到 kill/tgkill 投递
+        let thread_pending = unsafe { PROCESSOR.as_mut() }
+            .and_then(|processor| processor.get_task(tid))
+            .and_then(|thread| {
+                let bits = thread.pending & set;
+                if bits == 0 {
+                    return None;
+                }
+                let bit = bits.trailing_zeros() as usize;
+                thread.pending &= !(1 << bit);
+                Some(SignalNo::from(bit))
+            });
+        if let Some(signum) = thread_pending.or_else(|| proc.signal.dequeue_from_set(set)) {
+            if !write_user_siginfo(space, info as usize, signum) {
+                return -1;
+            }
+            return signum as isize;
+        }
+        // 超时为 0 时只检查一次；否则由睡眠队列在截止时刻唤醒并返回 -1
+        if let Some(deadline) = deadline {
+            if deadline <= riscv::register::time::read64() {
+                return -1;
+            }
+            SLEEPERS.lock().push(tid, deadline);
+        }
+        proc.sigwaiters.push((tid, set, info as usize));
+        BLOCKED_RETURN
+    }
+
+    fn sigreturn(&self, _caller: Caller) -> isize {
+        let (Some(pid), Some(tid)) = (CurrentTask::pid(), CurrentTask::tid()) else {
+            return -1;
+        };
         let Some(processor) = (unsafe { PROCESSOR.as_mut() }) else {
             return -1;
         };
@@ -1602,6 +3477,8 @@ extern "C" fn rust_main() -> ! {
     init_console(&SbiConsole);
     set_log_level(option_env!("LOG"));
     test_log();
+    // 分页开启前按物理地址读 RTC
+    init_boot_realtime();
 
     let layout = KernelLayout::locate();
     let heap_start = layout.end();
@@ -1612,6 +3489,9 @@ extern "C" fn rust_main() -> ! {
     kernel_alloc::init(heap_start);
     let heap_region = unsafe { core::slice::from_raw_parts_mut(heap_start as *mut u8, heap_size) };
     unsafe { kernel_alloc::transfer(heap_region) };
+    // 自检的分配与页表检查需要堆，因此放在堆初始化之后
+    #[cfg(feature = "selftest")]
+    rcore_selftest::run();
 
     let portal_size = MultislotPortal::calculate_size(1);
     assert!(portal_size <= PAGE_SIZE, "portal transit too large");
@@ -1651,10 +3531,12 @@ extern "C" fn rust_main() -> ! {
 
     let mut processor = PThreadManager::<Process, Thread, ThreadManager, ProcManager>::new();
     processor.set_proc_manager(ProcManager::new());
-    processor.set_manager(ThreadManager::new());
+    processor.set_manager(ThreadManager::with_policy(PrioritySchedule::with_default(
+        DEFAULT_PRIORITY,
+    )));
 
     let init_pid = ProcId::from_usize(0);
-    let init_tid = ThreadId::new();
+    let init_tid = ThreadId::alloc();
 
     let (initproc, initthread) = match fs::FS.open("initproc", OpenFlags::RDONLY) {
         Some(file) => {
@@ -1664,6 +3546,7 @@ extern "C" fn rust_main() -> ! {
                 unsafe { KERNEL_SPACE.as_ref().unwrap() },
                 init_pid,
                 init_tid,
+                "initproc",
             ) {
                 Some(item) => item,
                 None => {
@@ -1687,11 +3570,13 @@ extern "C" fn rust_main() -> ! {
 
     syscall::init_io(&SyscallContext);
     syscall::init_process(&SyscallContext);
+    syscall::init_memory(&SyscallContext);
     syscall::init_scheduling(&SyscallContext);
     syscall::init_clock(&SyscallContext);
     syscall::init_signal(&SyscallContext);
     syscall::init_thread(&SyscallContext);
     syscall::init_sync_mutex(&SyscallContext);
+    set_coop_budget(COOP_DEFAULT_BUDGET);
 
     let kernel_satp = (8 << 60) | unsafe { KERNEL_SPACE.as_ref().unwrap() }.root_ppn().val();
     satp::write(kernel_satp);
@@ -1706,142 +3591,165 @@ extern "C" fn rust_main() -> ! {
     }
 
     loop {
+        let now = riscv::register::time::read64();
+        wake_expired_sleepers(now);
+        fire_expired_alarms(now);
         let processor = unsafe { PROCESSOR.as_mut().unwrap() };
-        let thread_ptr = match processor.find_next() {
-            Some(thread) => thread as *mut Thread,
+        // coop 模式下系统调用后未挂起的线程继续运行
+        let next = match processor.current() {
+            Some(thread) => Some(thread as *mut Thread),
+            None => processor.find_next().map(|thread| thread as *mut Thread),
+        };
+        let thread_ptr = match next {
+            Some(thread) => thread,
             None => {
-                println!("no task");
-                break;
+                // 只剩睡眠线程时空转到最早的唤醒时刻
+                let next_wake = SLEEPERS.lock().next_deadline();
+                let Some(deadline) = next_wake else {
+                    println!("no task");
+                    break;
+                };
+                while riscv::register::time::read64() < deadline {
+                    core::hint::spin_loop();
+                }
+                continue;
             }
         };
         let (pid, tid) = unsafe { ((*thread_ptr).pid, (*thread_ptr).tid) };
 
-        let Some(space_ptr) = processor.get_proc(pid).map(|p| &p.space as *const _) else {
+        let Some(vfork_parent) = processor.get_proc(pid).map(|proc| proc.vfork_parent) else {
             exit_current_thread(pid, tid, -3);
             continue;
         };
+        // vfork 子进程在 exec 或 exit 之前借用父进程的地址空间
+        let space_pid = vfork_parent.map_or(pid, |(parent_pid, _)| parent_pid);
+        let Some(proc) = processor.get_proc(space_pid) else {
+            exit_current_thread(pid, tid, -3);
+            continue;
+        };
+        CurrentTask::set(&mut proc.space, pid, tid);
 
-        unsafe {
-            CURRENT_SPACE = Some(space_ptr);
-            CURRENT_PID = Some(pid);
-            CURRENT_TID = Some(tid);
-        }
-
-        let _ = set_timer(riscv::register::time::read64() + TIMER_SLICE_TICKS);
+        #[cfg(not(feature = "coop"))]
+        let _ = set_timer(riscv::register::time::read64() + TIMER_SLICE.to_ticks());
 
+        let entered = riscv::register::time::read64();
         unsafe {
             (*thread_ptr).context.execute(portal, ());
         }
+        let trapped = riscv::register::time::read64();
+        unsafe { (*thread_ptr).utime += (trapped - entered) as usize };
 
         satp::write(kernel_satp);
         unsafe { core::arch::asm!("sfence.vma zero, zero") };
 
-        let trap_cause = scause::read();
-        match trap_cause.cause() {
-            scause::Trap::Exception(scause::Exception::UserEnvCall) => {
-                unsafe { (*thread_ptr).context.context.move_next() };
-                let id = unsafe { SyscallId::from((*thread_ptr).context.context.a(7)) };
-                let args = [
-                    unsafe { (*thread_ptr).context.context.a(0) },
-                    unsafe { (*thread_ptr).context.context.a(1) },
-                    unsafe { (*thread_ptr).context.context.a(2) },
-                    unsafe { (*thread_ptr).context.context.a(3) },
-                    unsafe { (*thread_ptr).context.context.a(4) },
-                    unsafe { (*thread_ptr).context.context.a(5) },
-                ];
-                let caller = Caller {
-                    entity: pid.get_usize(),
-                    flow: tid.get_usize(),
-                };
-
-                let mut next_exit: Option<isize> = None;
-                let mut next_suspend = false;
-                let mut next_block = false;
+        let caller = Caller {
+            entity: pid.get_usize(),
+            flow: tid.get_usize(),
+        };
+        // 返回 (退出码, 是否挂起, 是否阻塞, 是否需要处理信号)
+        let (mut next_exit, mut next_suspend, mut next_block, check_signals) = trap::dispatch(
+            trap::decode(),
+            unsafe { &mut (*thread_ptr).context.context },
+            |ctx| {
+                ctx.move_next();
+                let id = SyscallId::from(ctx.a(7));
+                let args = [ctx.a(0), ctx.a(1), ctx.a(2), ctx.a(3), ctx.a(4), ctx.a(5)];
 
                 match syscall::handle(caller, id, args) {
                     SyscallResult::Done(ret) => {
                         if id == SyscallId::EXIT {
-                            next_exit = Some(ret);
+                            (Some(ret), false, false, false)
                         } else if ret == BLOCKED_RETURN {
-                            next_block = true;
+                            (None, false, true, true)
                         } else {
-                            unsafe { *(*thread_ptr).context.context.a_mut(0) = ret as usize };
-                            next_suspend = true;
+                            *ctx.a_mut(0) = ret as usize;
+                            let suspend = unsafe { (*thread_ptr).charge_syscall(id) };
+                            (None, suspend, false, true)
                         }
                     }
-                    SyscallResult::Unsupported(_) => {
-                        next_exit = Some(-2);
+                    SyscallResult::Unsupported(_) => (Some(-2), false, false, false),
+                }
+            },
+            |_| (None, true, false, true),
+            |cause, ctx| {
+                let kind = match cause {
+                    trap::TrapCause::LoadPageFault => Some(FaultKind::Load),
+                    trap::TrapCause::StorePageFault => Some(FaultKind::Store),
+                    trap::TrapCause::InstructionPageFault => Some(FaultKind::Instruction),
+                    _ => None,
+                };
+                // 缺页交给地址空间注册的策略；返回用户态前会整体刷新 TLB
+                let outcome = kind.and_then(|kind| {
+                    let proc = unsafe { PROCESSOR.as_mut() }?.get_proc(space_pid)?;
+                    let addr = stval::read();
+                    let mut outcome = proc.space.handle_fault(VAddr::new(addr), kind);
+                    // 栈底之下、栈槽位之内的访问让栈向下增长
+                    if outcome == FaultOutcome::Signal && proc.grow_stack_to(addr) {
+                        outcome = proc.space.handle_fault(VAddr::new(addr), kind);
                     }
+                    Some(outcome)
+                });
+                if outcome == Some(FaultOutcome::Mapped) {
+                    return (None, false, false, true);
                 }
-
-                if next_exit.is_none() {
-                    match handle_current_signals(pid, tid) {
-                        signal::SignalResult::NoSignal
-                        | signal::SignalResult::Ignored
-                        | signal::SignalResult::Handled
-                        | signal::SignalResult::IsHandlingSignal => {}
-                        signal::SignalResult::ProcessSuspended => {
-                            if !next_block {
-                                next_suspend = true;
-                            }
-                        }
-                        signal::SignalResult::ProcessKilled(code) => {
-                            next_exit = Some(code as isize);
-                            next_suspend = false;
-                            next_block = false;
+                log::error!(
+                    "[{}] trap {:?} stval={:#x} sepc={:#x}",
+                    unsafe { (*thread_ptr).name_str() },
+                    cause,
+                    stval::read(),
+                    ctx.pc()
+                );
+                if outcome == Some(FaultOutcome::Signal) {
+                    let target = unsafe { PROCESSOR.as_mut() }.and_then(|p| p.get_proc(pid));
+                    if let Some(proc) = target {
+                        // 同步缺页的 SIGSEGV 被屏蔽或处理函数正在运行时无法投递，
+                        // 返回用户态只会再次缺页，直接终止进程
+                        let blocked = proc.signal.mask() & (1 << SignalNo::SIGSEGV as usize) != 0;
+                        if !blocked && !proc.signal.is_handling_signal() {
+                            send_proc_signal(proc, SignalNo::SIGSEGV);
+                            return (None, false, false, true);
                         }
                     }
                 }
-
-                if let Some(code) = next_exit {
-                    exit_current_thread(pid, tid, code);
-                } else if next_block {
-                    let processor = unsafe { PROCESSOR.as_mut().unwrap() };
-                    processor.make_current_blocked();
-                } else if next_suspend {
-                    let processor = unsafe { PROCESSOR.as_mut().unwrap() };
-                    processor.make_current_suspend();
-                }
-            }
-            scause::Trap::Interrupt(scause::Interrupt::SupervisorTimer) => {
-                let mut next_exit: Option<isize> = None;
-                let mut next_suspend = true;
-                match handle_current_signals(pid, tid) {
-                    signal::SignalResult::NoSignal
-                    | signal::SignalResult::Ignored
-                    | signal::SignalResult::Handled
-                    | signal::SignalResult::IsHandlingSignal => {}
-                    signal::SignalResult::ProcessSuspended => {
+                (Some(-3), false, false, false)
+            },
+        );
+
+        if check_signals {
+            match handle_current_signals(pid, tid) {
+                signal::SignalResult::NoSignal
+                | signal::SignalResult::Ignored
+                | signal::SignalResult::Handled
+                | signal::SignalResult::IsHandlingSignal => {}
+                signal::SignalResult::ProcessSuspended => {
+                    if !next_block {
                         next_suspend = true;
                     }
-                    signal::SignalResult::ProcessKilled(code) => {
-                        next_exit = Some(code as isize);
-                        next_suspend = false;
-                    }
                 }
-                if let Some(code) = next_exit {
-                    exit_current_thread(pid, tid, code);
-                } else if next_suspend {
-                    let processor = unsafe { PROCESSOR.as_mut().unwrap() };
-                    processor.make_current_suspend();
+                signal::SignalResult::ProcessKilled(code) => {
+                    next_exit = Some(code as isize);
+                    next_suspend = false;
+                    next_block = false;
                 }
             }
-            _ => {
-                log::error!(
-                    "trap {:?} stval={:#x} sepc={:#x}",
-                    trap_cause.cause(),
-                    stval::read(),
-                    unsafe { (*thread_ptr).context.context.pc() }
-                );
-                exit_current_thread(pid, tid, -3);
-            }
         }
 
+        // 陷入处理（含系统调用与信号）计入内核态时间，须在线程退出前结算
         unsafe {
-            CURRENT_SPACE = None;
-            CURRENT_PID = None;
-            CURRENT_TID = None;
+            (*thread_ptr).stime += (riscv::register::time::read64() - trapped) as usize;
+        }
+
+        if let Some(code) = next_exit {
+            exit_current_thread(pid, tid, code);
+        } else if next_block {
+            let processor = unsafe { PROCESSOR.as_mut().unwrap() };
+            processor.make_current_blocked();
+        } else if next_suspend {
+            let processor = unsafe { PROCESSOR.as_mut().unwrap() };
+            processor.make_current_suspend();
         }
+
+        CurrentTask::clear();
     }
 
     sbi_rt::system_reset(Shutdown, NoReason);
@@ -1,4 +1,13 @@
 //! rcore-console: 提供可定制实现的 `print!`、`println!` 与 `log::Log`
+//!
+//! 默认每行日志都带 ANSI 颜色转义序列。串口或 CI 日志等不解析颜色的环境
+//! 可以启用 `no-color` feature，只输出 `[LEVEL] message`：
+//!
+//! ```toml
+//! rcore-console = { path = "../console", features = ["no-color"] }
+//! ```
+//!
+//! 启用 `log-location` feature 后，级别标签之后会追加 `{module}:{line}`。
 
 #![no_std]
 
@@ -22,11 +31,69 @@ pub trait Console: Sync {
             self.put_char(byte);
         }
     }
+
+    /// 批量输出字节（默认实现转发给 `put_str`）
+    ///
+    /// 基于 SBI `console_write` 或 MMIO FIFO 的实现可以覆盖此方法，一次输出整段数据。
+    /// 非 UTF-8 的输入会退回到逐字节调用 `put_char`。
+    fn put_bytes(&self, bytes: &[u8]) {
+        match core::str::from_utf8(bytes) {
+            Ok(s) => self.put_str(s),
+            Err(_) => {
+                for &byte in bytes {
+                    self.put_char(byte);
+                }
+            }
+        }
+    }
+}
+
+/// 把输出依次转发给多个后端的控制台
+///
+/// 例如同时输出到 SBI 控制台和 UART。`init_console` 要求 `&'static dyn Console`，
+/// 因此传给它的 `TeeConsole` 及其中的后端都必须是 `'static` 的，通常声明为 `static`：
+///
+/// ```ignore
+/// static TEE: TeeConsole<'static> = TeeConsole::new(&[&SbiConsole, &Uart]);
+/// init_console(&TEE);
+/// ```
+pub struct TeeConsole<'a> {
+    consoles: &'a [&'a dyn Console],
+}
+
+impl<'a> TeeConsole<'a> {
+    /// 创建转发到 `consoles` 的控制台
+    pub const fn new(consoles: &'a [&'a dyn Console]) -> Self {
+        Self { consoles }
+    }
+}
+
+impl Console for TeeConsole<'_> {
+    fn put_char(&self, c: u8) {
+        for console in self.consoles {
+            console.put_char(c);
+        }
+    }
+
+    fn put_str(&self, s: &str) {
+        for console in self.consoles {
+            console.put_str(s);
+        }
+    }
+
+    fn put_bytes(&self, bytes: &[u8]) {
+        for console in self.consoles {
+            console.put_bytes(bytes);
+        }
+    }
 }
 
 /// 全局控制台单例
 static CONSOLE: Once<&'static dyn Console> = Once::new();
 
+/// 日志时间戳来源
+static TIMESTAMP: Once<fn() -> u64> = Once::new();
+
 /// 初始化全局控制台单例并注册 logger
 /// 
 /// # 参数
@@ -42,37 +109,48 @@ pub fn init_console(console: &'static dyn Console) {
     let _ = log::set_logger(&Logger);
 }
 
+/// 注册日志时间戳来源
+///
+/// 注册后每条日志在级别标签前输出 `[{ticks:>12}]`，例如传入读取 `time` CSR 的函数。
+/// 只有第一次注册生效。
+pub fn set_timestamp_source(source: fn() -> u64) {
+    TIMESTAMP.call_once(|| source);
+}
+
 /// 设置全局最大日志级别
 /// 
 /// # 参数
 /// * `env` - 日志级别字符串（如 "trace", "debug", "info", "warn", "error"）
 ///         如果为 `None` 或无法解析，则设置为 `Trace`
 pub fn set_log_level(env: Option<&str>) {
-    let level = if let Some(env_str) = env {
-        // 手动转换为小写进行比较（no_std 环境）
-        let mut lower = [0u8; 16];
-        let mut i = 0;
-        for byte in env_str.bytes().take(15) {
-            lower[i] = if byte >= b'A' && byte <= b'Z' {
-                byte + 32
-            } else {
-                byte
-            };
-            i += 1;
-        }
-        let lower_str = core::str::from_utf8(&lower[..i]).unwrap_or("");
-        match lower_str {
-            "error" => LevelFilter::Error,
-            "warn" => LevelFilter::Warn,
-            "info" => LevelFilter::Info,
-            "debug" => LevelFilter::Debug,
-            "trace" => LevelFilter::Trace,
-            _ => LevelFilter::Trace,
-        }
-    } else {
+    if try_set_log_level(env).is_err() {
+        log::set_max_level(LevelFilter::Trace);
+    }
+}
+
+/// 设置全局最大日志级别，无法解析时返回错误
+///
+/// 级别名不区分大小写，并忽略首尾空白。`None` 或空串设置为 `Trace`；
+/// 无法识别的非空字符串返回 `Err`，此时不修改当前级别。
+pub fn try_set_log_level(env: Option<&str>) -> Result<LevelFilter, &'static str> {
+    let env_str = env.map(str::trim).unwrap_or("");
+    let level = if env_str.is_empty() {
         LevelFilter::Trace
+    } else {
+        [
+            ("error", LevelFilter::Error),
+            ("warn", LevelFilter::Warn),
+            ("info", LevelFilter::Info),
+            ("debug", LevelFilter::Debug),
+            ("trace", LevelFilter::Trace),
+        ]
+        .into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(env_str))
+        .map(|(_, level)| level)
+        .ok_or("unknown log level")?
     };
     log::set_max_level(level);
+    Ok(level)
 }
 
 /// 输出测试 banner 和五条不同级别的日志
@@ -114,12 +192,13 @@ struct ConsoleWriter {
 
 impl fmt::Write for ConsoleWriter {
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        self.console.put_str(s);
+        self.console.put_bytes(s.as_bytes());
         Ok(())
     }
 }
 
 /// 格式化颜色数字为字符串
+#[cfg(not(feature = "no-color"))]
 fn format_color(mut n: u8, buf: &mut [u8; 4]) -> &str {
     if n == 0 {
         buf[0] = b'0';
@@ -131,15 +210,17 @@ fn format_color(mut n: u8, buf: &mut [u8; 4]) -> &str {
         n /= 10;
         i += 1;
     }
-    core::str::from_utf8(&buf[3 - i..]).unwrap()
+    core::str::from_utf8(&buf[3 - i..3]).unwrap()
 }
 
 /// Logger 实现
 struct Logger;
 
 impl Log for Logger {
-    fn enabled(&self, _metadata: &Metadata) -> bool {
-        true
+    /// 低于编译期上限 `STATIC_MAX_LEVEL` 或运行时 `max_level()` 的记录不输出
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let level = metadata.level();
+        level <= log::STATIC_MAX_LEVEL && level <= log::max_level()
     }
     
     fn log(&self, record: &Record) {
@@ -148,14 +229,6 @@ impl Log for Logger {
         }
         
         let level = record.level();
-        let color = match level {
-            Level::Error => 31,
-            Level::Warn => 93,
-            Level::Info => 34,
-            Level::Debug => 32,
-            Level::Trace => 90,
-        };
-        
         let level_str = match level {
             Level::Error => "ERROR",
             Level::Warn => " WARN",
@@ -166,22 +239,46 @@ impl Log for Logger {
         
         let console = CONSOLE.get().unwrap();
         let args = record.args();
+        let mut writer = ConsoleWriter { console: *console };
         
-        // 格式化输出: \x1b[{color}m[{level:>5}] {args}\x1b[0m\n
-        console.put_str("\x1b[");
-        // 手动格式化数字（color 是 u8，范围 0-255）
-        let mut color_buf = [0u8; 4];
-        let color_str = format_color(color, &mut color_buf);
-        console.put_str(color_str);
-        console.put_str("m[");
+        // 格式化输出: \x1b[{color}m[{ticks:>12}] [{level:>5}] {args}\x1b[0m\n
+        #[cfg(not(feature = "no-color"))]
+        {
+            let color = match level {
+                Level::Error => 31,
+                Level::Warn => 93,
+                Level::Info => 34,
+                Level::Debug => 32,
+                Level::Trace => 90,
+            };
+            console.put_str("\x1b[");
+            // 手动格式化数字（color 是 u8，范围 0-255）
+            let mut color_buf = [0u8; 4];
+            let color_str = format_color(color, &mut color_buf);
+            console.put_str(color_str);
+            console.put_str("m");
+        }
+        if let Some(source) = TIMESTAMP.get() {
+            fmt::write(&mut writer, format_args!("[{:>12}] ", source())).unwrap();
+        }
+        console.put_str("[");
         console.put_str(level_str);
         console.put_str("] ");
+        #[cfg(feature = "log-location")]
+        if let Some(module) = record.module_path() {
+            console.put_str(module);
+            if let Some(line) = record.line() {
+                fmt::write(&mut writer, format_args!(":{line}")).unwrap();
+            }
+            console.put_str(" ");
+        }
         
         // 输出日志参数
-        let mut writer = ConsoleWriter { console: *console };
         fmt::write(&mut writer, *args).unwrap();
         
-        console.put_str("\x1b[0m\n");
+        #[cfg(not(feature = "no-color"))]
+        console.put_str("\x1b[0m");
+        console.put_str("\n");
     }
     
     fn flush(&self) {
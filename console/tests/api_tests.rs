@@ -4,7 +4,7 @@
 //! 测试在用户态环境运行，使用 std。
 
 use std::sync::{Arc, Mutex, Once};
-use rcore_console::{Console, init_console, set_log_level, test_log};
+use rcore_console::{Console, TeeConsole, init_console, set_log_level, test_log};
 
 // 测试用的 Console 实现
 struct TestConsole {
@@ -263,3 +263,33 @@ fn test_console_sync() {
     // 注意：由于 Mutex 是线程安全的，这里可以安全地跨线程使用
     // 实际使用中应该使用 Mutex 或其他同步原语
 }
+
+#[test]
+fn test_tee_console() {
+    // TeeConsole 把输出依次转发给每个后端
+    let first = TestConsole { output: Arc::new(Mutex::new(Vec::new())) };
+    let second = TestConsole { output: Arc::new(Mutex::new(Vec::new())) };
+    let backends: [&dyn Console; 2] = [&first, &second];
+    let tee = TeeConsole::new(&backends);
+
+    tee.put_char(b'>');
+    tee.put_str(" hello");
+    tee.put_bytes(b", tee\n");
+
+    let expected = b"> hello, tee\n";
+    assert_eq!(first.output.lock().unwrap().as_slice(), expected);
+    assert_eq!(second.output.lock().unwrap().as_slice(), expected);
+}
+
+#[test]
+fn test_tee_console_static() {
+    // 可以声明为 static，满足 init_console 的 'static 要求
+    struct Null;
+    impl Console for Null {
+        fn put_char(&self, _c: u8) {}
+    }
+    static NULL: Null = Null;
+    static TEE: TeeConsole<'static> = TeeConsole::new(&[&NULL, &NULL]);
+    let console: &'static dyn Console = &TEE;
+    console.put_str("ok");
+}
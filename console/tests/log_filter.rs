@@ -0,0 +1,129 @@
+//! Logger 级别过滤与输出格式测试
+//!
+//! 全局最大日志级别会影响 api_tests 中的日志输出测试，因此单独放在一个测试进程里。
+
+use log::{Level, LevelFilter, Metadata, Record};
+use rcore_console::{init_console, set_log_level, try_set_log_level, Console};
+use std::sync::Mutex;
+
+struct CaptureConsole(Mutex<Vec<u8>>);
+
+impl Console for CaptureConsole {
+    fn put_char(&self, c: u8) {
+        self.0.lock().unwrap().push(c);
+    }
+}
+
+static CONSOLE: CaptureConsole = CaptureConsole(Mutex::new(Vec::new()));
+
+/// 修改全局日志级别的测试需要串行执行
+static LEVEL_LOCK: Mutex<()> = Mutex::new(());
+
+fn enabled(level: Level) -> bool {
+    log::logger().enabled(&Metadata::builder().level(level).build())
+}
+
+#[test]
+fn test_enabled_respects_max_level() {
+    let _guard = LEVEL_LOCK.lock().unwrap();
+    init_console(&CONSOLE);
+
+    set_log_level(Some("warn"));
+    assert!(!enabled(Level::Info));
+    assert!(!enabled(Level::Trace));
+    assert!(enabled(Level::Warn));
+    assert!(enabled(Level::Error));
+    assert!(!log::log_enabled!(Level::Debug));
+
+    set_log_level(Some("trace"));
+    assert!(enabled(Level::Trace));
+    assert!(log::log_enabled!(Level::Debug));
+}
+
+#[test]
+fn test_log_line_format() {
+    init_console(&CONSOLE);
+    let log_line = line!() + 1;
+    log::error!("format check");
+
+    let bytes = CONSOLE.0.lock().unwrap().clone();
+    let output = String::from_utf8(bytes).unwrap();
+    let line = output
+        .lines()
+        .find(|line| line.contains("format check"))
+        .unwrap();
+    let location = if cfg!(feature = "log-location") {
+        format!("{}:{} ", module_path!(), log_line)
+    } else {
+        String::new()
+    };
+    if cfg!(feature = "no-color") {
+        assert_eq!(line, format!("[ERROR] {location}format check"));
+    } else {
+        assert_eq!(
+            line,
+            format!("\x1b[31m[ERROR] {location}format check\x1b[0m")
+        );
+    }
+}
+
+#[test]
+fn test_try_set_log_level() {
+    let _guard = LEVEL_LOCK.lock().unwrap();
+
+    // 大小写混合
+    assert_eq!(try_set_log_level(Some("WaRn")), Ok(LevelFilter::Warn));
+    assert_eq!(log::max_level(), LevelFilter::Warn);
+    assert_eq!(try_set_log_level(Some("ERROR")), Ok(LevelFilter::Error));
+    // 首尾空白
+    assert_eq!(try_set_log_level(Some("  info\n")), Ok(LevelFilter::Info));
+    assert_eq!(try_set_log_level(Some("\tDebug ")), Ok(LevelFilter::Debug));
+    // 未设置或空串视为 Trace
+    assert_eq!(try_set_log_level(None), Ok(LevelFilter::Trace));
+    assert_eq!(try_set_log_level(Some("   ")), Ok(LevelFilter::Trace));
+
+    // 无法识别的字符串返回错误且不修改当前级别
+    assert_eq!(try_set_log_level(Some("warn")), Ok(LevelFilter::Warn));
+    assert!(try_set_log_level(Some("inf")).is_err());
+    assert!(try_set_log_level(Some("WARNING")).is_err());
+    assert!(try_set_log_level(Some("warnwarnwarnwarnwarn")).is_err());
+    assert!(try_set_log_level(Some("tr ace")).is_err());
+    assert_eq!(log::max_level(), LevelFilter::Warn);
+
+    // set_log_level 保持回退到 Trace 的旧行为
+    set_log_level(Some("inf"));
+    assert_eq!(log::max_level(), LevelFilter::Trace);
+}
+
+#[test]
+fn test_log_location() {
+    init_console(&CONSOLE);
+    let logger = log::logger();
+    logger.log(
+        &Record::builder()
+            .level(Level::Error)
+            .module_path(Some("kernel::mm"))
+            .file(Some("src/mm.rs"))
+            .line(Some(42))
+            .args(format_args!("location check"))
+            .build(),
+    );
+    logger.log(
+        &Record::builder()
+            .level(Level::Error)
+            .line(Some(7))
+            .args(format_args!("no module check"))
+            .build(),
+    );
+
+    let bytes = CONSOLE.0.lock().unwrap().clone();
+    let output = String::from_utf8(bytes).unwrap();
+    let find = |msg: &str| output.lines().find(|line| line.contains(msg)).unwrap();
+    if cfg!(feature = "log-location") {
+        assert!(find("location check").contains("[ERROR] kernel::mm:42 location check"));
+    } else {
+        assert!(!find("location check").contains("kernel::mm"));
+    }
+    // 没有模块路径时省略整个位置字段
+    assert!(find("no module check").contains("[ERROR] no module check"));
+}
@@ -0,0 +1,75 @@
+//! `Console::put_bytes` 批量输出路径测试
+//!
+//! 全局控制台只能初始化一次，因此单独放在一个测试进程里。
+
+use rcore_console::{init_console, println, Console};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// 记录 `put_bytes` 与 `put_char` 分别被调用了多少次
+struct RecordingConsole {
+    bytes_calls: AtomicUsize,
+    char_calls: AtomicUsize,
+    output: Mutex<Vec<u8>>,
+}
+
+impl RecordingConsole {
+    const fn new() -> Self {
+        Self {
+            bytes_calls: AtomicUsize::new(0),
+            char_calls: AtomicUsize::new(0),
+            output: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Console for RecordingConsole {
+    fn put_char(&self, c: u8) {
+        self.char_calls.fetch_add(1, Ordering::SeqCst);
+        self.output.lock().unwrap().push(c);
+    }
+
+    fn put_bytes(&self, bytes: &[u8]) {
+        self.bytes_calls.fetch_add(1, Ordering::SeqCst);
+        self.output.lock().unwrap().extend_from_slice(bytes);
+    }
+}
+
+/// 只实现 `put_char` 的控制台
+struct CharConsole(Mutex<Vec<u8>>);
+
+impl Console for CharConsole {
+    fn put_char(&self, c: u8) {
+        self.0.lock().unwrap().push(c);
+    }
+}
+
+static CONSOLE: RecordingConsole = RecordingConsole::new();
+
+#[test]
+fn test_print_uses_put_bytes() {
+    init_console(&CONSOLE);
+    println!("fast path {}", 42);
+
+    assert!(CONSOLE.bytes_calls.load(Ordering::SeqCst) > 0);
+    assert_eq!(CONSOLE.char_calls.load(Ordering::SeqCst), 0);
+    let output = CONSOLE.output.lock().unwrap().clone();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .contains("fast path 42\n"));
+}
+
+#[test]
+fn test_put_bytes_default_forwards() {
+    let console = CharConsole(Mutex::new(Vec::new()));
+    console.put_bytes("hello, 世界".as_bytes());
+    assert_eq!(
+        console.0.lock().unwrap().as_slice(),
+        "hello, 世界".as_bytes()
+    );
+
+    // 非 UTF-8 数据原样逐字节输出
+    let console = CharConsole(Mutex::new(Vec::new()));
+    console.put_bytes(&[0xff, b'a', 0xfe]);
+    assert_eq!(console.0.lock().unwrap().as_slice(), &[0xff, b'a', 0xfe]);
+}
@@ -0,0 +1,45 @@
+//! 日志时间戳前缀测试
+//!
+//! 时间戳来源是全局状态，因此单独放在一个测试进程里。
+
+use rcore_console::{init_console, set_log_level, set_timestamp_source, Console};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+struct CaptureConsole(Mutex<Vec<u8>>);
+
+impl Console for CaptureConsole {
+    fn put_char(&self, c: u8) {
+        self.0.lock().unwrap().push(c);
+    }
+}
+
+static CONSOLE: CaptureConsole = CaptureConsole(Mutex::new(Vec::new()));
+
+/// 每次读取递增 1000 的假计数器
+static TICKS: AtomicU64 = AtomicU64::new(41_000);
+
+fn fake_ticks() -> u64 {
+    TICKS.fetch_add(1000, Ordering::SeqCst) + 1000
+}
+
+#[test]
+fn test_timestamp_prefix() {
+    init_console(&CONSOLE);
+    set_log_level(Some("trace"));
+
+    log::info!("before source");
+    set_timestamp_source(fake_ticks);
+    log::info!("after source");
+
+    let bytes = CONSOLE.0.lock().unwrap().clone();
+    let output = String::from_utf8(bytes).unwrap();
+    let before = output
+        .lines()
+        .find(|l| l.contains("before source"))
+        .unwrap();
+    let after = output.lines().find(|l| l.contains("after source")).unwrap();
+
+    assert!(!before.contains("42000"));
+    assert!(after.contains("[       42000] [ INFO] "));
+}
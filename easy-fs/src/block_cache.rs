@@ -97,59 +97,115 @@ impl Drop for BlockCache {
 
 /// 块缓存管理器
 /// 
-/// 管理最多 16 个块缓存，提供缓存复用和替换策略。
+/// 管理最多 16 个块缓存，按最近最少使用（LRU）替换，并可在顺序访问时预读后续块。
 pub struct BlockCacheManager {
-    /// 缓存队列，每个元素为 (block_id, Arc<Mutex<BlockCache>>)
+    /// 缓存队列，每个元素为 (block_id, Arc<Mutex<BlockCache>>)，按最近访问时间从旧到新排列
     queue: VecDeque<(usize, Arc<Mutex<BlockCache>>)>,
+    /// 顺序访问时预读的块数，0 表示关闭
+    readahead: usize,
+    /// 最近访问的块号（环形记录），用于识别顺序访问
+    recent: [usize; READAHEAD_HISTORY],
+    /// `recent` 中下一个写入位置
+    recent_pos: usize,
 }
 
 /// 块缓存管理器最大容量
-const BLOCK_CACHE_SIZE: usize = 16;
+pub const BLOCK_CACHE_SIZE: usize = 16;
+
+/// 识别顺序访问时回看的访问次数，容忍数据块之间穿插的索引块访问
+const READAHEAD_HISTORY: usize = 4;
 
 impl BlockCacheManager {
     /// 创建新的块缓存管理器
     pub fn new() -> Self {
         Self {
             queue: VecDeque::new(),
+            readahead: 0,
+            recent: [usize::MAX; READAHEAD_HISTORY],
+            recent_pos: 0,
         }
     }
 
+    /// 设置顺序访问时预读的块数，0 关闭预读
+    /// 
+    /// 预读块数不超过缓存容量的一半，避免预读挤掉正在使用的块。
+    /// 预读不越过 [`BlockDevice::num_blocks`] 报告的设备末尾，未报告块数的设备不预读。
+    pub fn set_readahead(&mut self, blocks: usize) {
+        self.readahead = blocks.min(BLOCK_CACHE_SIZE / 2);
+    }
+
+    /// 指定块当前是否在缓存中
+    pub fn is_cached(&self, block_id: usize) -> bool {
+        self.queue.iter().any(|pair| pair.0 == block_id)
+    }
+
     /// 获取指定块的缓存
     /// 
     /// 行为：
-    /// - 已缓存：返回现有引用
+    /// - 已缓存：返回现有引用，并标记为最近使用
     /// - 未缓存且未满：创建新缓存
-    /// - 未缓存且已满：替换 strong_count == 1 的条目
+    /// - 未缓存且已满：写回并替换最近最少使用的 strong_count == 1 的条目
     /// - 无可替换：panic
+    /// 
+    /// 若前一块最近刚被访问过，视为顺序访问，按 `set_readahead` 的设置预读后续块。
     pub fn get_block_cache(
         &mut self,
         block_id: usize,
         block_device: Arc<dyn BlockDevice>,
     ) -> Arc<Mutex<BlockCache>> {
-        // 检查是否已缓存
-        if let Some(pair) = self.queue.iter().find(|pair| pair.0 == block_id) {
-            return Arc::clone(&pair.1);
+        let sequential = block_id > 0 && self.recent.contains(&(block_id - 1));
+        self.recent[self.recent_pos] = block_id;
+        self.recent_pos = (self.recent_pos + 1) % READAHEAD_HISTORY;
+
+        // 检查是否已缓存，未缓存则读入
+        let block_cache = match self.queue.iter().position(|pair| pair.0 == block_id) {
+            Some(idx) => {
+                // 移到队尾，成为最近使用的块
+                let pair = self.queue.remove(idx).unwrap();
+                let block_cache = Arc::clone(&pair.1);
+                self.queue.push_back(pair);
+                block_cache
+            }
+            None => self
+                .insert(block_id, Arc::clone(&block_device))
+                .unwrap_or_else(|| panic!("Run out of BlockCache!")),
+        };
+
+        // 预读失败（没有可替换的条目）时直接放弃，不影响本次访问
+        if sequential {
+            let end = block_device
+                .num_blocks()
+                .unwrap_or(0)
+                .min(block_id.saturating_add(self.readahead + 1));
+            for id in block_id + 1..end {
+                if !self.is_cached(id) && self.insert(id, Arc::clone(&block_device)).is_none() {
+                    break;
+                }
+            }
         }
+        block_cache
+    }
 
-        // 未缓存，检查是否已满
+    /// 读入新块加入队尾；已满时写回并替换最靠近队首（最近最少使用）的 strong_count == 1 的条目，
+    /// 无可替换返回 `None`
+    fn insert(
+        &mut self,
+        block_id: usize,
+        block_device: Arc<dyn BlockDevice>,
+    ) -> Option<Arc<Mutex<BlockCache>>> {
         if self.queue.len() == BLOCK_CACHE_SIZE {
             // 查找可替换的缓存（strong_count == 1）
-            if let Some((idx, _)) = self
+            let idx = self
                 .queue
                 .iter()
-                .enumerate()
-                .find(|(_, pair)| Arc::strong_count(&pair.1) == 1)
-            {
-                self.queue.remove(idx);
-            } else {
-                panic!("Run out of BlockCache!");
-            }
+                .position(|pair| Arc::strong_count(&pair.1) == 1)?;
+            let (_, evicted) = self.queue.remove(idx).unwrap();
+            evicted.lock().sync();
         }
 
-        // 创建新缓存
-        let block_cache = Arc::new(Mutex::new(BlockCache::new(block_id, Arc::clone(&block_device))));
+        let block_cache = Arc::new(Mutex::new(BlockCache::new(block_id, block_device)));
         self.queue.push_back((block_id, Arc::clone(&block_cache)));
-        block_cache
+        Some(block_cache)
     }
 }
 
@@ -169,6 +225,11 @@ pub fn get_block_cache(
         .get_block_cache(block_id, block_device)
 }
 
+/// 设置全局块缓存的顺序预读块数，0 关闭预读
+pub fn set_readahead(blocks: usize) {
+    BLOCK_CACHE_MANAGER.lock().set_readahead(blocks);
+}
+
 /// 同步所有缓存中的脏块
 /// 
 /// 遍历所有缓存，将脏块写回块设备。
@@ -21,4 +21,11 @@ pub trait BlockDevice: Send + Sync + Any {
     /// - `block_id`: 块编号
     /// - `buf`: 源缓冲区，长度必须为 512 字节
     fn write_block(&self, block_id: usize, buf: &[u8]);
+
+    /// 设备总块数，未知时返回 `None`
+    /// 
+    /// 块缓存据此限制预读范围，返回 `None` 的设备不做预读。
+    fn num_blocks(&self) -> Option<usize> {
+        None
+    }
 }
@@ -12,6 +12,19 @@ const BLOCK_BITS: usize = BLOCK_SZ * 8;
 /// 每块可容纳的 inode 数量 (512 / 128 = 4)
 const INODES_PER_BLOCK: u32 = (BLOCK_SZ / core::mem::size_of::<DiskInode>()) as u32;
 
+/// 文件系统容量统计，由 [`EasyFileSystem::stat_fs`] 返回
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FsStat {
+    /// 数据区总块数
+    pub total_blocks: usize,
+    /// 空闲数据块数
+    pub free_blocks: usize,
+    /// inode 总数
+    pub total_inodes: usize,
+    /// 空闲 inode 数
+    pub free_inodes: usize,
+}
+
 /// easy-fs 文件系统
 /// 
 /// 整合磁盘布局、管理块分配/回收，提供文件系统创建和打开接口。
@@ -196,6 +209,17 @@ impl EasyFileSystem {
         )
     }
     
+    /// 由磁盘 inode 的位置反查 inode 编号，是 [`get_disk_inode_pos`](Self::get_disk_inode_pos) 的逆运算
+    /// 
+    /// # Arguments
+    /// 
+    /// * `block_id` - 块号
+    /// * `offset` - 块内偏移
+    pub fn get_inode_id(&self, block_id: u32, offset: usize) -> u32 {
+        (block_id - self.inode_area_start_block) * INODES_PER_BLOCK
+            + (offset / core::mem::size_of::<DiskInode>()) as u32
+    }
+    
     /// 获取数据块的磁盘块号
     /// 
     /// # Arguments
@@ -217,6 +241,13 @@ impl EasyFileSystem {
         self.inode_bitmap.alloc(&self.block_device).unwrap() as u32
     }
     
+    /// 回收一个 inode
+    /// 
+    /// 调用方需先回收该 inode 占用的数据块。
+    pub fn dealloc_inode(&mut self, inode_id: u32) {
+        self.inode_bitmap.dealloc(&self.block_device, inode_id as usize);
+    }
+    
     /// 分配一个数据块
     /// 
     /// 从数据位图分配，返回磁盘绝对块号。
@@ -246,6 +277,28 @@ impl EasyFileSystem {
         self.data_bitmap.dealloc(&self.block_device, data_block_id as usize);
     }
 
+    /// 查询总/空闲数据块数与 inode 数
+    ///
+    /// 总数取自超级块，已用数由两个位图 popcount 得到。
+    pub fn stat_fs(&self) -> FsStat {
+        let (total_blocks, total_inodes) = get_block_cache(0, Arc::clone(&self.block_device))
+            .lock()
+            .read(0, |super_block: &SuperBlock| {
+                (
+                    super_block.data_area_blocks as usize,
+                    (super_block.inode_area_blocks * INODES_PER_BLOCK) as usize,
+                )
+            });
+        let used_blocks = self.data_bitmap.count_ones(&self.block_device);
+        let used_inodes = self.inode_bitmap.count_ones(&self.block_device);
+        FsStat {
+            total_blocks,
+            free_blocks: total_blocks - used_blocks,
+            total_inodes,
+            free_inodes: total_inodes - used_inodes,
+        }
+    }
+
     /// 获取根目录的 Inode
     /// 
     /// # Arguments
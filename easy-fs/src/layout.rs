@@ -141,13 +141,29 @@ impl Bitmap {
     pub fn maximum(&self) -> usize {
         self.blocks * BLOCK_BITS
     }
+
+    /// 统计已分配（置 1）的 bit 数量
+    pub fn count_ones(&self, block_device: &Arc<dyn BlockDevice>) -> usize {
+        (0..self.blocks)
+            .map(|block_id| {
+                get_block_cache(self.start_block_id + block_id, Arc::clone(block_device))
+                    .lock()
+                    .read(0, |bitmap_block: &BitmapBlock| {
+                        bitmap_block
+                            .iter()
+                            .map(|bits64| bits64.count_ones() as usize)
+                            .sum::<usize>()
+                    })
+            })
+            .sum()
+    }
 }
 
 /// 位图块类型（每块 64 个 u64）
 type BitmapBlock = [u64; 64];
 
 /// 索引节点类型
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum DiskInodeType {
     File,
     Directory,
@@ -168,8 +184,12 @@ pub struct DiskInode {
     pub indirect2: u32,
     /// 类型（文件/目录）
     type_: DiskInodeType,
+    /// 硬链接数，占用原先的填充字节
+    nlink: u16,
 }
 
+const _: () = assert!(size_of::<DiskInode>() == 128);
+
 impl DiskInode {
     /// 初始化索引节点
     pub fn initialize(&mut self, type_: DiskInodeType) {
@@ -178,6 +198,30 @@ impl DiskInode {
         self.indirect1 = 0;
         self.indirect2 = 0;
         self.type_ = type_;
+        self.nlink = 1;
+    }
+
+    /// 索引节点类型
+    pub fn inode_type(&self) -> DiskInodeType {
+        self.type_
+    }
+
+    /// 硬链接数
+    ///
+    /// 加入该字段之前创建的镜像中这里是填充的 0，按 1 处理。
+    pub fn nlink(&self) -> u16 {
+        self.nlink.max(1)
+    }
+
+    /// 硬链接数加一
+    pub fn inc_nlink(&mut self) {
+        self.nlink = self.nlink().saturating_add(1);
+    }
+
+    /// 硬链接数减一，返回剩余的链接数
+    pub fn dec_nlink(&mut self) -> u16 {
+        self.nlink = self.nlink() - 1;
+        self.nlink
     }
 
     /// 是否是目录
@@ -330,6 +374,51 @@ impl DiskInode {
             });
     }
 
+    /// 缩小文件大小到 new_size，返回不再需要的块编号列表（含不再使用的间接索引块）
+    ///
+    /// 不清零最后一个保留块中 new_size 之后的字节，由调用方负责。
+    pub fn decrease_size(
+        &mut self,
+        new_size: u32,
+        block_device: &Arc<dyn BlockDevice>,
+    ) -> Vec<u32> {
+        assert!(new_size <= self.size);
+        let old_blocks = self.data_blocks() as usize;
+        let new_blocks = Self::_data_blocks(new_size) as usize;
+        let mut v: Vec<u32> = (new_blocks..old_blocks)
+            .map(|inner_id| self.get_block_id(inner_id as u32, block_device))
+            .collect();
+
+        // 直接索引
+        for entry in self.direct.iter_mut().take(old_blocks).skip(new_blocks) {
+            *entry = 0;
+        }
+
+        // 一级间接索引块不再使用
+        if old_blocks > DIRECT_BOUND && new_blocks <= DIRECT_BOUND {
+            v.push(self.indirect1);
+            self.indirect1 = 0;
+        }
+
+        // 二级间接索引下不再使用的一级间接块，以及二级间接索引块本身
+        if old_blocks > INDIRECT1_BOUND {
+            let groups =
+                |blocks: usize| blocks.saturating_sub(INDIRECT1_BOUND).div_ceil(INODE_INDIRECT1_COUNT);
+            let (old_groups, new_groups) = (groups(old_blocks), groups(new_blocks));
+            get_block_cache(self.indirect2 as usize, Arc::clone(block_device))
+                .lock()
+                .read(0, |indirect2: &IndirectBlock| {
+                    v.extend_from_slice(&indirect2[new_groups..old_groups]);
+                });
+            if new_blocks <= INDIRECT1_BOUND {
+                v.push(self.indirect2);
+                self.indirect2 = 0;
+            }
+        }
+        self.size = new_size;
+        v
+    }
+
     /// 清空文件，返回待回收的块编号列表
     pub fn clear_size(&mut self, block_device: &Arc<dyn BlockDevice>) -> Vec<u32> {
         let mut v: Vec<u32> = Vec::new();
@@ -6,15 +6,21 @@ mod block_cache;
 mod block_dev;
 mod efs;
 mod layout;
+mod pipe;
 mod vfs;
 
 pub use block_cache::{
-    block_cache_sync_all, get_block_cache, BlockCache, BlockCacheManager, BLOCK_CACHE_MANAGER,
+    block_cache_sync_all, get_block_cache, set_readahead, BlockCache, BlockCacheManager,
+    BLOCK_CACHE_MANAGER, BLOCK_CACHE_SIZE,
 };
 pub use block_dev::{BlockDevice, BLOCK_SZ};
-pub use efs::EasyFileSystem;
+pub use efs::{EasyFileSystem, FsStat};
 pub use layout::{
     Bitmap, DirEntry, DiskInode, DiskInodeType, SuperBlock,
     DIRENT_SZ, EFS_MAGIC, INODE_DIRECT_COUNT, NAME_LENGTH_LIMIT,
 };
-pub use vfs::{FSManager, FileHandle, Inode, OpenFlags, UserBuffer};
+pub use pipe::{pipe, PipeEnd, PipeError, PipeReader, PipeWriter, PIPE_BUFFER_SIZE};
+pub use vfs::{
+    FSManager, FileHandle, Inode, OpenFlags, ReadMode, ReadPoll, SeekFrom, Stat, StatMode,
+    UserBuffer,
+};
@@ -0,0 +1,174 @@
+//! 管道
+//!
+//! 一块有界环形缓冲区，由读端 [`PipeReader`] 和写端 [`PipeWriter`] 共享。
+//! 两端都可以克隆，缓冲区记录各自的存活数量，用来区分“暂时无数据”和“写端已全部关闭”。
+
+use alloc::sync::Arc;
+use spin::Mutex;
+
+/// 管道缓冲区容量（字节）
+pub const PIPE_BUFFER_SIZE: usize = 512;
+
+/// 管道读写无法立即完成的原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PipeError {
+    /// 读时缓冲区为空但仍有写端，或写时缓冲区已满；调用方应阻塞后重试
+    WouldBlock,
+    /// 写时所有读端都已关闭
+    BrokenPipe,
+}
+
+/// 两端共享的环形缓冲区
+struct PipeRing {
+    buf: [u8; PIPE_BUFFER_SIZE],
+    /// 下一个可读字节的位置
+    head: usize,
+    /// 缓冲区中的字节数
+    len: usize,
+    /// 存活的读端数量
+    readers: usize,
+    /// 存活的写端数量
+    writers: usize,
+}
+
+impl PipeRing {
+    fn read(&mut self, buf: &mut [u8]) -> usize {
+        let n = buf.len().min(self.len);
+        for byte in buf.iter_mut().take(n) {
+            *byte = self.buf[self.head];
+            self.head = (self.head + 1) % PIPE_BUFFER_SIZE;
+        }
+        self.len -= n;
+        n
+    }
+
+    fn write(&mut self, data: &[u8]) -> usize {
+        let n = data.len().min(PIPE_BUFFER_SIZE - self.len);
+        let mut tail = (self.head + self.len) % PIPE_BUFFER_SIZE;
+        for &byte in data.iter().take(n) {
+            self.buf[tail] = byte;
+            tail = (tail + 1) % PIPE_BUFFER_SIZE;
+        }
+        self.len += n;
+        n
+    }
+}
+
+/// 创建一对相连的管道读端和写端
+pub fn pipe() -> (PipeReader, PipeWriter) {
+    let ring = Arc::new(Mutex::new(PipeRing {
+        buf: [0; PIPE_BUFFER_SIZE],
+        head: 0,
+        len: 0,
+        readers: 1,
+        writers: 1,
+    }));
+    (PipeReader { ring: ring.clone() }, PipeWriter { ring })
+}
+
+/// 管道读端
+pub struct PipeReader {
+    ring: Arc<Mutex<PipeRing>>,
+}
+
+impl PipeReader {
+    /// 读取至多 `buf.len()` 字节
+    ///
+    /// 有数据时立即返回读到的字节数；缓冲区为空且写端已全部关闭时返回 `Ok(0)`（EOF）；
+    /// 缓冲区为空但仍有写端时返回 [`PipeError::WouldBlock`]。
+    pub fn read(&self, buf: &mut [u8]) -> Result<usize, PipeError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut ring = self.ring.lock();
+        if ring.len == 0 {
+            return if ring.writers == 0 {
+                Ok(0)
+            } else {
+                Err(PipeError::WouldBlock)
+            };
+        }
+        Ok(ring.read(buf))
+    }
+
+    /// 缓冲区中可读的字节数
+    pub fn available(&self) -> usize {
+        self.ring.lock().len
+    }
+
+    /// [`read`](Self::read) 是否不会返回 [`PipeError::WouldBlock`]：有数据可读或写端已全部关闭
+    pub fn is_ready(&self) -> bool {
+        let ring = self.ring.lock();
+        ring.len > 0 || ring.writers == 0
+    }
+}
+
+impl Clone for PipeReader {
+    fn clone(&self) -> Self {
+        self.ring.lock().readers += 1;
+        Self {
+            ring: self.ring.clone(),
+        }
+    }
+}
+
+impl Drop for PipeReader {
+    fn drop(&mut self) {
+        self.ring.lock().readers -= 1;
+    }
+}
+
+/// 管道写端
+pub struct PipeWriter {
+    ring: Arc<Mutex<PipeRing>>,
+}
+
+impl PipeWriter {
+    /// 写入至多 `data.len()` 字节，缓冲区剩余空间不足时只写入一部分
+    ///
+    /// 读端已全部关闭时返回 [`PipeError::BrokenPipe`]；
+    /// 缓冲区已满时返回 [`PipeError::WouldBlock`]。
+    pub fn write(&self, data: &[u8]) -> Result<usize, PipeError> {
+        let mut ring = self.ring.lock();
+        if ring.readers == 0 {
+            return Err(PipeError::BrokenPipe);
+        }
+        if data.is_empty() {
+            return Ok(0);
+        }
+        if ring.len == PIPE_BUFFER_SIZE {
+            return Err(PipeError::WouldBlock);
+        }
+        Ok(ring.write(data))
+    }
+
+    /// [`write`](Self::write) 是否不会返回 [`PipeError::WouldBlock`]：缓冲区有空间或读端已全部关闭
+    pub fn is_ready(&self) -> bool {
+        let ring = self.ring.lock();
+        ring.len < PIPE_BUFFER_SIZE || ring.readers == 0
+    }
+}
+
+impl Clone for PipeWriter {
+    fn clone(&self) -> Self {
+        self.ring.lock().writers += 1;
+        Self {
+            ring: self.ring.clone(),
+        }
+    }
+}
+
+impl Drop for PipeWriter {
+    fn drop(&mut self) {
+        self.ring.lock().writers -= 1;
+    }
+}
+
+/// 文件句柄持有的管道端
+#[derive(Clone)]
+pub enum PipeEnd {
+    /// 读端
+    Read(PipeReader),
+    /// 写端
+    Write(PipeWriter),
+}
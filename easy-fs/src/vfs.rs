@@ -9,9 +9,10 @@ use alloc::vec::Vec;
 use spin::Mutex;
 
 use crate::block_cache::{block_cache_sync_all, get_block_cache};
-use crate::block_dev::BlockDevice;
+use crate::block_dev::{BlockDevice, BLOCK_SZ};
 use crate::efs::EasyFileSystem;
-use crate::layout::{DirEntry, DiskInode, DiskInodeType, DIRENT_SZ};
+use crate::layout::{DirEntry, DiskInode, DiskInodeType, DIRENT_SZ, NAME_LENGTH_LIMIT};
+use crate::pipe::PipeEnd;
 
 /// 索引节点
 ///
@@ -57,6 +58,11 @@ impl Inode {
             .read(self.block_offset, f)
     }
 
+    /// 索引节点类型
+    pub fn inode_type(&self) -> DiskInodeType {
+        self.read_disk_inode(|disk_inode| disk_inode.inode_type())
+    }
+
     /// 以可变方式访问 DiskInode
     fn modify_disk_inode<V>(&self, f: impl FnOnce(&mut DiskInode) -> V) -> V {
         get_block_cache(self.block_id, Arc::clone(&self.block_device))
@@ -109,7 +115,9 @@ impl Inode {
         None
     }
 
-    /// 在当前目录下创建文件
+    /// 在当前目录下创建普通文件
+    ///
+    /// 等价于 `create_typed(name, DiskInodeType::File)`。
     ///
     /// # Arguments
     ///
@@ -119,6 +127,20 @@ impl Inode {
     ///
     /// 如果创建成功，返回 `Some(Arc<Inode>)`；如果文件已存在，返回 `None`。
     pub fn create(&self, name: &str) -> Option<Arc<Inode>> {
+        self.create_typed(name, DiskInodeType::File)
+    }
+
+    /// 在当前目录下创建指定类型的条目
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - 要创建的条目名
+    /// * `type_` - 新 inode 的类型
+    ///
+    /// # Returns
+    ///
+    /// 如果创建成功，返回 `Some(Arc<Inode>)`；如果同名条目已存在，返回 `None`。
+    pub fn create_typed(&self, name: &str, type_: DiskInodeType) -> Option<Arc<Inode>> {
         let mut fs = self.fs.lock();
         // 检查文件是否已存在
         let op = self.read_disk_inode(|disk_inode| {
@@ -130,12 +152,12 @@ impl Inode {
         }
         // 分配新 inode
         let new_inode_id = fs.alloc_inode();
-        // 初始化为 File
+        // 按请求的类型初始化
         let (new_inode_block_id, new_inode_block_offset) = fs.get_disk_inode_pos(new_inode_id);
         get_block_cache(new_inode_block_id as usize, Arc::clone(&self.block_device))
             .lock()
             .modify(new_inode_block_offset, |new_inode: &mut DiskInode| {
-                new_inode.initialize(DiskInodeType::File);
+                new_inode.initialize(type_);
             });
         // 追加目录项
         self.modify_disk_inode(|root_inode| {
@@ -158,6 +180,208 @@ impl Inode {
         )))
     }
 
+    /// 在当前目录内重命名条目
+    ///
+    /// 目标名已存在时覆盖它：被覆盖文件的链接数减一，归零时回收其数据块与 inode；
+    /// 目标是目录时拒绝覆盖。
+    ///
+    /// # Arguments
+    ///
+    /// * `old_name` - 原文件名
+    /// * `new_name` - 新文件名
+    ///
+    /// # Returns
+    ///
+    /// 成功返回 `true`；原文件不存在、新文件名过长或目标是目录时返回 `false`。
+    pub fn rename(&self, old_name: &str, new_name: &str) -> bool {
+        if new_name.len() > NAME_LENGTH_LIMIT {
+            return false;
+        }
+        let mut fs = self.fs.lock();
+        let (old_id, target_id) = self.read_disk_inode(|dir_inode| {
+            (
+                self.find_inode_id(old_name, dir_inode),
+                self.find_inode_id(new_name, dir_inode),
+            )
+        });
+        let Some(old_id) = old_id else {
+            return false;
+        };
+        // 新旧名是同一个文件的两个硬链接时什么也不做
+        if old_name == new_name || target_id == Some(old_id) {
+            return true;
+        }
+        if let Some(target_id) = target_id {
+            if !self.drop_link(target_id, &mut fs) {
+                return false;
+            }
+        }
+        self.modify_disk_inode(|dir_inode| {
+            if target_id.is_some() {
+                self.remove_dirent(new_name, dir_inode, &mut fs);
+            }
+            // 原地改写目录项，目录中不会出现新旧名同时存在或同时缺失的状态
+            let index = self.find_dirent_index(old_name, dir_inode).unwrap();
+            let dirent = DirEntry::new(new_name, old_id);
+            dir_inode.write_at(index * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
+        });
+        block_cache_sync_all();
+        true
+    }
+
+    /// 在 DiskInode 中查找目录项，返回其下标
+    fn find_dirent_index(&self, name: &str, disk_inode: &DiskInode) -> Option<usize> {
+        let file_count = disk_inode.size as usize / DIRENT_SZ;
+        let mut dirent = DirEntry::empty();
+        (0..file_count).find(|&i| {
+            disk_inode.read_at(i * DIRENT_SZ, dirent.as_bytes_mut(), &self.block_device);
+            dirent.name() == name
+        })
+    }
+
+    /// 从目录中删除名为 `name` 的目录项，返回其 inode 编号
+    ///
+    /// 剩余目录项按原顺序重新写回，并回收多出来的数据块。
+    fn remove_dirent(
+        &self,
+        name: &str,
+        dir_inode: &mut DiskInode,
+        fs: &mut EasyFileSystem,
+    ) -> Option<u32> {
+        let file_count = dir_inode.size as usize / DIRENT_SZ;
+        let mut removed = None;
+        let mut kept = Vec::with_capacity(file_count);
+        for i in 0..file_count {
+            let mut dirent = DirEntry::empty();
+            dir_inode.read_at(i * DIRENT_SZ, dirent.as_bytes_mut(), &self.block_device);
+            if removed.is_none() && dirent.name() == name {
+                removed = Some(dirent.inode_number());
+            } else {
+                kept.push(dirent);
+            }
+        }
+        removed?;
+        for data_block in dir_inode.clear_size(&self.block_device) {
+            fs.dealloc_data(data_block);
+        }
+        self.increase_size((kept.len() * DIRENT_SZ) as u32, dir_inode, fs);
+        for (i, dirent) in kept.iter().enumerate() {
+            dir_inode.write_at(i * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
+        }
+        removed
+    }
+
+    /// 在当前目录下为已有的普通文件 `old_name` 创建硬链接 `new_name`
+    ///
+    /// 新目录项指向同一个 inode，并把其链接数加一。
+    ///
+    /// # Arguments
+    ///
+    /// * `old_name` - 已有文件名
+    /// * `new_name` - 新文件名
+    ///
+    /// # Returns
+    ///
+    /// 成功返回 `true`；原文件不存在或是目录、新文件名已存在或过长时返回 `false`。
+    pub fn link(&self, old_name: &str, new_name: &str) -> bool {
+        if new_name.len() > NAME_LENGTH_LIMIT {
+            return false;
+        }
+        let mut fs = self.fs.lock();
+        let (old_id, target_id) = self.read_disk_inode(|dir_inode| {
+            (
+                self.find_inode_id(old_name, dir_inode),
+                self.find_inode_id(new_name, dir_inode),
+            )
+        });
+        let Some(old_id) = old_id else {
+            return false;
+        };
+        if target_id.is_some() {
+            return false;
+        }
+        let (block_id, block_offset) = fs.get_disk_inode_pos(old_id);
+        let linked = get_block_cache(block_id as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(block_offset, |disk_inode: &mut DiskInode| {
+                if disk_inode.is_dir() {
+                    return false;
+                }
+                disk_inode.inc_nlink();
+                true
+            });
+        if !linked {
+            return false;
+        }
+        self.modify_disk_inode(|dir_inode| {
+            let file_count = (dir_inode.size as usize) / DIRENT_SZ;
+            self.increase_size(((file_count + 1) * DIRENT_SZ) as u32, dir_inode, &mut fs);
+            let dirent = DirEntry::new(new_name, old_id);
+            dir_inode.write_at(file_count * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
+        });
+        block_cache_sync_all();
+        true
+    }
+
+    /// 删除当前目录下的普通文件 `name`
+    ///
+    /// 移除目录项并把 inode 的链接数减一，链接数归零时回收数据块与 inode。
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - 要删除的文件名
+    ///
+    /// # Returns
+    ///
+    /// 成功返回 `true`；文件不存在或是目录时返回 `false`。
+    pub fn unlink(&self, name: &str) -> bool {
+        let mut fs = self.fs.lock();
+        let Some(inode_id) = self.read_disk_inode(|dir_inode| self.find_inode_id(name, dir_inode))
+        else {
+            return false;
+        };
+        if !self.drop_link(inode_id, &mut fs) {
+            return false;
+        }
+        self.modify_disk_inode(|dir_inode| {
+            self.remove_dirent(name, dir_inode, &mut fs);
+        });
+        block_cache_sync_all();
+        true
+    }
+
+    /// 硬链接数
+    ///
+    /// 供 `fstat` 填写 `nlink`。
+    pub fn nlink(&self) -> u32 {
+        self.read_disk_inode(|disk_inode| disk_inode.nlink() as u32)
+    }
+
+    /// 普通文件的链接数减一，归零时回收其数据块与 inode；是目录时不做任何事并返回 `false`
+    fn drop_link(&self, inode_id: u32, fs: &mut EasyFileSystem) -> bool {
+        let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
+        let block = get_block_cache(block_id as usize, Arc::clone(&self.block_device));
+        if block.lock().read(block_offset, |disk_inode: &DiskInode| disk_inode.is_dir()) {
+            return false;
+        }
+        let data_blocks = block
+            .lock()
+            .modify(block_offset, |disk_inode: &mut DiskInode| {
+                if disk_inode.dec_nlink() > 0 {
+                    return None;
+                }
+                Some(disk_inode.clear_size(&self.block_device))
+            });
+        let Some(data_blocks) = data_blocks else {
+            return true;
+        };
+        for data_block in data_blocks {
+            fs.dealloc_data(data_block);
+        }
+        fs.dealloc_inode(inode_id);
+        true
+    }
+
     /// 扩容 DiskInode
     fn increase_size(
         &self,
@@ -202,6 +426,34 @@ impl Inode {
         })
     }
 
+    /// 从第 `start` 个目录项起读取至多 `max` 个目录项，附带各自的 inode 类型
+    ///
+    /// 越过末尾时返回空列表。子 inode 可能与当前 inode 位于同一个缓存块，
+    /// 因此先读出全部目录项，释放块缓存后再逐个查询类型。
+    pub fn dirents(&self, start: usize, max: usize) -> Vec<(DirEntry, DiskInodeType)> {
+        let fs = self.fs.lock();
+        let entries: Vec<DirEntry> = self.read_disk_inode(|disk_inode| {
+            let file_count = disk_inode.size as usize / DIRENT_SZ;
+            (start..file_count.min(start.saturating_add(max)))
+                .map(|i| {
+                    let mut dirent = DirEntry::empty();
+                    disk_inode.read_at(i * DIRENT_SZ, dirent.as_bytes_mut(), &self.block_device);
+                    dirent
+                })
+                .collect()
+        });
+        entries
+            .into_iter()
+            .map(|dirent| {
+                let (block_id, block_offset) = fs.get_disk_inode_pos(dirent.inode_number());
+                let inode_type = get_block_cache(block_id as usize, Arc::clone(&self.block_device))
+                    .lock()
+                    .read(block_offset, |disk_inode: &DiskInode| disk_inode.inode_type());
+                (dirent, inode_type)
+            })
+            .collect()
+    }
+
     /// 从文件指定偏移读取数据
     ///
     /// # Arguments
@@ -241,6 +493,54 @@ impl Inode {
         size
     }
 
+    /// 文件元数据
+    ///
+    /// 供 `fstat` 使用，一次读取 DiskInode 中的类型、链接数与大小。
+    pub fn stat(&self) -> Stat {
+        let ino = self
+            .fs
+            .lock()
+            .get_inode_id(self.block_id as u32, self.block_offset);
+        self.read_disk_inode(|disk_inode| Stat {
+            ino: ino as u64,
+            mode: disk_inode.inode_type().into(),
+            nlink: disk_inode.nlink() as u32,
+            size: disk_inode.size as u64,
+        })
+    }
+
+    /// 文件大小（字节）
+    pub fn size(&self) -> usize {
+        self.read_disk_inode(|disk_inode| disk_inode.size as usize)
+    }
+
+    /// 把文件大小调整为 `new_size`
+    ///
+    /// 变大时分配数据块（及所需的间接索引块），新增部分读出为 0；
+    /// 变小时回收多余的块，并把最后一个保留块中 `new_size` 之后的字节清零。
+    ///
+    /// # Arguments
+    ///
+    /// * `new_size` - 新的文件大小
+    pub fn truncate(&self, new_size: usize) {
+        let mut fs = self.fs.lock();
+        self.modify_disk_inode(|disk_inode| {
+            let old_size = disk_inode.size as usize;
+            if new_size >= old_size {
+                self.increase_size(new_size as u32, disk_inode, &mut fs);
+                return;
+            }
+            // 之后再变大时，这部分字节应读出为 0
+            let block_end = new_size.next_multiple_of(BLOCK_SZ).min(old_size);
+            let zeros = [0u8; BLOCK_SZ];
+            disk_inode.write_at(new_size, &zeros[..block_end - new_size], &self.block_device);
+            for data_block in disk_inode.decrease_size(new_size as u32, &self.block_device) {
+                fs.dealloc_data(data_block);
+            }
+        });
+        block_cache_sync_all();
+    }
+
     /// 清空文件内容
     ///
     /// 回收所有数据块，将文件大小设为 0。
@@ -256,6 +556,39 @@ impl Inode {
     }
 }
 
+/// 文件类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatMode {
+    /// 普通文件
+    File,
+    /// 目录
+    Directory,
+}
+
+impl From<DiskInodeType> for StatMode {
+    fn from(type_: DiskInodeType) -> Self {
+        match type_ {
+            DiskInodeType::File => Self::File,
+            DiskInodeType::Directory => Self::Directory,
+        }
+    }
+}
+
+/// 文件元数据
+///
+/// 由 [`Inode::stat`] 返回。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stat {
+    /// inode 编号
+    pub ino: u64,
+    /// 文件类型
+    pub mode: StatMode,
+    /// 硬链接数
+    pub nlink: u32,
+    /// 文件大小（字节）
+    pub size: u64,
+}
+
 /// 用户缓冲区
 ///
 /// 封装分散的用户空间缓冲区切片。
@@ -279,6 +612,11 @@ impl UserBuffer {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// 按顺序遍历各个切片，适合整段输出而不逐字节取指针
+    pub fn segments(&self) -> impl Iterator<Item = &[u8]> {
+        self.buffers.iter().map(|b| &b[..])
+    }
 }
 
 impl IntoIterator for UserBuffer {
@@ -331,6 +669,10 @@ bitflags::bitflags! {
         const CREATE = 1 << 9;
         /// 截断
         const TRUNC = 1 << 10;
+        /// 追加：每次写入前把偏移移到文件末尾
+        const APPEND = 1 << 11;
+        /// 与 `CREATE` 同用时创建目录而不是普通文件
+        const DIRECTORY = 1 << 16;
     }
 }
 
@@ -349,24 +691,92 @@ impl OpenFlags {
             (true, true)
         }
     }
+
+    /// 根据标志返回 `CREATE` 时应创建的 inode 类型
+    pub fn create_type(&self) -> DiskInodeType {
+        if self.contains(Self::DIRECTORY) {
+            DiskInodeType::Directory
+        } else {
+            DiskInodeType::File
+        }
+    }
+}
+
+/// 读操作的完成条件
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadMode {
+    /// 读满请求的字节数或遇到 EOF 才返回；普通文件的默认值
+    Full,
+    /// 至少读到 1 字节后，一旦暂时没有数据就返回（类似 raw 模式的终端）；字符设备的默认值
+    Partial,
+}
+
+/// 从流式数据源取一个字节的结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadPoll {
+    /// 读到一个字节
+    Byte(u8),
+    /// 暂时没有数据，之后可能还有
+    Empty,
+    /// 数据源已结束，不会再有数据
+    Eof,
+}
+
+impl ReadMode {
+    /// 按本模式反复调用 `poll` 填充 `buf`，返回读到的字节数
+    ///
+    /// 遇到 [`ReadPoll::Eof`] 立即返回已读字节数（可能为 0）；遇到 [`ReadPoll::Empty`] 时，
+    /// `Partial` 在已读到数据时返回，否则与 `Full` 一样继续轮询。
+    pub fn fill(self, buf: &mut [u8], mut poll: impl FnMut() -> ReadPoll) -> usize {
+        let mut len = 0;
+        while len < buf.len() {
+            match poll() {
+                ReadPoll::Byte(byte) => {
+                    buf[len] = byte;
+                    len += 1;
+                }
+                ReadPoll::Eof => break,
+                ReadPoll::Empty if self == Self::Partial && len > 0 => break,
+                ReadPoll::Empty => core::hint::spin_loop(),
+            }
+        }
+        len
+    }
+}
+
+/// [`FileHandle::seek`] 的目标位置
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeekFrom {
+    /// 相对文件开头
+    Start(usize),
+    /// 相对当前偏移
+    Current(isize),
+    /// 相对文件末尾
+    End(isize),
 }
 
 /// 文件句柄
 ///
-/// 包含 Inode 引用、权限和当前偏移。
+/// 包含 Inode 引用（或管道端）、权限、读模式、追加标志和当前偏移。
 pub struct FileHandle {
     /// 底层 Inode
     pub inode: Option<Arc<Inode>>,
+    /// 管道端，仅由 [`FileHandle::from_pipe`] 创建的句柄持有
+    pipe: Option<PipeEnd>,
     /// 可读
     readable: bool,
     /// 可写
     writable: bool,
+    /// 读操作的完成条件
+    read_mode: ReadMode,
+    /// 每次写入前是否移到文件末尾（`O_APPEND`）
+    append: bool,
     /// 当前偏移
     pub offset: usize,
 }
 
 impl FileHandle {
-    /// 创建新的文件句柄
+    /// 创建新的文件句柄，读模式为 [`ReadMode::Full`]
     ///
     /// # Arguments
     ///
@@ -376,13 +786,16 @@ impl FileHandle {
     pub fn new(readable: bool, writable: bool, inode: Arc<Inode>) -> Self {
         Self {
             inode: Some(inode),
+            pipe: None,
             readable,
             writable,
+            read_mode: ReadMode::Full,
+            append: false,
             offset: 0,
         }
     }
 
-    /// 创建空的文件句柄
+    /// 创建空的文件句柄（控制台等字符设备），读模式为 [`ReadMode::Partial`]
     ///
     /// # Arguments
     ///
@@ -391,12 +804,36 @@ impl FileHandle {
     pub fn empty(readable: bool, writable: bool) -> Self {
         Self {
             inode: None,
+            pipe: None,
             readable,
             writable,
+            read_mode: ReadMode::Partial,
+            append: false,
+            offset: 0,
+        }
+    }
+
+    /// 创建管道端的文件句柄，读模式为 [`ReadMode::Partial`]
+    ///
+    /// 读端只可读，写端只可写。管道句柄没有 Inode，读写由内核通过 [`FileHandle::pipe`] 完成。
+    pub fn from_pipe(end: PipeEnd) -> Self {
+        let readable = matches!(end, PipeEnd::Read(_));
+        Self {
+            inode: None,
+            pipe: Some(end),
+            readable,
+            writable: !readable,
+            read_mode: ReadMode::Partial,
+            append: false,
             offset: 0,
         }
     }
 
+    /// 句柄持有的管道端；普通文件和控制台返回 `None`
+    pub fn pipe(&self) -> Option<&PipeEnd> {
+        self.pipe.as_ref()
+    }
+
     /// 是否可读
     pub fn readable(&self) -> bool {
         self.readable
@@ -407,6 +844,64 @@ impl FileHandle {
         self.writable
     }
 
+    /// 读操作的完成条件
+    pub fn read_mode(&self) -> ReadMode {
+        self.read_mode
+    }
+
+    /// 设置读操作的完成条件
+    pub fn set_read_mode(&mut self, mode: ReadMode) {
+        self.read_mode = mode;
+    }
+
+    /// 底层文件的元数据；控制台等没有 Inode 的句柄返回 `None`
+    pub fn stat(&self) -> Option<Stat> {
+        self.inode.as_ref().map(|inode| inode.stat())
+    }
+
+    /// 移动当前偏移，结果限制在 `[0, 文件大小]` 内，返回新偏移
+    ///
+    /// 控制台、管道等没有 Inode 的句柄不能移动偏移，返回 `None`。
+    pub fn seek(&mut self, pos: SeekFrom) -> Option<usize> {
+        let size = self.stat()?.size as usize;
+        let (base, delta) = match pos {
+            SeekFrom::Start(offset) => (offset, 0),
+            SeekFrom::Current(delta) => (self.offset, delta),
+            SeekFrom::End(delta) => (size, delta),
+        };
+        let target = if delta < 0 {
+            base.saturating_sub(delta.unsigned_abs())
+        } else {
+            base.saturating_add(delta as usize)
+        };
+        self.offset = target.min(size);
+        Some(self.offset)
+    }
+
+    /// 是否以追加方式写入
+    pub fn append(&self) -> bool {
+        self.append
+    }
+
+    /// 设置是否以追加方式写入，通常取自 [`OpenFlags::APPEND`]
+    pub fn set_append(&mut self, append: bool) {
+        self.append = append;
+    }
+
+    /// 从目录游标处读取至多 `max` 个目录项，附带各自的 inode 类型
+    ///
+    /// 目录句柄的 `offset` 是目录项下标而不是字节偏移：每次从 `offset` 处继续，
+    /// 读出多少项就前进多少，读完所有目录项后返回空列表。句柄不是目录时返回 `None`。
+    pub fn read_dirents(&mut self, max: usize) -> Option<Vec<(DirEntry, DiskInodeType)>> {
+        let inode = self.inode.as_ref()?;
+        if inode.inode_type() != DiskInodeType::Directory {
+            return None;
+        }
+        let entries = inode.dirents(self.offset, max);
+        self.offset += entries.len();
+        Some(entries)
+    }
+
     /// 从当前偏移读取数据到 UserBuffer
     ///
     /// 读取后更新偏移。
@@ -439,7 +934,8 @@ impl FileHandle {
 
     /// 从 UserBuffer 写入数据到当前偏移
     ///
-    /// 写入后更新偏移。
+    /// 以追加方式打开时先把偏移移到文件末尾。写入后更新偏移。
+    /// 各切片直接交给 [`Inode::write_at`]，不经过中间缓冲；某个切片未能写满时停止。
     ///
     /// # Arguments
     ///
@@ -451,11 +947,16 @@ impl FileHandle {
     pub fn write(&mut self, buf: UserBuffer) -> usize {
         let mut total_write_size = 0usize;
         if let Some(inode) = &self.inode {
+            if self.append {
+                self.offset = inode.size();
+            }
             for slice in buf.buffers.iter() {
                 let write_size = inode.write_at(self.offset, slice);
-                assert_eq!(write_size, slice.len());
                 self.offset += write_size;
                 total_write_size += write_size;
+                if write_size < slice.len() {
+                    break;
+                }
             }
         }
         total_write_size
@@ -512,6 +1013,20 @@ pub trait FSManager: Send + Sync {
     /// 成功返回 0，失败返回 -1。
     fn unlink(&self, path: &str) -> isize;
 
+    /// 重命名文件
+    ///
+    /// 可跨目录移动；目标已存在时覆盖。
+    ///
+    /// # Arguments
+    ///
+    /// * `old_path` - 原路径
+    /// * `new_path` - 新路径
+    ///
+    /// # Returns
+    ///
+    /// 成功返回 0，失败返回 -1。
+    fn rename(&self, old_path: &str, new_path: &str) -> isize;
+
     /// 读取目录
     ///
     /// # Arguments
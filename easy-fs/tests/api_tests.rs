@@ -6,7 +6,11 @@
 //! 注意：easy-fs 是一个 no_std crate，但测试使用 std 来创建 mock 块设备。
 
 use std::sync::{Arc, Mutex as StdMutex, OnceLock};
-use easy_fs::{BlockDevice, EasyFileSystem, FileHandle, Inode, OpenFlags, UserBuffer, BLOCK_SZ};
+use easy_fs::{
+    get_block_cache, set_readahead, BlockCacheManager, BlockDevice, DirEntry, DiskInodeType, EasyFileSystem, FileHandle, Inode,
+    OpenFlags, PipeEnd, PipeError, ReadMode, ReadPoll, SeekFrom, Stat, StatMode, UserBuffer, BLOCK_CACHE_MANAGER,
+    BLOCK_CACHE_SIZE, BLOCK_SZ, PIPE_BUFFER_SIZE,
+};
 
 // Mock 块设备实现，用于测试
 struct MockBlockDevice {
@@ -43,6 +47,10 @@ impl BlockDevice for MockBlockDevice {
             block[..len].copy_from_slice(&buf[..len]);
         }
     }
+
+    fn num_blocks(&self) -> Option<usize> {
+        Some(self.blocks.lock().unwrap().len())
+    }
 }
 
 const TEST_TOTAL_BLOCKS: u32 = 4096;
@@ -120,6 +128,92 @@ fn test_block_device_multiple_blocks() {
     }
 }
 
+#[test]
+fn test_block_cache_readahead() {
+    with_test_device(|device| {
+        let cached = |id: usize| BLOCK_CACHE_MANAGER.lock().is_cached(id);
+
+        // 关闭预读时顺序访问只缓存访问到的块
+        set_readahead(0);
+        drop(get_block_cache(3000, device.clone()));
+        drop(get_block_cache(3001, device.clone()));
+        assert!(cached(3001));
+        assert!(!cached(3002));
+
+        // 开启后第二次顺序访问触发预读，后续块已驻留
+        set_readahead(4);
+        drop(get_block_cache(3100, device.clone()));
+        assert!(!cached(3101));
+        drop(get_block_cache(3101, device.clone()));
+        assert!((3102..=3105).all(cached));
+        assert!(!cached(3106));
+
+        // 随机访问不预读
+        drop(get_block_cache(3200, device.clone()));
+        assert!(!cached(3201));
+        set_readahead(0);
+    });
+}
+
+#[test]
+fn test_block_cache_readahead_stops_at_device_end() {
+    // 预读窗口被限制在设备末尾之前，不会读取不存在的块
+    struct Bounded(MockBlockDevice, StdMutex<Vec<usize>>);
+    impl BlockDevice for Bounded {
+        fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+            self.1.lock().unwrap().push(block_id);
+            self.0.read_block(block_id, buf);
+        }
+        fn write_block(&self, block_id: usize, buf: &[u8]) {
+            self.0.write_block(block_id, buf);
+        }
+        fn num_blocks(&self) -> Option<usize> {
+            self.0.num_blocks()
+        }
+    }
+
+    let device = Arc::new(Bounded(MockBlockDevice::new(BLOCK_SZ, 8), StdMutex::new(Vec::new())));
+    let mut manager = BlockCacheManager::new();
+    manager.set_readahead(4);
+    drop(manager.get_block_cache(5, device.clone()));
+    drop(manager.get_block_cache(6, device.clone()));
+    assert!(manager.is_cached(7));
+    assert_eq!(*device.1.lock().unwrap(), [5, 6, 7]);
+}
+
+#[test]
+fn test_block_cache_lru() {
+    // 测试超出容量时替换最近最少使用且未被引用的块，脏块在替换时写回
+    let device = Arc::new(MockBlockDevice::new(BLOCK_SZ, 64));
+    let mut manager = BlockCacheManager::new();
+    let mut get = |id: usize| manager.get_block_cache(id, device.clone());
+
+    let first = get(0);
+    first.lock().modify(0, |value: &mut u32| *value = 0xdead_beef);
+    drop(first);
+    for id in 1..BLOCK_CACHE_SIZE {
+        drop(get(id));
+    }
+    // 再次访问块 1，块 0 成为最近最少使用的块
+    drop(get(1));
+    drop(get(BLOCK_CACHE_SIZE));
+    assert!(!manager.is_cached(0));
+    assert!(manager.is_cached(1));
+    assert!(manager.is_cached(BLOCK_CACHE_SIZE));
+    // 被替换的脏块已写回设备
+    assert_eq!(&device.blocks.lock().unwrap()[0][..4], &0xdead_beefu32.to_ne_bytes());
+
+    // 仍被引用的最旧块（块 2）被跳过，替换下一个（块 3）
+    let held = manager.get_block_cache(2, device.clone());
+    for id in (3..=BLOCK_CACHE_SIZE).chain([1]) {
+        drop(manager.get_block_cache(id, device.clone()));
+    }
+    drop(manager.get_block_cache(BLOCK_CACHE_SIZE + 1, device.clone()));
+    assert!(manager.is_cached(2));
+    assert!(!manager.is_cached(3));
+    drop(held);
+}
+
 #[test]
 fn test_open_flags_basic() {
     // 测试 OpenFlags bitflags
@@ -171,6 +265,16 @@ fn test_open_flags_read_write() {
     assert!(writable);
 }
 
+#[test]
+fn test_open_flags_create_type() {
+    // 测试 DIRECTORY 提示决定 CREATE 时的 inode 类型
+    assert_eq!(OpenFlags::CREATE.create_type(), DiskInodeType::File);
+    assert_eq!(
+        (OpenFlags::CREATE | OpenFlags::DIRECTORY).create_type(),
+        DiskInodeType::Directory
+    );
+}
+
 #[test]
 fn test_file_handle_new() {
     // 测试 FileHandle::new 与读写偏移
@@ -198,6 +302,102 @@ fn test_file_handle_new() {
     });
 }
 
+/// 按页边界把 `data` 切成若干段，首段从页内偏移 `skew` 开始，模拟内核翻译得到的用户缓冲区
+fn scatter(data: &[u8], skew: usize) -> UserBuffer {
+    const PAGE: usize = 4096;
+    let mut buffers = Vec::new();
+    let mut rest: &'static mut [u8] = Box::leak(data.to_vec().into_boxed_slice());
+    let mut chunk = PAGE - skew;
+    while !rest.is_empty() {
+        let (head, tail) = rest.split_at_mut(chunk.min(rest.len()));
+        buffers.push(head);
+        rest = tail;
+        chunk = PAGE;
+    }
+    UserBuffer::new(buffers)
+}
+
+#[test]
+fn test_file_handle_scatter_write_matches_bytewise() {
+    // 跨多页的分段写入与逐字节写入得到相同的文件内容，分段读回也一致
+    with_test_fs(|_device, root| {
+        let data: Vec<u8> = (0..3 * 4096 + 100).map(|i| (i * 7 % 251) as u8).collect();
+
+        let bytewise = root.create("bytewise_file").unwrap();
+        for (i, byte) in data.iter().enumerate() {
+            assert_eq!(bytewise.write_at(i, core::slice::from_ref(byte)), 1);
+        }
+
+        let scattered = root.create("scatter_file").unwrap();
+        let mut handle = FileHandle::new(true, true, scattered.clone());
+        let buf = scatter(&data, 37);
+        assert!(buf.segments().count() > 3);
+        assert_eq!(buf.segments().flatten().copied().collect::<Vec<_>>(), data);
+        assert_eq!(handle.write(buf), data.len());
+        assert_eq!(handle.offset, data.len());
+
+        let mut expected = vec![0u8; data.len()];
+        let mut actual = vec![0u8; data.len()];
+        assert_eq!(bytewise.read_at(0, &mut expected), data.len());
+        assert_eq!(scattered.read_at(0, &mut actual), data.len());
+        assert_eq!(actual, expected);
+        assert_eq!(actual, data);
+
+        handle.offset = 0;
+        let read_buf = scatter(&vec![0u8; data.len()], 1000);
+        let ptrs: Vec<_> = read_buf.segments().map(|seg| (seg.as_ptr(), seg.len())).collect();
+        assert_eq!(handle.read(read_buf), data.len());
+        let read_back: Vec<u8> = ptrs
+            .into_iter()
+            .flat_map(|(ptr, len)| unsafe { std::slice::from_raw_parts(ptr, len) }.iter().copied())
+            .collect();
+        assert_eq!(read_back, data);
+    });
+}
+
+#[test]
+fn test_file_handle_read_dirents() {
+    // offset 作为目录项游标跨调用前进，每项附带 inode 类型
+    with_test_fs(|_device, root| {
+        let dir = root.create_typed("dents", DiskInodeType::Directory).unwrap();
+        let a = dir.create("a.txt").unwrap();
+        let b = dir.create_typed("sub", DiskInodeType::Directory).unwrap();
+        let c = dir.create("c").unwrap();
+        let expected = [
+            (a.stat().ino, DiskInodeType::File, "a.txt"),
+            (b.stat().ino, DiskInodeType::Directory, "sub"),
+            (c.stat().ino, DiskInodeType::File, "c"),
+        ];
+        fn check(entries: &[(DirEntry, DiskInodeType)], expected: &[(u64, DiskInodeType, &str)]) {
+            assert_eq!(entries.len(), expected.len());
+            for ((entry, inode_type), &(ino, ty, name)) in entries.iter().zip(expected) {
+                assert_eq!(entry.inode_number() as u64, ino);
+                assert_eq!(*inode_type, ty);
+                assert_eq!(entry.name(), name);
+            }
+        }
+
+        let mut handle = FileHandle::new(true, false, dir);
+        assert!(handle.read_dirents(0).unwrap().is_empty());
+        assert_eq!(handle.offset, 0);
+
+        check(&handle.read_dirents(2).unwrap(), &expected[..2]);
+        assert_eq!(handle.offset, 2);
+
+        // 请求的项数超过剩余项数时只返回剩余的
+        check(&handle.read_dirents(usize::MAX).unwrap(), &expected[2..]);
+        assert_eq!(handle.offset, 3);
+
+        // 游标已到末尾
+        assert!(handle.read_dirents(8).unwrap().is_empty());
+        assert_eq!(handle.offset, 3);
+
+        // 普通文件不能列目录
+        let mut file_handle = FileHandle::new(true, false, a);
+        assert!(file_handle.read_dirents(8).is_none());
+    });
+}
+
 #[test]
 fn test_file_handle_empty() {
     // 测试 FileHandle::empty
@@ -225,6 +425,53 @@ fn test_file_handle_readable_writable() {
     assert!(handle3.writable());
 }
 
+#[test]
+fn test_read_mode_defaults() {
+    // 普通文件默认读满，字符设备默认部分读
+    with_test_fs(|_device, root| {
+        let inode = root.create("mode_file").unwrap();
+        let mut handle = FileHandle::new(true, false, inode);
+        assert_eq!(handle.read_mode(), ReadMode::Full);
+        handle.set_read_mode(ReadMode::Partial);
+        assert_eq!(handle.read_mode(), ReadMode::Partial);
+    });
+    assert_eq!(FileHandle::empty(true, false).read_mode(), ReadMode::Partial);
+}
+
+#[test]
+fn test_read_mode_partial_pipe() {
+    // 管道里只有 1 字节且写端仍打开：部分读拿到 1 字节后立即返回，不等待更多数据
+    let mut pipe = std::collections::VecDeque::from(b"x".to_vec());
+    let mut empty_polls = 0;
+    let mut buf = [0u8; 4];
+    let len = ReadMode::Partial.fill(&mut buf, || match pipe.pop_front() {
+        Some(byte) => ReadPoll::Byte(byte),
+        None => {
+            empty_polls += 1;
+            assert!(empty_polls < 100, "partial read blocked waiting for more data");
+            ReadPoll::Empty
+        }
+    });
+    assert_eq!(len, 1);
+    assert_eq!(buf[0], b'x');
+    assert_eq!(empty_polls, 1);
+
+    // 读满模式会越过暂时为空的轮询，直到数据齐或 EOF
+    let mut source = vec![
+        ReadPoll::Eof,
+        ReadPoll::Byte(b'b'),
+        ReadPoll::Empty,
+        ReadPoll::Byte(b'a'),
+    ];
+    let len = ReadMode::Full.fill(&mut buf, || source.pop().unwrap());
+    assert_eq!(len, 2);
+    assert_eq!(&buf[..2], b"ab");
+
+    // EOF 在两种模式下都返回 0
+    assert_eq!(ReadMode::Partial.fill(&mut buf, || ReadPoll::Eof), 0);
+    assert_eq!(ReadMode::Full.fill(&mut buf, || ReadPoll::Eof), 0);
+}
+
 #[test]
 fn test_user_buffer_new() {
     // 测试 UserBuffer::new
@@ -312,6 +559,26 @@ fn test_inode_create_file() {
     });
 }
 
+#[test]
+fn test_inode_create_typed() {
+    // 测试按类型创建：普通文件与目录的类型被写入 DiskInode
+    with_test_fs(|_device, root| {
+        let file = root.create_typed("plain", DiskInodeType::File).unwrap();
+        let dir = root.create_typed("subdir", DiskInodeType::Directory).unwrap();
+        assert_eq!(file.inode_type(), DiskInodeType::File);
+        assert_eq!(dir.inode_type(), DiskInodeType::Directory);
+
+        // 重新查找得到的仍是磁盘上记录的类型
+        assert_eq!(root.find("plain").unwrap().inode_type(), DiskInodeType::File);
+        assert_eq!(root.find("subdir").unwrap().inode_type(), DiskInodeType::Directory);
+        assert_eq!(root.inode_type(), DiskInodeType::Directory);
+
+        // create 仍然创建普通文件，同名条目已存在时失败
+        assert_eq!(root.create("legacy").unwrap().inode_type(), DiskInodeType::File);
+        assert!(root.create_typed("subdir", DiskInodeType::File).is_none());
+    });
+}
+
 #[test]
 #[should_panic]
 fn test_inode_create_name_too_long_panics() {
@@ -519,3 +786,380 @@ fn test_inode_write_at_offset() {
         assert_eq!(&buf[..read_len], b"Hello, Rustd!");
     });
 }
+
+#[test]
+fn test_inode_rename() {
+    // 测试目录内重命名
+    with_test_fs(|_device, root| {
+        let file = root.create("old_name").unwrap();
+        file.write_at(0, b"payload");
+        root.create("other").unwrap();
+
+        assert!(root.rename("old_name", "new_name"));
+        assert!(root.find("old_name").is_none());
+        assert_eq!(
+            root.readdir(),
+            vec!["new_name".to_string(), "other".to_string()]
+        );
+
+        let renamed = root.find("new_name").unwrap();
+        let mut buf = [0u8; 7];
+        assert_eq!(renamed.read_at(0, &mut buf), 7);
+        assert_eq!(&buf, b"payload");
+
+        // 原文件不存在时失败
+        assert!(!root.rename("missing", "whatever"));
+    });
+}
+
+#[test]
+fn test_inode_rename_overwrite() {
+    // 测试重命名覆盖已存在的目标，并回收被覆盖文件
+    with_test_fs(|_device, root| {
+        root.create("src").unwrap().write_at(0, b"new");
+        root.create("dst").unwrap().write_at(0, &[7u8; 3 * BLOCK_SZ]);
+        root.create("tail").unwrap();
+
+        assert!(root.rename("src", "dst"));
+        assert!(root.find("src").is_none());
+        let mut names = root.readdir();
+        names.sort();
+        assert_eq!(names, vec!["dst".to_string(), "tail".to_string()]);
+
+        let dst = root.find("dst").unwrap();
+        let mut buf = [0u8; 8];
+        assert_eq!(dst.read_at(0, &mut buf), 3);
+        assert_eq!(&buf[..3], b"new");
+
+        // 被覆盖文件的 inode 被回收后可以被新文件复用，且内容为空
+        let reused = root.create("fresh").unwrap();
+        assert_eq!(reused.read_at(0, &mut buf), 0);
+    });
+}
+
+#[test]
+fn test_easy_filesystem_stat_fs() {
+    // 测试 stat_fs：创建文件消耗 inode/数据块，删除后归还
+    with_test_device(|device| {
+        let efs =
+            EasyFileSystem::create(device.clone(), TEST_TOTAL_BLOCKS, TEST_INODE_BITMAP_BLOCKS);
+        let root = EasyFileSystem::root_inode(&efs);
+        let initial = efs.lock().stat_fs();
+        // 根目录占用一个 inode，空目录尚无数据块
+        assert_eq!(initial.free_blocks, initial.total_blocks);
+        assert_eq!(initial.free_inodes, initial.total_inodes - 1);
+
+        root.create("a").unwrap().write_at(0, &[1u8; 4 * BLOCK_SZ]);
+        root.create("b").unwrap().write_at(0, b"b");
+        let after_create = efs.lock().stat_fs();
+        assert_eq!(after_create.total_blocks, initial.total_blocks);
+        assert_eq!(after_create.free_inodes, initial.free_inodes - 2);
+        assert!(after_create.free_blocks <= initial.free_blocks - 5);
+
+        // 重命名覆盖会回收目标文件的 inode 与数据块
+        assert!(root.rename("b", "a"));
+        let after_delete = efs.lock().stat_fs();
+        assert_eq!(after_delete.free_inodes, after_create.free_inodes + 1);
+        assert!(after_delete.free_blocks >= after_create.free_blocks + 4);
+
+        // 清空文件归还其数据块
+        root.find("a").unwrap().clear();
+        let after_clear = efs.lock().stat_fs();
+        assert_eq!(after_clear.free_blocks, after_delete.free_blocks + 1);
+    });
+}
+
+#[test]
+fn test_inode_link_unlink() {
+    // 测试硬链接：两个名字读到同一内容，删除最后一个链接时才回收 inode
+    with_test_device(|device| {
+        let efs =
+            EasyFileSystem::create(device.clone(), TEST_TOTAL_BLOCKS, TEST_INODE_BITMAP_BLOCKS);
+        let root = EasyFileSystem::root_inode(&efs);
+        let file = root.create("origin").unwrap();
+        file.write_at(0, b"shared data");
+        assert_eq!(file.nlink(), 1);
+        let before_link = efs.lock().stat_fs();
+
+        assert!(root.link("origin", "alias"));
+        assert_eq!(file.nlink(), 2);
+        assert_eq!(efs.lock().stat_fs().free_inodes, before_link.free_inodes);
+        let alias = root.find("alias").unwrap();
+        assert_eq!(alias.nlink(), 2);
+        let mut buf = [0u8; 11];
+        assert_eq!(alias.read_at(0, &mut buf), 11);
+        assert_eq!(&buf, b"shared data");
+        // 通过一个名字写入，另一个名字可见
+        alias.write_at(0, b"S");
+        assert_eq!(file.read_at(0, &mut buf), 11);
+        assert_eq!(&buf, b"Shared data");
+
+        // 目标已存在、原文件不存在或是目录时拒绝
+        assert!(!root.link("origin", "alias"));
+        assert!(!root.link("missing", "other"));
+        root.create_typed("dir", DiskInodeType::Directory).unwrap();
+        assert!(!root.link("dir", "dir2"));
+        assert!(!root.unlink("dir"));
+
+        // 删除一个名字后另一个仍可读
+        assert!(root.unlink("origin"));
+        assert!(root.find("origin").is_none());
+        assert_eq!(alias.nlink(), 1);
+        assert_eq!(root.find("alias").unwrap().read_at(0, &mut buf), 11);
+        assert!(!root.unlink("origin"));
+
+        // 删除最后一个链接回收 inode 与数据块
+        let before_unlink = efs.lock().stat_fs();
+        assert!(root.unlink("alias"));
+        let after_unlink = efs.lock().stat_fs();
+        assert_eq!(after_unlink.free_inodes, before_unlink.free_inodes + 1);
+        assert_eq!(after_unlink.free_blocks, before_unlink.free_blocks + 1);
+        assert_eq!(root.readdir(), vec!["dir".to_string()]);
+    });
+}
+
+#[test]
+fn test_inode_rename_between_links() {
+    // 测试在同一文件的两个硬链接之间重命名不做任何事
+    with_test_fs(|_device, root| {
+        root.create("a").unwrap().write_at(0, b"x");
+        assert!(root.link("a", "b"));
+        assert!(root.rename("a", "b"));
+        let mut names = root.readdir();
+        names.sort();
+        assert_eq!(names, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(root.find("b").unwrap().nlink(), 2);
+    });
+}
+
+#[test]
+fn test_inode_truncate() {
+    // 测试 truncate 变大补零、变小回收数据块（跨越一级、二级间接索引）
+    with_test_device(|device| {
+        let efs =
+            EasyFileSystem::create(device.clone(), TEST_TOTAL_BLOCKS, TEST_INODE_BITMAP_BLOCKS);
+        let root = EasyFileSystem::root_inode(&efs);
+        let file = root.create("file").unwrap();
+        let initial = efs.lock().stat_fs();
+        file.write_at(0, &[0xaau8; 100]);
+
+        // 变大：新增部分读出为 0
+        let big = 200 * BLOCK_SZ + 7;
+        file.truncate(big);
+        assert_eq!(file.size(), big);
+        let mut buf = vec![0xffu8; big];
+        assert_eq!(file.read_at(0, &mut buf), big);
+        assert!(buf[..100].iter().all(|&b| b == 0xaa));
+        assert!(buf[100..].iter().all(|&b| b == 0));
+
+        // 变小到块中间：之后再变大，被截掉的字节读出为 0
+        file.write_at(0, &vec![0x55u8; big]);
+        file.truncate(BLOCK_SZ + 10);
+        assert_eq!(file.size(), BLOCK_SZ + 10);
+        assert_eq!(file.read_at(0, &mut buf), BLOCK_SZ + 10);
+        assert!(buf[..BLOCK_SZ + 10].iter().all(|&b| b == 0x55));
+        assert_eq!(efs.lock().stat_fs().free_blocks, initial.free_blocks - 2);
+        file.truncate(2 * BLOCK_SZ);
+        assert_eq!(file.read_at(0, &mut buf), 2 * BLOCK_SZ);
+        assert!(buf[BLOCK_SZ + 10..2 * BLOCK_SZ].iter().all(|&b| b == 0));
+
+        // 截断到 0 归还全部数据块
+        file.truncate(0);
+        assert_eq!(file.size(), 0);
+        assert_eq!(efs.lock().stat_fs().free_blocks, initial.free_blocks);
+    });
+}
+
+#[test]
+fn test_file_handle_append() {
+    // 测试以追加方式写入时总是写到文件末尾
+    with_test_fs(|_device, root| {
+        let inode = root.create("log").unwrap();
+        inode.write_at(0, b"head");
+
+        let mut handle = FileHandle::new(true, true, inode.clone());
+        assert!(!handle.append());
+        handle.set_append(true);
+        assert!(handle.append());
+        let mut data = *b"-tail";
+        let buf = UserBuffer::new(vec![unsafe {
+            std::slice::from_raw_parts_mut(data.as_mut_ptr(), data.len())
+        }]);
+        assert_eq!(handle.write(buf), 5);
+        assert_eq!(handle.offset, 9);
+
+        // 其他途径写入后，追加仍然落在新的末尾
+        inode.write_at(9, b"+");
+        handle.offset = 0;
+        let buf = UserBuffer::new(vec![unsafe {
+            std::slice::from_raw_parts_mut(data.as_mut_ptr(), data.len())
+        }]);
+        handle.write(buf);
+        let mut out = [0u8; 15];
+        assert_eq!(inode.read_at(0, &mut out), 15);
+        assert_eq!(&out, b"head-tail+-tail");
+    });
+}
+
+#[test]
+fn test_inode_stat() {
+    // 测试 stat 返回大小、类型、链接数与 inode 编号
+    with_test_fs(|_device, root| {
+        let root_stat = root.stat();
+        assert_eq!(root_stat.ino, 0);
+        assert_eq!(root_stat.mode, StatMode::Directory);
+
+        let file = root.create("file").unwrap();
+        file.write_at(0, &[1u8; 1000]);
+        let stat = file.stat();
+        assert_eq!(stat.size, 1000);
+        assert_eq!(stat.mode, StatMode::File);
+        assert_eq!(stat.nlink, 1);
+        assert_ne!(stat.ino, root_stat.ino);
+        // 同一文件的不同 Inode 对象与硬链接得到相同的 inode 编号
+        assert!(root.link("file", "alias"));
+        assert_eq!(root.find("alias").unwrap().stat(), Stat { nlink: 2, ..stat });
+
+        let dir = root.create_typed("dir", DiskInodeType::Directory).unwrap();
+        assert_eq!(dir.stat().mode, StatMode::Directory);
+
+        // 通过 FileHandle 查询
+        let handle = FileHandle::new(true, false, file);
+        assert_eq!(handle.stat().unwrap().size, 1000);
+        assert!(FileHandle::empty(true, false).stat().is_none());
+    });
+}
+
+#[test]
+fn test_pipe_blocking_and_eof() {
+    // 测试空管道在有写端时阻塞，写端全部关闭后读到 EOF
+    let (reader, writer) = easy_fs::pipe();
+    let mut buf = [0u8; 8];
+    assert_eq!(reader.read(&mut buf), Err(PipeError::WouldBlock));
+
+    assert_eq!(writer.write(b"hello"), Ok(5));
+    assert_eq!(reader.available(), 5);
+    assert_eq!(reader.read(&mut buf[..3]), Ok(3));
+    assert_eq!(&buf[..3], b"hel");
+    assert_eq!(reader.read(&mut buf), Ok(2));
+    assert_eq!(&buf[..2], b"lo");
+    assert_eq!(reader.read(&mut buf), Err(PipeError::WouldBlock));
+
+    // 克隆出的写端也算存活写端
+    let writer2 = writer.clone();
+    drop(writer);
+    assert_eq!(reader.read(&mut buf), Err(PipeError::WouldBlock));
+    assert_eq!(writer2.write(b"!"), Ok(1));
+    drop(writer2);
+    // 剩余数据仍可读完，之后返回 EOF
+    assert_eq!(reader.read(&mut buf), Ok(1));
+    assert_eq!(buf[0], b'!');
+    assert_eq!(reader.read(&mut buf), Ok(0));
+}
+
+#[test]
+fn test_pipe_is_ready() {
+    // 测试 is_ready 与 read/write 是否会返回 WouldBlock 一致
+    let (reader, writer) = easy_fs::pipe();
+    assert!(!reader.is_ready());
+    assert!(writer.is_ready());
+
+    assert_eq!(writer.write(&[0u8; PIPE_BUFFER_SIZE]), Ok(PIPE_BUFFER_SIZE));
+    assert!(reader.is_ready());
+    assert!(!writer.is_ready());
+
+    let mut buf = [0u8; PIPE_BUFFER_SIZE];
+    assert_eq!(reader.read(&mut buf), Ok(PIPE_BUFFER_SIZE));
+    assert!(!reader.is_ready());
+    assert!(writer.is_ready());
+
+    // 写端关闭后空管道可读（EOF）；读端关闭后写端可写（BrokenPipe）
+    let (reader2, writer2) = easy_fs::pipe();
+    drop(writer);
+    assert!(reader.is_ready());
+    drop(reader2);
+    assert!(writer2.is_ready());
+}
+
+#[test]
+fn test_pipe_full_and_broken() {
+    // 测试缓冲区满时写阻塞、回绕后数据顺序正确、读端关闭后写失败
+    let (reader, writer) = easy_fs::pipe();
+    let data: Vec<u8> = (0..PIPE_BUFFER_SIZE + 10).map(|i| i as u8).collect();
+    assert_eq!(writer.write(&data), Ok(PIPE_BUFFER_SIZE));
+    assert_eq!(writer.write(b"x"), Err(PipeError::WouldBlock));
+
+    let mut buf = vec![0u8; 100];
+    assert_eq!(reader.read(&mut buf), Ok(100));
+    assert_eq!(&buf[..], &data[..100]);
+    // 写入跨越缓冲区末尾
+    assert_eq!(writer.write(&data[PIPE_BUFFER_SIZE..]), Ok(10));
+    let mut rest = vec![0u8; PIPE_BUFFER_SIZE];
+    assert_eq!(reader.read(&mut rest), Ok(PIPE_BUFFER_SIZE - 90));
+    assert_eq!(&rest[..PIPE_BUFFER_SIZE - 100], &data[100..PIPE_BUFFER_SIZE]);
+    assert_eq!(&rest[PIPE_BUFFER_SIZE - 100..PIPE_BUFFER_SIZE - 90], &data[PIPE_BUFFER_SIZE..]);
+
+    let reader2 = reader.clone();
+    drop(reader);
+    assert_eq!(writer.write(b"y"), Ok(1));
+    drop(reader2);
+    assert_eq!(writer.write(b"z"), Err(PipeError::BrokenPipe));
+}
+
+#[test]
+fn test_file_handle_from_pipe() {
+    // 测试管道端文件句柄的权限，以及句柄关闭后对端的表现
+    let (reader, writer) = easy_fs::pipe();
+    let read_end = FileHandle::from_pipe(PipeEnd::Read(reader));
+    let write_end = FileHandle::from_pipe(PipeEnd::Write(writer));
+    assert!(read_end.readable() && !read_end.writable());
+    assert!(!write_end.readable() && write_end.writable());
+    assert_eq!(read_end.read_mode(), ReadMode::Partial);
+    assert!(read_end.inode.is_none() && read_end.stat().is_none());
+    assert!(FileHandle::empty(true, false).pipe().is_none());
+
+    let Some(PipeEnd::Write(w)) = write_end.pipe() else { panic!("expected write end") };
+    assert_eq!(w.write(b"abc"), Ok(3));
+    drop(write_end);
+    let Some(PipeEnd::Read(r)) = read_end.pipe() else { panic!("expected read end") };
+    let mut buf = [0u8; 4];
+    assert_eq!(r.read(&mut buf), Ok(3));
+    assert_eq!(r.read(&mut buf), Ok(0));
+}
+
+#[test]
+fn test_file_handle_seek() {
+    // 测试三种起点的 seek，越界时限制在 [0, 文件大小] 内
+    with_test_fs(|_device, root| {
+        let inode = root.create("seek").unwrap();
+        inode.write_at(0, b"0123456789");
+        let mut handle = FileHandle::new(true, true, inode);
+        let read_byte = |handle: &mut FileHandle| {
+            let mut byte = [0u8; 1];
+            let buf = UserBuffer::new(vec![unsafe {
+                std::slice::from_raw_parts_mut(byte.as_mut_ptr(), 1)
+            }]);
+            let n = handle.read(buf);
+            (n, byte[0])
+        };
+
+        assert_eq!(handle.seek(SeekFrom::Start(3)), Some(3));
+        assert_eq!(read_byte(&mut handle), (1, b'3'));
+        assert_eq!(handle.seek(SeekFrom::Current(2)), Some(6));
+        assert_eq!(handle.seek(SeekFrom::Current(-5)), Some(1));
+        assert_eq!(read_byte(&mut handle), (1, b'1'));
+        assert_eq!(handle.seek(SeekFrom::End(-1)), Some(9));
+        assert_eq!(read_byte(&mut handle), (1, b'9'));
+
+        // 越过文件末尾停在末尾，读到 0 字节
+        assert_eq!(handle.seek(SeekFrom::Start(100)), Some(10));
+        assert_eq!(handle.seek(SeekFrom::End(5)), Some(10));
+        assert_eq!(read_byte(&mut handle).0, 0);
+        // 越过开头停在 0
+        assert_eq!(handle.seek(SeekFrom::Current(-100)), Some(0));
+        assert_eq!(read_byte(&mut handle), (1, b'0'));
+
+        // 没有 Inode 的句柄不能 seek
+        assert_eq!(FileHandle::empty(true, false).seek(SeekFrom::Start(0)), None);
+    });
+}
@@ -1,29 +1,108 @@
 //! 内核堆分配器：通过 `#[global_allocator]` 提供基于 buddy allocator 的全局分配器，
-//! 暴露 `init` / `transfer` 供内核初始化与托管内存。
+//! 暴露 `init` / `transfer` 供内核初始化与托管内存，`stats` 供查询用量。
+//!
+//! 默认不加锁，调用方须保证不存在并发的 alloc/dealloc/transfer，适合单 hart 的章节。
+//! 启用 `locked` feature 后每次操作都持有自旋锁，多个 hart 可以同时分配，
+//! 代价是每次分配多一次原子操作；持锁期间若被中断且中断处理也分配内存会死锁，
+//! 因此中断处理中不应分配，或在关中断的上下文中分配。
 
 #![cfg_attr(not(test), no_std)]
 
 #[cfg(not(test))]
 extern crate alloc;
 
+use core::alloc::Layout;
+#[cfg(not(feature = "locked"))]
 use core::cell::UnsafeCell;
 use core::ptr::NonNull;
 use customizable_buddy::{BuddyAllocator, LinkedListBuddy, UsizeBuddy};
+use spin::Once;
 
 #[cfg(not(test))]
-use alloc::alloc::{handle_alloc_error, Layout};
+use alloc::alloc::handle_alloc_error;
 #[cfg(not(test))]
 use core::alloc::GlobalAlloc;
 
 /// 伙伴分配器类型：阶数 21，最大可管理约 2^30 字节（约 1 GiB）。
 type Buddy = BuddyAllocator<21, UsizeBuddy, LinkedListBuddy>;
 
+/// 伙伴分配器及其用量计数，总是一起访问。
+struct Heap {
+    buddy: Buddy,
+    stats: AllocStats,
+}
+
 /// 无锁包装：调用方必须保证不存在并发的 alloc/dealloc/transfer（见 spec）。
-struct BuddyCell(UnsafeCell<Buddy>);
+#[cfg(not(feature = "locked"))]
+struct HeapCell(UnsafeCell<Heap>);
+
+#[cfg(not(feature = "locked"))]
+unsafe impl Sync for HeapCell {}
+
+#[cfg(not(feature = "locked"))]
+static HEAP: HeapCell = HeapCell(UnsafeCell::new(Heap::new()));
+
+#[cfg(feature = "locked")]
+static HEAP: spin::Mutex<Heap> = spin::Mutex::new(Heap::new());
+
+impl Heap {
+    const fn new() -> Self {
+        Self {
+            buddy: BuddyAllocator::new(),
+            stats: AllocStats {
+                total: 0,
+                used: 0,
+                free: 0,
+                allocations: 0,
+            },
+        }
+    }
+}
+
+/// 独占访问分配器：启用 `locked` 时持有自旋锁，否则依赖无并发约定。
+#[inline]
+fn with_heap<R>(f: impl FnOnce(&mut Heap) -> R) -> R {
+    #[cfg(feature = "locked")]
+    let result = f(&mut HEAP.lock());
+    #[cfg(not(feature = "locked"))]
+    let result = f(unsafe { &mut *HEAP.0.get() });
+    result
+}
+
+/// 堆用量统计，字节数均按请求的 `Layout` 大小计，不含伙伴块向上取整的部分。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// 已托管的字节数
+    pub total: usize,
+    /// 已分配未释放的字节数
+    pub used: usize,
+    /// `total - used`
+    pub free: usize,
+    /// 已分配未释放的块数
+    pub allocations: usize,
+}
+
+/// 读取当前的堆用量统计。
+pub fn stats() -> AllocStats {
+    let stats = with_heap(|heap| heap.stats);
+    AllocStats {
+        free: stats.total.saturating_sub(stats.used),
+        ..stats
+    }
+}
 
-unsafe impl Sync for BuddyCell {}
+/// 分配失败时、调用 `handle_alloc_error` 之前运行的回调。
+static OOM_HOOK: Once<fn(Layout)> = Once::new();
 
-static BUDDY: BuddyCell = BuddyCell(UnsafeCell::new(BuddyAllocator::new()));
+/// 注册分配失败回调，参数为无法满足的 `Layout`，可在其中打印 [`stats`] 等调试信息。
+///
+/// 只有第一次注册生效。回调返回后仍会调用 `handle_alloc_error`；回调内不应再分配堆内存。
+pub fn set_oom_hook(hook: fn(Layout)) {
+    OOM_HOOK.call_once(|| hook);
+}
+
+/// min_order = 6，与 design 中的容量估算一致；调用方须保证 base 与 transfer 区域按 2^6 对齐。
+const MIN_ORDER: usize = 6;
 
 /// 初始化全局堆分配器。
 ///
@@ -31,11 +110,7 @@ static BUDDY: BuddyCell = BuddyCell(UnsafeCell::new(BuddyAllocator::new()));
 /// 在首次堆分配或 `transfer` 前必须调用一次（可多次调用，行为由底层实现决定）。
 pub fn init(base_address: usize) {
     let base = NonNull::new(base_address as *mut u8).unwrap();
-    // min_order = 6，与 design 中的容量估算一致；调用方须保证 base 与 transfer 区域按 2^6 对齐。
-    const MIN_ORDER: usize = 6;
-    unsafe {
-        (*BUDDY.0.get()).init(MIN_ORDER, base);
-    }
+    with_heap(|heap| heap.buddy.init(MIN_ORDER, base));
 }
 
 /// 将一段内存托管给全局堆分配器。
@@ -46,7 +121,72 @@ pub fn init(base_address: usize) {
 /// `region` 未被其他对象引用；`region` 在内核中可安全访问。
 pub unsafe fn transfer(region: &'static mut [u8]) {
     let ptr = NonNull::new(region.as_mut_ptr()).unwrap();
-    (*BUDDY.0.get()).transfer(ptr, region.len());
+    with_heap(|heap| {
+        heap.buddy.transfer(ptr, region.len());
+        heap.stats.total += region.len();
+    });
+}
+
+/// 释放后填充的字节。
+#[cfg(all(feature = "debug-poison", debug_assertions))]
+pub const POISON_BYTE: u8 = 0xDE;
+
+/// 写在已释放块头部的金丝雀，用于识别重复释放。
+#[cfg(all(feature = "debug-poison", debug_assertions))]
+const FREED_CANARY: usize = 0xDEAD_F7EE_DEAD_F7EE_u64 as usize;
+
+/// 块首部留给伙伴分配器链表节点的字节数，释放后不会保留原值。
+#[cfg(all(feature = "debug-poison", debug_assertions))]
+const LINK_RESERVED: usize = 2 * core::mem::size_of::<usize>();
+
+/// 检测到的重复释放。
+#[cfg(all(feature = "debug-poison", debug_assertions))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DoubleFree(pub NonNull<u8>);
+
+/// `layout` 实际占用的伙伴块大小。
+#[cfg_attr(test, allow(dead_code))]
+#[inline]
+fn block_len(layout: Layout) -> usize {
+    layout
+        .size()
+        .max(layout.align())
+        .next_power_of_two()
+        .max(1 << MIN_ORDER)
+}
+
+/// 金丝雀在块中的位置，紧跟链表节点之后。
+#[cfg(all(feature = "debug-poison", debug_assertions))]
+#[inline]
+unsafe fn canary_of(ptr: NonNull<u8>) -> *mut usize {
+    ptr.as_ptr().add(LINK_RESERVED).cast()
+}
+
+/// 带重复释放检测的释放：先检查块头的金丝雀与毒化字节，
+/// 通过后将整个块填充为 [`POISON_BYTE`]、写入金丝雀，再交还伙伴分配器并更新 [`stats`]。
+///
+/// 检测到重复释放时不触碰伙伴分配器和统计，直接返回 [`DoubleFree`]。
+///
+/// # Safety
+///
+/// `ptr` 必须来自本分配器以 `layout` 分配的块。
+#[cfg(all(feature = "debug-poison", debug_assertions))]
+pub unsafe fn checked_dealloc(ptr: NonNull<u8>, layout: Layout) -> Result<(), DoubleFree> {
+    let len = block_len(layout);
+    let canary = canary_of(ptr);
+    let body_len = len - LINK_RESERVED - core::mem::size_of::<usize>();
+    let body = core::slice::from_raw_parts(canary.add(1).cast::<u8>(), body_len);
+    if *canary == FREED_CANARY && body.iter().all(|&b| b == POISON_BYTE) {
+        return Err(DoubleFree(ptr));
+    }
+    core::ptr::write_bytes(ptr.as_ptr(), POISON_BYTE, len);
+    *canary = FREED_CANARY;
+    with_heap(|heap| {
+        heap.buddy.deallocate_layout(ptr, layout);
+        heap.stats.used -= layout.size();
+        heap.stats.allocations -= 1;
+    });
+    Ok(())
 }
 
 #[allow(dead_code)]
@@ -65,16 +205,75 @@ static ALLOC: KernelAlloc = KernelAlloc;
 unsafe impl GlobalAlloc for KernelAlloc {
     #[inline]
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        match (*BUDDY.0.get()).allocate_layout::<u8>(layout) {
+        let result = with_heap(|heap| {
+            let result = heap.buddy.allocate_layout::<u8>(layout);
+            if result.is_ok() {
+                heap.stats.used += layout.size();
+                heap.stats.allocations += 1;
+            }
+            result
+        });
+        // 回调可能读取 stats，须在释放锁之后调用
+        match result {
+            #[cfg(all(feature = "debug-poison", debug_assertions))]
+            Ok((ptr, _)) => {
+                // 清掉上次释放留下的金丝雀，避免未写满的块被误判为重复释放
+                *canary_of(ptr) = 0;
+                ptr.as_ptr()
+            }
+            #[cfg(not(all(feature = "debug-poison", debug_assertions)))]
             Ok((ptr, _)) => ptr.as_ptr(),
-            Err(_) => handle_alloc_error(layout),
+            Err(_) => {
+                if let Some(hook) = OOM_HOOK.get() {
+                    hook(layout);
+                }
+                handle_alloc_error(layout)
+            }
         }
     }
 
     #[inline]
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
         if let Some(non_null) = NonNull::new(ptr) {
-            (*BUDDY.0.get()).deallocate_layout(non_null, layout);
+            #[cfg(all(feature = "debug-poison", debug_assertions))]
+            if checked_dealloc(non_null, layout).is_err() {
+                log::error!("kernel-alloc: double free of {ptr:p} ({layout:?})");
+                handle_alloc_error(layout);
+            }
+            #[cfg(not(all(feature = "debug-poison", debug_assertions)))]
+            with_heap(|heap| {
+                heap.buddy.deallocate_layout(non_null, layout);
+                heap.stats.used -= layout.size();
+                heap.stats.allocations -= 1;
+            });
+        }
+    }
+
+    /// 伙伴块大小是 `max(size, align, 2^MIN_ORDER)` 向上取整到 2 的幂，
+    /// 因此只要新大小对应的块不大于原块就能原地完成：
+    /// 同阶时只改计数；降阶时把尾部多出的伙伴逐半归还分配器。
+    /// 需要更高阶的块时，原块的伙伴未必空闲，退回分配、复制、释放。
+    #[inline]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        let old_len = block_len(layout);
+        let new_len = block_len(new_layout);
+        if new_len > old_len {
+            let new_ptr = self.alloc(new_layout);
+            core::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size());
+            self.dealloc(ptr, layout);
+            return new_ptr;
         }
+        with_heap(|heap| {
+            let mut len = old_len;
+            while len > new_len {
+                len /= 2;
+                let tail = NonNull::new_unchecked(ptr.add(len));
+                heap.buddy
+                    .deallocate_layout(tail, Layout::from_size_align_unchecked(len, len));
+            }
+            heap.stats.used = heap.stats.used - layout.size() + new_size;
+        });
+        ptr
     }
 }
@@ -0,0 +1,21 @@
+//! 独立测试二进制共用的堆初始化
+//!
+//! 全局分配器由整个测试进程共享：用量统计、刚释放的块和分配失败回调都会被并发运行的
+//! 其他测试干扰，所以依赖分配器独占状态的测试各自成一个测试二进制，每个只包含一个测试。
+
+/// 定义 `$size` 字节的静态堆，并在 `main` 之前用它初始化全局分配器
+macro_rules! test_heap {
+    ($size:expr) => {
+        // 按 2^6 对齐以满足 buddy allocator 的 transfer 要求
+        #[repr(align(64))]
+        struct TestHeap([u8; $size]);
+        static mut TEST_HEAP: TestHeap = TestHeap([0; $size]);
+
+        #[ctor::ctor]
+        unsafe fn init_allocator_before_main() {
+            let heap = &mut *core::ptr::addr_of_mut!(TEST_HEAP);
+            kernel_alloc::init(heap.0.as_mut_ptr() as usize);
+            kernel_alloc::transfer(&mut heap.0);
+        }
+    };
+}
@@ -0,0 +1,42 @@
+//! kernel-alloc 重复释放检测测试
+//!
+//! 第一次释放后块回到分配器，第二次释放检查的必须仍是这个空闲块，见 `common` 模块。
+//!
+//! ```bash
+//! cargo test -p kernel-alloc --features debug-poison --test double_free_tests
+//! ```
+
+#![cfg(all(feature = "debug-poison", debug_assertions))]
+
+use kernel_alloc::*;
+
+#[macro_use]
+mod common;
+
+test_heap!(1024 * 1024);
+
+#[test]
+fn test_double_free_detected() {
+    // 同一块释放两次：第一次成功并被毒化，第二次被识别为重复释放
+    use std::alloc::{alloc, Layout};
+    use std::ptr::NonNull;
+
+    let before = stats();
+    let layout = Layout::from_size_align(32, 8).unwrap();
+    let ptr = NonNull::new(unsafe { alloc(layout) }).unwrap();
+    unsafe { ptr.as_ptr().write_bytes(0x11, layout.size()) };
+    assert_eq!(stats().allocations, before.allocations + 1);
+
+    // checked_dealloc 与 dealloc 一样归还统计
+    assert_eq!(unsafe { checked_dealloc(ptr, layout) }, Ok(()));
+    assert_eq!(stats(), before);
+    let tail = unsafe { *ptr.as_ptr().add(layout.size() - 1) };
+    assert_eq!(tail, POISON_BYTE);
+
+    // 重复释放不改动统计
+    assert_eq!(
+        unsafe { checked_dealloc(ptr, layout) },
+        Err(DoubleFree(ptr))
+    );
+    assert_eq!(stats(), before);
+}
@@ -0,0 +1,46 @@
+//! kernel-alloc `locked` feature 并发测试
+//!
+//! 多个线程同时经全局分配器分配、写入、校验并释放，验证加锁后分配器状态不被破坏。
+//!
+//! ```bash
+//! cargo test -p kernel-alloc --features locked --test locked_tests
+//! ```
+
+#![cfg(feature = "locked")]
+
+use kernel_alloc::*;
+
+#[macro_use]
+mod common;
+
+test_heap!(1024 * 1024);
+
+#[test]
+fn test_concurrent_alloc() {
+    const THREADS: usize = 4;
+    const ROUNDS: usize = 200;
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|t| {
+            std::thread::spawn(move || {
+                let tag = t as u8 + 1;
+                for round in 0..ROUNDS {
+                    // 不同大小的块交错分配，任何重叠都会破坏其他线程写入的内容
+                    let blocks: Vec<Vec<u8>> =
+                        (1..8).map(|i| vec![tag; 16 * i + round % 64]).collect();
+                    for block in &blocks {
+                        assert!(block.iter().all(|&b| b == tag));
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    // 全部释放后仍能分配一块大内存，说明空闲链表完好
+    let big = vec![0u8; 256 * 1024];
+    assert_eq!(big.len(), 256 * 1024);
+    assert!(stats().used <= stats().total);
+}
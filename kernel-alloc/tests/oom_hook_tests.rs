@@ -0,0 +1,29 @@
+//! kernel-alloc 分配失败回调测试
+//!
+//! 回调返回后 `handle_alloc_error` 会终止进程，因此回调在确认 `Layout` 后直接以
+//! 退出码 0 结束测试进程；为什么单独成一个测试二进制见 `common` 模块。
+
+use kernel_alloc::*;
+use std::alloc::{alloc, Layout};
+
+#[macro_use]
+mod common;
+
+// 故意只托管很小的一块内存
+test_heap!(128 * 1024);
+
+const HUGE_SIZE: usize = 1024 * 1024;
+
+fn on_oom(layout: Layout) {
+    // 在分配器内部不能 panic，用退出码报告结果
+    let expected = Layout::from_size_align(HUGE_SIZE, 8).unwrap();
+    std::process::exit(if layout == expected { 0 } else { 1 });
+}
+
+#[test]
+fn test_oom_hook_called_with_layout() {
+    set_oom_hook(on_oom);
+    let layout = Layout::from_size_align(HUGE_SIZE, 8).unwrap();
+    let _ = unsafe { alloc(layout) };
+    unreachable!("allocation larger than the heap should have failed");
+}
@@ -0,0 +1,50 @@
+//! kernel-alloc `realloc` 测试
+//!
+//! 通过块地址与用量统计区分原地扩缩与复制回退，统计值需要独占的分配器，见 `common` 模块。
+
+use kernel_alloc::*;
+
+#[macro_use]
+mod common;
+
+test_heap!(1024 * 1024);
+
+#[test]
+fn test_realloc_in_place_and_fallback() {
+    use std::alloc::{alloc, dealloc, realloc, Layout};
+
+    let before = stats();
+    let layout = Layout::from_size_align(100, 8).unwrap();
+    let ptr = unsafe { alloc(layout) };
+    assert!(!ptr.is_null());
+    unsafe { core::ptr::write_bytes(ptr, 0xA5, 100) };
+
+    // 100 -> 120：仍在 128 字节的块内，原地扩展
+    let grown = unsafe { realloc(ptr, layout, 120) };
+    assert_eq!(grown, ptr);
+    assert_eq!(stats().used, before.used + 120);
+
+    // 120 -> 40：降到 64 字节的块，原地收缩并归还尾部
+    let layout = Layout::from_size_align(120, 8).unwrap();
+    let shrunk = unsafe { realloc(grown, layout, 40) };
+    assert_eq!(shrunk, ptr);
+    assert_eq!(stats().used, before.used + 40);
+    assert!(unsafe { core::slice::from_raw_parts(shrunk, 40) }
+        .iter()
+        .all(|&b| b == 0xA5));
+
+    // 40 -> 1000：需要更高阶的块，复制到新地址
+    let layout = Layout::from_size_align(40, 8).unwrap();
+    let moved = unsafe { realloc(shrunk, layout, 1000) };
+    assert!(!moved.is_null());
+    assert_ne!(moved, ptr);
+    assert!(unsafe { core::slice::from_raw_parts(moved, 40) }
+        .iter()
+        .all(|&b| b == 0xA5));
+    assert_eq!(stats().used, before.used + 1000);
+    assert_eq!(stats().allocations, before.allocations + 1);
+
+    unsafe { dealloc(moved, Layout::from_size_align(1000, 8).unwrap()) };
+    assert_eq!(stats().used, before.used);
+    assert_eq!(stats().allocations, before.allocations);
+}
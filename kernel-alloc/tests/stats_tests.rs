@@ -0,0 +1,40 @@
+//! kernel-alloc 用量统计测试
+//!
+//! 统计值需要独占的分配器，见 `common` 模块。
+
+use kernel_alloc::*;
+
+#[macro_use]
+mod common;
+
+test_heap!(1024 * 1024);
+
+#[test]
+fn test_alloc_stats() {
+    use std::alloc::{alloc, dealloc, Layout};
+
+    let before = stats();
+    assert_eq!(before.total, 1024 * 1024);
+    assert_eq!(before.free, before.total - before.used);
+
+    let small = Layout::from_size_align(24, 8).unwrap();
+    let large = Layout::from_size_align(4096, 4096).unwrap();
+    let a = unsafe { alloc(small) };
+    let b = unsafe { alloc(large) };
+    assert!(!a.is_null() && !b.is_null());
+
+    // 按请求的大小计数，不含向上取整
+    let during = stats();
+    assert_eq!(during.used, before.used + 24 + 4096);
+    assert_eq!(during.allocations, before.allocations + 2);
+    assert_eq!(during.free, before.free - 24 - 4096);
+    assert_eq!(during.total, before.total);
+
+    unsafe { dealloc(a, small) };
+    let after_one = stats();
+    assert_eq!(after_one.used, before.used + 4096);
+    assert_eq!(after_one.allocations, before.allocations + 1);
+
+    unsafe { dealloc(b, large) };
+    assert_eq!(stats(), before);
+}
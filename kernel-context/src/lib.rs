@@ -3,6 +3,7 @@
 //! kernel-context: RISC-V Supervisor-mode thread context representation and execution
 
 use core::arch::global_asm;
+use core::fmt;
 
 /// RISC-V local thread context representation.
 /// 
@@ -55,6 +56,19 @@ impl LocalContext {
         }
     }
 
+    /// Reset in place to a fresh user-mode context starting at `pc` with stack pointer `sp`.
+    /// All other integer registers are zeroed so no stale value survives an `exec`.
+    pub fn reset_user(&mut self, pc: usize, sp: usize) {
+        *self = Self::user(pc);
+        *self.sp_mut() = sp;
+    }
+
+    /// Reset in place to a fresh supervisor-mode thread context starting at `pc` with stack pointer `sp`.
+    pub fn reset_thread(&mut self, pc: usize, sp: usize, interrupt: bool) {
+        *self = Self::thread(pc, interrupt);
+        *self.sp_mut() = sp;
+    }
+
     /// Access the n-th integer register slot (1-based indexing).
     pub fn x(&self, n: usize) -> usize {
         assert!(n >= 1 && n <= 31, "register index must be in range [1, 31]");
@@ -95,6 +109,16 @@ impl LocalContext {
         self.x_mut(2)
     }
 
+    /// Read the value of `x4` (thread pointer).
+    pub fn tp(&self) -> usize {
+        self.x(4)
+    }
+
+    /// Mutably access `x4` (thread pointer), used to install a thread's TLS block.
+    pub fn tp_mut(&mut self) -> &mut usize {
+        self.x_mut(4)
+    }
+
     /// Return the saved program counter.
     pub fn pc(&self) -> usize {
         self.sepc
@@ -110,7 +134,63 @@ impl LocalContext {
         self.sepc = self.sepc.wrapping_add(4);
     }
 
+    /// Length in bytes of the snapshot produced by [`LocalContext::to_bytes`].
+    pub const SNAPSHOT_LEN: usize = 264;
+
+    /// Serialize the context into a stable byte layout for checkpointing.
+    ///
+    /// The layout follows the offset table used by `__execute_context`, with
+    /// every value stored as a little-endian `u64`:
+    /// - `x1..x31`: offsets 0..248
+    /// - `sepc`: offset 248
+    /// - `supervisor`: byte 256 (0 or 1)
+    /// - `interrupt`: byte 257 (0 or 1)
+    /// - bytes 258..264 are reserved and zero
+    pub fn to_bytes(&self) -> [u8; Self::SNAPSHOT_LEN] {
+        let mut bytes = [0u8; Self::SNAPSHOT_LEN];
+        for (i, reg) in self.x.iter().enumerate() {
+            bytes[i * 8..i * 8 + 8].copy_from_slice(&(*reg as u64).to_le_bytes());
+        }
+        bytes[248..256].copy_from_slice(&(self.sepc as u64).to_le_bytes());
+        bytes[256] = self.supervisor as u8;
+        bytes[257] = self.interrupt as u8;
+        bytes
+    }
+
+    /// Restore a context from a snapshot produced by [`LocalContext::to_bytes`].
+    ///
+    /// Returns `None` if `bytes` has the wrong length, a flag byte is not 0/1,
+    /// or the reserved bytes are non-zero.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::SNAPSHOT_LEN || bytes[258..].iter().any(|b| *b != 0) {
+            return None;
+        }
+        let flag = |b: u8| match b {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        };
+        let word = |off: usize| {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&bytes[off..off + 8]);
+            u64::from_le_bytes(buf) as usize
+        };
+        let mut x = [0usize; 31];
+        for (i, reg) in x.iter_mut().enumerate() {
+            *reg = word(i * 8);
+        }
+        Some(Self {
+            x,
+            sepc: word(248),
+            supervisor: flag(bytes[256])?,
+            interrupt: flag(bytes[257])?,
+        })
+    }
+
     /// Execute the context, switching into it using RISC-V `sret`-based control transfer.
+    ///
+    /// Safe to call concurrently on several harts as long as each hart runs on its own
+    /// kernel stack; see [`hart`] for the layout this relies on.
     #[cfg(target_arch = "riscv64")]
     pub unsafe fn execute(&mut self) -> usize {
         // Compute sstatus value based on supervisor and interrupt flags
@@ -143,6 +223,129 @@ impl LocalContext {
     }
 }
 
+/// ABI names of `x1..x31` in the order [`LocalContext`]'s `Debug` output prints them.
+const ABI_REGISTERS: [(&str, usize); 31] = [
+    ("ra", 1),
+    ("sp", 2),
+    ("gp", 3),
+    ("tp", 4),
+    ("a0", 10),
+    ("a1", 11),
+    ("a2", 12),
+    ("a3", 13),
+    ("a4", 14),
+    ("a5", 15),
+    ("a6", 16),
+    ("a7", 17),
+    ("s0", 8),
+    ("s1", 9),
+    ("s2", 18),
+    ("s3", 19),
+    ("s4", 20),
+    ("s5", 21),
+    ("s6", 22),
+    ("s7", 23),
+    ("s8", 24),
+    ("s9", 25),
+    ("s10", 26),
+    ("s11", 27),
+    ("t0", 5),
+    ("t1", 6),
+    ("t2", 7),
+    ("t3", 28),
+    ("t4", 29),
+    ("t5", 30),
+    ("t6", 31),
+];
+
+impl fmt::Debug for LocalContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("LocalContext");
+        for (name, n) in ABI_REGISTERS {
+            s.field(name, &format_args!("{:#x}", self.x(n)));
+        }
+        s.field("sepc", &format_args!("{:#x}", self.sepc))
+            .field("supervisor", &self.supervisor)
+            .field("interrupt", &self.interrupt)
+            .finish()
+    }
+}
+
+/// Per-hart layout of the trap save area used by [`LocalContext::execute`].
+///
+/// # Design note
+///
+/// `__execute_context` keeps no global state. It parks the kernel `sp` in `sscratch`, which is
+/// a per-hart CSR, and stores the context pointer on the calling hart's own kernel stack at
+/// [`ctx_slot`]`(sp)`. The trap handler finds both again through `sscratch` alone, so harts never
+/// share a save slot provided every hart calls `execute` on a distinct kernel stack, e.g. one
+/// carved out with [`stack_top`].
+///
+/// The save area cannot be indexed by hart id through `tp`: on trap entry `tp` still holds the
+/// interrupted context's value (a user thread's TLS pointer), and it is only saved, not trusted.
+/// The kernel's own `tp`, which holds the hart id, is kept in the execute frame at
+/// [`KERNEL_TP_OFFSET`](hart::KERNEL_TP_OFFSET) and restored before the trap handler returns to the kernel.
+///
+/// For the same reason `execute` does not take a hart id: the save slot is already per-hart by
+/// virtue of living on the caller's stack, so an id argument would have nothing to index. The
+/// constants below are passed into `__execute_context` as `const` operands and are the single
+/// source of the frame layout.
+pub mod hart {
+    /// Bytes `__execute_context` reserves on the kernel stack for callee-saved registers.
+    pub const EXECUTE_FRAME_SIZE: usize = 112;
+    /// Offset within the execute frame where the kernel's `tp` is kept while the context runs.
+    pub const KERNEL_TP_OFFSET: usize = 104;
+    /// Offset below the reserved frame where the context pointer is stored.
+    pub const CTX_SLOT_OFFSET: usize = 8;
+    /// Scratch words below the context pointer used by the trap handler.
+    pub const SCRATCH_WORDS: usize = 1;
+
+    /// Top of hart `hart_id`'s kernel stack when stacks of `stack_size` bytes are laid out
+    /// back to back from `base`.
+    pub const fn stack_top(base: usize, stack_size: usize, hart_id: usize) -> usize {
+        base + stack_size * (hart_id + 1)
+    }
+
+    /// Address where `execute` stores the context pointer when called with stack pointer `sp`.
+    pub const fn ctx_slot(sp: usize) -> usize {
+        sp - EXECUTE_FRAME_SIZE - CTX_SLOT_OFFSET
+    }
+
+    /// Lowest address of the save area `execute` touches below `sp`, inclusive.
+    pub const fn save_area_bottom(sp: usize) -> usize {
+        ctx_slot(sp) - SCRATCH_WORDS * 8
+    }
+
+    /// Id of the calling hart, read from the kernel's `tp`.
+    ///
+    /// `linker::boot0!` puts the hart id in `tp` at entry and `execute` restores it on every
+    /// trap, so this is valid anywhere in kernel code but not while a user `tp` is live.
+    #[cfg(target_arch = "riscv64")]
+    #[inline]
+    pub fn current_id() -> usize {
+        let id: usize;
+        unsafe { core::arch::asm!("mv {}, tp", out(reg) id) };
+        id
+    }
+
+    /// Host stand-in for the kernel `tp`, so code built off-target can be tested with
+    /// [`set_mock_id`].
+    #[cfg(not(target_arch = "riscv64"))]
+    static MOCK_ID: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+
+    /// Id of the calling hart; off-target this is the value last given to [`set_mock_id`].
+    #[cfg(not(target_arch = "riscv64"))]
+    pub fn current_id() -> usize {
+        MOCK_ID.load(core::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Pretend to run on hart `id`; only available off-target.
+    #[cfg(not(target_arch = "riscv64"))]
+    pub fn set_mock_id(id: usize) {
+        MOCK_ID.store(id, core::sync::atomic::Ordering::Relaxed);
+    }
+}
+
 // Assembly code for context switching
 // 
 // LocalContext layout:
@@ -190,7 +393,7 @@ global_asm!(r#"
 # Returns sstatus in a0 after trap
 __execute_context:
     # Save kernel's callee-saved registers on stack
-    addi sp, sp, -112
+    addi sp, sp, -{frame}
     sd ra, 0(sp)
     sd s0, 8(sp)
     sd s1, 16(sp)
@@ -204,6 +407,8 @@ __execute_context:
     sd s9, 80(sp)
     sd s10, 88(sp)
     sd s11, 96(sp)
+    # Kernel tp holds the hart id; the context's tp replaces it until the trap
+    sd tp, {kernel_tp}(sp)
     
     # Save kernel sp to sscratch (for trap handler to restore)
     csrw sscratch, sp
@@ -232,8 +437,8 @@ __execute_context:
     # 1. Store ctx address at a fixed location (or use sscratch cleverly)
     # 2. After sret, trap handler reads ctx from that location
     
-    # Simplest approach for ch2: store ctx address at [kernel_sp - 8]
-    sd a0, -8(sp)
+    # Simplest approach for ch2: store ctx address at [kernel_sp - ctx_slot]
+    sd a0, -{ctx_slot}(sp)
     
     # Now load all user registers from context (a0 = ctx)
     ld x1, 0(a0)        # ra
@@ -281,21 +486,22 @@ __trap_handler:
     # First, swap sp with sscratch to get kernel sp
     csrrw sp, sscratch, sp
     # Now sp = kernel sp, sscratch = user sp
+    # Only this hart's kernel stack is touched below, see `hart` for the layout
+    
+    sd t0, -{scratch}(sp)   # Save t0 first so we can use it
     
-    # Save user sp temporarily
-    sd t0, -16(sp)      # Save t0 first so we can use it
-    csrr t0, sscratch   # Get user sp
-    sd t0, -24(sp)      # Save user sp
+    # Load ctx pointer (stored at kernel_sp - ctx_slot before sret)
+    ld t0, -{ctx_slot}(sp)  # t0 = ctx
     
-    # Load ctx pointer (stored at kernel_sp - 8 before sret)
-    ld t0, -8(sp)       # t0 = ctx
+    # Save user t1 before using it as a second scratch register
+    sd t1, 40(t0)       # t1
     
-    # Retrieve saved user sp
-    ld t1, -24(sp)
+    # Save user sp
+    csrr t1, sscratch
     sd t1, 8(t0)        # Save user sp to ctx.x[1]
     
     # Retrieve saved t0 (user's t0)
-    ld t1, -16(sp)
+    ld t1, -{scratch}(sp)
     sd t1, 32(t0)       # Save user t0 to ctx.x[4]
     
     # Now save other user registers to context
@@ -303,8 +509,7 @@ __trap_handler:
     # sp already saved above
     sd x3, 16(t0)       # gp
     sd x4, 24(t0)       # tp
-    # t0 already saved above
-    sd x6, 40(t0)       # t1
+    # t0 and t1 already saved above
     sd x7, 48(t0)       # t2
     sd x8, 56(t0)       # s0
     sd x9, 64(t0)       # s1
@@ -349,13 +554,19 @@ __trap_handler:
     ld s9, 80(sp)
     ld s10, 88(sp)
     ld s11, 96(sp)
-    addi sp, sp, 112
+    ld tp, {kernel_tp}(sp)
+    addi sp, sp, {frame}
     
     # Return sstatus in a0
     csrr a0, sstatus
     
     ret
-"#);
+"#,
+    frame = const hart::EXECUTE_FRAME_SIZE,
+    kernel_tp = const hart::KERNEL_TP_OFFSET,
+    ctx_slot = const hart::CTX_SLOT_OFFSET,
+    scratch = const hart::CTX_SLOT_OFFSET + hart::SCRATCH_WORDS * 8,
+);
 
 #[cfg(feature = "foreign")]
 pub mod foreign {
@@ -479,13 +690,25 @@ pub mod foreign {
 
     pub trait ForeignPortal {
         unsafe fn transit_entry(&self) -> usize;
+        /// Cache slot selected by `key`.
+        ///
+        /// Panics if the slot index is not below the portal's slot count.
         unsafe fn transit_cache<K: SlotKey>(&mut self, key: K) -> &mut PortalCache;
+        /// Cache slot selected by `key`, or `None` if the index is out of range.
+        ///
+        /// # Safety
+        ///
+        /// The portal must have been initialized over a region of at least
+        /// `calculate_size(slots)` bytes.
+        unsafe fn try_transit_cache<K: SlotKey>(&mut self, key: K) -> Option<&mut PortalCache>;
     }
 
     pub trait MonoForeignPortal {
         unsafe fn transit_address(&self) -> usize;
         fn text_offset(&self) -> usize;
         fn cache_offset(&self, index: usize) -> usize;
+        /// Number of cache slots laid out after the portal code.
+        fn slots(&self) -> usize;
     }
 
     impl<T: MonoForeignPortal> ForeignPortal for T {
@@ -494,9 +717,21 @@ pub mod foreign {
         }
 
         unsafe fn transit_cache<K: SlotKey>(&mut self, key: K) -> &mut PortalCache {
-            let addr = self.transit_address() + self.cache_offset(key.index());
+            let index = key.index();
+            let slots = self.slots();
+            assert!(index < slots, "portal slot {index} out of range ({slots} slots)");
+            let addr = self.transit_address() + self.cache_offset(index);
             &mut *(addr as *mut PortalCache)
         }
+
+        unsafe fn try_transit_cache<K: SlotKey>(&mut self, key: K) -> Option<&mut PortalCache> {
+            let index = key.index();
+            if index >= self.slots() {
+                return None;
+            }
+            let addr = self.transit_address() + self.cache_offset(index);
+            Some(&mut *(addr as *mut PortalCache))
+        }
     }
 
     #[repr(C)]
@@ -530,5 +765,127 @@ pub mod foreign {
             const PORTAL_CODE_SIZE: usize = 256;
             core::mem::size_of::<usize>() + PORTAL_CODE_SIZE + index * core::mem::size_of::<PortalCache>()
         }
+
+        fn slots(&self) -> usize {
+            self.slots
+        }
+    }
+}
+
+pub mod trap {
+    //! Trap cause decoding and dispatch shared by the chapter kernels
+
+    use super::LocalContext;
+
+    /// Interrupt bit of `scause` (the most significant bit).
+    const INTERRUPT_BIT: usize = 1 << (usize::BITS - 1);
+
+    /// Decoded `scause` value.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum TrapCause {
+        /// `ecall` from U-mode.
+        UserEnvCall,
+        /// `ebreak`.
+        Breakpoint,
+        /// Supervisor software interrupt.
+        SupervisorSoft,
+        /// Supervisor timer interrupt.
+        SupervisorTimer,
+        /// Supervisor external interrupt.
+        SupervisorExternal,
+        InstructionMisaligned,
+        InstructionFault,
+        IllegalInstruction,
+        LoadMisaligned,
+        LoadFault,
+        StoreMisaligned,
+        StoreFault,
+        InstructionPageFault,
+        LoadPageFault,
+        StorePageFault,
+        /// Any other exception code.
+        UnknownException(usize),
+        /// Any other interrupt code.
+        UnknownInterrupt(usize),
+    }
+
+    impl TrapCause {
+        /// Decode a raw `scause` value.
+        pub fn from_bits(scause: usize) -> Self {
+            let code = scause & !INTERRUPT_BIT;
+            if scause & INTERRUPT_BIT != 0 {
+                match code {
+                    1 => Self::SupervisorSoft,
+                    5 => Self::SupervisorTimer,
+                    9 => Self::SupervisorExternal,
+                    _ => Self::UnknownInterrupt(code),
+                }
+            } else {
+                match code {
+                    0 => Self::InstructionMisaligned,
+                    1 => Self::InstructionFault,
+                    2 => Self::IllegalInstruction,
+                    3 => Self::Breakpoint,
+                    4 => Self::LoadMisaligned,
+                    5 => Self::LoadFault,
+                    6 => Self::StoreMisaligned,
+                    7 => Self::StoreFault,
+                    8 => Self::UserEnvCall,
+                    12 => Self::InstructionPageFault,
+                    13 => Self::LoadPageFault,
+                    15 => Self::StorePageFault,
+                    _ => Self::UnknownException(code),
+                }
+            }
+        }
+
+        /// Whether this cause is an interrupt rather than an exception.
+        pub fn is_interrupt(self) -> bool {
+            matches!(
+                self,
+                Self::SupervisorSoft
+                    | Self::SupervisorTimer
+                    | Self::SupervisorExternal
+                    | Self::UnknownInterrupt(_)
+            )
+        }
+    }
+
+    /// Read and decode the current `scause`.
+    #[cfg(target_arch = "riscv64")]
+    pub fn decode() -> TrapCause {
+        let scause: usize;
+        unsafe { core::arch::asm!("csrr {}, scause", out(reg) scause) };
+        TrapCause::from_bits(scause)
+    }
+
+    #[cfg(not(target_arch = "riscv64"))]
+    pub fn decode() -> TrapCause {
+        panic!("decode() is only available on RISC-V 64-bit targets");
+    }
+
+    /// Route `cause` to the matching handler.
+    ///
+    /// - `UserEnvCall` goes to `syscall`; advancing past `ecall` is left to the handler.
+    /// - `SupervisorTimer` goes to `preempt`, which yields the task and keeps it runnable.
+    /// - `Breakpoint` is the same for every kernel: `dispatch` steps over the `ebreak` and then
+    ///   calls `preempt`, so the task resumes after the breakpoint instead of being killed.
+    /// - Everything else goes to `fault`.
+    pub fn dispatch<R>(
+        cause: TrapCause,
+        ctx: &mut LocalContext,
+        syscall: impl FnOnce(&mut LocalContext) -> R,
+        preempt: impl FnOnce(&mut LocalContext) -> R,
+        fault: impl FnOnce(TrapCause, &mut LocalContext) -> R,
+    ) -> R {
+        match cause {
+            TrapCause::UserEnvCall => syscall(ctx),
+            TrapCause::SupervisorTimer => preempt(ctx),
+            TrapCause::Breakpoint => {
+                ctx.move_next();
+                preempt(ctx)
+            }
+            _ => fault(cause, ctx),
+        }
     }
 }
@@ -124,6 +124,17 @@ mod tests {
         assert_eq!(ctx.x(2), 0x12345678);
     }
 
+    #[test]
+    fn test_local_context_tp() {
+        // 测试 tp 访问器对应 x4
+        let mut ctx = LocalContext::user(0x1000);
+        assert_eq!(ctx.tp(), 0);
+        *ctx.tp_mut() = 0x8000_1000;
+        assert_eq!(ctx.tp(), 0x8000_1000);
+        assert_eq!(ctx.x(4), 0x8000_1000);
+        assert_eq!(ctx.x[3], 0x8000_1000);
+    }
+
     #[test]
     fn test_local_context_pc_accessors() {
         // 测试 pc() 和 pc_mut() 访问器
@@ -174,6 +185,42 @@ mod tests {
         assert_eq!(ctx2.x(1), 0x1234); // ctx2 不应该改变
     }
 
+    #[test]
+    fn test_local_context_bytes_roundtrip() {
+        // 测试 to_bytes/from_bytes 往返一致，且布局与偏移表一致
+        let mut ctx = LocalContext::thread(0x8020_0000, true);
+        for i in 1..=31 {
+            *ctx.x_mut(i) = 0x1000 + i;
+        }
+
+        let bytes = ctx.to_bytes();
+        assert_eq!(bytes.len(), LocalContext::SNAPSHOT_LEN);
+        assert_eq!(&bytes[0..8], &(0x1001u64).to_le_bytes());
+        assert_eq!(&bytes[240..248], &(0x101fu64).to_le_bytes());
+        assert_eq!(&bytes[248..256], &(0x8020_0000u64).to_le_bytes());
+        assert_eq!(bytes[256], 1);
+        assert_eq!(bytes[257], 1);
+
+        let restored = LocalContext::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.x, ctx.x);
+        assert_eq!(restored.sepc, ctx.sepc);
+        assert!(restored.supervisor);
+        assert!(restored.interrupt);
+
+        let user = LocalContext::from_bytes(&LocalContext::user(0x1000).to_bytes()).unwrap();
+        assert!(!user.supervisor);
+        assert!(user.interrupt);
+
+        // 长度错误、标志非法、保留字节非零都应拒绝
+        assert!(LocalContext::from_bytes(&bytes[..263]).is_none());
+        let mut bad = bytes;
+        bad[256] = 2;
+        assert!(LocalContext::from_bytes(&bad).is_none());
+        let mut bad = bytes;
+        bad[263] = 1;
+        assert!(LocalContext::from_bytes(&bad).is_none());
+    }
+
     #[test]
     fn test_local_context_size() {
         // 测试 LocalContext 的大小
@@ -231,6 +278,169 @@ mod tests {
             assert_eq!(ctx.x(i + 10), (i + 100) as usize);
         }
     }
+
+    #[test]
+    fn test_local_context_reset_user() {
+        // 测试 reset_user 清空旧寄存器并设置 pc/sp
+        let mut ctx = LocalContext::thread(0x8000_0000, false);
+        for i in 1..=31 {
+            *ctx.x_mut(i) = i * 3;
+        }
+
+        ctx.reset_user(0x1000, 0x7fff_f000);
+        assert_eq!(ctx.pc(), 0x1000);
+        assert_eq!(ctx.sp(), 0x7fff_f000);
+        assert!(!ctx.supervisor);
+        assert!(ctx.interrupt);
+        for i in (1..=31).filter(|&i| i != 2) {
+            assert_eq!(ctx.x(i), 0, "x{i} should be cleared");
+        }
+    }
+
+    #[test]
+    fn test_local_context_reset_thread() {
+        // 测试 reset_thread 生成内核线程上下文
+        let mut ctx = LocalContext::user(0x1000);
+        *ctx.a_mut(0) = 42;
+        *ctx.tp_mut() = 0x2000;
+
+        ctx.reset_thread(0x8020_0000, 0x8040_0000, false);
+        assert_eq!(ctx.pc(), 0x8020_0000);
+        assert_eq!(ctx.sp(), 0x8040_0000);
+        assert!(ctx.supervisor);
+        assert!(!ctx.interrupt);
+        assert_eq!(ctx.a(0), 0);
+        assert_eq!(ctx.tp(), 0);
+    }
+
+    #[test]
+    fn test_local_context_debug_abi_names() {
+        // 测试 Debug 输出使用 ABI 寄存器名
+        let mut ctx = LocalContext::user(0x8000);
+        *ctx.sp_mut() = 0x1000;
+        *ctx.a_mut(0) = 42;
+        *ctx.x_mut(8) = 0xdead; // s0
+        *ctx.x_mut(27) = 0xbeef; // s11
+        *ctx.x_mut(31) = 7; // t6
+
+        let text = format!("{:?}", ctx);
+        assert!(text.starts_with("LocalContext {"));
+        for expected in [
+            "ra: 0x0",
+            "sp: 0x1000",
+            "a0: 0x2a",
+            "s0: 0xdead",
+            "s11: 0xbeef",
+            "t6: 0x7",
+            "sepc: 0x8000",
+            "supervisor: false",
+            "interrupt: true",
+        ] {
+            assert!(text.contains(expected), "missing {expected:?} in {text}");
+        }
+        assert!(!text.contains("x["));
+        // 按 ra, sp, gp, tp, a*, s*, t* 的顺序输出
+        let pos = |name: &str| text.find(name).unwrap();
+        assert!(pos("tp:") < pos("a0:") && pos("a7:") < pos("s0:") && pos("s11:") < pos("t0:"));
+    }
+
+    #[test]
+    fn test_hart_save_slots_disjoint() {
+        // 模拟两个 hart 各自在自己的内核栈上调用 execute，保存区互不覆盖
+        use kernel_context::hart::{ctx_slot, save_area_bottom, stack_top};
+        const STACK_SIZE: usize = 4096;
+        let mut stacks = vec![0usize; 2 * STACK_SIZE / 8];
+        let base = stacks.as_mut_ptr() as usize;
+
+        let mut contexts = [LocalContext::user(0x1000), LocalContext::user(0x2000)];
+        let mut slots = Vec::new();
+        for (hart_id, ctx) in contexts.iter_mut().enumerate() {
+            let top = stack_top(base, STACK_SIZE, hart_id);
+            // execute 被调用时栈上已有调用者的帧
+            let sp = top - 64;
+            let slot = ctx_slot(sp);
+            assert!(save_area_bottom(sp) >= top - STACK_SIZE);
+            assert_eq!(slot % 8, 0);
+            stacks[(slot - base) / 8] = ctx as *mut LocalContext as usize;
+            slots.push(slot);
+        }
+
+        assert_ne!(slots[0], slots[1]);
+        assert_eq!(stack_top(base, STACK_SIZE, 0), base + STACK_SIZE);
+        for (hart_id, slot) in slots.iter().enumerate() {
+            let saved = stacks[(slot - base) / 8] as *const LocalContext;
+            assert_eq!(saved, &contexts[hart_id] as *const LocalContext);
+        }
+    }
+
+    #[test]
+    fn test_kernel_tp_slot_in_execute_frame() {
+        // 内核 tp 的保存位置在 ra、s0-s11 之后，且不超出 execute 保留的栈帧
+        use kernel_context::hart::{EXECUTE_FRAME_SIZE, KERNEL_TP_OFFSET};
+        assert!(KERNEL_TP_OFFSET >= 13 * 8);
+        assert!(KERNEL_TP_OFFSET + 8 <= EXECUTE_FRAME_SIZE);
+        assert_eq!(KERNEL_TP_OFFSET % 8, 0);
+    }
+
+    #[cfg(feature = "foreign")]
+    #[test]
+    fn test_multislot_portal_slot_bounds() {
+        // 测试越界的 slot 被拒绝，而不是返回 portal 之外的地址
+        use kernel_context::foreign::{ForeignPortal, MultislotPortal};
+        let size = MultislotPortal::calculate_size(2);
+        let mut buf = vec![0usize; size.div_ceil(8)];
+        let base = buf.as_mut_ptr() as usize;
+        let portal = unsafe { MultislotPortal::init_transit(buf.as_mut_ptr().cast(), 2) };
+
+        for index in 0..2 {
+            let cache = unsafe { portal.try_transit_cache(index) }.unwrap();
+            let addr = cache as *mut _ as usize;
+            assert!(addr >= base && addr < base + size);
+        }
+        assert!(unsafe { portal.try_transit_cache(2usize) }.is_none());
+        let rejected = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+            portal.transit_cache(2usize);
+        }));
+        assert!(rejected.is_err());
+    }
+
+    #[test]
+    fn test_trap_cause_from_bits() {
+        // 测试 TrapCause::from_bits 解码典型的 scause 值
+        use kernel_context::trap::TrapCause;
+        let intr = 1usize << (usize::BITS - 1);
+
+        assert_eq!(TrapCause::from_bits(8), TrapCause::UserEnvCall);
+        assert_eq!(TrapCause::from_bits(3), TrapCause::Breakpoint);
+        assert_eq!(TrapCause::from_bits(13), TrapCause::LoadPageFault);
+        assert_eq!(TrapCause::from_bits(15), TrapCause::StorePageFault);
+        assert_eq!(TrapCause::from_bits(2), TrapCause::IllegalInstruction);
+        assert_eq!(TrapCause::from_bits(intr | 5), TrapCause::SupervisorTimer);
+        assert_eq!(TrapCause::from_bits(intr | 9), TrapCause::SupervisorExternal);
+        assert_eq!(TrapCause::from_bits(14), TrapCause::UnknownException(14));
+        assert_eq!(TrapCause::from_bits(intr | 3), TrapCause::UnknownInterrupt(3));
+
+        assert!(TrapCause::from_bits(intr | 5).is_interrupt());
+        assert!(!TrapCause::from_bits(8).is_interrupt());
+    }
+
+    #[test]
+    fn test_trap_dispatch() {
+        // 测试 dispatch 的分发：Breakpoint 跳过 ebreak 后按抢占处理，其余情况不修改 pc
+        use kernel_context::trap::{dispatch, TrapCause};
+        let mut ctx = LocalContext::user(0x1000);
+
+        let route = |cause, ctx: &mut LocalContext| {
+            dispatch(cause, ctx, |_| "syscall", |_| "preempt", |_, _| "fault")
+        };
+        assert_eq!(route(TrapCause::UserEnvCall, &mut ctx), "syscall");
+        assert_eq!(route(TrapCause::SupervisorTimer, &mut ctx), "preempt");
+        assert_eq!(route(TrapCause::LoadPageFault, &mut ctx), "fault");
+        assert_eq!(route(TrapCause::IllegalInstruction, &mut ctx), "fault");
+        assert_eq!(ctx.pc(), 0x1000);
+        assert_eq!(route(TrapCause::Breakpoint, &mut ctx), "preempt");
+        assert_eq!(ctx.pc(), 0x1004);
+    }
 }
 
 #[cfg(not(target_arch = "riscv64"))]
@@ -241,3 +451,19 @@ fn test_kernel_context_requires_riscv64() {
     // 实际测试应该在 RISC-V 目标平台上运行。
     println!("kernel-context tests require RISC-V 64-bit target architecture");
 }
+
+#[cfg(not(target_arch = "riscv64"))]
+mod host_tests {
+    use kernel_context::hart::{current_id, set_mock_id};
+
+    #[test]
+    fn test_current_hart_id() {
+        // 模拟两个 hart 的 tp，current_id 返回各自的编号
+        set_mock_id(1);
+        assert_eq!(current_id(), 1);
+        set_mock_id(3);
+        assert_eq!(current_id(), 3);
+        set_mock_id(0);
+        assert_eq!(current_id(), 0);
+    }
+}
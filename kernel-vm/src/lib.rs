@@ -1,6 +1,7 @@
 //! kernel-vm: 内核虚拟内存/页表管理最小抽象
 //!
-//! 提供 `PageManager` trait 与 `AddressSpace` 容器，基于 `page-table` crate 完成映射建立、地址翻译与地址空间克隆。
+//! 提供 `PageManager` trait 与 `AddressSpace` 容器，基于 `page-table` crate 完成映射建立、地址翻译与地址空间克隆；
+//! 缺页由注册到地址空间的 `FaultHandler` 统一决定处理方式。
 
 #![no_std]
 
@@ -50,16 +51,108 @@ pub trait PageManager<Meta: VmMeta> {
     /// 指示 `pte` 指向的物理页是否由本 `PageManager` 拥有（用于决定是否可进入下级页表页）。
     fn check_owned(&self, pte: Pte<Meta>) -> bool;
 
+    /// 记录从 `pte` 指示的起始物理页起、长度为 `len` 的页序列又被一个地址空间共享（见 [`AddressSpace::cow_clone`]）。
+    ///
+    /// 使用写时复制时实现者必须按物理页维护引用计数：`share` 使计数加一，
+    /// `deallocate` 使计数减一，只有计数归零的页才真正回收。共享过的区间之后逐页 `deallocate(pte, 1)`，
+    /// 因此实现者还要能单独回收整体分配的连续页中的某一页。默认实现什么也不做，只适用于不使用写时复制的场景。
+    fn share(&mut self, _pte: Pte<Meta>, _len: usize) {}
+
+    /// 标记写时复制页的页表项位，必须是 `Meta` 页表项格式中留给软件使用的位（如 RISC-V 的 RSW 位）。
+    ///
+    /// 带此标记的页表项由 [`AddressSpace::cow_clone`] 写入，已清除写权限；
+    /// 写缺页时由 [`AddressSpace::handle_cow_fault`] 复制并恢复写权限。默认为 0，表示不支持写时复制。
+    const COW_FLAG: usize = 0;
+
     /// 释放根页表页（与 `new_root` 对应）。
     fn drop_root(&mut self);
 }
 
+// ============== TranslateError ==============
+
+/// [`AddressSpace::translate_checked`] 的失败原因。
+#[derive(Clone, Copy)]
+pub enum TranslateError<Meta: VmMeta> {
+    /// 地址所在页没有有效映射（对应 EFAULT）。
+    Unmapped,
+    /// 页已映射但缺少请求的权限（对应 EACCES），携带页表项中实际存在的标志。
+    PermissionDenied(VmFlags<Meta>),
+}
+
+impl<Meta: VmMeta> fmt::Debug for TranslateError<Meta> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unmapped => write!(f, "Unmapped"),
+            Self::PermissionDenied(flags) => {
+                write!(f, "PermissionDenied({:#x})", flags.val())
+            }
+        }
+    }
+}
+
+// ============== FaultHandler ==============
+
+/// 触发缺页的访问类型。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaultKind {
+    /// 读数据（LoadPageFault）。
+    Load,
+    /// 写数据（StorePageFault）。
+    Store,
+    /// 取指令（InstructionPageFault）。
+    Instruction,
+}
+
+impl FaultKind {
+    /// 用户态该访问要求页表项具备的权限，包括 `U`。
+    ///
+    /// 缺页都来自用户态，没有 `U` 的页（如内核映射进用户地址空间的传送门页）用户无权访问，
+    /// 不能当作伪缺页重新执行，否则会反复陷入。
+    pub fn required_flags<Meta: VmMeta>(self) -> VmFlags<Meta> {
+        match self {
+            Self::Load => VmFlags::build_from_str("UR"),
+            Self::Store => VmFlags::build_from_str("UW"),
+            Self::Instruction => VmFlags::build_from_str("UX"),
+        }
+    }
+}
+
+/// 缺页处理的结果，由陷入分发器据此恢复执行、发送信号或终止进程。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaultOutcome {
+    /// 映射已建立或修正，刷新 TLB 后重新执行触发缺页的指令。
+    Mapped,
+    /// 非法访问，应向进程发送 `SIGSEGV`。
+    Signal,
+    /// 无法恢复（如物理内存耗尽），应直接终止进程。
+    Fatal,
+}
+
+/// 缺页处理策略（写时复制、按需清零、栈增长等），通过 [`AddressSpace::set_fault_handler`] 注册。
+///
+/// 策略本身不持有状态，需要的信息（如写时复制标记）都记录在地址空间的页表与 `areas` 中。
+pub trait FaultHandler<Meta: VmMeta, M: PageManager<Meta>> {
+    /// 处理 `space` 中地址 `addr` 上类型为 `kind` 的缺页。
+    fn handle(
+        space: &mut AddressSpace<Meta, M>,
+        addr: VAddr<Meta>,
+        kind: FaultKind,
+    ) -> FaultOutcome;
+}
+
+type FaultFn<Meta, M> = fn(&mut AddressSpace<Meta, M>, VAddr<Meta>, FaultKind) -> FaultOutcome;
+
 // ============== AddressSpace ==============
 
 /// 地址空间容器：持有根页表与已映射虚拟区间记录。
 pub struct AddressSpace<Meta: VmMeta, M: PageManager<Meta>> {
     pub areas: Vec<Range<VPN<Meta>>>,
+    /// 由 [`map_lazy`](AddressSpace::map_lazy) 登记的按需分配区间及其标志，这些区间同时记录在 `areas` 中。
+    lazy: Vec<(Range<VPN<Meta>>, VmFlags<Meta>)>,
+    /// 经 [`cow_clone`](AddressSpace::cow_clone) 共享过的区间，其中各页可能指向不相关的物理页，逐页回收。
+    cow: Vec<Range<VPN<Meta>>>,
     manager: M,
+    fault_handler: Option<FaultFn<Meta, M>>,
 }
 
 impl<Meta: VmMeta, M: PageManager<Meta>> AddressSpace<Meta, M> {
@@ -71,7 +164,33 @@ impl<Meta: VmMeta, M: PageManager<Meta>> AddressSpace<Meta, M> {
         let manager = M::new_root();
         Self {
             areas: Vec::new(),
+            lazy: Vec::new(),
+            cow: Vec::new(),
             manager,
+            fault_handler: None,
+        }
+    }
+
+    /// 注册缺页处理策略 `H`，替换之前注册的策略；[`cloneself`](Self::cloneself) 会把它带到新地址空间。
+    pub fn set_fault_handler<H: FaultHandler<Meta, M>>(&mut self) {
+        self.fault_handler = Some(H::handle);
+    }
+
+    /// 处理 `addr` 上类型为 `kind` 的缺页，陷入分发器只需调用这一个方法。
+    ///
+    /// 按需分配区间中尚未分配的页先由 [`handle_lazy_fault`](Self::handle_lazy_fault) 补上，不经过策略。
+    /// 未注册策略时只识别伪缺页：页已映射且具备所需权限（如 TLB 未刷新）返回 [`FaultOutcome::Mapped`]，
+    /// 否则返回 [`FaultOutcome::Signal`]。
+    pub fn handle_fault(&mut self, addr: VAddr<Meta>, kind: FaultKind) -> FaultOutcome {
+        if self.handle_lazy_fault(addr) {
+            return FaultOutcome::Mapped;
+        }
+        match self.fault_handler {
+            Some(handler) => handler(self, addr, kind),
+            None if self.translate::<u8>(addr, kind.required_flags()).is_some() => {
+                FaultOutcome::Mapped
+            }
+            None => FaultOutcome::Signal,
         }
     }
 
@@ -85,6 +204,34 @@ impl<Meta: VmMeta, M: PageManager<Meta>> AddressSpace<Meta, M> {
         unsafe { PageTable::from_root(self.manager.root_ptr()) }
     }
 
+    /// 返回 `areas` 中记录的虚拟页总数，用于按页统计地址空间大小（如 RLIMIT_AS 检查）。
+    pub fn mapped_pages(&self) -> usize {
+        self.areas
+            .iter()
+            .map(|range| range.end.val() - range.start.val())
+            .sum()
+    }
+
+    /// 将根页表页号、`areas` 中的各区间以及页表映射写入 `out`。
+    ///
+    /// 与 `Debug` 不同，不需要 `fmt::Formatter`，可以输出到 `String` 或环形缓冲区等任意 `fmt::Write`。
+    pub fn dump_to(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(out, "root: {:#x?}", self.root_ppn().val())?;
+        for range in &self.areas {
+            writeln!(
+                out,
+                "area: [{:#x}, {:#x})",
+                range.start.val(),
+                range.end.val()
+            )?;
+        }
+        let formatter = PageTableFormatter {
+            pt: self.root(),
+            f: |ppn: PPN<Meta>| self.manager.p_to_v(ppn),
+        };
+        write!(out, "{:?}", formatter)
+    }
+
     /// 将虚拟页号区间 `range` 映射到从 `pbase` 开始的连续物理页，并记录到 `areas`。
     ///
     /// 前置条件：`range` 非空；目标页表项未映射；遍历路径上的页表页由本 `PageManager` 拥有且可访问。
@@ -123,6 +270,37 @@ impl<Meta: VmMeta, M: PageManager<Meta>> AddressSpace<Meta, M> {
         self.areas.push(range);
     }
 
+    /// 为设备 MMIO 窗口 `[phys_base, phys_base + len)` 建立恒等映射并记录到 `areas`，返回 `phys_base` 对应的虚拟地址。
+    ///
+    /// 区间按页向外取整；`len == 0` 或与已有 `areas` 重叠时拒绝映射并返回 `None`。
+    pub fn map_mmio(
+        &mut self,
+        phys_base: usize,
+        len: usize,
+        flags: VmFlags<Meta>,
+    ) -> Option<VAddr<Meta>> {
+        if len == 0 {
+            return None;
+        }
+        let page_size = 1usize << Meta::PAGE_BITS;
+        let end = phys_base.checked_add(len)?.checked_add(page_size - 1)?;
+        let start_vpn = phys_base >> Meta::PAGE_BITS;
+        let end_vpn = end >> Meta::PAGE_BITS;
+        let overlaps = self
+            .areas
+            .iter()
+            .any(|area| area.start.val() < end_vpn && start_vpn < area.end.val());
+        if overlaps {
+            return None;
+        }
+        self.map_extern(
+            VPN::new(start_vpn)..VPN::new(end_vpn),
+            PPN::new(start_vpn),
+            flags,
+        );
+        Some(VAddr::new(phys_base))
+    }
+
     /// 分配物理页、拷贝数据并建立映射：将 `data` 从偏移 `offset` 拷贝到新分配的页，前后零填充，再建立 `range` 到新物理页的映射。
     ///
     /// 前置条件：`count << Meta::PAGE_BITS >= data.len() + offset`。
@@ -162,6 +340,214 @@ impl<Meta: VmMeta, M: PageManager<Meta>> AddressSpace<Meta, M> {
         self.map_extern(range, pbase, flags);
     }
 
+    /// 登记按需分配的区间 `range`（如线程栈）：记录到 `areas`，但不分配物理页也不建立页表项。
+    ///
+    /// 区间内的页在首次访问缺页时由 [`handle_lazy_fault`](Self::handle_lazy_fault) 逐页分配并以 `flags` 映射，
+    /// 因此这些物理页互不连续，回收、克隆与撤销都按页处理。
+    pub fn map_lazy(&mut self, range: Range<VPN<Meta>>, flags: VmFlags<Meta>) {
+        assert!(
+            range.end.val() > range.start.val(),
+            "map_lazy: range must be non-empty"
+        );
+        self.areas.push(range.clone());
+        self.lazy.push((range, flags));
+    }
+
+    /// 若 `addr` 落在按需分配区间内且所在页尚未映射，分配一个清零的物理页并建立映射，返回 `true`。
+    ///
+    /// 其他情况不做修改并返回 `false`。[`handle_fault`](Self::handle_fault) 会先调用本方法；成功后调用方需刷新 TLB。
+    pub fn handle_lazy_fault(&mut self, addr: VAddr<Meta>) -> bool {
+        let vpn = addr.floor();
+        let Some(mut flags) = self.lazy_flags(vpn) else {
+            return false;
+        };
+        if self.leaf_pte(vpn).is_some() {
+            return false;
+        }
+        let page = self.manager.allocate(1, &mut flags);
+        unsafe { core::ptr::write_bytes(page.as_ptr(), 0, 1 << Meta::PAGE_BITS) };
+        let ppn = self.manager.v_to_p(page);
+        self.set_leaf_pte(vpn, flags.build_pte(ppn));
+        true
+    }
+
+    /// 把以 `old_end` 为上界的堆调整为以 `new_end` 为上界（如 `brk`），返回是否成功。
+    ///
+    /// 扩大时新增的每一页单独分配、清零并作为一个区间映射，收缩时逐页 [`unmap`](Self::unmap)，
+    /// 因此堆可以按页伸缩。扩大的部分与已有区间重叠，或收缩的部分不是由本方法逐页映射的，
+    /// 不做任何修改并返回 `false`。收缩成功后调用方需刷新 TLB。
+    pub fn resize_heap(
+        &mut self,
+        old_end: VPN<Meta>,
+        new_end: VPN<Meta>,
+        flags: VmFlags<Meta>,
+    ) -> bool {
+        let (old, new) = (old_end.val(), new_end.val());
+        if new >= old {
+            let overlaps = self
+                .areas
+                .iter()
+                .any(|area| area.start.val() < new && old < area.end.val());
+            if overlaps {
+                return false;
+            }
+            for vpn in old..new {
+                self.map(VPN::new(vpn)..VPN::new(vpn + 1), &[], 0, flags);
+            }
+            return true;
+        }
+        let single_pages = (new..old).all(|vpn| {
+            let page = VPN::new(vpn)..VPN::new(vpn + 1);
+            self.areas.contains(&page) && !self.is_lazy(&page)
+        });
+        if !single_pages {
+            return false;
+        }
+        for vpn in (new..old).rev() {
+            self.unmap(VPN::new(vpn)..VPN::new(vpn + 1));
+        }
+        true
+    }
+
+    /// 将以 `current_top` 为上界的栈区间向下扩展 `extra_pages` 页（如 `pthread_attr_setstacksize`）。
+    ///
+    /// 重新分配 `原页数 + extra_pages` 的连续物理页：原内容拷贝到高端，新增的低端页清零，
+    /// 整个区间改映射到新物理页并归还旧物理页，栈的虚拟地址保持不变。写时复制共享的栈由此得到私有副本。
+    ///
+    /// 找不到以 `current_top` 结尾的区间、新栈底下溢或与其他区间（堆、mmap 等）重叠时返回 `false`。
+    /// 成功后调用方需刷新 TLB。
+    pub fn grow_stack(&mut self, current_top: VPN<Meta>, extra_pages: usize) -> bool {
+        let Some(idx) = self
+            .areas
+            .iter()
+            .position(|area| area.end.val() == current_top.val())
+        else {
+            return false;
+        };
+        if extra_pages == 0 {
+            return true;
+        }
+        let old_start = self.areas[idx].start.val();
+        let Some(new_start) = old_start.checked_sub(extra_pages) else {
+            return false;
+        };
+        let collides = self
+            .areas
+            .iter()
+            .any(|area| area.start.val() < old_start && new_start < area.end.val());
+        if collides {
+            return false;
+        }
+        if let Some(lazy) = self
+            .lazy
+            .iter_mut()
+            .find(|(area, _)| *area == self.areas[idx])
+        {
+            // 按需分配的栈只需扩大登记的区间
+            lazy.0.start = VPN::new(new_start);
+            self.areas[idx].start = VPN::new(new_start);
+            return true;
+        }
+
+        // 旧栈首页的 PTE：取得物理页号、标志，并用于归还旧物理页
+        let mut old_pte: Option<Pte<Meta>> = None;
+        let mut get_visitor = GetPteVisitor {
+            target: VPN::new(old_start),
+            result: &mut old_pte,
+            manager: &self.manager,
+        };
+        self.root()
+            .walk(Pos::new(VPN::new(old_start), 0), &mut get_visitor);
+        let Some(old_pte) = old_pte else {
+            return false;
+        };
+        let flags = Self::private_flags(old_pte.flags());
+        let old_area = VPN::new(old_start)..current_top;
+        let count = current_top.val() - old_start + extra_pages;
+
+        let mut alloc_flags = flags;
+        let base = self.manager.allocate(count, &mut alloc_flags).as_ptr();
+        let extra_size = extra_pages << Meta::PAGE_BITS;
+        unsafe { core::ptr::write_bytes(base, 0, extra_size) };
+        self.copy_area(&old_area, unsafe { base.add(extra_size) });
+        self.release_area(&old_area);
+        self.cow.retain(|area| *area != old_area);
+
+        let pbase = self
+            .manager
+            .v_to_p::<u8>(unsafe { NonNull::new_unchecked(base) });
+        let root_ptr = self.manager.root_ptr();
+        for i in 0..count {
+            let vpn = VPN::new(new_start + i);
+            let mut set_decorator = SetPteDecorator {
+                target: vpn,
+                pte: flags.build_pte(PPN::new(pbase.val() + i)),
+                manager: &mut self.manager,
+            };
+            let mut pt = unsafe { PageTable::from_root(root_ptr) };
+            pt.walk_mut(Pos::new(vpn, 0), &mut set_decorator);
+        }
+
+        self.areas[idx].start = VPN::new(new_start);
+        true
+    }
+
+    /// 把已映射的单页 `vpn` 改映射到物理页 `ppn`，页表项标志改为 `flags`（须含 VALID），供缺页处理策略使用。
+    ///
+    /// 不修改 `areas`，也不回收原物理页；`vpn` 未映射时返回 `false`。成功后调用方需刷新 TLB。
+    pub fn remap_page(&mut self, vpn: VPN<Meta>, ppn: PPN<Meta>, flags: VmFlags<Meta>) -> bool {
+        let mut old: Option<(PPN<Meta>, VmFlags<Meta>)> = None;
+        let mut visitor = TranslateVisitor {
+            target: vpn,
+            result: &mut old,
+            manager: &self.manager,
+        };
+        self.root().walk(Pos::new(vpn, 0), &mut visitor);
+        if old.is_none() {
+            return false;
+        }
+        let root_ptr = self.manager.root_ptr();
+        let mut set_decorator = SetPteDecorator {
+            target: vpn,
+            pte: flags.build_pte(ppn),
+            manager: &mut self.manager,
+        };
+        let mut pt = unsafe { PageTable::from_root(root_ptr) };
+        pt.walk_mut(Pos::new(vpn, 0), &mut set_decorator);
+        true
+    }
+
+    /// 修改 `range` 内每页的页表项标志为 `flags`（如 `mprotect`），物理页号保持不变，VALID 位始终保留。
+    ///
+    /// 区间内任一页未映射时不做任何修改并返回 `false`。不修改 `areas`；成功后调用方需刷新 TLB。
+    pub fn set_flags(&mut self, range: Range<VPN<Meta>>, flags: VmFlags<Meta>) -> bool {
+        let fully_mapped = (range.start.val()..range.end.val()).all(|vpn| {
+            let mut result: Option<(PPN<Meta>, VmFlags<Meta>)> = None;
+            let mut visitor = TranslateVisitor {
+                target: VPN::new(vpn),
+                result: &mut result,
+                manager: &self.manager,
+            };
+            self.root().walk(Pos::new(VPN::new(vpn), 0), &mut visitor);
+            result.is_some()
+        });
+        if !fully_mapped {
+            return false;
+        }
+        let flags = unsafe { VmFlags::from_raw(flags.val() | Meta::VALID_FLAG) };
+        let root_ptr = self.manager.root_ptr();
+        for vpn in range.start.val()..range.end.val() {
+            let mut flags_decorator = SetFlagsDecorator {
+                target: VPN::new(vpn),
+                flags,
+                manager: &self.manager,
+            };
+            let mut pt = unsafe { PageTable::from_root(root_ptr) };
+            pt.walk_mut(Pos::new(VPN::new(vpn), 0), &mut flags_decorator);
+        }
+        true
+    }
+
     /// 从 `src` 地址空间复制 VPN 对应的叶子 PTE 到本地址空间。
     /// 用于 ch4 将 kernel 的 portal PTE 复制到 process，确保 process 看到同一物理页。
     pub fn copy_leaf_pte_from(&mut self, src: &Self, vpn: VPN<Meta>) {
@@ -186,11 +572,37 @@ impl<Meta: VmMeta, M: PageManager<Meta>> AddressSpace<Meta, M> {
     }
 
     /// 在页表中查询 `addr` 所在页的映射并检查权限；满足时返回当前地址空间中该页的指针（加 `addr.offset()`）。
+    ///
+    /// 不关心失败原因时使用；需要区分未映射与权限不足时使用 [`translate_checked`](Self::translate_checked)。
     pub fn translate<T>(
         &self,
         addr: VAddr<Meta>,
         flags: VmFlags<Meta>,
     ) -> Option<NonNull<T>> {
+        self.translate_checked(addr, flags).ok()
+    }
+
+    /// 与 [`translate`](Self::translate) 相同，但失败时报告原因：
+    /// 页未映射返回 [`TranslateError::Unmapped`]，已映射但缺少 `flags` 中的权限返回
+    /// [`TranslateError::PermissionDenied`]，并携带页表项中实际存在的标志。
+    pub fn translate_checked<T>(
+        &self,
+        addr: VAddr<Meta>,
+        flags: VmFlags<Meta>,
+    ) -> Result<NonNull<T>, TranslateError<Meta>> {
+        let (ppn, pte_flags, byte_offset) = self.query(addr).ok_or(TranslateError::Unmapped)?;
+        if !pte_flags.contains(flags) {
+            return Err(TranslateError::PermissionDenied(pte_flags));
+        }
+        let base = self.manager.p_to_v::<u8>(ppn);
+        let ptr = unsafe { NonNull::new_unchecked(base.as_ptr().add(byte_offset) as *mut T) };
+        Ok(ptr)
+    }
+
+    /// 查询 `addr` 所在页的映射，返回物理页号、页表项标志与页内字节偏移；页未映射时返回 `None`。
+    ///
+    /// 只读取页表，不检查权限也不产生可解引用的指针，适合校验用户指针或输出映射信息。
+    pub fn query(&self, addr: VAddr<Meta>) -> Option<(PPN<Meta>, VmFlags<Meta>, usize)> {
         let vpn = addr.floor();
         let mut result: Option<(PPN<Meta>, VmFlags<Meta>)> = None;
         let mut visitor = TranslateVisitor {
@@ -198,58 +610,238 @@ impl<Meta: VmMeta, M: PageManager<Meta>> AddressSpace<Meta, M> {
             result: &mut result,
             manager: &self.manager,
         };
-        let pt = self.root();
-        pt.walk(Pos::new(vpn, 0), &mut visitor);
+        self.root().walk(Pos::new(vpn, 0), &mut visitor);
+        result.map(|(ppn, flags)| (ppn, flags, addr.offset()))
+    }
 
-        let (ppn, pte_flags) = result?;
-        if !pte_flags.contains(flags) {
-            return None;
+    /// 翻译从 `addr` 开始、长度为 `len` 字节的缓冲区，按页边界切分为若干 `(指针, 长度)` 段，
+    /// 调用方可逐段 `copy_nonoverlapping`，避免逐字节查询页表。
+    ///
+    /// 每页只遍历一次页表；`len` 为 0 时返回空列表。任一页未映射、缺少 `flags` 中的权限
+    /// 或地址越界回绕时返回 `None`。
+    pub fn translate_range(
+        &self,
+        addr: VAddr<Meta>,
+        len: usize,
+        flags: VmFlags<Meta>,
+    ) -> Option<Vec<(NonNull<u8>, usize)>> {
+        let page_size = 1usize << Meta::PAGE_BITS;
+        let start = (addr.floor().val() << Meta::PAGE_BITS) + addr.offset();
+        let end = start.checked_add(len)?;
+        let mut segments = Vec::new();
+        let mut cur = start;
+        while cur < end {
+            let vpn = VPN::<Meta>::new(cur >> Meta::PAGE_BITS);
+            let mut result: Option<(PPN<Meta>, VmFlags<Meta>)> = None;
+            let mut visitor = TranslateVisitor {
+                target: vpn,
+                result: &mut result,
+                manager: &self.manager,
+            };
+            self.root().walk(Pos::new(vpn, 0), &mut visitor);
+            let (ppn, pte_flags) = result?;
+            if !pte_flags.contains(flags) {
+                return None;
+            }
+            let offset = cur & (page_size - 1);
+            let chunk = (page_size - offset).min(end - cur);
+            let base = self.manager.p_to_v::<u8>(ppn);
+            let ptr = unsafe { NonNull::new_unchecked(base.as_ptr().add(offset)) };
+            segments.push((ptr, chunk));
+            cur += chunk;
         }
-        let base = self.manager.p_to_v::<u8>(ppn);
-        let byte_offset = addr.offset();
-        let ptr = unsafe { NonNull::new_unchecked(base.as_ptr().add(byte_offset) as *mut T) };
-        Some(ptr)
+        Some(segments)
+    }
+
+    /// 内核访问从 `addr` 开始、长度为 `len` 字节的用户缓冲区之前，以 `kind` 类型的访问补齐其中的缺页，
+    /// 之后 [`translate_range`](Self::translate_range) 才能成功。
+    ///
+    /// 已具备所需权限的页不做处理，其余页交给 [`handle_fault`](Self::handle_fault)：按需分配区间分配清零的页，
+    /// 写时复制页由注册的策略分出私有副本。任一页处理失败或地址越界回绕时返回 `false`，已处理的页保持不变。
+    /// 调用方需在返回用户态前刷新 TLB。
+    pub fn populate(&mut self, addr: VAddr<Meta>, len: usize, kind: FaultKind) -> bool {
+        let page_size = 1usize << Meta::PAGE_BITS;
+        let start = (addr.floor().val() << Meta::PAGE_BITS) + addr.offset();
+        let Some(end) = start.checked_add(len) else {
+            return false;
+        };
+        let mut page = start & !(page_size - 1);
+        while page < end {
+            let vaddr = VAddr::new(page);
+            let present = self.translate::<u8>(vaddr, kind.required_flags()).is_some();
+            if !present && self.handle_fault(vaddr, kind) != FaultOutcome::Mapped {
+                return false;
+            }
+            page += page_size;
+        }
+        true
+    }
+
+    /// 丢弃 `range` 中页面的内容（`MADV_DONTNEED`）：清除页表项并归还物理页，之后访问这些页时按需分配清零的新页。
+    ///
+    /// 只处理落在 `areas` 内、已映射且由本 `PageManager` 拥有的页，`areas` 记录保持不变。
+    /// 普通区间在第一次丢弃时以所丢弃页的标志（去掉写时复制标记）转为按需分配区间，此后逐页回收；
+    /// 写时复制页只释放本地址空间的一个引用。返回归还的页数，调用方需刷新 TLB。
+    pub fn discard(&mut self, range: Range<VPN<Meta>>) -> usize {
+        let invalid = unsafe { VmFlags::<Meta>::from_raw(0) }.build_pte(PPN::new(0));
+        let mut discarded = 0;
+        for vpn in range.start.val()..range.end.val() {
+            let Some(area) = self
+                .areas
+                .iter()
+                .find(|area| area.start.val() <= vpn && vpn < area.end.val())
+                .cloned()
+            else {
+                continue;
+            };
+            let Some(pte) = self.leaf_pte(VPN::new(vpn)) else {
+                continue;
+            };
+            if !self.manager.check_owned(pte) {
+                continue;
+            }
+            if !self.is_lazy(&area) {
+                self.lazy.push((area, Self::private_flags(pte.flags())));
+            }
+            self.set_leaf_pte(VPN::new(vpn), invalid);
+            self.manager.deallocate(pte, 1);
+            discarded += 1;
+        }
+        discarded
+    }
+
+    /// 撤销 `range` 的映射（如 `munmap`、线程退出时回收线程栈）：清除叶子页表项，
+    /// 归还由本 `PageManager` 拥有的物理页，并从 `areas` 中删除对应区间。
+    ///
+    /// 物理页按 `areas` 中的区间整体归还，因此 `range` 必须恰好由若干完整区间组成，
+    /// 且其中每一页都已映射（按需分配区间中尚未分配的页除外）；否则不做任何修改并返回 `false`。
+    /// 成功后调用方需刷新 TLB。
+    pub fn unmap(&mut self, range: Range<VPN<Meta>>) -> bool {
+        let (start, end) = (range.start.val(), range.end.val());
+        if start >= end {
+            return false;
+        }
+        let partial = self.areas.iter().any(|area| {
+            let overlaps = area.start.val() < end && start < area.end.val();
+            let inside = start <= area.start.val() && area.end.val() <= end;
+            overlaps && !inside
+        });
+        let covered: usize = self
+            .areas
+            .iter()
+            .filter(|area| start <= area.start.val() && area.end.val() <= end)
+            .map(|area| area.end.val() - area.start.val())
+            .sum();
+        if partial || covered != end - start {
+            return false;
+        }
+        let fully_mapped = (start..end).all(|vpn| {
+            let vpn = VPN::new(vpn);
+            self.lazy_flags(vpn).is_some() || self.leaf_pte(vpn).is_some()
+        });
+        if !fully_mapped {
+            return false;
+        }
+
+        let root_ptr = self.manager.root_ptr();
+        let invalid = unsafe { VmFlags::<Meta>::from_raw(0) }.build_pte(PPN::new(0));
+        let (removed, kept): (Vec<_>, Vec<_>) = core::mem::take(&mut self.areas)
+            .into_iter()
+            .partition(|area| start <= area.start.val() && area.end.val() <= end);
+        self.areas = kept;
+        for area in removed {
+            // 按需分配与写时复制共享过的区间中各页单独分配或单独计数，逐页归还
+            if self.is_lazy(&area) || self.is_cow(&area) {
+                for vpn in area.start.val()..area.end.val() {
+                    if let Some(pte) = self.leaf_pte(VPN::new(vpn)) {
+                        self.set_leaf_pte(VPN::new(vpn), invalid);
+                        if self.manager.check_owned(pte) {
+                            self.manager.deallocate(pte, 1);
+                        }
+                    }
+                }
+                self.lazy.retain(|(lazy, _)| *lazy != area);
+                self.cow.retain(|cow| *cow != area);
+                continue;
+            }
+            let mut first: Option<Pte<Meta>> = None;
+            let mut get_visitor = GetPteVisitor {
+                target: area.start,
+                result: &mut first,
+                manager: &self.manager,
+            };
+            self.root().walk(Pos::new(area.start, 0), &mut get_visitor);
+            for vpn in area.start.val()..area.end.val() {
+                let mut clear_decorator = SetPteDecorator {
+                    target: VPN::new(vpn),
+                    pte: invalid,
+                    manager: &mut self.manager,
+                };
+                let mut pt = unsafe { PageTable::from_root(root_ptr) };
+                pt.walk_mut(Pos::new(VPN::new(vpn), 0), &mut clear_decorator);
+            }
+            if let Some(pte) = first.filter(|&pte| self.manager.check_owned(pte)) {
+                self.manager
+                    .deallocate(pte, area.end.val() - area.start.val());
+            }
+        }
+        true
     }
 
     /// 释放本地址空间中由 `map()` 分配的物理页，并释放根页表页。
     /// 用于 exec 等场景在替换地址空间前回收旧空间占用的内核堆。
     /// `skip_vpn`：若某 area 包含此 VPN，则跳过（用于 portal 等从内核复制的页）。
     pub fn free_allocated_pages_and_root(&mut self, skip_vpn: Option<VPN<Meta>>) {
-        let mut pte_buf = None;
         for range in core::mem::take(&mut self.areas) {
             if let Some(skip) = skip_vpn {
                 if range.start.val() <= skip.val() && skip.val() < range.end.val() {
                     continue; // 跳过 portal 等外部映射
                 }
             }
-            let count = range.end.val() - range.start.val();
-            if count == 0 {
-                continue;
-            }
-            let vpn0 = range.start;
-            let mut get_visitor = GetPteVisitor {
-                target: vpn0,
-                result: &mut pte_buf,
-                manager: &self.manager,
-            };
-            let pt = self.root();
-            pt.walk(Pos::new(vpn0, 0), &mut get_visitor);
-            if let Some(pte) = pte_buf.take() {
-                self.manager.deallocate(pte, count);
-            }
+            self.release_area(&range);
         }
+        self.lazy.clear();
+        self.cow.clear();
         self.manager.drop_root();
     }
 
     /// 将本地址空间的 `areas` 中每个虚拟区间在 `new_addrspace` 中重新分配物理页、拷贝数据并建立同等映射。
+    /// 数据逐页拷贝，写时复制共享过的区间也能得到正确的内容。
+    ///
+    /// 注册的缺页处理策略一并复制；按需分配区间在新地址空间中仍是按需分配，只拷贝已分配的页。
     pub fn cloneself(&self, new_addrspace: &mut AddressSpace<Meta, M>) {
+        new_addrspace.fault_handler = self.fault_handler;
+        for (range, flags) in &self.lazy {
+            new_addrspace.map_lazy(range.clone(), Self::private_flags(*flags));
+            for vpn in range.start.val()..range.end.val() {
+                let vpn = VPN::new(vpn);
+                let Some(pte) = self.leaf_pte(vpn) else {
+                    continue;
+                };
+                let mut flags = Self::private_flags(pte.flags());
+                let page = new_addrspace.manager.allocate(1, &mut flags);
+                let src = self.manager.p_to_v::<u8>(pte.ppn());
+                unsafe {
+                    core::ptr::copy_nonoverlapping(
+                        src.as_ptr(),
+                        page.as_ptr(),
+                        1 << Meta::PAGE_BITS,
+                    );
+                }
+                let ppn = new_addrspace.manager.v_to_p(page);
+                new_addrspace.set_leaf_pte(vpn, flags.build_pte(ppn));
+            }
+        }
         for range in &self.areas {
+            if self.is_lazy(range) {
+                continue;
+            }
             let count = range.end.val() - range.start.val();
             if count == 0 {
                 continue;
             }
 
-            // 从本地址空间读取该区间首页的 PTE，得到 ppn 与 flags
+            // 从本地址空间读取该区间首页的 PTE 得到 flags，写时复制页在副本中恢复为私有可写
             let vpn0 = range.start;
             let mut src_pte: Option<(PPN<Meta>, VmFlags<Meta>)> = None;
             let mut visitor = TranslateVisitor {
@@ -260,22 +852,17 @@ impl<Meta: VmMeta, M: PageManager<Meta>> AddressSpace<Meta, M> {
             let pt = self.root();
             pt.walk(Pos::new(vpn0, 0), &mut visitor);
 
-            let (src_ppn, flags) = match src_pte {
-                Some(x) => x,
+            let flags = match src_pte {
+                Some((_, flags)) => Self::private_flags(flags),
                 None => continue,
             };
 
-            let size = count << Meta::PAGE_BITS;
-            let src_ptr = self.manager.p_to_v::<u8>(src_ppn);
-
             let mut flags_clone = flags;
             let new_ptr = new_addrspace
                 .manager
                 .allocate(count, &mut flags_clone);
             let dst_ptr = new_ptr.as_ptr();
-            unsafe {
-                core::ptr::copy_nonoverlapping(src_ptr.as_ptr(), dst_ptr, size);
-            }
+            self.copy_area(range, dst_ptr);
 
             let new_pbase = new_addrspace
                 .manager
@@ -283,6 +870,191 @@ impl<Meta: VmMeta, M: PageManager<Meta>> AddressSpace<Meta, M> {
             new_addrspace.map_extern(range.clone(), new_pbase, flags);
         }
     }
+
+    /// 写时复制版本的 [`cloneself`](Self::cloneself)，用于 `fork`：
+    /// `new_addrspace` 的各区间映射到与本地址空间相同的物理页，不拷贝数据。
+    ///
+    /// 可写页在双方页表中都清除写权限并加上 [`PageManager::COW_FLAG`]，直到某一方写入时由
+    /// [`handle_cow_fault`](Self::handle_cow_fault) 分出私有副本；只读页直接共享。
+    /// 每个共享的页都通过 `share(pte, 1)` 登记一次，之后双方都逐页回收这些区间，
+    /// `PageManager` 必须按页维护引用计数，否则父子地址空间各自回收时会重复释放同一物理页。
+    /// 注册的缺页处理策略一并复制，调用方需为双方注册会调用 `handle_cow_fault` 的策略，
+    /// 并在返回后刷新本地址空间的 TLB。`COW_FLAG` 为 0 时 panic。
+    pub fn cow_clone(&mut self, new_addrspace: &mut AddressSpace<Meta, M>) {
+        assert_ne!(
+            M::COW_FLAG,
+            0,
+            "cow_clone: PageManager::COW_FLAG is not set"
+        );
+        new_addrspace.fault_handler = self.fault_handler;
+        let write = VmFlags::<Meta>::build_from_str("W").val();
+        let root_ptr = self.manager.root_ptr();
+        for range in self.areas.clone() {
+            let mut mapped = false;
+            for vpn in range.start.val()..range.end.val() {
+                let vpn = VPN::new(vpn);
+                let Some(pte) = self.leaf_pte(vpn) else {
+                    continue;
+                };
+                mapped = true;
+                self.manager.share(pte, 1);
+
+                let flags = pte.flags().val();
+                let shared = if flags & write != 0 {
+                    let cow = unsafe { VmFlags::from_raw(flags & !write | M::COW_FLAG) };
+                    let shared = cow.build_pte(pte.ppn());
+                    let mut set_decorator = SetPteDecorator {
+                        target: vpn,
+                        pte: shared,
+                        manager: &mut self.manager,
+                    };
+                    let mut pt = unsafe { PageTable::from_root(root_ptr) };
+                    pt.walk_mut(Pos::new(vpn, 0), &mut set_decorator);
+                    shared
+                } else {
+                    pte
+                };
+                let child_root = new_addrspace.manager.root_ptr();
+                let mut set_decorator = SetPteDecorator {
+                    target: vpn,
+                    pte: shared,
+                    manager: &mut new_addrspace.manager,
+                };
+                let mut pt = unsafe { PageTable::from_root(child_root) };
+                pt.walk_mut(Pos::new(vpn, 0), &mut set_decorator);
+            }
+            if self.is_lazy(&range) {
+                let flags = self.lazy_flags(range.start).unwrap();
+                let cow = if flags.val() & write != 0 {
+                    unsafe { VmFlags::from_raw(flags.val() & !write | M::COW_FLAG) }
+                } else {
+                    flags
+                };
+                new_addrspace.map_lazy(range, cow);
+            } else if mapped {
+                if !self.is_cow(&range) {
+                    self.cow.push(range.clone());
+                }
+                new_addrspace.cow.push(range.clone());
+                new_addrspace.areas.push(range);
+            }
+        }
+    }
+
+    /// 处理 `addr` 所在写时复制页上的写缺页：分配新页、拷贝内容，去掉 [`PageManager::COW_FLAG`] 并恢复写权限，
+    /// 再通过 `deallocate(pte, 1)` 释放本地址空间对原页的一个引用。
+    ///
+    /// 页未映射或不带 `COW_FLAG` 时不做修改并返回 [`FaultOutcome::Signal`]；
+    /// 成功返回 [`FaultOutcome::Mapped`]，调用方需刷新 TLB。
+    pub fn handle_cow_fault(&mut self, addr: VAddr<Meta>) -> FaultOutcome {
+        let vpn = addr.floor();
+        let mut old: Option<Pte<Meta>> = None;
+        let mut get_visitor = GetPteVisitor {
+            target: vpn,
+            result: &mut old,
+            manager: &self.manager,
+        };
+        self.root().walk(Pos::new(vpn, 0), &mut get_visitor);
+        let Some(old) = old.filter(|pte| pte.flags().val() & M::COW_FLAG != 0) else {
+            return FaultOutcome::Signal;
+        };
+
+        let mut flags = Self::private_flags(old.flags());
+        let copy = self.manager.allocate(1, &mut flags);
+        let src = self.manager.p_to_v::<u8>(old.ppn());
+        unsafe {
+            core::ptr::copy_nonoverlapping(src.as_ptr(), copy.as_ptr(), 1 << Meta::PAGE_BITS);
+        }
+        let ppn = self.manager.v_to_p(copy);
+        self.remap_page(vpn, ppn, flags);
+        self.manager.deallocate(old, 1);
+        FaultOutcome::Mapped
+    }
+}
+
+impl<Meta: VmMeta, M: PageManager<Meta>> AddressSpace<Meta, M> {
+    /// 包含 `vpn` 的按需分配区间的标志。
+    fn lazy_flags(&self, vpn: VPN<Meta>) -> Option<VmFlags<Meta>> {
+        self.lazy
+            .iter()
+            .find(|(area, _)| area.start.val() <= vpn.val() && vpn.val() < area.end.val())
+            .map(|&(_, flags)| flags)
+    }
+
+    fn is_lazy(&self, area: &Range<VPN<Meta>>) -> bool {
+        self.lazy.iter().any(|(lazy, _)| lazy == area)
+    }
+
+    fn is_cow(&self, area: &Range<VPN<Meta>>) -> bool {
+        self.cow.contains(area)
+    }
+
+    /// 私有副本使用的标志：带写时复制标记的页去掉标记并恢复写权限。
+    fn private_flags(flags: VmFlags<Meta>) -> VmFlags<Meta> {
+        if flags.val() & M::COW_FLAG == 0 {
+            return flags;
+        }
+        let write = VmFlags::<Meta>::build_from_str("W").val();
+        unsafe { VmFlags::from_raw(flags.val() & !M::COW_FLAG | write) }
+    }
+
+    /// 把非按需分配区间 `area` 的内容逐页拷贝到从 `dst` 开始的连续内存，未映射的页跳过。
+    fn copy_area(&self, area: &Range<VPN<Meta>>, dst: *mut u8) {
+        for (i, vpn) in (area.start.val()..area.end.val()).enumerate() {
+            if let Some(pte) = self.leaf_pte(VPN::new(vpn)) {
+                let src = self.manager.p_to_v::<u8>(pte.ppn());
+                unsafe {
+                    core::ptr::copy_nonoverlapping(
+                        src.as_ptr(),
+                        dst.add(i << Meta::PAGE_BITS),
+                        1 << Meta::PAGE_BITS,
+                    );
+                }
+            }
+        }
+    }
+
+    /// 归还区间 `area` 占用的物理页，不修改页表。
+    ///
+    /// 按需分配与写时复制共享过的区间逐页 `deallocate(pte, 1)`，其他区间是一段连续分配的物理页，整体归还。
+    fn release_area(&mut self, area: &Range<VPN<Meta>>) {
+        if self.is_lazy(area) || self.is_cow(area) {
+            for vpn in area.start.val()..area.end.val() {
+                if let Some(pte) = self.leaf_pte(VPN::new(vpn)) {
+                    self.manager.deallocate(pte, 1);
+                }
+            }
+        } else if area.start.val() < area.end.val() {
+            if let Some(pte) = self.leaf_pte(area.start) {
+                self.manager
+                    .deallocate(pte, area.end.val() - area.start.val());
+            }
+        }
+    }
+
+    /// `vpn` 的有效叶子页表项。
+    fn leaf_pte(&self, vpn: VPN<Meta>) -> Option<Pte<Meta>> {
+        let mut pte: Option<Pte<Meta>> = None;
+        let mut get_visitor = GetPteVisitor {
+            target: vpn,
+            result: &mut pte,
+            manager: &self.manager,
+        };
+        self.root().walk(Pos::new(vpn, 0), &mut get_visitor);
+        pte
+    }
+
+    /// 把 `vpn` 的叶子页表项写为 `pte`，缺少的页表页按需分配。
+    fn set_leaf_pte(&mut self, vpn: VPN<Meta>, pte: Pte<Meta>) {
+        let root_ptr = self.manager.root_ptr();
+        let mut set_decorator = SetPteDecorator {
+            target: vpn,
+            pte,
+            manager: &mut self.manager,
+        };
+        let mut pt = unsafe { PageTable::from_root(root_ptr) };
+        pt.walk_mut(Pos::new(vpn, 0), &mut set_decorator);
+    }
 }
 
 impl<Meta: VmMeta, M: PageManager<Meta>> Default for AddressSpace<Meta, M> {
@@ -293,14 +1065,7 @@ impl<Meta: VmMeta, M: PageManager<Meta>> Default for AddressSpace<Meta, M> {
 
 impl<Meta: VmMeta, M: PageManager<Meta>> fmt::Debug for AddressSpace<Meta, M> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let root_ppn = self.root_ppn();
-        writeln!(f, "root: {:#x?}", root_ppn.val())?;
-        let pt = self.root();
-        let formatter = PageTableFormatter {
-            pt,
-            f: |ppn: PPN<Meta>| self.manager.p_to_v(ppn),
-        };
-        fmt::Debug::fmt(&formatter, f)
+        self.dump_to(f)
     }
 }
 
@@ -374,6 +1139,41 @@ impl<Meta: VmMeta, M: PageManager<Meta>> Decorator<Meta> for SetPteDecorator<'_,
     }
 }
 
+// ============== set_flags 用 Decorator ==============
+
+struct SetFlagsDecorator<'a, Meta: VmMeta, M: PageManager<Meta>> {
+    target: VPN<Meta>,
+    flags: VmFlags<Meta>,
+    manager: &'a M,
+}
+
+impl<Meta: VmMeta, M: PageManager<Meta>> Decorator<Meta> for SetFlagsDecorator<'_, Meta, M> {
+    fn arrive(&mut self, pte: &mut Pte<Meta>, target: Pos<Meta>) -> Pos<Meta> {
+        if target.vpn == self.target && pte.is_valid() {
+            *pte = self.flags.build_pte(pte.ppn());
+        }
+        Pos::stop()
+    }
+
+    fn meet(
+        &mut self,
+        _level: usize,
+        pte: Pte<Meta>,
+        _target: Pos<Meta>,
+    ) -> Option<NonNull<Pte<Meta>>> {
+        if self.manager.check_owned(pte) {
+            Some(self.manager.p_to_v(pte.ppn()))
+        } else {
+            None
+        }
+    }
+
+    fn block(&mut self, _level: usize, _pte: Pte<Meta>, _target: Pos<Meta>) -> Update<Meta> {
+        // 只改写已有映射，不创建页表页
+        Update::Target(Pos::stop())
+    }
+}
+
 // ============== map_extern 用 Decorator ==============
 
 struct MapExternDecorator<'a, Meta: VmMeta, M: PageManager<Meta>> {
@@ -7,7 +7,147 @@
 //! 特定的架构支持（如 RISC-V），这些测试主要验证类型和基本 API 的存在性。
 
 use kernel_vm::*;
-use page_table::{VmMeta, PPN, VPN, VAddr, VmFlags, Pte};
+use page_table::{VmMeta, PPN, VPN, VAddr, VmFlags, Pte, Sv39};
+use std::alloc::{alloc_zeroed, Layout};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ptr::NonNull;
+
+const PAGE_SIZE: usize = 4096;
+
+// 用宿主堆模拟物理内存的 PageManager：物理页号即宿主地址右移 12 位，
+// 页表页与数据页都来自堆，测试中不回收。
+struct HostManager {
+    root: NonNull<Pte<Sv39>>,
+}
+
+fn alloc_pages(count: usize) -> NonNull<u8> {
+    let layout = Layout::from_size_align(count * PAGE_SIZE, PAGE_SIZE).unwrap();
+    NonNull::new(unsafe { alloc_zeroed(layout) }).unwrap()
+}
+
+impl PageManager<Sv39> for HostManager {
+    fn new_root() -> Self {
+        Self {
+            root: alloc_pages(1).cast(),
+        }
+    }
+
+    fn root_ptr(&self) -> NonNull<Pte<Sv39>> {
+        self.root
+    }
+
+    fn root_ppn(&self) -> PPN<Sv39> {
+        self.v_to_p(self.root)
+    }
+
+    fn p_to_v<T>(&self, ppn: PPN<Sv39>) -> NonNull<T> {
+        NonNull::new((ppn.val() << 12) as *mut T).unwrap()
+    }
+
+    fn v_to_p<T>(&self, ptr: NonNull<T>) -> PPN<Sv39> {
+        PPN::new(ptr.as_ptr() as usize >> 12)
+    }
+
+    fn allocate(&mut self, len: usize, _flags: &mut VmFlags<Sv39>) -> NonNull<u8> {
+        alloc_pages(len)
+    }
+
+    fn deallocate(&mut self, _pte: Pte<Sv39>, len: usize) -> usize {
+        len
+    }
+
+    fn check_owned(&self, _pte: Pte<Sv39>) -> bool {
+        true
+    }
+
+    // RISC-V 页表项中留给软件使用的 RSW 位
+    const COW_FLAG: usize = 1 << 8;
+
+    fn drop_root(&mut self) {}
+}
+
+// 按物理页维护引用计数的 PageManager，用于检查写时复制区间的回收：
+// 释放计数已归零或从未分配的页会 panic。页表页不回收，始终留在计数表中。
+struct CountingManager {
+    root: NonNull<Pte<Sv39>>,
+}
+
+thread_local! {
+    static REFS: RefCell<HashMap<usize, usize>> = RefCell::new(HashMap::new());
+}
+
+fn refs(ppn: usize) -> usize {
+    REFS.with(|refs| refs.borrow().get(&ppn).copied().unwrap_or(0))
+}
+
+impl PageManager<Sv39> for CountingManager {
+    fn new_root() -> Self {
+        Self {
+            root: alloc_pages(1).cast(),
+        }
+    }
+
+    fn root_ptr(&self) -> NonNull<Pte<Sv39>> {
+        self.root
+    }
+
+    fn root_ppn(&self) -> PPN<Sv39> {
+        self.v_to_p(self.root)
+    }
+
+    fn p_to_v<T>(&self, ppn: PPN<Sv39>) -> NonNull<T> {
+        NonNull::new((ppn.val() << 12) as *mut T).unwrap()
+    }
+
+    fn v_to_p<T>(&self, ptr: NonNull<T>) -> PPN<Sv39> {
+        PPN::new(ptr.as_ptr() as usize >> 12)
+    }
+
+    fn allocate(&mut self, len: usize, _flags: &mut VmFlags<Sv39>) -> NonNull<u8> {
+        let ptr = alloc_pages(len);
+        let base = ptr.as_ptr() as usize >> 12;
+        REFS.with(|refs| refs.borrow_mut().extend((base..base + len).map(|ppn| (ppn, 1))));
+        ptr
+    }
+
+    fn deallocate(&mut self, pte: Pte<Sv39>, len: usize) -> usize {
+        let base = pte.ppn().val();
+        REFS.with(|refs| {
+            let mut refs = refs.borrow_mut();
+            for ppn in base..base + len {
+                let count = refs.get_mut(&ppn).expect("page freed twice");
+                *count -= 1;
+                if *count == 0 {
+                    refs.remove(&ppn);
+                }
+            }
+        });
+        len
+    }
+
+    fn check_owned(&self, _pte: Pte<Sv39>) -> bool {
+        true
+    }
+
+    fn share(&mut self, pte: Pte<Sv39>, len: usize) {
+        let base = pte.ppn().val();
+        REFS.with(|refs| {
+            let mut refs = refs.borrow_mut();
+            for ppn in base..base + len {
+                *refs.get_mut(&ppn).expect("sharing a free page") += 1;
+            }
+        });
+    }
+
+    const COW_FLAG: usize = 1 << 8;
+
+    fn drop_root(&mut self) {}
+}
+
+fn vaddr(vpn: usize) -> VAddr<Sv39> {
+    VAddr::new(vpn << 12)
+}
 
 #[test]
 fn test_address_space_new_exists() {
@@ -85,6 +225,531 @@ fn test_address_space_methods_exist() {
     // - cloneself(&self, new_addrspace: &mut AddressSpace<Meta, M>)
 }
 
+#[test]
+fn test_translate_checked() {
+    // 测试 translate_checked 区分未映射与权限不足
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    space.map(VPN::new(0x100)..VPN::new(0x101), &[0xab], 0, VmFlags::build_from_str("VRU"));
+
+    // 权限满足：返回指向数据的指针
+    let ptr = space
+        .translate_checked::<u8>(vaddr(0x100), VmFlags::build_from_str("R"))
+        .unwrap();
+    assert_eq!(unsafe { *ptr.as_ptr() }, 0xab);
+    assert!(space.translate::<u8>(vaddr(0x100), VmFlags::build_from_str("R")).is_some());
+
+    // 已映射但不可写：PermissionDenied 携带实际标志
+    match space.translate_checked::<u8>(vaddr(0x100), VmFlags::build_from_str("W")) {
+        Err(TranslateError::PermissionDenied(flags)) => {
+            assert!(flags.contains(VmFlags::build_from_str("VRU")));
+            assert!(!flags.contains(VmFlags::build_from_str("W")));
+        }
+        other => panic!("expected PermissionDenied, got {:?}", other.map(|_| ())),
+    }
+    assert!(space.translate::<u8>(vaddr(0x100), VmFlags::build_from_str("W")).is_none());
+
+    // 未映射
+    assert!(matches!(
+        space.translate_checked::<u8>(vaddr(0x200), VmFlags::build_from_str("R")),
+        Err(TranslateError::Unmapped)
+    ));
+}
+
+#[test]
+fn test_mapped_pages() {
+    // 测试 mapped_pages 统计 areas 中的虚拟页总数
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    assert_eq!(space.mapped_pages(), 0);
+
+    space.map(VPN::new(0x100)..VPN::new(0x102), &[], 0, VmFlags::build_from_str("VRWU"));
+    assert_eq!(space.mapped_pages(), 2);
+
+    space.map(VPN::new(0x200)..VPN::new(0x205), &[], 0, VmFlags::build_from_str("VRU"));
+    assert_eq!(space.mapped_pages(), 7);
+}
+
+#[test]
+fn test_discard() {
+    // 测试 discard 归还区间内的页，保留 areas，之后访问得到清零的新页
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    let data = [0x5au8; 8192];
+    space.map(VPN::new(0x100)..VPN::new(0x102), &data, 0, VmFlags::build_from_str("VRWU"));
+
+    // 只丢弃第二页，外加一个未映射的页
+    assert_eq!(space.discard(VPN::new(0x101)..VPN::new(0x103)), 1);
+    assert_eq!(space.mapped_pages(), 2);
+    assert!(space.query(vaddr(0x101)).is_none());
+    // 已归还的页不会再被丢弃一次
+    assert_eq!(space.discard(VPN::new(0x101)..VPN::new(0x102)), 0);
+
+    let read = |space: &AddressSpace<Sv39, HostManager>, vpn: usize| unsafe {
+        *space
+            .translate::<u8>(vaddr(vpn), VmFlags::build_from_str("R"))
+            .unwrap()
+            .as_ptr()
+    };
+    assert_eq!(read(&space, 0x100), 0x5a);
+    assert_eq!(space.handle_fault(vaddr(0x101), FaultKind::Load), FaultOutcome::Mapped);
+    assert_eq!(read(&space, 0x101), 0);
+
+    // 区间整体撤销时逐页回收
+    assert!(space.unmap(VPN::new(0x100)..VPN::new(0x102)));
+    assert_eq!(space.mapped_pages(), 0);
+}
+
+#[test]
+fn test_map_mmio() {
+    // 测试 map_mmio 建立恒等映射，并拒绝与已有区间重叠
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    let base = space
+        .map_mmio(0x1000_1000, 0x1000, VmFlags::build_from_str("VRW"))
+        .unwrap();
+    assert_eq!(base.val(), 0x1000_1000);
+    assert_eq!(space.mapped_pages(), 1);
+
+    // 窗口内地址翻译到同一物理地址（HostManager 的 p_to_v 为恒等映射）
+    let ptr = space
+        .translate::<u8>(VAddr::new(0x1000_1234), VmFlags::build_from_str("RW"))
+        .unwrap();
+    assert_eq!(ptr.as_ptr() as usize, 0x1000_1234);
+
+    // 重叠与空区间都被拒绝
+    assert!(space.map_mmio(0x1000_1800, 0x10, VmFlags::build_from_str("VRW")).is_none());
+    assert!(space.map_mmio(0x1000_0000, 0x1001, VmFlags::build_from_str("VRW")).is_none());
+    assert!(space.map_mmio(0x1000_3000, 0, VmFlags::build_from_str("VRW")).is_none());
+    assert_eq!(space.mapped_pages(), 1);
+
+    // 相邻区间可以映射
+    assert!(space.map_mmio(0x1000_2000, 0x1000, VmFlags::build_from_str("VRW")).is_some());
+    assert_eq!(space.mapped_pages(), 2);
+}
+
+#[test]
+fn test_grow_stack() {
+    // 测试 grow_stack 向下扩展栈，保留原内容，并拒绝与其他区间冲突
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    space.map(VPN::new(0x100)..VPN::new(0x108), &[], 0, VmFlags::build_from_str("VRWU"));
+    let data = [0x5au8; 2 * PAGE_SIZE];
+    space.map(VPN::new(0x200)..VPN::new(0x202), &data, 0, VmFlags::build_from_str("VRWU"));
+
+    // 栈顶不对应任何区间
+    assert!(!space.grow_stack(VPN::new(0x201), 1));
+
+    assert!(space.grow_stack(VPN::new(0x202), 4));
+    assert_eq!(space.areas.last().unwrap().start, VPN::new(0x1fc));
+    assert_eq!(space.mapped_pages(), 8 + 6);
+
+    // 新页可写且初始为零，原栈内容保持不变
+    let ptr = space
+        .translate::<u8>(vaddr(0x1fc), VmFlags::build_from_str("RWU"))
+        .unwrap();
+    assert_eq!(unsafe { *ptr.as_ptr() }, 0);
+    unsafe { *ptr.as_ptr() = 0x42 };
+    let ptr = space
+        .translate::<u8>(vaddr(0x1fc), VmFlags::build_from_str("R"))
+        .unwrap();
+    assert_eq!(unsafe { *ptr.as_ptr() }, 0x42);
+    let ptr = space
+        .translate::<u8>(vaddr(0x201), VmFlags::build_from_str("R"))
+        .unwrap();
+    assert_eq!(unsafe { *ptr.as_ptr() }, 0x5a);
+
+    // 继续扩展会与 [0x100, 0x108) 重叠
+    assert!(!space.grow_stack(VPN::new(0x202), 0xf5));
+    assert!(space.grow_stack(VPN::new(0x202), 0xf4));
+    assert_eq!(space.mapped_pages(), 8 + 0xfa);
+}
+
+#[test]
+fn test_dump_to() {
+    // 测试 dump_to 把各映射区间写入普通的 String
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    space.map(VPN::new(0x100)..VPN::new(0x102), &[], 0, VmFlags::build_from_str("VRWU"));
+    space.map(VPN::new(0x200)..VPN::new(0x203), &[], 0, VmFlags::build_from_str("VRU"));
+
+    let mut out = String::new();
+    space.dump_to(&mut out).unwrap();
+    assert!(out.starts_with("root: "));
+    assert!(out.contains("area: [0x100, 0x102)"));
+    assert!(out.contains("area: [0x200, 0x203)"));
+
+    // Debug 与 dump_to 输出一致
+    assert_eq!(format!("{:?}", space), out);
+}
+
 // 注意：由于 kernel-vm 需要 PageManager trait 的具体实现才能进行完整的功能测试，
 // 而这些实现通常需要特定的架构支持（如 RISC-V Sv39），完整的功能测试应该在
 // 实际的内核环境中进行（如 ch4-ch8 中的测试）。
+
+// 测试用写时复制策略：只读的用户页在写缺页时复制到新页并恢复写权限
+struct CowHandler;
+
+impl FaultHandler<Sv39, HostManager> for CowHandler {
+    fn handle(
+        space: &mut AddressSpace<Sv39, HostManager>,
+        addr: VAddr<Sv39>,
+        kind: FaultKind,
+    ) -> FaultOutcome {
+        if kind != FaultKind::Store {
+            return FaultOutcome::Signal;
+        }
+        let Some(src) = space.translate::<u8>(addr, VmFlags::build_from_str("RU")) else {
+            return FaultOutcome::Signal;
+        };
+        let page = (src.as_ptr() as usize & !(PAGE_SIZE - 1)) as *const u8;
+        let copy = alloc_pages(1);
+        unsafe { std::ptr::copy_nonoverlapping(page, copy.as_ptr(), PAGE_SIZE) };
+        let ppn = PPN::new(copy.as_ptr() as usize >> 12);
+        if space.remap_page(addr.floor(), ppn, VmFlags::build_from_str("VRWU")) {
+            FaultOutcome::Mapped
+        } else {
+            FaultOutcome::Fatal
+        }
+    }
+}
+
+#[test]
+fn test_fault_handler_cow() {
+    // 写时复制缺页经 handle_fault 交给注册的策略，得到 Mapped 后页面可写且与原页分离
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    space.map(VPN::new(0x100)..VPN::new(0x101), b"shared", 0, VmFlags::build_from_str("VRU"));
+    let original = space
+        .translate::<u8>(vaddr(0x100), VmFlags::build_from_str("R"))
+        .unwrap();
+    space.set_fault_handler::<CowHandler>();
+
+    let addr = VAddr::<Sv39>::new((0x100 << 12) + 3);
+    assert_eq!(space.handle_fault(addr, FaultKind::Store), FaultOutcome::Mapped);
+    let copy = space
+        .translate::<u8>(vaddr(0x100), VmFlags::build_from_str("RW"))
+        .unwrap();
+    assert_ne!(copy, original);
+    let read = |ptr: NonNull<u8>| unsafe { std::slice::from_raw_parts(ptr.as_ptr(), 6).to_vec() };
+    assert_eq!(read(copy), b"shared");
+    unsafe { *copy.as_ptr() = b'S' };
+    assert_eq!(read(copy), b"Shared");
+    assert_eq!(read(original), b"shared");
+
+    // 策略决定其他情况：读缺页与未映射地址都交给信号处理
+    assert_eq!(space.handle_fault(vaddr(0x100), FaultKind::Load), FaultOutcome::Signal);
+    assert_eq!(space.handle_fault(vaddr(0x200), FaultKind::Store), FaultOutcome::Signal);
+    // remap_page 不会凭空建立映射
+    assert!(!space.remap_page(VPN::new(0x200), PPN::new(0x1234), VmFlags::build_from_str("VRU")));
+}
+
+#[test]
+fn test_fault_default_policy() {
+    // 未注册策略时只把伪缺页视为已映射
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    space.map(VPN::new(0x100)..VPN::new(0x101), &[], 0, VmFlags::build_from_str("VRU"));
+    assert_eq!(space.handle_fault(vaddr(0x100), FaultKind::Load), FaultOutcome::Mapped);
+    assert_eq!(space.handle_fault(vaddr(0x100), FaultKind::Store), FaultOutcome::Signal);
+    assert_eq!(space.handle_fault(vaddr(0x100), FaultKind::Instruction), FaultOutcome::Signal);
+    assert_eq!(space.handle_fault(vaddr(0x300), FaultKind::Load), FaultOutcome::Signal);
+    // 没有 U 的页即使具备所需权限，用户访问也不是伪缺页
+    space.map(VPN::new(0x400)..VPN::new(0x401), &[], 0, VmFlags::build_from_str("VRWX"));
+    assert_eq!(space.handle_fault(vaddr(0x400), FaultKind::Load), FaultOutcome::Signal);
+    assert_eq!(space.handle_fault(vaddr(0x400), FaultKind::Instruction), FaultOutcome::Signal);
+
+    // cloneself 带上已注册的策略
+    space.set_fault_handler::<CowHandler>();
+    let mut child = AddressSpace::<Sv39, HostManager>::new();
+    space.cloneself(&mut child);
+    assert_eq!(child.handle_fault(vaddr(0x100), FaultKind::Store), FaultOutcome::Mapped);
+    assert!(child.translate::<u8>(vaddr(0x100), VmFlags::build_from_str("W")).is_some());
+}
+
+#[test]
+fn test_unmap() {
+    // 测试 unmap 撤销整段区间，其他区间的映射不受影响
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    space.map(VPN::new(0x100)..VPN::new(0x102), &[0x11], 0, VmFlags::build_from_str("VRWU"));
+    space.map(VPN::new(0x200)..VPN::new(0x203), &[0x22], 0, VmFlags::build_from_str("VRWU"));
+
+    // 未完全映射或只覆盖区间一部分时拒绝
+    assert!(!space.unmap(VPN::new(0x300)..VPN::new(0x301)));
+    assert!(!space.unmap(VPN::new(0x100)..VPN::new(0x103)));
+    assert!(!space.unmap(VPN::new(0x201)..VPN::new(0x203)));
+    assert_eq!(space.mapped_pages(), 5);
+
+    assert!(space.unmap(VPN::new(0x200)..VPN::new(0x203)));
+    assert_eq!(space.areas, vec![VPN::new(0x100)..VPN::new(0x102)]);
+    for vpn in 0x200..0x203 {
+        assert!(matches!(
+            space.translate_checked::<u8>(vaddr(vpn), VmFlags::build_from_str("R")),
+            Err(TranslateError::Unmapped)
+        ));
+    }
+    let ptr = space
+        .translate::<u8>(vaddr(0x100), VmFlags::build_from_str("R"))
+        .unwrap();
+    assert_eq!(unsafe { *ptr.as_ptr() }, 0x11);
+
+    // 撤销后可以重新映射同一区间
+    assert!(!space.unmap(VPN::new(0x200)..VPN::new(0x203)));
+    space.map(VPN::new(0x200)..VPN::new(0x201), &[0x33], 0, VmFlags::build_from_str("VRU"));
+    let ptr = space
+        .translate::<u8>(vaddr(0x200), VmFlags::build_from_str("R"))
+        .unwrap();
+    assert_eq!(unsafe { *ptr.as_ptr() }, 0x33);
+}
+
+#[test]
+fn test_cow_clone() {
+    // 测试 cow_clone 共享物理页，写缺页后双方内容分离
+    let mut parent = AddressSpace::<Sv39, HostManager>::new();
+    parent.map(VPN::new(0x100)..VPN::new(0x102), b"cow page", 0, VmFlags::build_from_str("VRWU"));
+    parent.map(VPN::new(0x200)..VPN::new(0x201), b"text", 0, VmFlags::build_from_str("VRXU"));
+    let mut child = AddressSpace::<Sv39, HostManager>::new();
+    parent.cow_clone(&mut child);
+    assert_eq!(child.areas, parent.areas);
+
+    let read = |space: &AddressSpace<Sv39, HostManager>, vpn: usize| {
+        space
+            .translate::<u8>(vaddr(vpn), VmFlags::build_from_str("R"))
+            .unwrap()
+    };
+    let bytes = |ptr: NonNull<u8>| unsafe { std::slice::from_raw_parts(ptr.as_ptr(), 8).to_vec() };
+    // 克隆后读到同一物理页，双方都失去写权限
+    assert_eq!(read(&child, 0x100), read(&parent, 0x100));
+    assert_eq!(bytes(read(&child, 0x100)), b"cow page");
+    for space in [&parent, &child] {
+        match space.translate_checked::<u8>(vaddr(0x101), VmFlags::build_from_str("W")) {
+            Err(TranslateError::PermissionDenied(flags)) => {
+                assert_ne!(flags.val() & HostManager::COW_FLAG, 0)
+            }
+            other => panic!("expected PermissionDenied, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    // 模拟子进程写缺页：分出私有副本并恢复写权限
+    assert_eq!(child.handle_cow_fault(vaddr(0x100)), FaultOutcome::Mapped);
+    let copy = child
+        .translate::<u8>(vaddr(0x100), VmFlags::build_from_str("RW"))
+        .unwrap();
+    assert_ne!(copy, read(&parent, 0x100));
+    unsafe { *copy.as_ptr() = b'C' };
+    assert_eq!(bytes(copy), b"Cow page");
+    assert_eq!(bytes(read(&parent, 0x100)), b"cow page");
+    // 未发生缺页的页仍然共享
+    assert_eq!(read(&child, 0x101), read(&parent, 0x101));
+
+    // 只读页直接共享，不带写时复制标记
+    assert_eq!(read(&child, 0x200), read(&parent, 0x200));
+    assert_eq!(child.handle_cow_fault(vaddr(0x200)), FaultOutcome::Signal);
+    assert_eq!(child.handle_cow_fault(vaddr(0x300)), FaultOutcome::Signal);
+}
+
+#[test]
+fn test_cow_release_after_fault() {
+    // 区间中非首页发生写缺页后回收双方：共享页、原页与副本都恰好释放到零，没有重复释放
+    let mut parent = AddressSpace::<Sv39, CountingManager>::new();
+    parent.map(VPN::new(0x100)..VPN::new(0x103), b"cow", 0, VmFlags::build_from_str("VRWU"));
+    let ppn = |space: &AddressSpace<Sv39, CountingManager>, vpn: usize| {
+        space.query(vaddr(vpn)).unwrap().0.val()
+    };
+    let originals: Vec<usize> = (0x100..0x103).map(|vpn| ppn(&parent, vpn)).collect();
+    let mut child = AddressSpace::<Sv39, CountingManager>::new();
+    parent.cow_clone(&mut child);
+    assert!(originals.iter().all(|&page| refs(page) == 2));
+
+    assert_eq!(child.handle_cow_fault(vaddr(0x101)), FaultOutcome::Mapped);
+    let copy = ppn(&child, 0x101);
+    assert_ne!(copy, originals[1]);
+    assert_eq!((refs(originals[1]), refs(copy)), (1, 1));
+
+    // 子进程整体回收，父进程撤销区间后回收
+    child.free_allocated_pages_and_root(None);
+    assert_eq!(refs(copy), 0);
+    assert!(originals.iter().all(|&page| refs(page) == 1));
+    assert!(parent.unmap(VPN::new(0x100)..VPN::new(0x103)));
+    assert!(originals.iter().all(|&page| refs(page) == 0));
+    parent.free_allocated_pages_and_root(None);
+}
+
+#[test]
+fn test_translate_range() {
+    // 测试 translate_range 按页边界切分跨 3 页的缓冲区，遇到空洞返回 None
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    let data: Vec<u8> = (0..3 * PAGE_SIZE).map(|i| i as u8).collect();
+    space.map(VPN::new(0x100)..VPN::new(0x103), &data, 0, VmFlags::build_from_str("VRWU"));
+    space.map(VPN::new(0x200)..VPN::new(0x201), &[], 0, VmFlags::build_from_str("VRU"));
+    space.map(VPN::new(0x202)..VPN::new(0x203), &[], 0, VmFlags::build_from_str("VRU"));
+    let flags = VmFlags::build_from_str("R");
+
+    // 从第一页中间开始，跨越 3 页
+    let start = VAddr::<Sv39>::new((0x100 << 12) + 0x100);
+    let len = 3 * PAGE_SIZE - 0x100;
+    let segments = space.translate_range(start, len, flags).unwrap();
+    let lens: Vec<usize> = segments.iter().map(|&(_, chunk)| chunk).collect();
+    assert_eq!(lens, [PAGE_SIZE - 0x100, PAGE_SIZE, PAGE_SIZE]);
+    let mut out = Vec::new();
+    for (ptr, chunk) in segments {
+        out.extend_from_slice(unsafe { std::slice::from_raw_parts(ptr.as_ptr(), chunk) });
+    }
+    assert_eq!(out, data[0x100..]);
+
+    // 恰好结束在页边界时不会多出空段，也不会查询下一页
+    let segments = space.translate_range(vaddr(0x101), PAGE_SIZE, flags).unwrap();
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].1, PAGE_SIZE);
+    assert_eq!(unsafe { *segments[0].0.as_ptr() }, data[PAGE_SIZE]);
+
+    // 零长度
+    assert_eq!(space.translate_range(vaddr(0x300), 0, flags).unwrap().len(), 0);
+
+    // 中间页未映射或缺少权限
+    assert!(space.translate_range(vaddr(0x200), 3 * PAGE_SIZE, flags).is_none());
+    assert!(space.translate_range(vaddr(0x200), PAGE_SIZE, VmFlags::build_from_str("W")).is_none());
+    assert_eq!(space.translate_range(vaddr(0x200), PAGE_SIZE, flags).unwrap().len(), 1);
+}
+
+#[test]
+fn test_set_flags() {
+    // 测试 set_flags 把只读页改为可写，物理页不变
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    space.map(VPN::new(0x100)..VPN::new(0x102), b"data", 0, VmFlags::build_from_str("VRU"));
+    let before = space
+        .translate::<u8>(vaddr(0x100), VmFlags::build_from_str("R"))
+        .unwrap();
+    assert!(space.translate::<u8>(vaddr(0x100), VmFlags::build_from_str("W")).is_none());
+
+    // VALID 位即使未给出也会保留
+    assert!(space.set_flags(VPN::new(0x100)..VPN::new(0x102), VmFlags::build_from_str("RWU")));
+    let after = space
+        .translate::<u8>(vaddr(0x100), VmFlags::build_from_str("RW"))
+        .unwrap();
+    assert_eq!(after, before);
+    assert!(space.translate::<u8>(vaddr(0x101), VmFlags::build_from_str("W")).is_some());
+
+    // 区间内有未映射页时不做任何修改
+    assert!(!space.set_flags(VPN::new(0x101)..VPN::new(0x103), VmFlags::build_from_str("VRU")));
+    assert!(space.translate::<u8>(vaddr(0x101), VmFlags::build_from_str("W")).is_some());
+}
+
+#[test]
+fn test_query() {
+    // 测试 query 返回映射的物理页号、标志与页内偏移，不检查权限
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    let flags = VmFlags::<Sv39>::build_from_str("VRU");
+    space.map_extern(VPN::new(0x100)..VPN::new(0x102), PPN::new(0x80000), flags);
+
+    let (ppn, pte_flags, offset) = space
+        .query(VAddr::new((0x101 << 12) + 0x234))
+        .unwrap();
+    assert_eq!(ppn.val(), 0x80001);
+    assert_eq!(pte_flags.val(), flags.val());
+    assert_eq!(offset, 0x234);
+    // translate 要求写权限时失败，query 仍然返回映射
+    assert!(space.translate::<u8>(vaddr(0x100), VmFlags::build_from_str("W")).is_none());
+    assert_eq!(space.query(vaddr(0x100)).unwrap().0.val(), 0x80000);
+
+    assert!(space.query(vaddr(0x102)).is_none());
+    assert!(space.query(vaddr(0x300)).is_none());
+}
+
+#[test]
+fn test_map_lazy() {
+    // 测试按需分配区间在缺页前没有页表项，缺页后逐页分配清零的物理页
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    space.map_lazy(VPN::new(0x100)..VPN::new(0x104), VmFlags::build_from_str("VRWU"));
+    assert_eq!(space.mapped_pages(), 4);
+    for vpn in 0x100..0x104 {
+        assert!(space.query(vaddr(vpn)).is_none());
+    }
+
+    assert!(space.handle_lazy_fault(VAddr::new((0x103 << 12) + 0x10)));
+    let ptr = space
+        .translate::<u64>(vaddr(0x103), VmFlags::build_from_str("RW"))
+        .unwrap();
+    assert_eq!(unsafe { *ptr.as_ptr() }, 0);
+    unsafe { *ptr.as_ptr() = 42 };
+    // 其他页仍未分配，已分配的页不会被重复处理
+    assert!(space.query(vaddr(0x102)).is_none());
+    assert!(!space.handle_lazy_fault(vaddr(0x103)));
+    assert!(!space.handle_lazy_fault(vaddr(0x200)));
+
+    // handle_fault 先补齐按需分配的页
+    assert_eq!(space.handle_fault(vaddr(0x100), FaultKind::Store), FaultOutcome::Mapped);
+    assert!(space.translate::<u8>(vaddr(0x100), VmFlags::build_from_str("W")).is_some());
+
+    // 克隆时只拷贝已分配的页
+    let mut child = AddressSpace::<Sv39, HostManager>::new();
+    space.cloneself(&mut child);
+    let copy = child
+        .translate::<u64>(vaddr(0x103), VmFlags::build_from_str("R"))
+        .unwrap();
+    assert_ne!(copy, ptr);
+    assert_eq!(unsafe { *copy.as_ptr() }, 42);
+    assert!(child.query(vaddr(0x101)).is_none());
+    assert!(child.handle_lazy_fault(vaddr(0x101)));
+
+    // 未分配的页不妨碍撤销整个区间
+    assert!(space.unmap(VPN::new(0x100)..VPN::new(0x104)));
+    assert!(space.query(vaddr(0x103)).is_none());
+    assert!(!space.handle_lazy_fault(vaddr(0x101)));
+}
+
+#[test]
+fn test_populate() {
+    // 测试 populate 为内核访问补齐按需分配的页与写时复制页
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    space.set_fault_handler::<CowHandler>();
+    space.map_lazy(VPN::new(0x100)..VPN::new(0x102), VmFlags::build_from_str("VRWU"));
+    space.map(VPN::new(0x102)..VPN::new(0x103), b"shared", 0, VmFlags::build_from_str("VRU"));
+    let flags = VmFlags::build_from_str("W");
+    let start = VAddr::<Sv39>::new((0x100 << 12) + 0x10);
+    let len = 3 * PAGE_SIZE - 0x20;
+    assert!(space.translate_range(start, len, flags).is_none());
+
+    assert!(space.populate(start, len, FaultKind::Store));
+    let segments = space.translate_range(start, len, flags).unwrap();
+    assert_eq!(segments.len(), 3);
+    assert_eq!(unsafe { *segments[0].0.as_ptr() }, 0);
+    assert_eq!(unsafe { *segments[2].0.as_ptr() }, b's');
+
+    // 已具备权限的页不会再次处理，读访问不会分出副本
+    assert!(space.populate(start, len, FaultKind::Store));
+    assert_eq!(space.translate_range(start, len, flags).unwrap(), segments);
+    assert!(space.populate(vaddr(0x100), 0, FaultKind::Load));
+
+    // 区间外的页无法补齐，越界回绕同样失败
+    assert!(!space.populate(vaddr(0x102), 2 * PAGE_SIZE, FaultKind::Load));
+    assert!(!space.populate(vaddr(0x100), usize::MAX, FaultKind::Load));
+}
+
+#[test]
+fn test_resize_heap() {
+    // 测试堆按页扩大（新页清零）、收缩，以及与已有区间冲突时失败
+    let mut space = AddressSpace::<Sv39, HostManager>::new();
+    let flags = VmFlags::build_from_str("VRWU");
+    space.map(VPN::new(0xff)..VPN::new(0x101), &[1u8; 16], 0, flags);
+
+    assert!(space.resize_heap(VPN::new(0x101), VPN::new(0x104), flags));
+    assert_eq!(space.mapped_pages(), 5);
+    for vpn in 0x101..0x104 {
+        let ptr = space
+            .translate::<u8>(vaddr(vpn), VmFlags::build_from_str("RW"))
+            .unwrap();
+        let page = unsafe { core::slice::from_raw_parts(ptr.as_ptr(), 1 << 12) };
+        assert!(page.iter().all(|&b| b == 0));
+    }
+    let ptr = space
+        .translate::<u64>(vaddr(0x102), VmFlags::build_from_str("W"))
+        .unwrap();
+    unsafe { *ptr.as_ptr() = 7 };
+
+    // 收缩释放高端的页，保留的页内容不变
+    assert!(space.resize_heap(VPN::new(0x104), VPN::new(0x103), flags));
+    assert!(space.query(vaddr(0x103)).is_none());
+    assert_eq!(unsafe { *ptr.as_ptr() }, 7);
+    assert_eq!(space.mapped_pages(), 4);
+
+    // 不能收缩到不是堆页的区间，也不能扩大到已有区间上
+    assert!(!space.resize_heap(VPN::new(0x103), VPN::new(0x100), flags));
+    assert_eq!(space.mapped_pages(), 4);
+    space.map(VPN::new(0x105)..VPN::new(0x106), &[], 0, flags);
+    assert!(!space.resize_heap(VPN::new(0x103), VPN::new(0x106), flags));
+    assert!(space.query(vaddr(0x103)).is_none());
+    assert!(space.resize_heap(VPN::new(0x103), VPN::new(0x105), flags));
+}
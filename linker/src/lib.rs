@@ -12,6 +12,9 @@ pub struct KernelLayout {
     text: usize,
     rodata: usize,
     data: usize,
+    tdata: usize,
+    tdata_end: usize,
+    tbss_end: usize,
     sbss: usize,
     ebss: usize,
     boot: usize,
@@ -24,6 +27,9 @@ impl KernelLayout {
         text: usize::MAX,
         rodata: usize::MAX,
         data: usize::MAX,
+        tdata: usize::MAX,
+        tdata_end: usize::MAX,
+        tbss_end: usize::MAX,
         sbss: usize::MAX,
         ebss: usize::MAX,
         boot: usize::MAX,
@@ -36,6 +42,9 @@ impl KernelLayout {
             static __start: u8;
             static __rodata: u8;
             static __data: u8;
+            static __tdata_start: u8;
+            static __tdata_end: u8;
+            static __tbss_end: u8;
             static __sbss: u8;
             static __ebss: u8;
             static __boot: u8;
@@ -47,6 +56,9 @@ impl KernelLayout {
                 text: &__start as *const u8 as usize,
                 rodata: &__rodata as *const u8 as usize,
                 data: &__data as *const u8 as usize,
+                tdata: &__tdata_start as *const u8 as usize,
+                tdata_end: &__tdata_end as *const u8 as usize,
+                tbss_end: &__tbss_end as *const u8 as usize,
                 sbss: &__sbss as *const u8 as usize,
                 ebss: &__ebss as *const u8 as usize,
                 boot: &__boot as *const u8 as usize,
@@ -70,6 +82,32 @@ impl KernelLayout {
         self.end.saturating_sub(self.text)
     }
 
+    /// 判断地址是否落在内核映像 [__start, __end) 内
+    pub fn contains(&self, addr: usize) -> bool {
+        (self.text..self.end).contains(&addr)
+    }
+
+    /// 返回地址所在的内核分区，不在任何分区内时返回 `None`
+    /// 
+    /// 用于陷入或 panic 时把 `sepc`/`stval` 归类为具体分区，便于诊断。
+    pub fn region_of(&self, addr: usize) -> Option<KernelRegionTitle> {
+        self.iter()
+            .find(|region| region.range.contains(&addr))
+            .map(|region| region.title)
+    }
+
+    /// 返回线程局部存储模板 [__tdata_start, __tdata_end)，即 `.tdata` 的初始映像
+    /// 
+    /// 每个 hart 初始化自己的 TLS 块时，先拷贝该区间，再将剩余部分清零至 [`tls_size`](Self::tls_size)。
+    pub fn tls_template(&self) -> core::ops::Range<usize> {
+        self.tdata..self.tdata_end
+    }
+
+    /// 返回每个 TLS 块的总大小（`.tdata` 与 `.tbss` 之和）
+    pub fn tls_size(&self) -> usize {
+        self.tbss_end.saturating_sub(self.tdata)
+    }
+
     /// 将地址区间 [__sbss, __ebss) 清零
     /// 
     /// 使用 volatile 写入以确保对其他处理器核可见。
@@ -85,7 +123,7 @@ impl KernelLayout {
         }
     }
 
-    /// 返回按固定顺序遍历的内核分区迭代器（Text → Rodata → Data → Boot）
+    /// 返回按固定顺序遍历的内核分区迭代器（Text → Rodata → Data → Tls → Bss → Boot）
     pub fn iter(&self) -> KernelRegionIterator<'_> {
         KernelRegionIterator {
             layout: self,
@@ -123,10 +161,24 @@ impl<'a> Iterator for KernelRegionIterator<'a> {
                 self.index += 1;
                 Some(KernelRegion {
                     title: KernelRegionTitle::Data,
-                    range: self.layout.data..self.layout.boot,
+                    range: self.layout.data..self.layout.tdata,
                 })
             }
             3 => {
+                self.index += 1;
+                Some(KernelRegion {
+                    title: KernelRegionTitle::Tls,
+                    range: self.layout.tdata..self.layout.tbss_end,
+                })
+            }
+            4 => {
+                self.index += 1;
+                Some(KernelRegion {
+                    title: KernelRegionTitle::Bss,
+                    range: self.layout.sbss..self.layout.boot,
+                })
+            }
+            5 => {
                 self.index += 1;
                 Some(KernelRegion {
                     title: KernelRegionTitle::Boot,
@@ -144,6 +196,8 @@ pub enum KernelRegionTitle {
     Text,
     Rodata,
     Data,
+    Tls,
+    Bss,
     Boot,
 }
 
@@ -159,6 +213,8 @@ impl core::fmt::Display for KernelRegion {
             KernelRegionTitle::Text => ".text",
             KernelRegionTitle::Rodata => ".rodata",
             KernelRegionTitle::Data => ".data",
+            KernelRegionTitle::Tls => ".tdata/.tbss",
+            KernelRegionTitle::Bss => ".bss",
             KernelRegionTitle::Boot => ".boot",
         };
         write!(
@@ -169,6 +225,12 @@ impl core::fmt::Display for KernelRegion {
     }
 }
 
+/// [`AppMeta::validate`] 接受的最大应用数量
+pub const MAX_APPS: u64 = 1024;
+
+/// 拷贝到固定槽位时每个应用占用的空间，映像之后的部分清零
+const APP_SLOT_SIZE: u64 = 0x20_0000;
+
 /// 应用程序元数据头
 #[repr(C)]
 pub struct AppMeta {
@@ -188,12 +250,40 @@ impl AppMeta {
     }
 
     /// 返回应用程序迭代器
+    /// 
+    /// 不检查元数据，`apps` 可能损坏时应使用 [`try_iter`](Self::try_iter)。
     pub fn iter(&'static self) -> AppIterator {
         AppIterator {
             meta: self,
             index: 0,
         }
     }
+
+    /// 校验通过时返回应用程序迭代器，否则返回 `None`
+    pub fn try_iter(&'static self) -> Option<AppIterator> {
+        self.validate().then(|| self.iter())
+    }
+
+    /// 检查元数据是否可以安全遍历
+    /// 
+    /// 要求 `count` 不超过 [`MAX_APPS`]，紧跟 `first` 的 `count + 1` 个地址单调不减；
+    /// 需要拷贝到固定槽位（`base != 0`）时，每个应用还必须放得进一个槽位。
+    /// 地址数组的长度由 `count` 决定，因此先检查 `count` 再读取数组。
+    pub fn validate(&self) -> bool {
+        if self.count > MAX_APPS {
+            return false;
+        }
+        if self.count == 0 {
+            return true;
+        }
+        let addrs = unsafe {
+            core::slice::from_raw_parts(&self.first as *const u64, self.count as usize + 1)
+        };
+        addrs.windows(2).all(|pair| {
+            let size = pair[1].wrapping_sub(pair[0]);
+            pair[0] <= pair[1] && (self.base == 0 || size <= APP_SLOT_SIZE)
+        })
+    }
 }
 
 /// 应用程序迭代器
@@ -241,7 +331,7 @@ impl Iterator for AppIterator {
                 
                 // 清零剩余空间
                 let zero_start = dst + size;
-                let zero_end = dst + 0x20_0000;
+                let zero_end = dst + APP_SLOT_SIZE as usize;
                 let mut zero_ptr = zero_start as *mut u8;
                 let zero_end_ptr = zero_end as *mut u8;
                 while zero_ptr < zero_end_ptr {
@@ -265,28 +355,81 @@ impl Iterator for AppIterator {
 
 /// 定义内核启动入口 `_start`
 /// 
+/// `_start` 只设置 `sp` 和 `tp` 后跳转，不改动 SBI 传入的 `a0`（hartid）和 `a1`（设备树地址），
+/// 因此入口函数可以声明为 `extern "C" fn rust_main(hartid: usize, dtb: usize) -> !` 接收它们；
+/// 按 C 调用约定，无参数的 `extern "C" fn rust_main() -> !` 只是忽略这两个寄存器，同样可以链接。
+/// `tp` 被设为 hartid，内核约定它在内核态始终保存当前 hart 编号。
+/// 
 /// # 参数
 /// - `$entry`: 入口函数名（当前实现固定跳转到 `rust_main`）
-/// - `stack`: 启动栈大小表达式
+/// - `stack`: 每个 hart 的启动栈大小表达式
+/// - `harts`: 可选，启动栈的数量。省略时只有一个栈，所有进入 `_start` 的 hart 共用 `__end` 下方的栈；
+///   指定后在 `.boot.stack` 中为每个 hart 分配一个栈，按 `a0` 中的 hartid（SBI 约定）
+///   取 `sp = STACK + (hartid + 1) * stack`，hartid 不小于 `harts` 的 hart 停在 `wfi` 循环中
 /// 
 /// # 示例
 /// ```no_run
 /// linker::boot0!(rust_main; stack = 4 * 4096);
 /// ```
+/// 
+/// ```no_run
+/// linker::boot0!(rust_main; stack = 4 * 4096, harts = 4);
+/// ```
 #[macro_export]
 macro_rules! boot0 {
     ($entry:ident; stack = $stack:expr) => {
         #[link_section = ".boot.stack"]
         static mut STACK: [u8; $stack] = [0; $stack];
 
+        #[no_mangle]
+        #[link_section = ".text.entry"]
+        pub unsafe extern "C" fn _start() -> ! {
+            core::arch::asm!($crate::__boot0_entry!(), options(noreturn));
+        }
+    };
+    ($entry:ident; stack = $stack:expr, harts = $harts:expr) => {
+        #[link_section = ".boot.stack"]
+        static mut STACK: [u8; $stack * $harts] = [0; $stack * $harts];
+
         #[no_mangle]
         #[link_section = ".text.entry"]
         pub unsafe extern "C" fn _start() -> ! {
             core::arch::asm!(
-                "la sp, __end",
-                "j rust_main",
+                $crate::__boot0_entry!(harts),
+                harts = const $harts,
+                stack = const $stack,
+                stack_base = sym STACK,
                 options(noreturn)
             );
         }
     };
 }
+
+/// [`boot0!`] 生成的 `_start` 汇编模板，单独导出以便测试其不改写 `a0`/`a1` 并设置 `tp`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __boot0_entry {
+    () => {
+        concat!(
+            "mv tp, a0\n",
+            "la sp, __end\n",
+            "j rust_main",
+        )
+    };
+    (harts) => {
+        concat!(
+            "mv   tp, a0\n",
+            "li   t0, {harts}\n",
+            "bgeu a0, t0, 2f\n",
+            "addi t1, a0, 1\n",
+            "li   t0, {stack}\n",
+            "mul  t1, t1, t0\n",
+            "la   sp, {stack_base}\n",
+            "add  sp, sp, t1\n",
+            "j rust_main\n",
+            "2:\n",
+            "wfi\n",
+            "j 2b",
+        )
+    };
+}
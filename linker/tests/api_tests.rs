@@ -69,9 +69,9 @@ fn test_kernel_layout_iter() {
     // 测试 KernelLayout 的迭代器
     let layout = KernelLayout::INIT;
     
-    // 应该能迭代出 4 个区域
+    // 应该能迭代出 6 个区域
     let regions: Vec<_> = layout.iter().collect();
-    assert_eq!(regions.len(), 4);
+    assert_eq!(regions.len(), 6);
     
     // 验证区域顺序
     let mut iter2 = layout.iter();
@@ -85,7 +85,13 @@ fn test_kernel_layout_iter() {
     assert!(matches!(region3.title, KernelRegionTitle::Data));
     
     let region4 = iter2.next().unwrap();
-    assert!(matches!(region4.title, KernelRegionTitle::Boot));
+    assert!(matches!(region4.title, KernelRegionTitle::Tls));
+    
+    let region5 = iter2.next().unwrap();
+    assert!(matches!(region5.title, KernelRegionTitle::Bss));
+    
+    let region6 = iter2.next().unwrap();
+    assert!(matches!(region6.title, KernelRegionTitle::Boot));
     
     assert!(iter2.next().is_none());
 }
@@ -109,6 +115,14 @@ fn test_kernel_region_display() {
     let display_str = format!("{}", data_region);
     assert!(display_str.contains(".data"));
     
+    let tls_region = iter.next().unwrap();
+    let display_str = format!("{}", tls_region);
+    assert!(display_str.contains(".tdata"));
+    
+    let bss_region = iter.next().unwrap();
+    let display_str = format!("{}", bss_region);
+    assert!(display_str.contains(".bss"));
+    
     let boot_region = iter.next().unwrap();
     let display_str = format!("{}", boot_region);
     assert!(display_str.contains(".boot"));
@@ -160,3 +174,97 @@ fn test_app_iterator_structure() {
     // AppIterator 包含一个指针和一个 u64，大小取决于平台
     assert!(core::mem::size_of::<AppIterator>() > 0);
 }
+
+#[test]
+fn test_boot0_preserves_boot_args() {
+    // 跳转到 rust_main 之前不能有指令写 a0（hartid）或 a1（设备树地址）
+    fn assert_preserved(template: &str) {
+        let before_jump = template.split("j rust_main").next().unwrap();
+        for line in before_jump.lines().map(str::trim) {
+            let mut parts = line.splitn(2, char::is_whitespace);
+            let mnemonic = parts.next().unwrap();
+            let Some(operands) = parts.next() else { continue };
+            // 分支指令只读取操作数
+            if mnemonic.starts_with('b') {
+                continue;
+            }
+            let rd = operands.split(',').next().unwrap().trim();
+            assert!(rd != "a0" && rd != "a1", "`{line}` clobbers {rd}");
+        }
+        assert!(template.contains("j rust_main"));
+    }
+    assert_preserved(linker::__boot0_entry!());
+    assert_preserved(linker::__boot0_entry!(harts));
+}
+
+#[test]
+fn test_boot0_sets_tp_to_hartid() {
+    // 内核约定 tp 保存 hart 编号，_start 须在跳转前把 a0 复制到 tp
+    fn assert_sets_tp(template: &str) {
+        let before_jump = template.split("j rust_main").next().unwrap();
+        let sets_tp = before_jump.lines().any(|line| {
+            let mut parts = line.split_whitespace();
+            parts.next() == Some("mv") && parts.collect::<String>() == "tp,a0"
+        });
+        assert!(sets_tp, "`tp` is not set to the hartid before `j rust_main`");
+    }
+    assert_sets_tp(linker::__boot0_entry!());
+    assert_sets_tp(linker::__boot0_entry!(harts));
+}
+
+/// 手工构造的 `apps` 数据：元数据头之后紧跟其余地址
+#[repr(C)]
+struct AppBlob<const N: usize> {
+    meta: AppMeta,
+    rest: [u64; N],
+}
+
+fn leak_blob<const N: usize>(
+    base: u64,
+    count: u64,
+    addrs: [u64; N],
+    first: u64,
+) -> &'static AppMeta {
+    let blob = Box::leak(Box::new(AppBlob {
+        meta: AppMeta {
+            base,
+            step: 0,
+            count,
+            first,
+        },
+        rest: addrs,
+    }));
+    &blob.meta
+}
+
+#[test]
+fn test_app_meta_validate() {
+    static IMAGES: [u8; 16] = *b"app0app1app2app3";
+    let start = IMAGES.as_ptr() as u64;
+
+    // 合法：三个应用，地址单调不减（允许空应用）
+    let meta = leak_blob(0, 3, [start + 4, start + 4, start + 12], start);
+    assert!(meta.validate());
+    let apps: Vec<_> = meta.try_iter().unwrap().collect();
+    assert_eq!(apps, [&b"app0"[..], &b""[..], &b"app1app2"[..]]);
+
+    // count 为 0 时不读取地址数组
+    let meta = leak_blob(0, 0, [], 0);
+    assert!(meta.validate());
+    assert_eq!(meta.try_iter().unwrap().count(), 0);
+
+    // 地址倒序
+    let meta = leak_blob(0, 2, [start + 8, start + 4], start);
+    assert!(!meta.validate());
+    assert!(meta.try_iter().is_none());
+
+    // count 超出上限
+    let meta = leak_blob(0, MAX_APPS + 1, [start + 4], start);
+    assert!(!meta.validate());
+
+    // 需要拷贝到槽位时，应用大小不能超过一个槽位
+    let meta = leak_blob(0x8040_0000, 1, [start + 0x20_0001], start);
+    assert!(!meta.validate());
+    let meta = leak_blob(0x8040_0000, 1, [start + 16], start);
+    assert!(meta.validate());
+}
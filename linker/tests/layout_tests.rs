@@ -0,0 +1,86 @@
+//! `KernelLayout` 符号定位测试：TLS 区间与地址归类
+//!
+//! 在测试二进制中定义桩链接符号，使 `KernelLayout::locate` 能在宿主机上链接。
+//! 所有符号都定义在同一段数据中，按真实链接脚本的顺序依次排列。
+
+use linker::{KernelLayout, KernelRegionTitle};
+
+core::arch::global_asm!(
+    ".pushsection .data.linker_stub, \"aw\"",
+    ".balign 4096",
+    ".globl __start, __rodata, __data, __tdata_start, __tdata_end, __tbss_end",
+    ".globl __sbss, __ebss, __boot, __end",
+    "__start:",
+    ".skip 0x1000",
+    "__rodata:",
+    ".skip 0x1000",
+    "__data:",
+    ".skip 0x1000",
+    "__tdata_start:",
+    ".skip 0x40",
+    "__tdata_end:",
+    ".skip 0x20",
+    "__tbss_end:",
+    ".skip 0xfa0",
+    "__sbss:",
+    ".skip 0x100",
+    "__ebss:",
+    ".skip 0xf00",
+    "__boot:",
+    ".skip 0x1000",
+    "__end:",
+    ".popsection",
+);
+
+#[test]
+fn test_tls_template_range() {
+    let layout = KernelLayout::locate();
+    let template = layout.tls_template();
+    assert_eq!(template.end - template.start, 0x40);
+    assert_eq!(layout.tls_size(), 0x60);
+
+    let regions: Vec<_> = layout.iter().collect();
+    let data = &regions[2];
+    let tls = &regions[3];
+    let bss = &regions[4];
+    assert_eq!(data.title, KernelRegionTitle::Data);
+    assert_eq!(data.range.end, template.start);
+    assert_eq!(tls.title, KernelRegionTitle::Tls);
+    assert_eq!(tls.range, template.start..template.start + 0x60);
+    assert_eq!(bss.title, KernelRegionTitle::Bss);
+    assert!(bss.range.start >= tls.range.end);
+
+    // 各区间首尾相接，覆盖整个内核映像（Tls 与 Bss 之间只有对齐填充）
+    assert_eq!(regions[0].range.start, layout.start());
+    assert_eq!(regions.last().unwrap().range.end, layout.end());
+    assert_eq!(layout.len(), 0x6000);
+}
+
+#[test]
+fn test_region_of_boundaries() {
+    use KernelRegionTitle::*;
+
+    let layout = KernelLayout::locate();
+    let start = layout.start();
+
+    assert!(layout.contains(start));
+    assert!(layout.contains(layout.end() - 1));
+    assert!(!layout.contains(layout.end()));
+    assert!(!layout.contains(start - 1));
+
+    // 区间左闭右开，边界地址归属下一个分区
+    assert_eq!(layout.region_of(start), Some(Text));
+    assert_eq!(layout.region_of(start + 0xfff), Some(Text));
+    assert_eq!(layout.region_of(start + 0x1000), Some(Rodata));
+    assert_eq!(layout.region_of(start + 0x2000), Some(Data));
+    assert_eq!(layout.region_of(start + 0x3000), Some(Tls));
+    assert_eq!(layout.region_of(start + 0x305f), Some(Tls));
+    // .tbss 之后到 .bss 之前的对齐填充不属于任何分区
+    assert_eq!(layout.region_of(start + 0x3060), None);
+    assert_eq!(layout.region_of(start + 0x4000), Some(Bss));
+    assert_eq!(layout.region_of(start + 0x4fff), Some(Bss));
+    assert_eq!(layout.region_of(start + 0x5000), Some(Boot));
+    assert_eq!(layout.region_of(layout.end() - 1), Some(Boot));
+    assert_eq!(layout.region_of(layout.end()), None);
+    assert_eq!(layout.region_of(start - 1), None);
+}
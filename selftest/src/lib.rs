@@ -0,0 +1,196 @@
+//! rcore-selftest: 启动时运行的冒烟测试
+//!
+//! 在真实硬件上无法运行 `cargo test`，内核可以在控制台和堆初始化之后调用 [`run`]，
+//! 依次检查堆分配、页表映射与上下文结构，逐项打印 PASS/FAIL。
+//! 每项检查都是独立的函数，也可以在宿主机上单独测试。
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::alloc::{alloc_zeroed, dealloc, handle_alloc_error, Layout};
+use alloc::vec::Vec;
+use core::ptr::NonNull;
+use kernel_context::LocalContext;
+use kernel_vm::page_table::{Pte, Sv39, VAddr, VmFlags, PPN, VPN};
+use kernel_vm::{AddressSpace, PageManager, TranslateError};
+use rcore_console::println;
+
+/// 一项检查：名称与检查函数，失败时返回原因
+pub type Check = (&'static str, fn() -> Result<(), &'static str>);
+
+/// [`run`] 依次执行的检查
+pub const CHECKS: &[Check] = &[
+    ("alloc", check_alloc),
+    ("page-map", check_page_map),
+    ("context", check_context),
+];
+
+/// 依次运行 [`CHECKS`] 并打印结果，返回失败的项数
+///
+/// 需要已初始化的控制台和全局堆；页表检查只构造临时页表而不切换 `satp`，
+/// 要求内核堆的虚拟地址等于物理地址。
+pub fn run() -> usize {
+    let mut failed = 0;
+    for (name, check) in CHECKS {
+        match check() {
+            Ok(()) => println!("[selftest] {:<10} PASS", name),
+            Err(reason) => {
+                failed += 1;
+                println!("[selftest] {:<10} FAIL: {}", name, reason);
+            }
+        }
+    }
+    println!(
+        "[selftest] {} passed, {} failed",
+        CHECKS.len() - failed,
+        failed
+    );
+    failed
+}
+
+/// 分配、扩容并释放 `Vec`，检查全局分配器不会破坏数据
+pub fn check_alloc() -> Result<(), &'static str> {
+    let mut v: Vec<usize> = Vec::with_capacity(256);
+    v.extend((0..256).map(|i| i * 3));
+    if v.iter().enumerate().any(|(i, &x)| x != i * 3) {
+        return Err("vec content mismatch");
+    }
+    // 扩容会搬到新的块上
+    v.extend(0..1024);
+    if v.len() != 1280 || v[255] != 255 * 3 || v[1279] != 1023 {
+        return Err("vec grow corrupted data");
+    }
+    drop(v);
+    let page = unsafe { alloc_zeroed(page_layout(1)) };
+    if page.is_null() {
+        return Err("page-aligned allocation failed");
+    }
+    let aligned = page as usize % PAGE_SIZE == 0;
+    unsafe { dealloc(page, page_layout(1)) };
+    if !aligned {
+        return Err("page allocation not aligned");
+    }
+    Ok(())
+}
+
+/// 在临时地址空间里映射一页，检查翻译结果、权限与未映射页，撤销映射后确认不再可翻译，最后释放
+pub fn check_page_map() -> Result<(), &'static str> {
+    const VPN0: usize = 0x1000;
+    const DATA: &[u8] = b"selftest";
+    let mut space = AddressSpace::<Sv39, ScratchManager>::new();
+    space.map(
+        VPN::new(VPN0)..VPN::new(VPN0 + 1),
+        DATA,
+        0,
+        VmFlags::build_from_str("VRW"),
+    );
+    let result = (|| {
+        let addr = VAddr::<Sv39>::new(VPN0 << 12);
+        let ptr = space
+            .translate_checked::<u8>(addr, VmFlags::build_from_str("R"))
+            .map_err(|_| "mapped page does not translate")?;
+        if unsafe { core::slice::from_raw_parts(ptr.as_ptr(), DATA.len()) } != DATA {
+            return Err("mapped page content mismatch");
+        }
+        match space.translate_checked::<u8>(addr, VmFlags::build_from_str("X")) {
+            Err(TranslateError::PermissionDenied(_)) => {}
+            _ => return Err("missing permission not rejected"),
+        }
+        let next = VAddr::<Sv39>::new((VPN0 + 1) << 12);
+        match space.translate_checked::<u8>(next, VmFlags::build_from_str("R")) {
+            Err(TranslateError::Unmapped) => {}
+            _ => return Err("unmapped page translates"),
+        }
+        if !space.unmap(VPN::new(VPN0)..VPN::new(VPN0 + 1)) {
+            return Err("unmap failed");
+        }
+        match space.translate_checked::<u8>(addr, VmFlags::build_from_str("R")) {
+            Err(TranslateError::Unmapped) => {}
+            _ => return Err("page still translates after unmap"),
+        }
+        Ok(())
+    })();
+    space.free_allocated_pages_and_root(None);
+    result
+}
+
+/// 修改寄存器后做快照往返，检查 `LocalContext` 的访问器与序列化
+pub fn check_context() -> Result<(), &'static str> {
+    let mut ctx = LocalContext::user(0x1000);
+    *ctx.a_mut(0) = 42;
+    *ctx.sp_mut() = 0x8000;
+    ctx.move_next();
+    if ctx.pc() != 0x1004 || ctx.x(10) != 42 || ctx.x(2) != 0x8000 {
+        return Err("register accessors disagree");
+    }
+    let restored = LocalContext::from_bytes(&ctx.to_bytes()).ok_or("snapshot rejected")?;
+    if restored.x != ctx.x
+        || restored.pc() != ctx.pc()
+        || restored.supervisor != ctx.supervisor
+        || restored.interrupt != ctx.interrupt
+    {
+        return Err("snapshot round-trip mismatch");
+    }
+    Ok(())
+}
+
+const PAGE_SIZE: usize = 4096;
+
+fn page_layout(count: usize) -> Layout {
+    Layout::from_size_align(count * PAGE_SIZE, PAGE_SIZE).unwrap()
+}
+
+fn alloc_pages(count: usize) -> NonNull<u8> {
+    let layout = page_layout(count);
+    NonNull::new(unsafe { alloc_zeroed(layout) }).unwrap_or_else(|| handle_alloc_error(layout))
+}
+
+/// 用全局堆充当物理内存的页管理器：物理页号即堆地址右移 12 位
+///
+/// 中间级页表页与 `free_allocated_pages_and_root` 的行为一致，不回收。
+struct ScratchManager {
+    root: NonNull<Pte<Sv39>>,
+}
+
+impl PageManager<Sv39> for ScratchManager {
+    fn new_root() -> Self {
+        Self {
+            root: alloc_pages(1).cast(),
+        }
+    }
+
+    fn root_ptr(&self) -> NonNull<Pte<Sv39>> {
+        self.root
+    }
+
+    fn root_ppn(&self) -> PPN<Sv39> {
+        self.v_to_p(self.root)
+    }
+
+    fn p_to_v<T>(&self, ppn: PPN<Sv39>) -> NonNull<T> {
+        NonNull::new((ppn.val() << 12) as *mut T).unwrap()
+    }
+
+    fn v_to_p<T>(&self, ptr: NonNull<T>) -> PPN<Sv39> {
+        PPN::new(ptr.as_ptr() as usize >> 12)
+    }
+
+    fn allocate(&mut self, len: usize, _flags: &mut VmFlags<Sv39>) -> NonNull<u8> {
+        alloc_pages(len)
+    }
+
+    fn deallocate(&mut self, pte: Pte<Sv39>, len: usize) -> usize {
+        let ptr = self.p_to_v::<u8>(pte.ppn());
+        unsafe { dealloc(ptr.as_ptr(), page_layout(len)) };
+        len
+    }
+
+    fn check_owned(&self, _pte: Pte<Sv39>) -> bool {
+        true
+    }
+
+    fn drop_root(&mut self) {
+        unsafe { dealloc(self.root.as_ptr().cast(), page_layout(1)) };
+    }
+}
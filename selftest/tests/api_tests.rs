@@ -0,0 +1,27 @@
+//! rcore-selftest crate 功能性验证测试
+//!
+//! 在宿主机上逐项运行启动自检，检查函数本身不依赖 RISC-V。
+
+use rcore_selftest::*;
+
+#[test]
+fn test_check_alloc() {
+    assert_eq!(check_alloc(), Ok(()));
+}
+
+#[test]
+fn test_check_page_map() {
+    assert_eq!(check_page_map(), Ok(()));
+}
+
+#[test]
+fn test_check_context() {
+    assert_eq!(check_context(), Ok(()));
+}
+
+#[test]
+fn test_checks_table() {
+    // run() 依次执行的检查覆盖三个子系统，且名称互不相同
+    let names: Vec<&str> = CHECKS.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, ["alloc", "page-map", "context"]);
+}
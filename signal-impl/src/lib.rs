@@ -3,8 +3,10 @@
 extern crate alloc;
 
 use alloc::boxed::Box;
+#[cfg(feature = "rt-signals")]
+use alloc::collections::VecDeque;
 use kernel_context::LocalContext;
-use signal::{Signal, SignalAction, SignalNo, SignalResult, MAX_SIG};
+use signal::{HandlingState, MaskHow, Signal, SignalAction, SignalNo, SignalResult};
 
 /// Bitset helper for pending/mask signal sets.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -51,13 +53,27 @@ impl SignalSet {
     }
 }
 
+/// Highest signal number with a slot in [`SignalImpl::actions`].
+///
+/// With the `rt-signals` feature this extends to `SIGRT31`, otherwise only
+/// the standard signals up to [`signal::MAX_SIG`] are tracked.
+#[cfg(feature = "rt-signals")]
+pub const SIG_LIMIT: usize = SignalNo::SIGRT31 as usize;
+#[cfg(not(feature = "rt-signals"))]
+pub const SIG_LIMIT: usize = signal::MAX_SIG;
+
 /// In-progress signal handling state.
 #[derive(Clone)]
 pub enum HandlingSignal {
     /// Process is suspended by SIGSTOP and waiting for SIGCONT.
     Frozen,
-    /// Process is running a user signal handler, with pre-handler context saved.
-    UserSignal(LocalContext),
+    /// Process is running a user signal handler, with the pre-handler context and mask saved.
+    UserSignal {
+        context: LocalContext,
+        /// Mask to restore on `sig_return`; while the handler runs, the action's
+        /// mask and the signal itself are blocked on top of it.
+        old_mask: SignalSet,
+    },
 }
 
 /// Per-process signal implementation.
@@ -65,30 +81,49 @@ pub struct SignalImpl {
     pub received: SignalSet,
     pub mask: SignalSet,
     pub handling: Option<HandlingSignal>,
-    pub actions: [Option<SignalAction>; MAX_SIG + 1],
+    pub actions: [Option<SignalAction>; SIG_LIMIT + 1],
+    /// Alternate stack for user handlers as `(base, size)`, if installed.
+    pub alt_stack: Option<(usize, usize)>,
+    /// Pending real-time signals in arrival order; unlike standard signals,
+    /// repeated deliveries of the same signal are all kept.
+    #[cfg(feature = "rt-signals")]
+    pub rt_queue: VecDeque<SignalNo>,
 }
 
 impl SignalImpl {
+    /// Signals that can never be blocked: SIGKILL and SIGSTOP.
+    const UNMASKABLE: SignalSet =
+        SignalSet((1 << SignalNo::SIGKILL as usize) | (1 << SignalNo::SIGSTOP as usize));
+
     #[inline]
     pub fn new() -> Self {
         Self {
             received: SignalSet(0),
             mask: SignalSet(0),
             handling: None,
-            actions: [None; MAX_SIG + 1],
+            actions: [None; SIG_LIMIT + 1],
+            alt_stack: None,
+            #[cfg(feature = "rt-signals")]
+            rt_queue: VecDeque::new(),
         }
     }
 
     #[inline]
     fn valid_index(signum: SignalNo) -> Option<usize> {
         let idx = signum as usize;
-        if idx == 0 || idx > MAX_SIG {
+        if idx == 0 || idx > SIG_LIMIT {
             None
         } else {
             Some(idx)
         }
     }
 
+    /// Store `mask` with the unmaskable bits cleared.
+    #[inline]
+    fn set_mask(&mut self, mask: SignalSet) {
+        self.mask = mask.difference(Self::UNMASKABLE);
+    }
+
     #[inline]
     fn kill_code(signum: SignalNo) -> i32 {
         -(signum as i32)
@@ -99,8 +134,22 @@ impl SignalImpl {
         matches!(signum, SignalNo::SIGCHLD | SignalNo::SIGURG | SignalNo::SIGCONT)
     }
 
+    /// Remove and return the first queued real-time signal not in `blocked`.
+    #[cfg(feature = "rt-signals")]
+    fn take_rt_signal(&mut self, blocked: SignalSet) -> Option<SignalNo> {
+        let pos = self
+            .rt_queue
+            .iter()
+            .position(|&signum| !blocked.contain_bit(signum as usize))?;
+        self.rt_queue.remove(pos)
+    }
+
     #[inline]
     fn take_deliverable_signal(&mut self) -> Option<SignalNo> {
+        #[cfg(feature = "rt-signals")]
+        if let Some(signum) = self.take_rt_signal(self.mask) {
+            return Some(signum);
+        }
         let bit = self.received.find_first_one(self.mask)?;
         self.received.remove_bit(bit);
         let signum = SignalNo::from(bit);
@@ -122,6 +171,44 @@ impl SignalImpl {
             SignalResult::ProcessSuspended
         }
     }
+
+    /// Dispatch an already-dequeued signal according to its action.
+    fn deliver(&mut self, signum: SignalNo, current_context: &mut LocalContext) -> SignalResult {
+        match signum {
+            SignalNo::SIGKILL => SignalResult::ProcessKilled(Self::kill_code(signum)),
+            SignalNo::SIGSTOP => {
+                self.handling = Some(HandlingSignal::Frozen);
+                SignalResult::ProcessSuspended
+            }
+            _ => {
+                let idx = signum as usize;
+                let action = self.actions[idx].unwrap_or_default();
+                if action.handler != 0 {
+                    self.handling = Some(HandlingSignal::UserSignal {
+                        context: current_context.clone(),
+                        old_mask: self.mask,
+                    });
+                    let mut mask = self.mask.union(SignalSet(action.mask));
+                    mask.add_bit(idx);
+                    self.set_mask(mask);
+                    // An alternate stack whose top overflows the address space is
+                    // unusable; stay on the normal stack instead.
+                    let alt_top = self.alt_stack.and_then(|(base, size)| base.checked_add(size));
+                    if let Some(top) = alt_top {
+                        // Keep the stack pointer 16-byte aligned as the RISC-V ABI requires.
+                        *current_context.sp_mut() = top & !0xf;
+                    }
+                    *current_context.pc_mut() = action.handler;
+                    *current_context.a_mut(0) = idx;
+                    SignalResult::Handled
+                } else if Self::should_ignore_by_default(signum) {
+                    SignalResult::Ignored
+                } else {
+                    SignalResult::ProcessKilled(Self::kill_code(signum))
+                }
+            }
+        }
+    }
 }
 
 impl Default for SignalImpl {
@@ -137,6 +224,9 @@ impl Signal for SignalImpl {
             mask: self.mask,
             handling: None,
             actions: self.actions,
+            alt_stack: self.alt_stack,
+            #[cfg(feature = "rt-signals")]
+            rt_queue: VecDeque::new(),
         })
     }
 
@@ -144,13 +234,33 @@ impl Signal for SignalImpl {
         self.received = SignalSet(0);
         self.mask = SignalSet(0);
         self.handling = None;
-        self.actions = [None; MAX_SIG + 1];
+        self.actions = [None; SIG_LIMIT + 1];
+        self.alt_stack = None;
+        #[cfg(feature = "rt-signals")]
+        self.rt_queue.clear();
     }
 
     fn add_signal(&mut self, signal: SignalNo) {
-        if let Some(idx) = Self::valid_index(signal) {
-            self.received.add_bit(idx);
+        let Some(idx) = Self::valid_index(signal) else {
+            return;
+        };
+        #[cfg(feature = "rt-signals")]
+        if idx >= SignalNo::SIGRTMIN as usize {
+            self.rt_queue.push_back(signal);
+            return;
         }
+        self.received.add_bit(idx);
+    }
+
+    fn dequeue_from_set(&mut self, set: usize) -> Option<SignalNo> {
+        #[cfg(feature = "rt-signals")]
+        if let Some(signum) = self.take_rt_signal(SignalSet(!set)) {
+            return Some(signum);
+        }
+        let bit = self.received.find_first_one(SignalSet(!set))?;
+        self.received.remove_bit(bit);
+        let signum = SignalNo::from(bit);
+        Self::valid_index(signum).map(|_| signum)
     }
 
     fn is_handling_signal(&self) -> bool {
@@ -178,10 +288,39 @@ impl Signal for SignalImpl {
 
     fn update_mask(&mut self, mask: usize) -> usize {
         let old = self.mask.0;
-        self.mask = SignalSet(mask);
+        self.set_mask(SignalSet(mask));
         old
     }
 
+    fn modify_mask(&mut self, how: MaskHow, bits: usize) -> usize {
+        let old = self.mask.0;
+        self.set_mask(SignalSet(how.apply(old, bits)));
+        old
+    }
+
+    fn mask(&self) -> usize {
+        self.mask.0
+    }
+
+    fn set_altstack(&mut self, sp: usize, size: usize) {
+        self.alt_stack = (size != 0).then_some((sp, size));
+    }
+
+    fn pending(&self) -> usize {
+        // Received signals minus the deliverable ones leaves those held back by the mask.
+        #[cfg(feature = "rt-signals")]
+        let received = self
+            .rt_queue
+            .iter()
+            .fold(self.received, |mut set, &signum| {
+                set.add_bit(signum as usize);
+                set
+            });
+        #[cfg(not(feature = "rt-signals"))]
+        let received = self.received;
+        received.difference(received.difference(self.mask)).0
+    }
+
     fn handle_signals(&mut self, current_context: &mut LocalContext) -> SignalResult {
         let sigkill_idx = SignalNo::SIGKILL as usize;
         if self.received.contain_bit(sigkill_idx) && !self.mask.contain_bit(sigkill_idx) {
@@ -191,35 +330,26 @@ impl Signal for SignalImpl {
 
         match self.handling.as_ref() {
             Some(HandlingSignal::Frozen) => return self.handle_frozen(),
-            Some(HandlingSignal::UserSignal(_)) => return SignalResult::IsHandlingSignal,
+            Some(HandlingSignal::UserSignal { .. }) => return SignalResult::IsHandlingSignal,
             None => {}
         }
 
         let Some(signum) = self.take_deliverable_signal() else {
             return SignalResult::NoSignal;
         };
+        self.deliver(signum, current_context)
+    }
 
-        match signum {
-            SignalNo::SIGKILL => SignalResult::ProcessKilled(Self::kill_code(signum)),
-            SignalNo::SIGSTOP => {
-                self.handling = Some(HandlingSignal::Frozen);
-                SignalResult::ProcessSuspended
-            }
-            _ => {
-                let idx = signum as usize;
-                let action = self.actions[idx].unwrap_or_default();
-                if action.handler != 0 {
-                    self.handling = Some(HandlingSignal::UserSignal(current_context.clone()));
-                    *current_context.pc_mut() = action.handler;
-                    *current_context.a_mut(0) = idx;
-                    SignalResult::Handled
-                } else if Self::should_ignore_by_default(signum) {
-                    SignalResult::Ignored
-                } else {
-                    SignalResult::ProcessKilled(Self::kill_code(signum))
-                }
-            }
+    fn deliver_signal(
+        &mut self,
+        signum: SignalNo,
+        current_context: &mut LocalContext,
+    ) -> Option<SignalResult> {
+        let idx = Self::valid_index(signum)?;
+        if self.handling.is_some() || self.mask.contain_bit(idx) {
+            return None;
         }
+        Some(self.deliver(signum, current_context))
     }
 
     fn sig_return(&mut self, current_context: &mut LocalContext) -> bool {
@@ -232,10 +362,31 @@ impl Signal for SignalImpl {
                 self.handling = Some(HandlingSignal::Frozen);
                 false
             }
-            HandlingSignal::UserSignal(saved_ctx) => {
-                *current_context = saved_ctx;
+            HandlingSignal::UserSignal { context, old_mask } => {
+                *current_context = context;
+                self.mask = old_mask;
                 true
             }
         }
     }
+
+    fn handling_state(&self) -> HandlingState {
+        match self.handling {
+            None => HandlingState::None,
+            Some(HandlingSignal::Frozen) => HandlingState::Frozen,
+            Some(HandlingSignal::UserSignal { .. }) => HandlingState::InHandler,
+        }
+    }
+
+    fn force_clear_handling(&mut self, current_context: &mut LocalContext) -> HandlingState {
+        match self.handling.take() {
+            None => HandlingState::None,
+            Some(HandlingSignal::Frozen) => HandlingState::Frozen,
+            Some(HandlingSignal::UserSignal { context, old_mask }) => {
+                *current_context = context;
+                self.mask = old_mask;
+                HandlingState::InHandler
+            }
+        }
+    }
 }
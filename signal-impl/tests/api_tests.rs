@@ -32,7 +32,9 @@
 #[cfg(target_arch = "riscv64")]
 mod tests {
     use signal_impl::*;
-    use signal::{Signal, SignalAction, SignalNo, SignalResult, MAX_SIG};
+    use signal::{
+        HandlingState, MaskHow, Signal, SignalAction, SignalNo, SignalResult, MAX_SIG,
+    };
 
     #[test]
     fn test_signal_impl_new() {
@@ -41,7 +43,9 @@ mod tests {
         assert_eq!(sig_impl.received.0, 0);
         assert_eq!(sig_impl.mask.0, 0);
         assert!(sig_impl.handling.is_none());
-        assert_eq!(sig_impl.actions.len(), MAX_SIG + 1);
+        assert_eq!(sig_impl.actions.len(), SIG_LIMIT + 1);
+        #[cfg(not(feature = "rt-signals"))]
+        assert_eq!(SIG_LIMIT, MAX_SIG);
     }
 
     #[test]
@@ -128,11 +132,297 @@ mod tests {
         let mut sig_impl = SignalImpl::new();
         let old_mask = sig_impl.update_mask(0x1234);
         assert_eq!(old_mask, 0);
-        assert_eq!(sig_impl.mask.0, 0x1234);
+        // 第 9 位（SIGKILL）不可屏蔽，被清除
+        assert_eq!(sig_impl.mask.0, 0x1034);
         
         let old_mask2 = sig_impl.update_mask(0x5678);
-        assert_eq!(old_mask2, 0x1234);
-        assert_eq!(sig_impl.mask.0, 0x5678);
+        assert_eq!(old_mask2, 0x1034);
+        assert_eq!(sig_impl.mask.0, 0x5478);
+    }
+
+    #[test]
+    fn test_signal_impl_unmaskable() {
+        // 测试 SIGKILL 与 SIGSTOP 无法被屏蔽
+        use kernel_context::LocalContext;
+
+        let mut sig_impl = SignalImpl::new();
+        let mut ctx = LocalContext::user(0x2000);
+        assert_eq!(sig_impl.update_mask(usize::MAX), 0);
+        assert!(!sig_impl.mask.contain_bit(SignalNo::SIGKILL as usize));
+        assert!(!sig_impl.mask.contain_bit(SignalNo::SIGSTOP as usize));
+        assert!(sig_impl.mask.contain_bit(SignalNo::SIGUSR1 as usize));
+
+        sig_impl.modify_mask(MaskHow::Block, 1 << SignalNo::SIGSTOP as usize);
+        assert!(!sig_impl.mask.contain_bit(SignalNo::SIGSTOP as usize));
+
+        // 被屏蔽的 SIGUSR1 不投递，SIGSTOP 仍然使进程挂起
+        sig_impl.add_signal(SignalNo::SIGUSR1);
+        sig_impl.add_signal(SignalNo::SIGSTOP);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::ProcessSuspended);
+        assert_eq!(sig_impl.handling_state(), HandlingState::Frozen);
+        assert!(sig_impl.received.contain_bit(SignalNo::SIGUSR1 as usize));
+    }
+
+    #[test]
+    fn test_signal_impl_modify_mask() {
+        // 测试 modify_mask 的 block / unblock / setmask
+        let mut sig_impl = SignalImpl::new();
+
+        assert_eq!(sig_impl.modify_mask(MaskHow::Block, 0b0110), 0);
+        assert_eq!(sig_impl.mask.0, 0b0110);
+
+        assert_eq!(sig_impl.modify_mask(MaskHow::Block, 0b1000), 0b0110);
+        assert_eq!(sig_impl.mask.0, 0b1110);
+
+        assert_eq!(sig_impl.modify_mask(MaskHow::Unblock, 0b0010), 0b1110);
+        assert_eq!(sig_impl.mask.0, 0b1100);
+
+        assert_eq!(sig_impl.modify_mask(MaskHow::SetMask, 0b0001), 0b1100);
+        assert_eq!(sig_impl.mask.0, 0b0001);
+        // mask 只读取，不改变掩码
+        assert_eq!(sig_impl.mask(), 0b0001);
+        assert_eq!(sig_impl.mask(), 0b0001);
+    }
+
+    #[test]
+    fn test_signal_impl_pending() {
+        // 测试 pending 只返回已到达且被屏蔽的信号
+        let mut sig_impl = SignalImpl::new();
+        assert_eq!(sig_impl.pending(), 0);
+
+        sig_impl.add_signal(SignalNo::SIGINT);
+        sig_impl.add_signal(SignalNo::SIGUSR1);
+        sig_impl.add_signal(SignalNo::SIGUSR2);
+        assert_eq!(sig_impl.pending(), 0);
+
+        sig_impl.update_mask(
+            (1 << SignalNo::SIGUSR1 as usize)
+                | (1 << SignalNo::SIGUSR2 as usize)
+                | (1 << SignalNo::SIGTERM as usize),
+        );
+        assert_eq!(
+            sig_impl.pending(),
+            (1 << SignalNo::SIGUSR1 as usize) | (1 << SignalNo::SIGUSR2 as usize)
+        );
+        // 查询不改变待处理集合
+        assert!(sig_impl.received.contain_bit(SignalNo::SIGINT as usize));
+
+        sig_impl.update_mask(0);
+        assert_eq!(sig_impl.pending(), 0);
+    }
+
+    #[cfg(feature = "rt-signals")]
+    #[test]
+    fn test_signal_impl_rt_queue() {
+        // 测试实时信号按次排队，每次到达都触发一次处理函数
+        use kernel_context::LocalContext;
+
+        let mut sig_impl = SignalImpl::new();
+        let action = SignalAction {
+            handler: 0x1000,
+            mask: 0,
+        };
+        assert!(sig_impl.set_action(SignalNo::SIGRTMIN, &action));
+        let mut ctx = LocalContext::user(0x2000);
+
+        for _ in 0..3 {
+            sig_impl.add_signal(SignalNo::SIGRTMIN);
+        }
+        assert_eq!(sig_impl.rt_queue.len(), 3);
+        assert_eq!(sig_impl.received.0, 0);
+
+        let mut invocations = 0;
+        while sig_impl.handle_signals(&mut ctx) == SignalResult::Handled {
+            assert_eq!(ctx.pc(), 0x1000);
+            assert_eq!(ctx.a(0), SignalNo::SIGRTMIN as usize);
+            assert!(sig_impl.sig_return(&mut ctx));
+            invocations += 1;
+        }
+        assert_eq!(invocations, 3);
+        assert!(sig_impl.rt_queue.is_empty());
+
+        // 被屏蔽的实时信号留在队列中
+        sig_impl.update_mask(1 << SignalNo::SIGRTMIN as usize);
+        sig_impl.add_signal(SignalNo::SIGRTMIN);
+        sig_impl.add_signal(SignalNo::SIGRTMIN);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::NoSignal);
+        assert_eq!(sig_impl.pending(), 1 << SignalNo::SIGRTMIN as usize);
+        assert_eq!(sig_impl.rt_queue.len(), 2);
+    }
+
+    #[test]
+    fn test_signal_impl_deliver_signal() {
+        // 测试 deliver_signal 绕过 received 直接投递，并遵守掩码与处理中状态
+        use kernel_context::LocalContext;
+
+        let mut sig_impl = SignalImpl::new();
+        let action = SignalAction {
+            handler: 0x1000,
+            mask: 0,
+        };
+        sig_impl.set_action(SignalNo::SIGUSR1, &action);
+        let mut ctx = LocalContext::user(0x2000);
+
+        // 被屏蔽时不投递
+        sig_impl.update_mask(1 << SignalNo::SIGUSR1 as usize);
+        assert!(sig_impl.deliver_signal(SignalNo::SIGUSR1, &mut ctx).is_none());
+        assert_eq!(ctx.pc(), 0x2000);
+
+        sig_impl.update_mask(0);
+        assert!(matches!(
+            sig_impl.deliver_signal(SignalNo::SIGUSR1, &mut ctx),
+            Some(SignalResult::Handled)
+        ));
+        assert_eq!(ctx.pc(), 0x1000);
+        assert_eq!(sig_impl.received.0, 0);
+
+        // 处理中时不再投递
+        assert!(sig_impl.deliver_signal(SignalNo::SIGUSR1, &mut ctx).is_none());
+        assert!(sig_impl.sig_return(&mut ctx));
+        assert_eq!(ctx.pc(), 0x2000);
+    }
+
+    #[test]
+    fn test_signal_impl_handler_mask() {
+        // 测试处理函数运行期间屏蔽 action.mask 与信号本身，sig_return 后恢复原掩码
+        use kernel_context::LocalContext;
+
+        let mut sig_impl = SignalImpl::new();
+        let action = SignalAction {
+            handler: 0x1000,
+            mask: 1 << SignalNo::SIGUSR2 as usize,
+        };
+        sig_impl.set_action(SignalNo::SIGUSR1, &action);
+        sig_impl.update_mask(1 << SignalNo::SIGINT as usize);
+        let mut ctx = LocalContext::user(0x2000);
+
+        sig_impl.add_signal(SignalNo::SIGUSR1);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::Handled);
+        assert_eq!(
+            sig_impl.mask.0,
+            (1 << SignalNo::SIGINT as usize)
+                | (1 << SignalNo::SIGUSR1 as usize)
+                | (1 << SignalNo::SIGUSR2 as usize)
+        );
+
+        // 处理期间再次到达的同一信号保持挂起
+        sig_impl.add_signal(SignalNo::SIGUSR1);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::IsHandlingSignal);
+        assert!(sig_impl.deliver_signal(SignalNo::SIGUSR1, &mut ctx).is_none());
+        assert!(sig_impl.received.contain_bit(SignalNo::SIGUSR1 as usize));
+
+        assert!(sig_impl.sig_return(&mut ctx));
+        assert_eq!(ctx.pc(), 0x2000);
+        assert_eq!(sig_impl.mask.0, 1 << SignalNo::SIGINT as usize);
+
+        // 恢复掩码后挂起的信号可以再次投递
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::Handled);
+        assert_eq!(ctx.pc(), 0x1000);
+        assert!(!sig_impl.received.contain_bit(SignalNo::SIGUSR1 as usize));
+    }
+
+    #[test]
+    fn test_signal_impl_altstack() {
+        // 测试安装备用栈后处理函数运行在备用栈上，sig_return 恢复原 sp
+        use kernel_context::LocalContext;
+
+        let mut sig_impl = SignalImpl::new();
+        let action = SignalAction {
+            handler: 0x1000,
+            mask: 0,
+        };
+        sig_impl.set_action(SignalNo::SIGSEGV, &action);
+        let mut ctx = LocalContext::user(0x2000);
+        *ctx.sp_mut() = 0x8000;
+
+        // 未安装备用栈时沿用原栈
+        sig_impl.add_signal(SignalNo::SIGSEGV);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::Handled);
+        assert_eq!(ctx.sp(), 0x8000);
+        assert!(sig_impl.sig_return(&mut ctx));
+
+        let (base, size) = (0x10000, 0x1000);
+        sig_impl.set_altstack(base, size);
+        sig_impl.add_signal(SignalNo::SIGSEGV);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::Handled);
+        assert_eq!(ctx.pc(), 0x1000);
+        assert!(ctx.sp() > base && ctx.sp() <= base + size);
+
+        assert!(sig_impl.sig_return(&mut ctx));
+        assert_eq!(ctx.sp(), 0x8000);
+        assert_eq!(ctx.pc(), 0x2000);
+
+        // 栈顶溢出地址空间的备用栈不可用，沿用原栈
+        sig_impl.set_altstack(usize::MAX - 8, 0x1000);
+        sig_impl.add_signal(SignalNo::SIGSEGV);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::Handled);
+        assert_eq!(ctx.sp(), 0x8000);
+        assert!(sig_impl.sig_return(&mut ctx));
+
+        // size 为 0 时移除备用栈
+        sig_impl.set_altstack(0, 0);
+        assert_eq!(sig_impl.alt_stack, None);
+    }
+
+    #[test]
+    fn test_signal_impl_handling_state() {
+        // 测试 handling_state 区分未处理、SIGSTOP 冻结与用户处理函数
+        use kernel_context::LocalContext;
+
+        let mut sig_impl = SignalImpl::new();
+        let mut ctx = LocalContext::user(0x2000);
+        assert_eq!(sig_impl.handling_state(), HandlingState::None);
+
+        sig_impl.add_signal(SignalNo::SIGSTOP);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::ProcessSuspended);
+        assert_eq!(sig_impl.handling_state(), HandlingState::Frozen);
+
+        sig_impl.add_signal(SignalNo::SIGCONT);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::Handled);
+        assert_eq!(sig_impl.handling_state(), HandlingState::None);
+
+        let action = SignalAction {
+            handler: 0x1000,
+            mask: 0,
+        };
+        sig_impl.set_action(SignalNo::SIGUSR1, &action);
+        sig_impl.add_signal(SignalNo::SIGUSR1);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::Handled);
+        assert_eq!(sig_impl.handling_state(), HandlingState::InHandler);
+    }
+
+    #[test]
+    fn test_signal_impl_force_clear_handling() {
+        // 测试 force_clear_handling 取消处理函数并恢复保存的上下文
+        use kernel_context::LocalContext;
+
+        let mut sig_impl = SignalImpl::new();
+        let mut ctx = LocalContext::user(0x2000);
+        *ctx.a_mut(0) = 7;
+        assert_eq!(sig_impl.force_clear_handling(&mut ctx), HandlingState::None);
+
+        let action = SignalAction {
+            handler: 0x1000,
+            mask: 0,
+        };
+        sig_impl.set_action(SignalNo::SIGUSR1, &action);
+        sig_impl.add_signal(SignalNo::SIGUSR1);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::Handled);
+        assert_eq!(ctx.pc(), 0x1000);
+
+        assert_eq!(sig_impl.force_clear_handling(&mut ctx), HandlingState::InHandler);
+        assert_eq!(ctx.pc(), 0x2000);
+        assert_eq!(ctx.a(0), 7);
+        assert_eq!(sig_impl.handling_state(), HandlingState::None);
+        // 已恢复，sig_return 不再生效
+        assert!(!sig_impl.sig_return(&mut ctx));
+
+        // 冻结状态被解除，上下文不变
+        sig_impl.add_signal(SignalNo::SIGSTOP);
+        assert_eq!(sig_impl.handle_signals(&mut ctx), SignalResult::ProcessSuspended);
+        assert_eq!(sig_impl.force_clear_handling(&mut ctx), HandlingState::Frozen);
+        assert_eq!(sig_impl.handling_state(), HandlingState::None);
+        assert_eq!(ctx.pc(), 0x2000);
     }
 
     #[test]
@@ -176,6 +466,25 @@ mod tests {
         assert!(!new_sig_impl.is_handling_signal());
     }
 
+    #[test]
+    fn test_dequeue_from_set() {
+        // 测试 dequeue_from_set 只取出集合内的信号，且不受掩码影响
+        let mut sig_impl = SignalImpl::new();
+        sig_impl.add_signal(SignalNo::SIGUSR1);
+        sig_impl.add_signal(SignalNo::SIGUSR2);
+        sig_impl.update_mask(1 << SignalNo::SIGUSR2 as usize);
+
+        assert_eq!(sig_impl.dequeue_from_set(1 << SignalNo::SIGINT as usize), None);
+        assert_eq!(
+            sig_impl.dequeue_from_set(1 << SignalNo::SIGUSR2 as usize),
+            Some(SignalNo::SIGUSR2)
+        );
+        assert!(!sig_impl.received.contain_bit(SignalNo::SIGUSR2 as usize));
+        assert!(sig_impl.received.contain_bit(SignalNo::SIGUSR1 as usize));
+        assert_eq!(sig_impl.dequeue_from_set(usize::MAX), Some(SignalNo::SIGUSR1));
+        assert_eq!(sig_impl.dequeue_from_set(usize::MAX), None);
+    }
+
     #[test]
     fn test_signal_result_variants() {
         // 测试 SignalResult 枚举的所有变体
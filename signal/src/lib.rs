@@ -24,6 +24,51 @@ pub enum SignalResult {
     ProcessSuspended,
 }
 
+/// What a process is doing with respect to signal handling, for debuggers and `ps`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandlingState {
+    /// No signal is being handled.
+    None,
+    /// Stopped by SIGSTOP and waiting for SIGCONT.
+    Frozen,
+    /// Running a user signal handler.
+    InHandler,
+}
+
+/// How [`Signal::modify_mask`] combines the given bits with the current mask.
+///
+/// Discriminants match Linux `SIG_BLOCK`/`SIG_UNBLOCK`/`SIG_SETMASK`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskHow {
+    /// Add the bits to the mask (OR).
+    Block = 0,
+    /// Remove the bits from the mask (AND-NOT).
+    Unblock = 1,
+    /// Replace the mask with the bits.
+    SetMask = 2,
+}
+
+impl MaskHow {
+    /// Decode a raw `how` argument; returns `None` for unknown values.
+    pub fn from_usize(how: usize) -> Option<Self> {
+        match how {
+            0 => Some(Self::Block),
+            1 => Some(Self::Unblock),
+            2 => Some(Self::SetMask),
+            _ => None,
+        }
+    }
+
+    /// Apply this operation to `mask` and return the new mask.
+    pub fn apply(self, mask: usize, bits: usize) -> usize {
+        match self {
+            Self::Block => mask | bits,
+            Self::Unblock => mask & !bits,
+            Self::SetMask => bits,
+        }
+    }
+}
+
 /// Abstract signal subsystem bound to one process/task.
 pub trait Signal: Send + Sync {
     /// Clone signal state for a forked child.
@@ -35,6 +80,13 @@ pub trait Signal: Send + Sync {
     /// Add one pending signal.
     fn add_signal(&mut self, signal: SignalNo);
 
+    /// Remove and return the lowest-numbered pending signal whose bit is set
+    /// in `set`, without running its action (used by `sigwaitinfo`).
+    ///
+    /// The signal mask is ignored: waiting on a blocked signal is the usual
+    /// way to take it synchronously. Signals outside `set` stay pending.
+    fn dequeue_from_set(&mut self, set: usize) -> Option<SignalNo>;
+
     /// Whether this process is currently handling a signal.
     fn is_handling_signal(&self) -> bool;
 
@@ -45,11 +97,53 @@ pub trait Signal: Send + Sync {
     fn get_action_ref(&self, signum: SignalNo) -> Option<SignalAction>;
 
     /// Replace signal mask and return old mask.
+    ///
+    /// SIGKILL and SIGSTOP cannot be blocked; their bits are dropped from the
+    /// stored mask (this also applies to [`Signal::modify_mask`]).
     fn update_mask(&mut self, mask: usize) -> usize;
 
+    /// Block, unblock, or replace mask bits in one step and return old mask.
+    fn modify_mask(&mut self, how: MaskHow, bits: usize) -> usize;
+
+    /// Current signal mask.
+    fn mask(&self) -> usize;
+
+    /// Run user handlers on the stack `[sp, sp + size)` instead of the
+    /// interrupted stack; `size == 0` removes the alternate stack.
+    ///
+    /// The interrupted `sp` is part of the context restored by [`Signal::sig_return`].
+    fn set_altstack(&mut self, sp: usize, size: usize);
+
+    /// Raw bits of the signals that are pending but blocked by the mask
+    /// (used by `sigpending`).
+    fn pending(&self) -> usize;
+
     /// Try to handle one pending signal.
     fn handle_signals(&mut self, current_context: &mut LocalContext) -> SignalResult;
 
-    /// Return from user signal handler.
+    /// Deliver `signum` directly to the running thread, bypassing the pending set
+    /// (used for thread-directed signals such as `tgkill`).
+    ///
+    /// Returns `None` without consuming the signal when it is masked or another
+    /// signal is being handled; the caller keeps it pending and retries later.
+    fn deliver_signal(
+        &mut self,
+        signum: SignalNo,
+        current_context: &mut LocalContext,
+    ) -> Option<SignalResult>;
+
+    /// Return from user signal handler, restoring the context and signal mask
+    /// saved when the handler was entered.
     fn sig_return(&mut self, current_context: &mut LocalContext) -> bool;
+
+    /// Query the current signal-handling state.
+    fn handling_state(&self) -> HandlingState;
+
+    /// Cancel in-progress handling on behalf of a debugger and return the
+    /// state before clearing.
+    ///
+    /// A running user handler is abandoned and `current_context` is restored
+    /// to the saved pre-handler context and mask, as [`Signal::sig_return`] would;
+    /// a frozen process is thawed without touching the context.
+    fn force_clear_handling(&mut self, current_context: &mut LocalContext) -> HandlingState;
 }
@@ -33,7 +33,7 @@
 mod tests {
     use std::boxed::Box;
     use std::marker::{Send, Sync};
-    use signal::{Signal, SignalAction, SignalNo, SignalResult};
+    use signal::{MaskHow, Signal, SignalAction, SignalNo, SignalResult};
 
     // 注意：由于 signal 不再依赖 signal-impl，我们无法直接测试 SignalImpl
     // 这些测试主要验证 Signal trait 和 SignalResult 的定义
@@ -135,6 +135,19 @@ mod tests {
         assert_eq!(SignalNo::SIGSTOP as u8, 19);
     }
 
+    #[test]
+    fn test_mask_how() {
+        // 测试 MaskHow 的解码与三种掩码运算
+        assert_eq!(MaskHow::from_usize(0), Some(MaskHow::Block));
+        assert_eq!(MaskHow::from_usize(1), Some(MaskHow::Unblock));
+        assert_eq!(MaskHow::from_usize(2), Some(MaskHow::SetMask));
+        assert_eq!(MaskHow::from_usize(3), None);
+
+        assert_eq!(MaskHow::Block.apply(0b0011, 0b0110), 0b0111);
+        assert_eq!(MaskHow::Unblock.apply(0b0111, 0b0110), 0b0001);
+        assert_eq!(MaskHow::SetMask.apply(0b0111, 0b1000), 0b1000);
+    }
+
     #[test]
     fn test_signal_trait_send_sync() {
         // 验证 Signal trait 是 Send + Sync 的
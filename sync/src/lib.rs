@@ -4,10 +4,10 @@ extern crate alloc;
 
 use alloc::collections::VecDeque;
 use alloc::sync::Arc;
-use core::cell::{RefCell, RefMut};
+use alloc::vec::Vec;
+use core::cell::{RefCell, RefMut, UnsafeCell};
 use core::ops::{Deref, DerefMut};
 use rcore_task_manage::ThreadId;
-use spin::Lazy;
 
 #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
 mod arch_intr {
@@ -26,6 +26,15 @@ mod arch_intr {
             riscv::register::sstatus::set_sie();
         }
     }
+
+    /// 内核约定 `tp` 保存当前 hart 编号：`linker::boot0!` 在入口写入，
+    /// kernel-context 在运行用户上下文期间把它保存在栈上，陷入返回内核时恢复
+    #[cfg(feature = "smp")]
+    pub fn hart_id() -> usize {
+        let id: usize;
+        unsafe { core::arch::asm!("mv {}, tp", out(reg) id) };
+        id
+    }
 }
 
 #[cfg(not(any(target_arch = "riscv32", target_arch = "riscv64")))]
@@ -37,36 +46,99 @@ mod arch_intr {
     pub fn disable_intr() {}
 
     pub fn enable_intr() {}
+
+    #[cfg(feature = "smp")]
+    pub fn hart_id() -> usize {
+        0
+    }
 }
 
-struct IntrState {
+/// 支持的 hart 数；未启用 `smp` 时只有一个槽位，不读取 `tp`
+///
+/// `linker::boot0!` 的 `harts` 不能超过它，否则编号越界的 hart 第一次关中断就会 panic。
+#[cfg(feature = "smp")]
+pub const MAX_HARTS: usize = 8;
+#[cfg(not(feature = "smp"))]
+pub const MAX_HARTS: usize = 1;
+
+/// 单个 hart 的关中断嵌套状态。
+///
+/// 中断嵌套只与所在 hart 有关，每个 hart 只访问自己的状态，因此不需要加锁。
+pub struct IntrState {
     nesting: usize,
     prev_enabled: bool,
 }
 
-static INTR_STATE: Lazy<spin::Mutex<IntrState>> = Lazy::new(|| {
-    spin::Mutex::new(IntrState {
-        nesting: 0,
-        prev_enabled: false,
-    })
-});
+impl IntrState {
+    pub const fn new() -> Self {
+        Self {
+            nesting: 0,
+            prev_enabled: false,
+        }
+    }
+
+    /// 当前嵌套层数
+    pub fn nesting(&self) -> usize {
+        self.nesting
+    }
 
-fn push_off() {
-    let mut state = INTR_STATE.lock();
-    if state.nesting == 0 {
-        state.prev_enabled = arch_intr::intr_enabled();
-        arch_intr::disable_intr();
+    /// 进入一层关中断区，`enabled` 为进入前的中断使能状态，只在最外层记录。
+    pub fn push(&mut self, enabled: bool) {
+        if self.nesting == 0 {
+            self.prev_enabled = enabled;
+        }
+        self.nesting += 1;
+    }
+
+    /// 离开一层关中断区，返回是否应重新开中断。
+    pub fn pop(&mut self) -> bool {
+        if self.nesting == 0 {
+            panic!("interrupt nesting underflow");
+        }
+        self.nesting -= 1;
+        self.nesting == 0 && self.prev_enabled
     }
-    state.nesting += 1;
 }
 
-fn pop_off() {
-    let mut state = INTR_STATE.lock();
-    if state.nesting == 0 {
-        panic!("interrupt nesting underflow");
+impl Default for IntrState {
+    fn default() -> Self {
+        Self::new()
     }
-    state.nesting -= 1;
-    if state.nesting == 0 && state.prev_enabled {
+}
+
+struct PerHartIntrState([UnsafeCell<IntrState>; MAX_HARTS]);
+
+// 每个 hart 只在关中断后访问自己的槽位
+unsafe impl Sync for PerHartIntrState {}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const INTR_STATE_INIT: UnsafeCell<IntrState> = UnsafeCell::new(IntrState::new());
+static INTR_STATE: PerHartIntrState = PerHartIntrState([INTR_STATE_INIT; MAX_HARTS]);
+
+/// 当前 hart 的关中断状态，调用前须已关中断
+unsafe fn current_intr_state() -> &'static mut IntrState {
+    #[cfg(feature = "smp")]
+    let hart = {
+        let hart = arch_intr::hart_id();
+        assert!(
+            hart < MAX_HARTS,
+            "hart {hart} out of range: sync supports at most {MAX_HARTS} harts"
+        );
+        hart
+    };
+    #[cfg(not(feature = "smp"))]
+    let hart = 0;
+    &mut *INTR_STATE.0[hart].get()
+}
+
+fn push_off() {
+    let enabled = arch_intr::intr_enabled();
+    arch_intr::disable_intr();
+    unsafe { current_intr_state() }.push(enabled);
+}
+
+fn pop_off() {
+    if unsafe { current_intr_state() }.pop() {
         arch_intr::enable_intr();
     }
 }
@@ -127,12 +199,22 @@ impl<'a, T> Drop for UPIntrRefMut<'a, T> {
 }
 
 pub trait Mutex {
+    /// 为 `tid` 加锁，成功返回 `true`；锁被占用时 `tid` 进入等待队列并返回 `false`。
+    ///
+    /// `tid` 已持有该锁时同样返回 `false`，但不会入队，调用方应先用 [`Mutex::owner`] 排除这种情况。
     fn lock(&self, tid: ThreadId) -> bool;
+    /// 锁空闲时为 `tid` 获取并返回 `true`；被占用时直接返回 `false`，不进入等待队列。
+    fn try_lock(&self, tid: ThreadId) -> bool;
     fn unlock(&self) -> Option<ThreadId>;
+    /// 当前持有锁的线程
+    fn owner(&self) -> Option<ThreadId>;
 }
 
 struct MutexBlockingInner {
     locked: bool,
+    /// 持有者；移交模式下 unlock 直接转给被唤醒的等待者
+    owner: Option<ThreadId>,
+    barging: bool,
     waiting: VecDeque<ThreadId>,
 }
 
@@ -142,10 +224,22 @@ pub struct MutexBlocking {
 
 impl MutexBlocking {
     pub fn new() -> Self {
+        Self::with_barging(false)
+    }
+
+    /// 非移交模式：unlock 时即使有等待者也释放锁，被唤醒的线程需要重新 lock，
+    /// 期间其他线程可以抢先拿到锁。
+    pub fn new_barging() -> Self {
+        Self::with_barging(true)
+    }
+
+    fn with_barging(barging: bool) -> Self {
         Self {
             inner: unsafe {
                 UPIntrFreeCell::new(MutexBlockingInner {
                     locked: false,
+                    owner: None,
+                    barging,
                     waiting: VecDeque::new(),
                 })
             },
@@ -156,11 +250,26 @@ impl MutexBlocking {
 impl Mutex for MutexBlocking {
     fn lock(&self, tid: ThreadId) -> bool {
         self.inner.exclusive_session(|inner| {
-            if inner.locked {
+            if inner.owner == Some(tid) {
+                false
+            } else if inner.locked {
                 inner.waiting.push_back(tid);
                 false
             } else {
                 inner.locked = true;
+                inner.owner = Some(tid);
+                true
+            }
+        })
+    }
+
+    fn try_lock(&self, tid: ThreadId) -> bool {
+        self.inner.exclusive_session(|inner| {
+            if inner.locked {
+                false
+            } else {
+                inner.locked = true;
+                inner.owner = Some(tid);
                 true
             }
         })
@@ -171,46 +280,211 @@ impl Mutex for MutexBlocking {
             if !inner.locked {
                 panic!("unlock on unlocked mutex");
             }
-            if let Some(tid) = inner.waiting.pop_front() {
-                Some(tid)
-            } else {
+            let woken = inner.waiting.pop_front();
+            if woken.is_none() || inner.barging {
                 inner.locked = false;
-                None
+                inner.owner = None;
+            } else {
+                inner.owner = woken;
             }
+            woken
         })
     }
+
+    fn owner(&self) -> Option<ThreadId> {
+        self.inner.exclusive_session(|inner| inner.owner)
+    }
+}
+
+/// 条件变量的等待者
+struct CondvarWaiter {
+    tid: ThreadId,
+    /// 被唤醒时需要重新获取的互斥锁
+    mutex: Option<Arc<dyn Mutex>>,
+    /// 超时时刻（时钟周期数）
+    deadline: Option<u64>,
 }
 
 pub struct Condvar {
-    waiting: UPIntrFreeCell<VecDeque<ThreadId>>,
+    waiting: UPIntrFreeCell<VecDeque<CondvarWaiter>>,
+    /// 已超时、尚未由 [`Condvar::take_timeout`] 取走结果的等待者
+    timed_out: UPIntrFreeCell<Vec<ThreadId>>,
+}
+
+/// [`Condvar::wait`] 的结果：调用者已进入等待队列，应由调度器阻塞。
+#[must_use]
+pub struct WaitTicket {
+    /// 进入等待的线程
+    pub tid: ThreadId,
+    /// 释放互斥锁时被唤醒的等待者，调度器需要让它就绪
+    pub handoff: Option<ThreadId>,
 }
 
 impl Condvar {
     pub fn new() -> Self {
         Self {
             waiting: unsafe { UPIntrFreeCell::new(VecDeque::new()) },
+            timed_out: unsafe { UPIntrFreeCell::new(Vec::new()) },
         }
     }
 
+    /// 唤醒一个等待者，返回可以立即就绪的线程。
+    ///
+    /// 若等待者经 [`Condvar::wait`] 登记了互斥锁，先替它重新 lock；
+    /// 锁被占用时它转入互斥锁的等待队列，由之后的 unlock 唤醒，此时返回 `None`。
     pub fn signal(&self) -> Option<ThreadId> {
-        self.waiting
-            .exclusive_session(|queue| queue.pop_front())
+        let waiter = self.waiting.exclusive_session(|queue| queue.pop_front())?;
+        Self::reacquire(waiter)
+    }
+
+    /// 替离开队列的等待者重新获取互斥锁，拿到锁（或无需加锁）时返回它。
+    fn reacquire(waiter: CondvarWaiter) -> Option<ThreadId> {
+        match waiter.mutex {
+            Some(mutex) if !mutex.lock(waiter.tid) => None,
+            _ => Some(waiter.tid),
+        }
     }
 
     pub fn wait_no_sched(&self, tid: ThreadId) -> bool {
-        self.waiting
-            .exclusive_session(|queue| queue.push_back(tid));
+        self.waiting.exclusive_session(|queue| {
+            queue.push_back(CondvarWaiter {
+                tid,
+                mutex: None,
+                deadline: None,
+            })
+        });
         false
     }
 
+    /// 将 `tid` 加入等待队列并释放它持有的 `mutex`，被 signal 时重新获取该锁。
+    ///
+    /// 先入队再解锁，保证解锁后被唤醒的线程发出的 signal 不会丢失。
+    pub fn wait(&self, tid: ThreadId, mutex: Arc<dyn Mutex>) -> WaitTicket {
+        self.enqueue_and_unlock(tid, mutex, None)
+    }
+
+    /// 同 [`Condvar::wait`]，但等待到 `deadline`（时钟周期数）为止，之后由 [`Condvar::expire`] 移出队列。
+    pub fn wait_timeout(&self, tid: ThreadId, mutex: Arc<dyn Mutex>, deadline: u64) -> WaitTicket {
+        self.enqueue_and_unlock(tid, mutex, Some(deadline))
+    }
+
+    fn enqueue_and_unlock(
+        &self,
+        tid: ThreadId,
+        mutex: Arc<dyn Mutex>,
+        deadline: Option<u64>,
+    ) -> WaitTicket {
+        self.take_timeout(tid);
+        self.waiting.exclusive_session(|queue| {
+            queue.push_back(CondvarWaiter {
+                tid,
+                mutex: Some(mutex.clone()),
+                deadline,
+            })
+        });
+        WaitTicket {
+            tid,
+            handoff: mutex.unlock(),
+        }
+    }
+
+    /// 移出所有在 `now` 时已超时的等待者，返回可以立即就绪的线程。
+    ///
+    /// 与 [`Condvar::signal`] 一样先替它们重新 lock；锁被占用的等待者转入互斥锁的等待队列，不在返回值中。
+    /// 两种情况下超时都会被记录，等待者之后无论由谁唤醒，都应通过 [`Condvar::take_timeout`] 得知这次等待已超时。
+    pub fn expire(&self, now: u64) -> Vec<ThreadId> {
+        let expired = self.waiting.exclusive_session(|queue| {
+            let mut expired = Vec::new();
+            let mut i = 0;
+            while i < queue.len() {
+                if queue[i].deadline.is_some_and(|deadline| deadline <= now) {
+                    expired.extend(queue.remove(i));
+                } else {
+                    i += 1;
+                }
+            }
+            expired
+        });
+        self.timed_out
+            .exclusive_session(|timed_out| timed_out.extend(expired.iter().map(|w| w.tid)));
+        expired.into_iter().filter_map(Self::reacquire).collect()
+    }
+
+    /// 取走 `tid` 上一次等待的结果：因超时离开等待队列时返回 `true`，被 signal 唤醒时返回 `false`。
+    ///
+    /// 调度器在等待者恢复运行前调用，据此决定 wait 的返回值。
+    pub fn take_timeout(&self, tid: ThreadId) -> bool {
+        self.timed_out.exclusive_session(|timed_out| {
+            let len = timed_out.len();
+            timed_out.retain(|&t| t != tid);
+            timed_out.len() != len
+        })
+    }
+
+    /// 释放 `mutex` 后立即替 `tid` 重新获取，返回是否拿到锁以及释放时被唤醒的等待者。
+    ///
+    /// 没有其他等待者时 unlock 已将锁释放，唤醒者为 `None`。
     pub fn wait_with_mutex(
         &self,
         tid: ThreadId,
         mutex: Arc<dyn Mutex>,
     ) -> (bool, Option<ThreadId>) {
-        let woken_tid = mutex.unlock().unwrap();
+        let woken_tid = mutex.unlock();
         let got_lock = mutex.lock(tid);
-        (got_lock, Some(woken_tid))
+        (got_lock, woken_tid)
+    }
+}
+
+/// 按唤醒时刻（时钟周期数）排序的睡眠队列
+///
+/// 不自带锁，由内核放在自己的锁里使用。
+pub struct SleepQueue {
+    sleepers: VecDeque<(u64, ThreadId)>,
+}
+
+impl SleepQueue {
+    pub const fn new() -> Self {
+        Self {
+            sleepers: VecDeque::new(),
+        }
+    }
+
+    /// 登记 `tid` 睡眠到 `deadline`，唤醒时刻相同的线程按登记顺序唤醒
+    pub fn push(&mut self, tid: ThreadId, deadline: u64) {
+        let pos = self.sleepers.partition_point(|&(d, _)| d <= deadline);
+        self.sleepers.insert(pos, (deadline, tid));
+    }
+
+    /// 移出并按唤醒时刻顺序返回所有唤醒时刻不晚于 `now` 的线程
+    pub fn expire(&mut self, now: u64) -> Vec<ThreadId> {
+        let end = self.sleepers.partition_point(|&(d, _)| d <= now);
+        self.sleepers.drain(..end).map(|(_, tid)| tid).collect()
+    }
+
+    /// 撤销 `tid` 的登记，用于线程被其他事件提前唤醒的情形；返回是否确有登记
+    pub fn cancel(&mut self, tid: ThreadId) -> bool {
+        let before = self.sleepers.len();
+        self.sleepers.retain(|&(_, t)| t != tid);
+        self.sleepers.len() != before
+    }
+
+    /// 最早的唤醒时刻
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.sleepers.front().map(|&(deadline, _)| deadline)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sleepers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sleepers.is_empty()
+    }
+}
+
+impl Default for SleepQueue {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -220,7 +494,8 @@ pub struct Semaphore {
 
 struct SemaphoreInner {
     count: isize,
-    waiting: VecDeque<ThreadId>,
+    /// 等待者及其超时时刻（时钟周期数）
+    waiting: VecDeque<(ThreadId, Option<u64>)>,
 }
 
 impl Semaphore {
@@ -236,10 +511,19 @@ impl Semaphore {
     }
 
     pub fn down(&self, tid: ThreadId) -> bool {
+        self.down_with_deadline(tid, None)
+    }
+
+    /// 同 [`Semaphore::down`]，但最多等待到 `deadline`（时钟周期数），之后由 [`Semaphore::expire`] 移出队列。
+    pub fn down_timeout(&self, tid: ThreadId, deadline: u64) -> bool {
+        self.down_with_deadline(tid, Some(deadline))
+    }
+
+    fn down_with_deadline(&self, tid: ThreadId, deadline: Option<u64>) -> bool {
         self.inner.exclusive_session(|inner| {
             inner.count -= 1;
             if inner.count < 0 {
-                inner.waiting.push_back(tid);
+                inner.waiting.push_back((tid, deadline));
                 false
             } else {
                 true
@@ -247,10 +531,39 @@ impl Semaphore {
         })
     }
 
+    /// 移出所有在 `now` 时已超时的等待者并归还它们预占的资源，返回这些线程。
+    pub fn expire(&self, now: u64) -> Vec<ThreadId> {
+        self.inner.exclusive_session(|inner| {
+            let mut expired = Vec::new();
+            inner.waiting.retain(|&(tid, deadline)| {
+                if deadline.is_some_and(|deadline| deadline <= now) {
+                    expired.push(tid);
+                    false
+                } else {
+                    true
+                }
+            });
+            inner.count += expired.len() as isize;
+            expired
+        })
+    }
+
+    /// 有剩余资源时占用一份并返回 `true`；否则直接返回 `false`，不进入等待队列。
+    pub fn try_down(&self) -> bool {
+        self.inner.exclusive_session(|inner| {
+            if inner.count > 0 {
+                inner.count -= 1;
+                true
+            } else {
+                false
+            }
+        })
+    }
+
     pub fn up(&self) -> Option<ThreadId> {
         self.inner.exclusive_session(|inner| {
             inner.count += 1;
-            inner.waiting.pop_front()
+            inner.waiting.pop_front().map(|(tid, _)| tid)
         })
     }
 }
@@ -27,7 +27,7 @@
 mod tests {
     use std::sync::Arc;
     use rcore_task_manage::ThreadId;
-    use sync::{Condvar, Mutex, MutexBlocking, Semaphore};
+    use sync::{Condvar, IntrState, Mutex, MutexBlocking, Semaphore, SleepQueue};
 
     #[test]
     fn test_mutex_blocking_new() {
@@ -71,6 +71,81 @@ mod tests {
         assert!(m.unlock().is_none());
     }
 
+    #[test]
+    fn test_mutex_blocking_try_lock() {
+        let m = MutexBlocking::new();
+        let t1 = ThreadId::from_usize(1);
+        let t2 = ThreadId::from_usize(2);
+
+        assert!(m.try_lock(t1));
+        assert!(!m.try_lock(t2));
+        // 失败的 try_lock 不进入等待队列，unlock 没有可唤醒的线程
+        assert!(m.unlock().is_none());
+        assert!(m.lock(t1));
+
+        // 已有等待者时 try_lock 失败，队列保持不变
+        assert!(!m.lock(t2));
+        assert!(!m.try_lock(t1));
+        assert_eq!(m.unlock(), Some(t2));
+        assert!(m.unlock().is_none());
+        assert!(m.try_lock(t2));
+    }
+
+    #[test]
+    fn test_mutex_blocking_owner() {
+        let m = MutexBlocking::new();
+        let t1 = ThreadId::from_usize(1);
+        let t2 = ThreadId::from_usize(2);
+        assert_eq!(m.owner(), None);
+
+        // 持有者重复加锁被拒绝，且不进入等待队列
+        assert!(m.lock(t1));
+        assert_eq!(m.owner(), Some(t1));
+        assert!(!m.lock(t1));
+        assert!(m.unlock().is_none());
+        assert_eq!(m.owner(), None);
+
+        // 移交模式下持有者转给被唤醒的等待者
+        assert!(m.lock(t1));
+        assert!(!m.lock(t2));
+        assert_eq!(m.owner(), Some(t1));
+        assert_eq!(m.unlock(), Some(t2));
+        assert_eq!(m.owner(), Some(t2));
+        assert!(m.unlock().is_none());
+
+        // barging 模式下 unlock 释放锁，持有者清空
+        let m = MutexBlocking::new_barging();
+        assert!(m.lock(t1));
+        assert!(!m.lock(t2));
+        assert_eq!(m.unlock(), Some(t2));
+        assert_eq!(m.owner(), None);
+    }
+
+    #[test]
+    fn test_mutex_blocking_handoff_vs_barging() {
+        let t1 = ThreadId::from_usize(1);
+        let t2 = ThreadId::from_usize(2);
+        let t3 = ThreadId::from_usize(3);
+
+        // 默认移交模式：锁直接交给 t2，t3 无法抢占
+        let m = MutexBlocking::new();
+        assert!(m.lock(t1));
+        assert!(!m.lock(t2));
+        assert_eq!(m.unlock(), Some(t2));
+        assert!(!m.lock(t3));
+
+        // barging 模式：unlock 唤醒 t2 但释放锁，t3 可以抢先拿到
+        let m = MutexBlocking::new_barging();
+        assert!(m.lock(t1));
+        assert!(!m.lock(t2));
+        assert_eq!(m.unlock(), Some(t2));
+        assert!(m.lock(t3));
+        // 被唤醒的 t2 重试时需要重新排队
+        assert!(!m.lock(t2));
+        assert_eq!(m.unlock(), Some(t2));
+        assert!(m.lock(t2));
+    }
+
     #[test]
     fn test_condvar_new() {
         let cv = Condvar::new();
@@ -113,6 +188,160 @@ mod tests {
         assert!(!got_lock);
     }
 
+    #[test]
+    fn test_condvar_wait_with_uncontended_mutex() {
+        let cv = Condvar::new();
+        let mutex: Arc<dyn Mutex> = Arc::new(MutexBlocking::new());
+        let t1 = ThreadId::from_usize(1);
+
+        // 没有其他等待者：释放后 t1 直接重新拿到锁
+        assert!(mutex.lock(t1));
+        let (got_lock, woken) = cv.wait_with_mutex(t1, mutex.clone());
+        assert!(got_lock);
+        assert_eq!(woken, None);
+        assert!(mutex.unlock().is_none());
+    }
+
+    #[test]
+    fn test_condvar_wait_and_requeue() {
+        let cv = Condvar::new();
+        let mutex: Arc<dyn Mutex> = Arc::new(MutexBlocking::new());
+        let t1 = ThreadId::from_usize(1);
+        let t2 = ThreadId::from_usize(2);
+
+        // t1 持锁后等待：入队并释放锁，无人交接
+        assert!(mutex.lock(t1));
+        let ticket = cv.wait(t1, mutex.clone());
+        assert_eq!(ticket.tid, t1);
+        assert!(ticket.handoff.is_none());
+
+        // 锁已释放，t2 可以拿到；此时 signal 使 t1 转入互斥锁等待队列
+        assert!(mutex.lock(t2));
+        assert!(cv.signal().is_none());
+        assert!(cv.signal().is_none());
+        // t2 解锁时把锁交给 t1
+        assert_eq!(mutex.unlock(), Some(t1));
+        assert!(!mutex.lock(t2));
+
+        // 锁空闲时 signal 直接替等待者拿到锁并返回它
+        assert_eq!(mutex.unlock(), Some(t2));
+        let ticket = cv.wait(t2, mutex.clone());
+        assert!(ticket.handoff.is_none());
+        assert_eq!(cv.signal(), Some(t2));
+        assert!(!mutex.lock(t1));
+    }
+
+    #[test]
+    fn test_condvar_wait_hands_off_mutex() {
+        let cv = Condvar::new();
+        let mutex: Arc<dyn Mutex> = Arc::new(MutexBlocking::new());
+        let t1 = ThreadId::from_usize(1);
+        let t2 = ThreadId::from_usize(2);
+
+        assert!(mutex.lock(t1));
+        assert!(!mutex.lock(t2));
+        // 等待时释放锁，锁移交给排队的 t2，由调度器唤醒
+        let ticket = cv.wait(t1, mutex.clone());
+        assert_eq!(ticket.handoff, Some(t2));
+    }
+
+    #[test]
+    fn test_condvar_wait_timeout_expire() {
+        let cv = Condvar::new();
+        let mutex: Arc<dyn Mutex> = Arc::new(MutexBlocking::new());
+        let t1 = ThreadId::from_usize(1);
+        let t2 = ThreadId::from_usize(2);
+        let t3 = ThreadId::from_usize(3);
+
+        // t1 限时 100，t2 限时 200，t3 不限时
+        assert!(mutex.lock(t1));
+        assert!(cv.wait_timeout(t1, mutex.clone(), 100).handoff.is_none());
+        assert!(mutex.lock(t2));
+        assert!(cv.wait_timeout(t2, mutex.clone(), 200).handoff.is_none());
+        cv.wait_no_sched(t3);
+
+        assert!(cv.expire(99).is_empty());
+        // 到期的 t1 离开队列并重新拿到锁
+        assert_eq!(cv.expire(100), vec![t1]);
+        assert_eq!(mutex.owner(), Some(t1));
+
+        assert!(cv.take_timeout(t1));
+        assert!(!cv.take_timeout(t1));
+
+        // 锁被占用时到期的 t2 转入互斥锁队列，不立即就绪，但超时已被记录
+        assert!(cv.expire(1000).is_empty());
+        assert_eq!(mutex.unlock(), Some(t2));
+        assert!(cv.take_timeout(t2));
+
+        // 不限时的等待者不受 expire 影响，被 signal 唤醒时不算超时
+        assert_eq!(cv.signal(), Some(t3));
+        assert!(!cv.take_timeout(t3));
+        assert!(cv.signal().is_none());
+    }
+
+    #[test]
+    fn test_sleep_queue_push_expire() {
+        let mut q = SleepQueue::new();
+        let t1 = ThreadId::from_usize(1);
+        let t2 = ThreadId::from_usize(2);
+        let t3 = ThreadId::from_usize(3);
+        assert!(q.is_empty());
+        assert_eq!(q.next_deadline(), None);
+
+        // 乱序登记，按唤醒时刻排列；时刻相同的按登记顺序
+        q.push(t1, 300);
+        q.push(t2, 100);
+        q.push(t3, 300);
+        assert_eq!(q.len(), 3);
+        assert_eq!(q.next_deadline(), Some(100));
+
+        assert!(q.expire(99).is_empty());
+        assert_eq!(q.expire(100), vec![t2]);
+        assert_eq!(q.next_deadline(), Some(300));
+        assert_eq!(q.expire(u64::MAX), vec![t1, t3]);
+        assert!(q.is_empty());
+        assert_eq!(q.next_deadline(), None);
+    }
+
+    #[test]
+    fn test_sleep_queue_cancel() {
+        let mut q = SleepQueue::new();
+        let t1 = ThreadId::from_usize(1);
+        let t2 = ThreadId::from_usize(2);
+        q.push(t1, 100);
+        q.push(t2, 200);
+
+        // 撤销后不再被 expire 返回，也不影响其他线程
+        assert!(q.cancel(t1));
+        assert!(!q.cancel(t1));
+        assert_eq!(q.next_deadline(), Some(200));
+        assert_eq!(q.expire(u64::MAX), vec![t2]);
+        assert!(!q.cancel(t2));
+    }
+
+    #[test]
+    fn test_intr_state_per_hart() {
+        // 模拟两个 hart 各自的嵌套状态，互不影响
+        let mut harts = [IntrState::new(), IntrState::new()];
+
+        // hart 0 进入时中断开启，嵌套两层
+        harts[0].push(true);
+        harts[0].push(false);
+        // hart 1 进入时中断关闭
+        harts[1].push(false);
+        assert_eq!(harts[0].nesting(), 2);
+        assert_eq!(harts[1].nesting(), 1);
+
+        // hart 1 退出不会开中断，也不改变 hart 0 的层数
+        assert!(!harts[1].pop());
+        assert_eq!(harts[0].nesting(), 2);
+
+        // hart 0 只在最外层退出时恢复中断
+        assert!(!harts[0].pop());
+        assert!(harts[0].pop());
+        assert_eq!(harts[0].nesting(), 0);
+    }
+
     #[test]
     fn test_semaphore_new() {
         let s = Semaphore::new(3);
@@ -145,6 +374,43 @@ mod tests {
         assert!(s.up().is_none());
     }
 
+    #[test]
+    fn test_semaphore_try_down() {
+        let s = Semaphore::new(1);
+        let tid = ThreadId::from_usize(1);
+
+        assert!(s.try_down());
+        assert!(!s.try_down());
+        // 失败的 try_down 不占用资源也不排队
+        assert!(s.up().is_none());
+        assert!(s.down(tid));
+        assert!(!s.try_down());
+        assert!(s.up().is_none());
+        assert!(s.try_down());
+    }
+
+    #[test]
+    fn test_semaphore_down_timeout_expire() {
+        let s = Semaphore::new(0);
+        let t1 = ThreadId::from_usize(1);
+        let t2 = ThreadId::from_usize(2);
+        let t3 = ThreadId::from_usize(3);
+
+        assert!(!s.down_timeout(t1, 100));
+        assert!(!s.down(t2));
+        assert!(!s.down_timeout(t3, 50));
+
+        assert!(s.expire(49).is_empty());
+        assert_eq!(s.expire(100), vec![t1, t3]);
+        assert!(s.expire(1000).is_empty());
+
+        // 超时的等待者归还了预占的计数：up 只唤醒 t2，之后的 down 可以直接拿到资源
+        assert_eq!(s.up(), Some(t2));
+        assert!(s.up().is_none());
+        assert!(s.down(t1));
+        assert!(!s.down(t3));
+    }
+
     #[test]
     fn test_semaphore_multiple_waiters() {
         let s = Semaphore::new(0);
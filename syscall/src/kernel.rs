@@ -23,6 +23,66 @@ pub trait Process: Send + Sync {
     fn wait(&self, caller: Caller, exit_code_ptr: *mut i32) -> isize;
     fn waitpid(&self, caller: Caller, pid: isize, exit_code_ptr: *mut i32) -> isize;
     fn getpid(&self, caller: Caller) -> isize;
+
+    /// 创建与父进程共享地址空间的子进程，父进程阻塞到子进程 exec 或 exit 为止
+    ///
+    /// 子进程直接运行在父进程的页表和用户栈上，它对内存的任何写入（包括栈上的局部变量）
+    /// 都会在父进程恢复后可见，因此子进程除了 exec 和 exit 之外不应做其他事情。
+    fn vfork(&self, _caller: Caller) -> isize {
+        -1
+    }
+
+    /// 设置当前进程的资源上限，`resource` 取 [`crate::RLIMIT_AS`] 等，`limit` 单位为字节
+    fn setrlimit(&self, _caller: Caller, _resource: usize, _limit: usize) -> isize {
+        -1
+    }
+
+    /// 把当前进程及其已回收子进程的用户态/内核态时间写入 `tms` 指向的 [`crate::Tms`]
+    fn times(&self, _caller: Caller, _tms: *mut crate::Tms) -> isize {
+        -1
+    }
+
+    /// 返回当前进程的用户 id
+    fn getuid(&self, _caller: Caller) -> isize {
+        -1
+    }
+
+    /// 返回当前进程的组 id
+    fn getgid(&self, _caller: Caller) -> isize {
+        -1
+    }
+
+    /// 设置当前进程的用户 id，目前不做权限检查
+    fn setuid(&self, _caller: Caller, _uid: usize) -> isize {
+        -1
+    }
+
+    /// 设置当前进程的组 id，目前不做权限检查
+    fn setgid(&self, _caller: Caller, _gid: usize) -> isize {
+        -1
+    }
+
+    /// 把进程 `pid` 移入同一会话中的进程组 `pgid`，`pid`/`pgid` 为 0 时表示当前进程
+    fn setpgid(&self, _caller: Caller, _pid: usize, _pgid: usize) -> isize {
+        -1
+    }
+
+    /// 返回进程 `pid` 的进程组 id，`pid` 为 0 时表示当前进程
+    fn getpgid(&self, _caller: Caller, _pid: usize) -> isize {
+        -1
+    }
+
+    /// 返回进程 `pid` 的会话 id，`pid` 为 0 时表示当前进程
+    fn getsid(&self, _caller: Caller, _pid: usize) -> isize {
+        -1
+    }
+
+    /// 以当前进程为首进程创建新会话和新进程组，返回新会话 id
+    ///
+    /// 首进程退出时，会话中因此成为孤儿的进程组的成员会收到 `SIGHUP`。
+    fn setsid(&self, _caller: Caller) -> isize {
+        -1
+    }
 }
 
 /// IO 操作 trait
@@ -31,37 +91,175 @@ pub trait IO: Send + Sync {
     fn write(&self, caller: Caller, fd: usize, buf: *const u8, count: usize) -> isize;
     fn open(&self, caller: Caller, path: *const u8, flags: u32) -> isize;
     fn close(&self, caller: Caller, fd: usize) -> isize;
+
+    /// 等待 `fds` 指向的 `nfds` 个 [`crate::PollFd`] 中任一项就绪，写回 `revents`
+    ///
+    /// `timeout` 为空表示一直等待，`sigmask` 非空时在等待期间临时替换信号掩码，
+    /// `sigsetsize` 为信号集的字节数。返回就绪项数，超时返回 0
+    fn ppoll(
+        &self,
+        _caller: Caller,
+        _fds: *mut crate::PollFd,
+        _nfds: usize,
+        _timeout: *const crate::TimeSpec,
+        _sigmask: *const usize,
+        _sigsetsize: usize,
+    ) -> isize {
+        -1
+    }
+
+    /// 重命名文件，`old_path`/`new_path` 为以 `\0` 结尾的用户态字符串
+    fn rename(&self, _caller: Caller, _old_path: *const u8, _new_path: *const u8) -> isize {
+        -1
+    }
+
+    /// 查询 `path` 所在文件系统的容量，写入 `buf` 指向的 [`crate::FsStat`]
+    fn statfs(&self, _caller: Caller, _path: *const u8, _buf: *mut crate::FsStat) -> isize {
+        -1
+    }
+
+    /// 创建管道，把读端和写端的文件描述符依次写入 `fds` 指向的两个 `usize`
+    fn pipe(&self, _caller: Caller, _fds: *mut usize) -> isize {
+        -1
+    }
+
+    /// 按 `whence`（[`crate::SEEK_SET`] 等）移动 `fd` 的偏移，返回新偏移
+    fn lseek(&self, _caller: Caller, _fd: usize, _offset: isize, _whence: usize) -> isize {
+        -1
+    }
+
+    /// 复制 `fd` 到最小的空闲描述符，两者共享同一个文件句柄（包括偏移）
+    fn dup(&self, _caller: Caller, _fd: usize) -> isize {
+        -1
+    }
+
+    /// 复制 `oldfd` 到 `newfd`，`newfd` 已打开时先关闭；`oldfd` 无效时返回 -1
+    fn dup2(&self, _caller: Caller, _oldfd: usize, _newfd: usize) -> isize {
+        -1
+    }
+
+    /// 从目录 `fd` 的游标处读取目录项，以 [`crate::Dirent`] 记录写入 `buf` 指向的 `len` 字节
+    ///
+    /// 返回写入的字节数，读完后返回 0；`fd` 不是目录或 `len` 放不下一条记录时返回 -1
+    fn getdents(&self, _caller: Caller, _fd: usize, _buf: *mut u8, _len: usize) -> isize {
+        -1
+    }
 }
 
 /// 内存管理 trait
 pub trait Memory: Send + Sync {
     fn mmap(&self, caller: Caller, addr: usize, len: usize, prot: usize, flags: usize, fd: isize, offset: usize) -> isize;
     fn munmap(&self, caller: Caller, addr: usize, len: usize) -> isize;
+
+    /// 把 `[addr, addr + len)` 内 `MAP_SHARED` 文件映射的内容写回文件，`flags` 取 [`crate::MS_SYNC`] 等
+    fn msync(&self, _caller: Caller, _addr: usize, _len: usize, _flags: usize) -> isize {
+        -1
+    }
+
+    /// 对 `[addr, addr + len)` 给出使用建议，`advice` 取 [`crate::MADV_DONTNEED`] 等
+    fn madvise(&self, _caller: Caller, _addr: usize, _len: usize, _advice: usize) -> isize {
+        -1
+    }
+
+    /// 把堆顶（program break）设为 `new_top`，返回新的堆顶；`new_top` 为 0 时只查询当前堆顶
+    fn brk(&self, _caller: Caller, _new_top: usize) -> isize {
+        -1
+    }
+
+    /// 把堆顶移动 `delta` 字节，返回原来的堆顶
+    fn sbrk(&self, _caller: Caller, _delta: isize) -> isize {
+        -1
+    }
 }
 
 /// 调度 trait
 pub trait Scheduling: Send + Sync {
     fn sched_yield(&self, caller: Caller) -> isize;
+
+    /// 查询当前所在的 hart 与 NUMA 节点，指针为空时跳过对应写回
+    fn getcpu(&self, _caller: Caller, _cpu: *mut usize, _node: *mut usize) -> isize {
+        -1
+    }
+
+    /// 设置当前线程的调度优先级，`prio` 不在 [`crate::PRIO_MIN`]..=[`crate::PRIO_MAX`] 内时返回 -1
+    fn set_priority(&self, _caller: Caller, _prio: isize) -> isize {
+        -1
+    }
+
+    /// 返回当前线程的调度优先级
+    fn get_priority(&self, _caller: Caller) -> isize {
+        -1
+    }
 }
 
 /// 时钟 trait
 pub trait Clock: Send + Sync {
     fn clock_gettime(&self, caller: Caller, clockid: usize, tp: *mut crate::TimeSpec) -> isize;
+
+    /// 把 `CLOCK_REALTIME` 以秒和微秒写入 `tv`
+    fn gettimeofday(&self, _caller: Caller, _tv: *mut crate::TimeVal) -> isize {
+        -1
+    }
+
+    /// 睡眠到 `req` 指定的时间；`flags` 含 [`crate::TIMER_ABSTIME`] 时 `req` 为绝对时间，否则为相对时长
+    fn clock_nanosleep(&self, _caller: Caller, _clockid: usize, _flags: usize, _req: *const crate::TimeSpec) -> isize {
+        -1
+    }
+
+    /// 按 `CLOCK_MONOTONIC` 睡眠 `req` 指定的相对时长
+    fn nanosleep(&self, _caller: Caller, _req: *const crate::TimeSpec) -> isize {
+        -1
+    }
+
+    /// `seconds` 秒后向当前进程发送 `SIGALRM`，取代之前设置的闹钟；`seconds` 为 0 时取消闹钟
+    ///
+    /// 返回之前的闹钟剩余的秒数（向上取整），没有闹钟时返回 0
+    fn alarm(&self, _caller: Caller, _seconds: usize) -> isize {
+        -1
+    }
 }
 
 /// 信号 trait
 pub trait Signal: Send + Sync {
     fn kill(&self, caller: Caller, pid: isize, signum: u8) -> isize;
+    /// 向进程 `pid` 中的线程 `tid` 发送信号，只在该线程运行时投递
+    fn tgkill(&self, _caller: Caller, _pid: isize, _tid: isize, _signum: u8) -> isize {
+        -1
+    }
     fn sigaction(&self, caller: Caller, signum: u8, action: *const crate::SignalAction, old_action: *mut crate::SignalAction) -> isize;
-    fn sigprocmask(&self, caller: Caller, mask: usize) -> isize;
+    /// 按 `how`（[`crate::SIG_BLOCK`] 等）修改信号掩码，`oldset` 非空时写回旧掩码
+    fn sigprocmask(&self, caller: Caller, how: usize, set: usize, oldset: *mut usize) -> isize;
+    /// 把已到达但被掩码阻塞的信号集合写入 `set`
+    fn sigpending(&self, _caller: Caller, _set: *mut usize) -> isize {
+        -1
+    }
+    /// 阻塞到 `*set` 中的某个信号待处理，同步取出它（不执行处理函数）并返回信号编号，
+    /// `info` 非空时写入 [`crate::SigInfo`]；`timeout` 非空时最多等待这么久，超时返回 -1。
+    /// `sigsetsize` 是 `*set` 的字节数
+    fn sigtimedwait(
+        &self,
+        _caller: Caller,
+        _set: *const usize,
+        _info: *mut crate::SigInfo,
+        _timeout: *const crate::TimeSpec,
+        _sigsetsize: usize,
+    ) -> isize {
+        -1
+    }
     fn sigreturn(&self, caller: Caller) -> isize;
 }
 
 /// 线程管理 trait
 pub trait Thread: Send + Sync {
-    fn thread_create(&self, caller: Caller, entry: usize, arg: usize) -> isize;
+    /// 创建线程，`tls` 非 0 时作为新线程的 `tp`
+    fn thread_create(&self, caller: Caller, entry: usize, arg: usize, tls: usize) -> isize;
     fn gettid(&self, caller: Caller) -> isize;
     fn waittid(&self, caller: Caller, tid: usize) -> isize;
+
+    /// 线程属性控制，目前支持 [`crate::PR_SET_NAME`] 与 [`crate::PR_GET_NAME`]
+    fn prctl(&self, _caller: Caller, _option: usize, _arg2: usize) -> isize {
+        -1
+    }
 }
 
 /// 同步原语 trait
@@ -69,12 +267,40 @@ pub trait SyncMutex: Send + Sync {
     fn semaphore_create(&self, caller: Caller, res_count: usize) -> isize;
     fn semaphore_up(&self, caller: Caller, sem_id: usize) -> isize;
     fn semaphore_down(&self, caller: Caller, sem_id: usize) -> isize;
+    /// 最多阻塞 `timeout` 指定时长的 P 操作：成功返回 0，超时返回 -1
+    fn semaphore_down_timeout(
+        &self,
+        _caller: Caller,
+        _sem_id: usize,
+        _timeout: *const crate::TimeSpec,
+    ) -> isize {
+        -1
+    }
+    /// 不阻塞的 P 操作：成功返回 0，信号量没有剩余资源时返回 -1
+    fn semaphore_trydown(&self, _caller: Caller, _sem_id: usize) -> isize {
+        -1
+    }
     fn mutex_create(&self, caller: Caller, blocking: bool) -> isize;
     fn mutex_lock(&self, caller: Caller, mutex_id: usize) -> isize;
+    /// 不阻塞地尝试加锁：成功返回 0，锁被占用时返回 -1
+    fn mutex_trylock(&self, _caller: Caller, _mutex_id: usize) -> isize {
+        -1
+    }
     fn mutex_unlock(&self, caller: Caller, mutex_id: usize) -> isize;
     fn condvar_create(&self, caller: Caller) -> isize;
     fn condvar_signal(&self, caller: Caller, condvar_id: usize) -> isize;
     fn condvar_wait(&self, caller: Caller, condvar_id: usize, mutex_id: usize) -> isize;
+    /// 最多等待 `timeout` 指定时长的 [`SyncMutex::condvar_wait`]：被唤醒返回 0，超时返回 -1，
+    /// 两种情况下返回时都已重新持有互斥锁
+    fn condvar_wait_timeout(
+        &self,
+        _caller: Caller,
+        _condvar_id: usize,
+        _mutex_id: usize,
+        _timeout: *const crate::TimeSpec,
+    ) -> isize {
+        -1
+    }
 }
 
 // Handler 存储（使用 Once 确保一次性初始化）
@@ -159,6 +385,69 @@ pub fn handle(caller: Caller, id: SyscallId, args: [usize; 6]) -> SyscallResult
                 SyscallResult::Unsupported(id)
             }
         }
+        SyscallId::PPOLL => {
+            if let Some(handler) = IO_HANDLER.get() {
+                SyscallResult::Done(handler.ppoll(
+                    caller,
+                    args[0] as *mut crate::PollFd,
+                    args[1],
+                    args[2] as *const crate::TimeSpec,
+                    args[3] as *const usize,
+                    args[4],
+                ))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::RENAME => {
+            if let Some(handler) = IO_HANDLER.get() {
+                SyscallResult::Done(handler.rename(caller, args[0] as *const u8, args[1] as *const u8))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::STATFS => {
+            if let Some(handler) = IO_HANDLER.get() {
+                SyscallResult::Done(handler.statfs(caller, args[0] as *const u8, args[1] as *mut crate::FsStat))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::PIPE2 => {
+            if let Some(handler) = IO_HANDLER.get() {
+                SyscallResult::Done(handler.pipe(caller, args[0] as *mut usize))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::LSEEK => {
+            if let Some(handler) = IO_HANDLER.get() {
+                SyscallResult::Done(handler.lseek(caller, args[0], args[1] as isize, args[2]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::DUP => {
+            if let Some(handler) = IO_HANDLER.get() {
+                SyscallResult::Done(handler.dup(caller, args[0]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::DUP3 => {
+            if let Some(handler) = IO_HANDLER.get() {
+                SyscallResult::Done(handler.dup2(caller, args[0], args[1]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::GETDENTS => {
+            if let Some(handler) = IO_HANDLER.get() {
+                SyscallResult::Done(handler.getdents(caller, args[0], args[1] as *mut u8, args[2]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
         // Process syscalls
         SyscallId::FORK => {
             if let Some(handler) = PROCESS_HANDLER.get() {
@@ -167,6 +456,13 @@ pub fn handle(caller: Caller, id: SyscallId, args: [usize; 6]) -> SyscallResult
                 SyscallResult::Unsupported(id)
             }
         }
+        SyscallId::VFORK => {
+            if let Some(handler) = PROCESS_HANDLER.get() {
+                SyscallResult::Done(handler.vfork(caller))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
         SyscallId::EXECVE => {
             if let Some(handler) = PROCESS_HANDLER.get() {
                 SyscallResult::Done(handler.exec(caller, args[0] as *const u8))
@@ -196,6 +492,127 @@ pub fn handle(caller: Caller, id: SyscallId, args: [usize; 6]) -> SyscallResult
                 SyscallResult::Unsupported(id)
             }
         }
+        SyscallId::SETRLIMIT => {
+            if let Some(handler) = PROCESS_HANDLER.get() {
+                SyscallResult::Done(handler.setrlimit(caller, args[0], args[1]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::TIMES => {
+            if let Some(handler) = PROCESS_HANDLER.get() {
+                SyscallResult::Done(handler.times(caller, args[0] as *mut crate::Tms))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::GETUID => {
+            if let Some(handler) = PROCESS_HANDLER.get() {
+                SyscallResult::Done(handler.getuid(caller))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::GETGID => {
+            if let Some(handler) = PROCESS_HANDLER.get() {
+                SyscallResult::Done(handler.getgid(caller))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::SETUID => {
+            if let Some(handler) = PROCESS_HANDLER.get() {
+                SyscallResult::Done(handler.setuid(caller, args[0]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::SETGID => {
+            if let Some(handler) = PROCESS_HANDLER.get() {
+                SyscallResult::Done(handler.setgid(caller, args[0]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::SETPGID => {
+            if let Some(handler) = PROCESS_HANDLER.get() {
+                SyscallResult::Done(handler.setpgid(caller, args[0], args[1]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::GETPGID => {
+            if let Some(handler) = PROCESS_HANDLER.get() {
+                SyscallResult::Done(handler.getpgid(caller, args[0]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::GETSID => {
+            if let Some(handler) = PROCESS_HANDLER.get() {
+                SyscallResult::Done(handler.getsid(caller, args[0]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::SETSID => {
+            if let Some(handler) = PROCESS_HANDLER.get() {
+                SyscallResult::Done(handler.setsid(caller))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        // Memory syscalls
+        SyscallId::MMAP => {
+            if let Some(handler) = MEMORY_HANDLER.get() {
+                SyscallResult::Done(handler.mmap(
+                    caller,
+                    args[0],
+                    args[1],
+                    args[2],
+                    args[3],
+                    args[4] as isize,
+                    args[5],
+                ))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::MUNMAP => {
+            if let Some(handler) = MEMORY_HANDLER.get() {
+                SyscallResult::Done(handler.munmap(caller, args[0], args[1]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::BRK => {
+            if let Some(handler) = MEMORY_HANDLER.get() {
+                SyscallResult::Done(handler.brk(caller, args[0]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::SBRK => {
+            if let Some(handler) = MEMORY_HANDLER.get() {
+                SyscallResult::Done(handler.sbrk(caller, args[0] as isize))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::MSYNC => {
+            if let Some(handler) = MEMORY_HANDLER.get() {
+                SyscallResult::Done(handler.msync(caller, args[0], args[1], args[2]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::MADVISE => {
+            if let Some(handler) = MEMORY_HANDLER.get() {
+                SyscallResult::Done(handler.madvise(caller, args[0], args[1], args[2]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
         // Scheduling syscalls
         SyscallId::SCHED_YIELD => {
             if let Some(handler) = SCHEDULING_HANDLER.get() {
@@ -204,6 +621,27 @@ pub fn handle(caller: Caller, id: SyscallId, args: [usize; 6]) -> SyscallResult
                 SyscallResult::Unsupported(id)
             }
         }
+        SyscallId::GETCPU => {
+            if let Some(handler) = SCHEDULING_HANDLER.get() {
+                SyscallResult::Done(handler.getcpu(caller, args[0] as *mut usize, args[1] as *mut usize))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::SET_PRIORITY => {
+            if let Some(handler) = SCHEDULING_HANDLER.get() {
+                SyscallResult::Done(handler.set_priority(caller, args[0] as isize))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::GET_PRIORITY => {
+            if let Some(handler) = SCHEDULING_HANDLER.get() {
+                SyscallResult::Done(handler.get_priority(caller))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
         // Clock syscalls
         SyscallId::CLOCK_GETTIME => {
             if let Some(handler) = CLOCK_HANDLER.get() {
@@ -212,6 +650,34 @@ pub fn handle(caller: Caller, id: SyscallId, args: [usize; 6]) -> SyscallResult
                 SyscallResult::Unsupported(id)
             }
         }
+        SyscallId::NANOSLEEP => {
+            if let Some(handler) = CLOCK_HANDLER.get() {
+                SyscallResult::Done(handler.nanosleep(caller, args[0] as *const crate::TimeSpec))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::GETTIMEOFDAY => {
+            if let Some(handler) = CLOCK_HANDLER.get() {
+                SyscallResult::Done(handler.gettimeofday(caller, args[0] as *mut crate::TimeVal))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::CLOCK_NANOSLEEP => {
+            if let Some(handler) = CLOCK_HANDLER.get() {
+                SyscallResult::Done(handler.clock_nanosleep(caller, args[0], args[1], args[2] as *const crate::TimeSpec))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::ALARM => {
+            if let Some(handler) = CLOCK_HANDLER.get() {
+                SyscallResult::Done(handler.alarm(caller, args[0]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
         // Signal syscalls
         SyscallId::KILL => {
             if let Some(handler) = SIGNAL_HANDLER.get() {
@@ -220,6 +686,13 @@ pub fn handle(caller: Caller, id: SyscallId, args: [usize; 6]) -> SyscallResult
                 SyscallResult::Unsupported(id)
             }
         }
+        SyscallId::TGKILL => {
+            if let Some(handler) = SIGNAL_HANDLER.get() {
+                SyscallResult::Done(handler.tgkill(caller, args[0] as isize, args[1] as isize, args[2] as u8))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
         SyscallId::SIGACTION => {
             if let Some(handler) = SIGNAL_HANDLER.get() {
                 SyscallResult::Done(handler.sigaction(caller, args[0] as u8, args[1] as *const crate::SignalAction, args[2] as *mut crate::SignalAction))
@@ -229,7 +702,27 @@ pub fn handle(caller: Caller, id: SyscallId, args: [usize; 6]) -> SyscallResult
         }
         SyscallId::SIGPROCMASK => {
             if let Some(handler) = SIGNAL_HANDLER.get() {
-                SyscallResult::Done(handler.sigprocmask(caller, args[0]))
+                SyscallResult::Done(handler.sigprocmask(caller, args[0], args[1], args[2] as *mut usize))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::RT_SIGPENDING => {
+            if let Some(handler) = SIGNAL_HANDLER.get() {
+                SyscallResult::Done(handler.sigpending(caller, args[0] as *mut usize))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::RT_SIGTIMEDWAIT => {
+            if let Some(handler) = SIGNAL_HANDLER.get() {
+                SyscallResult::Done(handler.sigtimedwait(
+                    caller,
+                    args[0] as *const usize,
+                    args[1] as *mut crate::SigInfo,
+                    args[2] as *const crate::TimeSpec,
+                    args[3],
+                ))
             } else {
                 SyscallResult::Unsupported(id)
             }
@@ -244,7 +737,7 @@ pub fn handle(caller: Caller, id: SyscallId, args: [usize; 6]) -> SyscallResult
         // Thread syscalls
         SyscallId::THREAD_CREATE => {
             if let Some(handler) = THREAD_HANDLER.get() {
-                SyscallResult::Done(handler.thread_create(caller, args[0], args[1]))
+                SyscallResult::Done(handler.thread_create(caller, args[0], args[1], args[2]))
             } else {
                 SyscallResult::Unsupported(id)
             }
@@ -256,6 +749,13 @@ pub fn handle(caller: Caller, id: SyscallId, args: [usize; 6]) -> SyscallResult
                 SyscallResult::Unsupported(id)
             }
         }
+        SyscallId::PRCTL => {
+            if let Some(handler) = THREAD_HANDLER.get() {
+                SyscallResult::Done(handler.prctl(caller, args[0], args[1]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
         SyscallId::WAITTID => {
             if let Some(handler) = THREAD_HANDLER.get() {
                 SyscallResult::Done(handler.waittid(caller, args[0]))
@@ -299,6 +799,31 @@ pub fn handle(caller: Caller, id: SyscallId, args: [usize; 6]) -> SyscallResult
                 SyscallResult::Unsupported(id)
             }
         }
+        SyscallId::MUTEX_TRYLOCK => {
+            if let Some(handler) = SYNC_MUTEX_HANDLER.get() {
+                SyscallResult::Done(handler.mutex_trylock(caller, args[0]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::SEMAPHORE_DOWN_TIMEOUT => {
+            if let Some(handler) = SYNC_MUTEX_HANDLER.get() {
+                SyscallResult::Done(handler.semaphore_down_timeout(
+                    caller,
+                    args[0],
+                    args[1] as *const crate::TimeSpec,
+                ))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
+        SyscallId::SEMAPHORE_TRYDOWN => {
+            if let Some(handler) = SYNC_MUTEX_HANDLER.get() {
+                SyscallResult::Done(handler.semaphore_trydown(caller, args[0]))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
         SyscallId::MUTEX_UNLOCK => {
             if let Some(handler) = SYNC_MUTEX_HANDLER.get() {
                 SyscallResult::Done(handler.mutex_unlock(caller, args[0]))
@@ -327,6 +852,18 @@ pub fn handle(caller: Caller, id: SyscallId, args: [usize; 6]) -> SyscallResult
                 SyscallResult::Unsupported(id)
             }
         }
+        SyscallId::CONDVAR_WAIT_TIMEOUT => {
+            if let Some(handler) = SYNC_MUTEX_HANDLER.get() {
+                SyscallResult::Done(handler.condvar_wait_timeout(
+                    caller,
+                    args[0],
+                    args[1],
+                    args[2] as *const crate::TimeSpec,
+                ))
+            } else {
+                SyscallResult::Unsupported(id)
+            }
+        }
         _ => SyscallResult::Unsupported(id),
     }
 }
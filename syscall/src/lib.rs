@@ -8,6 +8,8 @@ compile_error!("features `kernel` and `user` cannot be enabled at the same time"
 #[allow(dead_code)]
 mod syscalls;
 
+use core::sync::atomic::{AtomicU64, Ordering};
+
 // Re-export signal-defs 的类型
 pub use signal_defs::{SignalAction, SignalNo, MAX_SIG};
 
@@ -52,6 +54,8 @@ impl TimeSpec {
     pub const MILLSECOND: TimeSpec = TimeSpec { tv_sec: 0, tv_nsec: 1_000_000 };
     pub const MICROSECOND: TimeSpec = TimeSpec { tv_sec: 0, tv_nsec: 1_000 };
     pub const NANOSECOND: TimeSpec = TimeSpec { tv_sec: 0, tv_nsec: 1 };
+    /// 可表示的最大时间，`Add` 溢出时饱和到此值
+    pub const MAX: TimeSpec = TimeSpec { tv_sec: usize::MAX, tv_nsec: NSEC_PER_SEC - 1 };
 
     /// 从毫秒数创建 TimeSpec
     pub fn from_millsecond(millsecond: usize) -> Self {
@@ -60,22 +64,117 @@ impl TimeSpec {
             tv_nsec: (millsecond % 1000) * 1_000_000,
         }
     }
+
+    /// 从毫秒数创建 TimeSpec，中间结果溢出时返回 `None`
+    ///
+    /// 秒数由除法得到、纳秒数小于 10^9，对 `usize` 输入目前总能成功，
+    /// 提供此接口是为了与 [`TimeSpec::checked_add`] 一起统一处理溢出。
+    pub fn try_from_millis(millsecond: usize) -> Option<Self> {
+        Some(TimeSpec {
+            tv_sec: millsecond / 1000,
+            tv_nsec: (millsecond % 1000).checked_mul(1_000_000)?,
+        })
+    }
+
+    /// 相加并规格化纳秒，任何一步溢出都返回 `None`
+    ///
+    /// 允许操作数的 `tv_nsec` 不小于 10^9，多出的部分进位到秒。
+    pub fn checked_add(self, other: TimeSpec) -> Option<TimeSpec> {
+        let tv_nsec = self.tv_nsec.checked_add(other.tv_nsec)?;
+        let tv_sec = self
+            .tv_sec
+            .checked_add(other.tv_sec)?
+            .checked_add(tv_nsec / NSEC_PER_SEC)?;
+        Some(TimeSpec {
+            tv_sec,
+            tv_nsec: tv_nsec % NSEC_PER_SEC,
+        })
+    }
+
+    /// 从纳秒数创建 TimeSpec
+    pub fn from_nanos(nanos: u64) -> Self {
+        TimeSpec {
+            tv_sec: (nanos / NSEC_PER_SEC as u64) as usize,
+            tv_nsec: (nanos % NSEC_PER_SEC as u64) as usize,
+        }
+    }
+
+    /// 换算为纳秒数，溢出时饱和到 `u64::MAX`
+    pub fn as_nanos(&self) -> u64 {
+        (self.tv_sec as u64)
+            .saturating_mul(NSEC_PER_SEC as u64)
+            .saturating_add(self.tv_nsec as u64)
+    }
+
+    /// 按当前的 [`clock_freq`] 把 `time` 寄存器的计数换算为时间
+    pub fn from_ticks(ticks: u64) -> Self {
+        let freq = clock_freq();
+        let nsec = (ticks % freq) as u128 * NSEC_PER_SEC as u128 / freq as u128;
+        TimeSpec {
+            tv_sec: (ticks / freq) as usize,
+            tv_nsec: nsec as usize,
+        }
+    }
+
+    /// 按当前的 [`clock_freq`] 换算为 `time` 寄存器的计数，溢出时饱和到 `u64::MAX`
+    pub fn to_ticks(&self) -> u64 {
+        let freq = clock_freq();
+        let nsec_ticks = self.tv_nsec as u128 * freq as u128 / NSEC_PER_SEC as u128;
+        (self.tv_sec as u64)
+            .saturating_mul(freq)
+            .saturating_add(u64::try_from(nsec_ticks).unwrap_or(u64::MAX))
+    }
+}
+
+/// 每秒的纳秒数
+const NSEC_PER_SEC: usize = 1_000_000_000;
+
+/// QEMU virt 平台的 `timebase-frequency`，未调用 [`set_clock_freq`] 时使用
+pub const DEFAULT_CLOCK_FREQ: u64 = 10_000_000;
+
+static CLOCK_FREQ: AtomicU64 = AtomicU64::new(DEFAULT_CLOCK_FREQ);
+
+/// 设置 `time` 寄存器的计数频率（Hz）
+///
+/// 内核应在启动时用设备树 `/cpus` 节点的 `timebase-frequency` 调用；`hz` 为 0 时忽略。
+pub fn set_clock_freq(hz: u64) {
+    if hz != 0 {
+        CLOCK_FREQ.store(hz, Ordering::Relaxed);
+    }
 }
 
+/// `time` 寄存器的计数频率（Hz）
+pub fn clock_freq() -> u64 {
+    CLOCK_FREQ.load(Ordering::Relaxed)
+}
+
+/// `time` 寄存器为 0 时的墙上时间，自 Unix 纪元起的纳秒数
+static BOOT_REALTIME: AtomicU64 = AtomicU64::new(0);
+
+/// 设置 `time` 寄存器为 0 时对应的墙上时间
+///
+/// 内核应在启动时读取 RTC（或使用固定纪元），减去已经过的单调时间后调用一次；
+/// 未调用时 `CLOCK_REALTIME` 从 Unix 纪元起算。
+pub fn set_boot_realtime(ts: TimeSpec) {
+    BOOT_REALTIME.store(ts.as_nanos(), Ordering::Relaxed);
+}
+
+/// `time` 寄存器为 0 时的墙上时间
+pub fn boot_realtime() -> TimeSpec {
+    TimeSpec::from_nanos(BOOT_REALTIME.load(Ordering::Relaxed))
+}
+
+/// `time` 寄存器读数为 `ticks` 时的 `CLOCK_REALTIME`，即启动时的墙上时间加上单调时间
+pub fn realtime_from_ticks(ticks: u64) -> TimeSpec {
+    boot_realtime() + TimeSpec::from_ticks(ticks)
+}
+
+/// 溢出时饱和到 [`TimeSpec::MAX`] 而不是回绕，避免睡眠截止时间回绕到过去而立即返回。
 impl core::ops::Add for TimeSpec {
     type Output = TimeSpec;
 
     fn add(self, other: TimeSpec) -> TimeSpec {
-        let mut tv_sec = self.tv_sec + other.tv_sec;
-        let mut tv_nsec = self.tv_nsec + other.tv_nsec;
-        
-        // 处理纳秒溢出
-        if tv_nsec >= 1_000_000_000 {
-            tv_sec += tv_nsec / 1_000_000_000;
-            tv_nsec %= 1_000_000_000;
-        }
-        
-        TimeSpec { tv_sec, tv_nsec }
+        self.checked_add(other).unwrap_or(TimeSpec::MAX)
     }
 }
 
@@ -94,6 +193,221 @@ pub const STDOUT: usize = 1;
 /// 标准调试输出文件描述符
 pub const STDDEBUG: usize = 2;
 
+/// `poll` 事件：有数据可读
+pub const POLLIN: i16 = 0x1;
+
+/// `poll` 事件：可以写入
+pub const POLLOUT: i16 = 0x4;
+
+/// `poll` 事件：文件描述符无效（仅出现在 `revents` 中）
+pub const POLLNVAL: i16 = 0x20;
+
+/// `poll` 的单个等待项
+///
+/// 使用 `#[repr(C)]` 保持与 Linux `struct pollfd` 一致的布局
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PollFd {
+    /// 等待的文件描述符，负数表示忽略该项
+    pub fd: i32,
+    /// 关心的事件（`POLLIN`/`POLLOUT`）
+    pub events: i16,
+    /// 内核写回的已就绪事件
+    pub revents: i16,
+}
+
+/// `statfs` 返回的文件系统容量统计
+///
+/// 使用 `#[repr(C)]` 以便内核直接按字节写回用户态
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FsStat {
+    /// 数据区总块数
+    pub total_blocks: u64,
+    /// 空闲数据块数
+    pub free_blocks: u64,
+    /// inode 总数
+    pub total_inodes: u64,
+    /// 空闲 inode 数
+    pub free_inodes: u64,
+}
+
+/// [`Dirent::d_type`]：目录
+pub const DT_DIR: u8 = 4;
+
+/// [`Dirent::d_type`]：普通文件
+pub const DT_REG: u8 = 8;
+
+/// `getdents` 写入用户缓冲区的目录项记录
+///
+/// 使用 `#[repr(C)]` 的定长布局，恰好 40 字节且没有填充，内核可直接按字节拷贝
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dirent {
+    /// inode 编号
+    pub d_ino: u64,
+    /// 文件类型，[`DT_REG`] 或 [`DT_DIR`]
+    pub d_type: u8,
+    /// 以 `\0` 结尾的文件名
+    pub d_name: [u8; 31],
+}
+
+impl Dirent {
+    /// 记录大小（字节）
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// 由 inode 编号、类型和文件名构造记录，过长的文件名被截断以保留结尾的 `\0`
+    pub fn new(d_ino: u64, d_type: u8, name: &str) -> Self {
+        let mut d_name = [0u8; 31];
+        let len = name.len().min(d_name.len() - 1);
+        d_name[..len].copy_from_slice(&name.as_bytes()[..len]);
+        Self {
+            d_ino,
+            d_type,
+            d_name,
+        }
+    }
+
+    /// 记录的字节表示
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self as *const _ as *const u8, Self::SIZE) }
+    }
+
+    /// 文件名，不含结尾的 `\0`
+    pub fn name(&self) -> &str {
+        let len = self.d_name.iter().position(|&c| c == 0).unwrap_or(self.d_name.len());
+        core::str::from_utf8(&self.d_name[..len]).unwrap_or("")
+    }
+}
+
+/// `gettimeofday` 返回的墙上时间，精确到微秒
+///
+/// 使用 `#[repr(C)]` 保持与 C `struct timeval` 一致的布局
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimeVal {
+    pub tv_sec: usize,
+    pub tv_usec: usize,
+}
+
+impl From<TimeSpec> for TimeVal {
+    /// 不足一微秒的部分向下取整
+    fn from(ts: TimeSpec) -> Self {
+        TimeVal {
+            tv_sec: ts.tv_sec,
+            tv_usec: ts.tv_nsec / 1_000,
+        }
+    }
+}
+
+/// `times` 返回的进程 CPU 时间，单位为 `time` 计数器的 tick
+///
+/// 使用 `#[repr(C)]` 保持与 C `struct tms` 一致的布局
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tms {
+    /// 用户态时间
+    pub tms_utime: usize,
+    /// 内核态时间
+    pub tms_stime: usize,
+    /// 已回收子进程的用户态时间之和
+    pub tms_cutime: usize,
+    /// 已回收子进程的内核态时间之和
+    pub tms_cstime: usize,
+}
+
+/// `sigwaitinfo` 返回的信号信息
+///
+/// 使用 `#[repr(C)]`，字段与 C `siginfo_t` 开头的三个字段一致
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SigInfo {
+    /// 信号编号
+    pub si_signo: i32,
+    /// 错误码，目前总为 0
+    pub si_errno: i32,
+    /// 信号来源，目前总为 [`SI_USER`]
+    pub si_code: i32,
+}
+
+/// `SigInfo::si_code`：信号由 `kill` 等用户调用发送
+pub const SI_USER: i32 = 0;
+
+/// `clock_nanosleep` 标志：`req` 是绝对时间而不是相对时长
+pub const TIMER_ABSTIME: usize = 1;
+
+/// `sigprocmask` 操作：把 `set` 中的信号加入掩码
+pub const SIG_BLOCK: usize = 0;
+
+/// `sigprocmask` 操作：把 `set` 中的信号移出掩码
+pub const SIG_UNBLOCK: usize = 1;
+
+/// `sigprocmask` 操作：用 `set` 替换掩码
+pub const SIG_SETMASK: usize = 2;
+
+/// `lseek` 起点：文件开头
+pub const SEEK_SET: usize = 0;
+
+/// `lseek` 起点：当前偏移
+pub const SEEK_CUR: usize = 1;
+
+/// `lseek` 起点：文件末尾
+pub const SEEK_END: usize = 2;
+
+/// `mmap` 保护位：不可访问
+pub const PROT_NONE: usize = 0;
+
+/// `mmap` 保护位：可读
+pub const PROT_READ: usize = 1;
+
+/// `mmap` 保护位：可写
+pub const PROT_WRITE: usize = 2;
+
+/// `mmap` 保护位：可执行
+pub const PROT_EXEC: usize = 4;
+
+/// `mmap` 标志：共享映射，修改在 `msync`/`munmap` 时写回文件
+pub const MAP_SHARED: usize = 0x01;
+
+/// `mmap` 标志：私有映射，建立时拷入文件内容，修改不写回
+pub const MAP_PRIVATE: usize = 0x02;
+
+/// `mmap` 标志：匿名映射，不关联文件，内容初始化为 0，忽略 `fd` 与 `offset`
+pub const MAP_ANONYMOUS: usize = 0x20;
+
+/// `msync` 标志：异步写回（目前与 [`MS_SYNC`] 相同，立即写回）
+pub const MS_ASYNC: usize = 1;
+
+/// `msync` 标志：同步写回
+pub const MS_SYNC: usize = 4;
+
+/// `madvise` 建议：无特殊处理
+pub const MADV_NORMAL: usize = 0;
+
+/// `madvise` 建议：丢弃区间内容，之后读取得到全零
+pub const MADV_DONTNEED: usize = 4;
+
+/// `setrlimit` 资源号：进程地址空间大小上限（字节）
+pub const RLIMIT_AS: usize = 9;
+
+/// 资源上限取值：不限制
+pub const RLIM_INFINITY: usize = usize::MAX;
+
+/// `set_priority` 接受的最低优先级
+pub const PRIO_MIN: isize = 1;
+
+/// `set_priority` 接受的最高优先级，数值越大越先被调度
+pub const PRIO_MAX: isize = 255;
+
+/// `prctl` 操作：设置当前线程名，`arg2` 指向以 0 结尾的字符串
+pub const PR_SET_NAME: usize = 15;
+
+/// `prctl` 操作：把当前线程名写入 `arg2` 指向的 [`TASK_COMM_LEN`] 字节缓冲区
+pub const PR_GET_NAME: usize = 16;
+
+/// 线程名缓冲区长度，含结尾的 0，最多保存 15 字节
+pub const TASK_COMM_LEN: usize = 16;
+
 #[cfg(feature = "user")]
 mod user;
 
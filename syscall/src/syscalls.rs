@@ -5,7 +5,13 @@ impl crate::SyscallId {
     pub const READ: crate::SyscallId = crate::SyscallId(63);
     pub const WRITE: crate::SyscallId = crate::SyscallId(64);
     pub const OPEN: crate::SyscallId = crate::SyscallId(56);
+    pub const DUP: crate::SyscallId = crate::SyscallId(23);
+    pub const DUP3: crate::SyscallId = crate::SyscallId(24);
     pub const CLOSE: crate::SyscallId = crate::SyscallId(57);
+    pub const PIPE2: crate::SyscallId = crate::SyscallId(59);
+    pub const LSEEK: crate::SyscallId = crate::SyscallId(62);
+    pub const PPOLL: crate::SyscallId = crate::SyscallId(73);
+    pub const STATFS: crate::SyscallId = crate::SyscallId(43);
     pub const EXIT: crate::SyscallId = crate::SyscallId(93);
     pub const EXIT_GROUP: crate::SyscallId = crate::SyscallId(94);
     pub const FORK: crate::SyscallId = crate::SyscallId(220);
@@ -13,13 +19,36 @@ impl crate::SyscallId {
     pub const WAIT4: crate::SyscallId = crate::SyscallId(260);
     pub const WAITID: crate::SyscallId = crate::SyscallId(281);
     pub const GETPID: crate::SyscallId = crate::SyscallId(172);
+    pub const SETRLIMIT: crate::SyscallId = crate::SyscallId(164);
+    pub const TIMES: crate::SyscallId = crate::SyscallId(153);
+    pub const GETUID: crate::SyscallId = crate::SyscallId(174);
+    pub const GETGID: crate::SyscallId = crate::SyscallId(176);
+    pub const SETUID: crate::SyscallId = crate::SyscallId(146);
+    pub const SETGID: crate::SyscallId = crate::SyscallId(144);
+    pub const SETPGID: crate::SyscallId = crate::SyscallId(154);
+    pub const GETPGID: crate::SyscallId = crate::SyscallId(155);
+    pub const GETSID: crate::SyscallId = crate::SyscallId(156);
+    pub const SETSID: crate::SyscallId = crate::SyscallId(157);
     pub const GETTID: crate::SyscallId = crate::SyscallId(178);
+    pub const PRCTL: crate::SyscallId = crate::SyscallId(167);
     pub const KILL: crate::SyscallId = crate::SyscallId(129);
+    pub const TGKILL: crate::SyscallId = crate::SyscallId(131);
     pub const SIGACTION: crate::SyscallId = crate::SyscallId(134);
     pub const SIGPROCMASK: crate::SyscallId = crate::SyscallId(135);
+    pub const RT_SIGPENDING: crate::SyscallId = crate::SyscallId(136);
+    pub const RT_SIGTIMEDWAIT: crate::SyscallId = crate::SyscallId(137);
     pub const RT_SIGRETURN: crate::SyscallId = crate::SyscallId(139);
+    pub const BRK: crate::SyscallId = crate::SyscallId(214);
+    pub const MUNMAP: crate::SyscallId = crate::SyscallId(215);
+    pub const MMAP: crate::SyscallId = crate::SyscallId(222);
+    pub const MSYNC: crate::SyscallId = crate::SyscallId(227);
+    pub const MADVISE: crate::SyscallId = crate::SyscallId(233);
     pub const SCHED_YIELD: crate::SyscallId = crate::SyscallId(124);
+    pub const GETCPU: crate::SyscallId = crate::SyscallId(168);
     pub const CLOCK_GETTIME: crate::SyscallId = crate::SyscallId(113);
+    pub const GETTIMEOFDAY: crate::SyscallId = crate::SyscallId(169);
+    pub const CLOCK_NANOSLEEP: crate::SyscallId = crate::SyscallId(115);
+    pub const NANOSLEEP: crate::SyscallId = crate::SyscallId(101);
     pub const CLONE: crate::SyscallId = crate::SyscallId(220);
     pub const SEMOP: crate::SyscallId = crate::SyscallId(65);
     pub const SEMGET: crate::SyscallId = crate::SyscallId(66);
@@ -32,4 +61,15 @@ impl crate::SyscallId {
     pub const CONDVAR_WAIT: crate::SyscallId = crate::SyscallId(405);
     pub const THREAD_CREATE: crate::SyscallId = crate::SyscallId(406);
     pub const WAITTID: crate::SyscallId = crate::SyscallId(407);
+    pub const RENAME: crate::SyscallId = crate::SyscallId(408);
+    pub const VFORK: crate::SyscallId = crate::SyscallId(409);
+    pub const SBRK: crate::SyscallId = crate::SyscallId(410);
+    pub const ALARM: crate::SyscallId = crate::SyscallId(411);
+    pub const MUTEX_TRYLOCK: crate::SyscallId = crate::SyscallId(412);
+    pub const SEMAPHORE_TRYDOWN: crate::SyscallId = crate::SyscallId(413);
+    pub const SET_PRIORITY: crate::SyscallId = crate::SyscallId(414);
+    pub const GET_PRIORITY: crate::SyscallId = crate::SyscallId(415);
+    pub const CONDVAR_WAIT_TIMEOUT: crate::SyscallId = crate::SyscallId(416);
+    pub const SEMAPHORE_DOWN_TIMEOUT: crate::SyscallId = crate::SyscallId(417);
+    pub const GETDENTS: crate::SyscallId = crate::SyscallId(418);
 }
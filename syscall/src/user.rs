@@ -2,7 +2,7 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 use bitflags::bitflags;
-use crate::{SyscallId, ClockId, TimeSpec, SignalNo, SignalAction};
+use crate::{SyscallId, ClockId, TimeSpec, TimeVal, SignalNo, SignalAction, PollFd, FsStat, Tms, SigInfo};
 
 bitflags! {
     /// 文件打开标志
@@ -12,6 +12,8 @@ bitflags! {
         const RDWR = 2;
         const CREATE = 512;
         const TRUNC = 1024;
+        const APPEND = 2048;
+        const DIRECTORY = 65536;
     }
 }
 
@@ -230,6 +232,99 @@ pub fn close(fd: usize) -> isize {
     }
 }
 
+/// 创建管道，`fds[0]` 为读端，`fds[1]` 为写端
+///
+/// 读空管道会阻塞直到有数据写入；所有写端关闭后读返回 0。
+pub fn pipe(fds: &mut [usize; 2]) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::PIPE2, fds.as_mut_ptr() as usize)
+    }
+}
+
+/// 移动文件偏移，`whence` 取 `SEEK_SET`/`SEEK_CUR`/`SEEK_END`，返回新偏移
+pub fn lseek(fd: usize, offset: isize, whence: usize) -> isize {
+    unsafe {
+        native::syscall3(SyscallId::LSEEK, fd, offset as usize, whence)
+    }
+}
+
+/// 复制文件描述符，返回新的描述符；新旧描述符共享偏移
+pub fn dup(fd: usize) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::DUP, fd)
+    }
+}
+
+/// 把 `oldfd` 复制到 `newfd`，`newfd` 已打开时先关闭，返回 `newfd`
+pub fn dup2(oldfd: usize, newfd: usize) -> isize {
+    unsafe {
+        native::syscall3(SyscallId::DUP3, oldfd, newfd, 0)
+    }
+}
+
+/// 等待 `fds` 中任一文件描述符就绪，返回就绪项数；超时返回 0
+///
+/// `timeout` 为 `None` 表示一直等待，为零表示只检查一次。不替换信号掩码
+pub fn ppoll(fds: &mut [PollFd], timeout: Option<&TimeSpec>) -> isize {
+    let timeout = timeout.map_or(core::ptr::null(), |t| t as *const TimeSpec);
+    unsafe {
+        native::syscall5(
+            SyscallId::PPOLL,
+            fds.as_mut_ptr() as usize,
+            fds.len(),
+            timeout as usize,
+            0,
+            core::mem::size_of::<usize>(),
+        )
+    }
+}
+
+/// 以毫秒为超时单位的 [`ppoll`]，`timeout_ms` 为负表示一直等待
+pub fn poll(fds: &mut [PollFd], timeout_ms: isize) -> isize {
+    if timeout_ms < 0 {
+        return ppoll(fds, None);
+    }
+    let ms = timeout_ms as usize;
+    let timeout = TimeSpec {
+        tv_sec: ms / 1000,
+        tv_nsec: ms % 1000 * 1_000_000,
+    };
+    ppoll(fds, Some(&timeout))
+}
+
+/// 重命名文件
+pub fn rename(old_path: &str, new_path: &str) -> isize {
+    let mut c_old = Vec::with_capacity(old_path.len() + 1);
+    c_old.extend_from_slice(old_path.as_bytes());
+    c_old.push(0);
+    let mut c_new = Vec::with_capacity(new_path.len() + 1);
+    c_new.extend_from_slice(new_path.as_bytes());
+    c_new.push(0);
+    unsafe {
+        native::syscall2(SyscallId::RENAME, c_old.as_ptr() as usize, c_new.as_ptr() as usize)
+    }
+}
+
+/// 查询 `path` 所在文件系统的容量
+pub fn statfs(path: &str, buf: &mut FsStat) -> isize {
+    let mut c_path = Vec::with_capacity(path.len() + 1);
+    c_path.extend_from_slice(path.as_bytes());
+    c_path.push(0);
+    unsafe {
+        native::syscall2(SyscallId::STATFS, c_path.as_ptr() as usize, buf as *mut FsStat as usize)
+    }
+}
+
+/// 从目录 `fd` 读取目录项，以 [`crate::Dirent`] 记录填充 `buf`，返回写入的字节数
+///
+/// 多次调用依次返回后续目录项，读完后返回 0。记录格式不是 Linux 的 `linux_dirent64`，
+/// 因此使用自定义的调用号而不是 `getdents64`
+pub fn getdents(fd: usize, buf: &mut [u8]) -> isize {
+    unsafe {
+        native::syscall3(SyscallId::GETDENTS, fd, buf.as_mut_ptr() as usize, buf.len())
+    }
+}
+
 /// 退出进程
 pub fn exit(exit_code: i32) -> isize {
     unsafe {
@@ -244,6 +339,27 @@ pub fn sched_yield() -> isize {
     }
 }
 
+/// 获取当前所在的 hart 编号与 NUMA 节点
+pub fn getcpu(cpu: *mut usize, node: *mut usize) -> isize {
+    unsafe {
+        native::syscall2(SyscallId::GETCPU, cpu as usize, node as usize)
+    }
+}
+
+/// 设置当前线程的调度优先级，取值范围为 [`crate::PRIO_MIN`]..=[`crate::PRIO_MAX`]
+pub fn set_priority(prio: isize) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::SET_PRIORITY, prio as usize)
+    }
+}
+
+/// 获取当前线程的调度优先级
+pub fn get_priority() -> isize {
+    unsafe {
+        native::syscall0(SyscallId::GET_PRIORITY)
+    }
+}
+
 /// 获取时钟时间
 pub fn clock_gettime(clockid: ClockId, tp: *mut TimeSpec) -> isize {
     unsafe {
@@ -251,6 +367,39 @@ pub fn clock_gettime(clockid: ClockId, tp: *mut TimeSpec) -> isize {
     }
 }
 
+/// 获取墙上时间（秒和微秒）
+pub fn gettimeofday(tv: &mut TimeVal) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::GETTIMEOFDAY, tv as *mut TimeVal as usize)
+    }
+}
+
+/// 睡眠 `req` 指定的时长，期间线程阻塞而不占用 CPU
+pub fn nanosleep(req: &TimeSpec) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::NANOSLEEP, req as *const TimeSpec as usize)
+    }
+}
+
+/// 睡眠到 `req` 指定的时间，`flags` 为 `TIMER_ABSTIME` 时按绝对时间处理
+pub fn clock_nanosleep(clockid: ClockId, flags: usize, req: &TimeSpec) -> isize {
+    unsafe {
+        native::syscall3(
+            SyscallId::CLOCK_NANOSLEEP,
+            clockid.0,
+            flags,
+            req as *const TimeSpec as usize,
+        )
+    }
+}
+
+/// `seconds` 秒后向当前进程发送 `SIGALRM`，为 0 时取消；返回之前的闹钟剩余的秒数
+pub fn alarm(seconds: usize) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::ALARM, seconds)
+    }
+}
+
 /// 创建子进程
 pub fn fork() -> isize {
     unsafe {
@@ -258,6 +407,18 @@ pub fn fork() -> isize {
     }
 }
 
+/// 创建与父进程共享地址空间的子进程，父进程阻塞到子进程 exec 或 exit 后才返回
+///
+/// 子进程返回 0，父进程返回子进程 pid。子进程与父进程共用同一个栈，
+/// 因此子进程只能立即调用 [`exec`] 或 [`exit`]，不能从调用 `vfork` 的函数返回。
+/// 这里强制内联，避免子进程后续的调用覆盖本函数的栈帧，使父进程恢复后返回到错误的地址。
+#[inline(always)]
+pub fn vfork() -> isize {
+    unsafe {
+        native::syscall0(SyscallId::VFORK)
+    }
+}
+
 /// 执行程序
 pub fn exec(path: &str) -> isize {
     let mut c_path = Vec::with_capacity(path.len() + 1);
@@ -295,6 +456,52 @@ pub fn waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
     }
 }
 
+/// 把文件 `fd` 从 `offset` 开始的 `len` 字节映射到内存，成功返回映射起始地址，失败返回 -1
+///
+/// `prot` 取 `PROT_READ` 等的组合，`flags` 取 `MAP_SHARED` 或 `MAP_PRIVATE`；
+/// `addr` 仅作提示，内核自行选择映射位置。
+/// `flags` 为 `MAP_PRIVATE | MAP_ANONYMOUS` 时映射 `len` 字节（须按页对齐）的全零内存，不使用 `fd`。
+pub fn mmap(addr: usize, len: usize, prot: usize, flags: usize, fd: isize, offset: usize) -> isize {
+    unsafe {
+        native::syscall6(SyscallId::MMAP, addr, len, prot, flags, fd as usize, offset)
+    }
+}
+
+/// 解除 [`mmap`] 建立的映射，`MAP_SHARED` 映射的内容先写回文件
+pub fn munmap(addr: usize, len: usize) -> isize {
+    unsafe {
+        native::syscall2(SyscallId::MUNMAP, addr, len)
+    }
+}
+
+/// 把堆顶设为 `new_top`，返回新的堆顶；`new_top` 为 0 时返回当前堆顶
+pub fn brk(new_top: usize) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::BRK, new_top)
+    }
+}
+
+/// 把堆顶移动 `delta` 字节，返回原来的堆顶；新增的堆空间清零
+pub fn sbrk(delta: isize) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::SBRK, delta as usize)
+    }
+}
+
+/// 把 `[addr, addr + len)` 内 `MAP_SHARED` 映射的修改写回文件
+pub fn msync(addr: usize, len: usize, flags: usize) -> isize {
+    unsafe {
+        native::syscall3(SyscallId::MSYNC, addr, len, flags)
+    }
+}
+
+/// 对 `[addr, addr + len)` 给出使用建议（如 `MADV_DONTNEED`）
+pub fn madvise(addr: usize, len: usize, advice: usize) -> isize {
+    unsafe {
+        native::syscall3(SyscallId::MADVISE, addr, len, advice)
+    }
+}
+
 /// 获取当前进程 ID
 pub fn getpid() -> isize {
     unsafe {
@@ -302,6 +509,76 @@ pub fn getpid() -> isize {
     }
 }
 
+/// 设置当前进程的资源上限（如 `RLIMIT_AS`，单位字节）
+pub fn setrlimit(resource: usize, limit: usize) -> isize {
+    unsafe {
+        native::syscall2(SyscallId::SETRLIMIT, resource, limit)
+    }
+}
+
+/// 查询当前进程及已回收子进程的 CPU 时间
+pub fn times(tms: &mut Tms) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::TIMES, tms as *mut Tms as usize)
+    }
+}
+
+/// 获取当前进程的用户 id
+pub fn getuid() -> isize {
+    unsafe {
+        native::syscall0(SyscallId::GETUID)
+    }
+}
+
+/// 获取当前进程的组 id
+pub fn getgid() -> isize {
+    unsafe {
+        native::syscall0(SyscallId::GETGID)
+    }
+}
+
+/// 设置当前进程的用户 id，子进程在 fork 时继承
+pub fn setuid(uid: usize) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::SETUID, uid)
+    }
+}
+
+/// 设置当前进程的组 id，子进程在 fork 时继承
+pub fn setgid(gid: usize) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::SETGID, gid)
+    }
+}
+
+/// 把进程 `pid` 移入进程组 `pgid`，两者为 0 时表示当前进程
+pub fn setpgid(pid: usize, pgid: usize) -> isize {
+    unsafe {
+        native::syscall2(SyscallId::SETPGID, pid, pgid)
+    }
+}
+
+/// 获取进程 `pid` 的进程组 id，`pid` 为 0 时表示当前进程
+pub fn getpgid(pid: usize) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::GETPGID, pid)
+    }
+}
+
+/// 获取进程 `pid` 的会话 id，`pid` 为 0 时表示当前进程
+pub fn getsid(pid: usize) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::GETSID, pid)
+    }
+}
+
+/// 创建以当前进程为首进程的新会话，返回会话 id
+pub fn setsid() -> isize {
+    unsafe {
+        native::syscall0(SyscallId::SETSID)
+    }
+}
+
 /// 发送信号
 pub fn kill(pid: isize, signum: SignalNo) -> isize {
     unsafe {
@@ -309,6 +586,13 @@ pub fn kill(pid: isize, signum: SignalNo) -> isize {
     }
 }
 
+/// 向进程 `pid` 中的线程 `tid` 发送信号
+pub fn tgkill(pid: isize, tid: isize, signum: SignalNo) -> isize {
+    unsafe {
+        native::syscall3(SyscallId::TGKILL, pid as usize, tid as usize, signum as u8 as usize)
+    }
+}
+
 /// 设置信号处理动作
 pub fn sigaction(signum: SignalNo, action: *const SignalAction, old_action: *const SignalAction) -> isize {
     unsafe {
@@ -321,13 +605,45 @@ pub fn sigaction(signum: SignalNo, action: *const SignalAction, old_action: *con
     }
 }
 
-/// 设置信号掩码
-pub fn sigprocmask(mask: usize) -> isize {
+/// 修改信号掩码
+///
+/// `how` 取 `SIG_BLOCK`/`SIG_UNBLOCK`/`SIG_SETMASK`；`oldset` 非空时写回旧掩码
+pub fn sigprocmask(how: usize, set: usize, oldset: *mut usize) -> isize {
+    unsafe {
+        native::syscall3(SyscallId::SIGPROCMASK, how, set, oldset as usize)
+    }
+}
+
+/// 查询已到达但被掩码阻塞的信号，位图写入 `set`
+pub fn sigpending(set: &mut usize) -> isize {
     unsafe {
-        native::syscall1(SyscallId::SIGPROCMASK, mask)
+        native::syscall1(SyscallId::RT_SIGPENDING, set as *mut usize as usize)
     }
 }
 
+/// 阻塞到 `set` 中的某个信号待处理，同步取出并返回其编号
+///
+/// 取出的信号不会再触发处理函数；通常先用 [`sigprocmask`] 屏蔽这些信号，
+/// 避免它们在调用前被异步处理掉。`info` 非空时写入 [`SigInfo`]。
+/// `timeout` 为 `None` 时一直等待，否则超时返回 -1。
+pub fn sigtimedwait(set: usize, info: *mut SigInfo, timeout: Option<&TimeSpec>) -> isize {
+    let timeout = timeout.map_or(core::ptr::null(), |t| t as *const TimeSpec);
+    unsafe {
+        native::syscall4(
+            SyscallId::RT_SIGTIMEDWAIT,
+            &set as *const usize as usize,
+            info as usize,
+            timeout as usize,
+            core::mem::size_of::<usize>(),
+        )
+    }
+}
+
+/// 不带超时的 [`sigtimedwait`]
+pub fn sigwaitinfo(set: usize, info: *mut SigInfo) -> isize {
+    sigtimedwait(set, info, None)
+}
+
 /// 从信号处理函数返回
 pub fn sigreturn() -> isize {
     unsafe {
@@ -337,8 +653,13 @@ pub fn sigreturn() -> isize {
 
 /// 创建线程
 pub fn thread_create(entry: usize, arg: usize) -> isize {
+    thread_create_with_tls(entry, arg, 0)
+}
+
+/// 创建线程，并把新线程的 `tp` 设为 `tls`（为 0 时不设置）
+pub fn thread_create_with_tls(entry: usize, arg: usize, tls: usize) -> isize {
     unsafe {
-        native::syscall2(SyscallId::THREAD_CREATE, entry, arg)
+        native::syscall3(SyscallId::THREAD_CREATE, entry, arg, tls)
     }
 }
 
@@ -349,6 +670,13 @@ pub fn gettid() -> isize {
     }
 }
 
+/// 线程属性控制，如 `prctl(PR_SET_NAME, name.as_ptr() as usize)`
+pub fn prctl(option: usize, arg2: usize) -> isize {
+    unsafe {
+        native::syscall2(SyscallId::PRCTL, option, arg2)
+    }
+}
+
 /// 等待线程退出
 pub fn waittid(tid: usize) -> isize {
     unsafe {
@@ -377,6 +705,24 @@ pub fn semaphore_down(sem_id: usize) -> isize {
     }
 }
 
+/// 最多阻塞 `timeout` 指定时长的信号量 P 操作，超时返回 -1
+pub fn semaphore_down_timeout(sem_id: usize, timeout: &TimeSpec) -> isize {
+    unsafe {
+        native::syscall2(
+            SyscallId::SEMAPHORE_DOWN_TIMEOUT,
+            sem_id,
+            timeout as *const TimeSpec as usize,
+        )
+    }
+}
+
+/// 不阻塞的信号量 P 操作，没有剩余资源时返回 -1
+pub fn semaphore_trydown(sem_id: usize) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::SEMAPHORE_TRYDOWN, sem_id)
+    }
+}
+
 /// 创建互斥锁
 pub fn mutex_create(blocking: bool) -> isize {
     unsafe {
@@ -391,6 +737,13 @@ pub fn mutex_lock(mutex_id: usize) -> isize {
     }
 }
 
+/// 尝试锁定互斥锁，锁被占用时不阻塞，直接返回 -1
+pub fn mutex_trylock(mutex_id: usize) -> isize {
+    unsafe {
+        native::syscall1(SyscallId::MUTEX_TRYLOCK, mutex_id)
+    }
+}
+
 /// 解锁互斥锁
 pub fn mutex_unlock(mutex_id: usize) -> isize {
     unsafe {
@@ -418,3 +771,15 @@ pub fn condvar_wait(condvar_id: usize, mutex_id: usize) -> isize {
         native::syscall2(SyscallId::CONDVAR_WAIT, condvar_id, mutex_id)
     }
 }
+
+/// 在条件变量上最多等待 `timeout` 指定的时长，超时返回 -1；返回时已重新持有互斥锁
+pub fn condvar_wait_timeout(condvar_id: usize, mutex_id: usize, timeout: &TimeSpec) -> isize {
+    unsafe {
+        native::syscall3(
+            SyscallId::CONDVAR_WAIT_TIMEOUT,
+            condvar_id,
+            mutex_id,
+            timeout as *const TimeSpec as usize,
+        )
+    }
+}
@@ -26,7 +26,47 @@ fn test_syscall_id_constants() {
     assert_eq!(SyscallId::CLOCK_GETTIME.0, 113);
     assert_eq!(SyscallId::GETPID.0, 172);
     assert_eq!(SyscallId::GETTID.0, 178);
+    assert_eq!(SyscallId::PRCTL.0, 167);
     assert_eq!(SyscallId::SCHED_YIELD.0, 124);
+    assert_eq!(SyscallId::GETCPU.0, 168);
+    assert_eq!(SyscallId::RENAME.0, 408);
+    assert_eq!(SyscallId::VFORK.0, 409);
+    assert_eq!(SyscallId::STATFS.0, 43);
+    assert_eq!(SyscallId::PIPE2.0, 59);
+    assert_eq!(SyscallId::DUP.0, 23);
+    assert_eq!(SyscallId::DUP3.0, 24);
+    assert_eq!(SyscallId::LSEEK.0, 62);
+    assert_eq!(SyscallId::BRK.0, 214);
+    assert_eq!(SyscallId::SBRK.0, 410);
+    assert_eq!(SyscallId::ALARM.0, 411);
+    assert_eq!(SyscallId::MUTEX_TRYLOCK.0, 412);
+    assert_eq!(SyscallId::SEMAPHORE_TRYDOWN.0, 413);
+    assert_eq!(SyscallId::SET_PRIORITY.0, 414);
+    assert_eq!(SyscallId::GET_PRIORITY.0, 415);
+    assert_eq!(SyscallId::CONDVAR_WAIT_TIMEOUT.0, 416);
+    assert_eq!(SyscallId::SEMAPHORE_DOWN_TIMEOUT.0, 417);
+    assert_eq!(SyscallId::GETDENTS.0, 418);
+    assert_eq!(SyscallId::TGKILL.0, 131);
+    assert_eq!(SyscallId::SETRLIMIT.0, 164);
+    assert_eq!(SyscallId::TIMES.0, 153);
+    assert_eq!(SyscallId::GETUID.0, 174);
+    assert_eq!(SyscallId::GETGID.0, 176);
+    assert_eq!(SyscallId::SETUID.0, 146);
+    assert_eq!(SyscallId::SETGID.0, 144);
+    assert_eq!(SyscallId::PPOLL.0, 73);
+    assert_eq!(SyscallId::MADVISE.0, 233);
+    assert_eq!(SyscallId::RT_SIGPENDING.0, 136);
+    assert_eq!(SyscallId::RT_SIGTIMEDWAIT.0, 137);
+    assert_eq!(SyscallId::MMAP.0, 222);
+    assert_eq!(SyscallId::MUNMAP.0, 215);
+    assert_eq!(SyscallId::MSYNC.0, 227);
+    assert_eq!(SyscallId::CLOCK_NANOSLEEP.0, 115);
+    assert_eq!(SyscallId::NANOSLEEP.0, 101);
+    assert_eq!(SyscallId::GETTIMEOFDAY.0, 169);
+    assert_eq!(SyscallId::SETPGID.0, 154);
+    assert_eq!(SyscallId::GETPGID.0, 155);
+    assert_eq!(SyscallId::GETSID.0, 156);
+    assert_eq!(SyscallId::SETSID.0, 157);
 }
 
 #[test]
@@ -37,6 +77,102 @@ fn test_io_constants() {
     assert_eq!(STDDEBUG, 2);
 }
 
+#[test]
+fn test_poll_fd_layout() {
+    // 测试 PollFd 与 Linux struct pollfd 布局一致
+    assert_eq!(core::mem::size_of::<PollFd>(), 8);
+    assert_eq!(core::mem::align_of::<PollFd>(), 4);
+    let pfd = PollFd { fd: 3, events: POLLIN | POLLOUT, revents: 0 };
+    assert_eq!(pfd.events & POLLIN, POLLIN);
+    assert_eq!(pfd.events & POLLNVAL, 0);
+    assert_eq!(PollFd::default().fd, 0);
+}
+
+#[test]
+fn test_dirent_layout() {
+    // 测试 Dirent 为 40 字节的定长记录，字段偏移固定
+    assert_eq!(Dirent::SIZE, 40);
+    assert_eq!(core::mem::align_of::<Dirent>(), 8);
+    assert_eq!(core::mem::offset_of!(Dirent, d_ino), 0);
+    assert_eq!(core::mem::offset_of!(Dirent, d_type), 8);
+    assert_eq!(core::mem::offset_of!(Dirent, d_name), 9);
+    let dirent = Dirent::new(5, DT_DIR, "sub");
+    assert_eq!(dirent.d_ino, 5);
+    assert_eq!(dirent.name(), "sub");
+    assert_eq!(dirent.as_bytes().len(), Dirent::SIZE);
+    assert_eq!(&dirent.as_bytes()[9..13], b"sub\0");
+    assert_ne!(DT_DIR, DT_REG);
+    // 过长的文件名被截断，仍以 `\0` 结尾
+    let long = Dirent::new(1, DT_REG, &"x".repeat(40));
+    assert_eq!(long.name().len(), 30);
+}
+
+#[test]
+fn test_priority_range() {
+    // 优先级范围可以无损放进调度器的 u8 优先级
+    assert_eq!(PRIO_MIN, 1);
+    assert_eq!(PRIO_MAX, 255);
+    assert_eq!(PRIO_MAX as u8 as isize, PRIO_MAX);
+}
+
+#[test]
+fn test_rlimit_constants() {
+    // 测试资源上限常量
+    assert_eq!(RLIMIT_AS, 9);
+    assert_eq!(RLIM_INFINITY, usize::MAX);
+}
+
+#[test]
+fn test_prctl_constants() {
+    // 测试 prctl 操作号与 Linux 一致
+    assert_eq!(PR_SET_NAME, 15);
+    assert_eq!(PR_GET_NAME, 16);
+    assert_eq!(TASK_COMM_LEN, 16);
+}
+
+#[test]
+fn test_sigprocmask_how_constants() {
+    // 测试 sigprocmask 的 how 取值与 Linux 一致
+    assert_eq!(SIG_BLOCK, 0);
+    assert_eq!(SIG_UNBLOCK, 1);
+    assert_eq!(SIG_SETMASK, 2);
+}
+
+#[test]
+fn test_madvise_constants() {
+    // 测试 madvise 建议常量与 Linux 一致
+    assert_eq!(MADV_NORMAL, 0);
+    assert_eq!(MADV_DONTNEED, 4);
+}
+
+#[test]
+fn test_mmap_constants() {
+    // 测试 mmap/msync 常量与 Linux 一致
+    assert_eq!(PROT_NONE, 0);
+    assert_eq!(PROT_READ | PROT_WRITE | PROT_EXEC, 7);
+    assert_eq!(MAP_SHARED, 0x01);
+    assert_eq!(MAP_PRIVATE, 0x02);
+    assert_eq!(MAP_ANONYMOUS, 0x20);
+    assert_eq!(MS_ASYNC, 1);
+    assert_eq!(MS_SYNC, 4);
+}
+
+#[test]
+fn test_seek_constants() {
+    // 测试 lseek 起点常量与 Linux 一致
+    assert_eq!(SEEK_SET, 0);
+    assert_eq!(SEEK_CUR, 1);
+    assert_eq!(SEEK_END, 2);
+}
+
+#[test]
+fn test_siginfo_layout() {
+    // 测试 SigInfo 与 C siginfo_t 开头字段布局一致
+    assert_eq!(core::mem::size_of::<SigInfo>(), 12);
+    assert_eq!(SigInfo::default().si_signo, 0);
+    assert_eq!(SI_USER, 0);
+}
+
 #[test]
 fn test_clock_id_constants() {
     // 测试 ClockId 常量
@@ -44,6 +180,7 @@ fn test_clock_id_constants() {
     assert_eq!(ClockId::CLOCK_MONOTONIC.0, 1);
     assert_eq!(ClockId::CLOCK_PROCESS_CPUTIME_ID.0, 2);
     assert_eq!(ClockId::CLOCK_THREAD_CPUTIME_ID.0, 3);
+    assert_eq!(TIMER_ABSTIME, 1);
 }
 
 #[test]
@@ -115,6 +252,114 @@ fn test_time_spec_add() {
     assert_eq!(result2.tv_nsec, 100_000_000);
 }
 
+#[test]
+fn test_time_spec_overflow() {
+    // 测试接近上限时 checked_add 报告溢出，Add 饱和而不回绕
+    let near_max = TimeSpec {
+        tv_sec: usize::MAX,
+        tv_nsec: 600_000_000,
+    };
+    let half = TimeSpec {
+        tv_sec: 0,
+        tv_nsec: 500_000_000,
+    };
+    // 纳秒进位导致秒数溢出
+    assert_eq!(near_max.checked_add(half), None);
+    assert_eq!(near_max + half, TimeSpec::MAX);
+    // 秒数直接溢出
+    assert_eq!(near_max.checked_add(TimeSpec::SECOND), None);
+    assert_eq!(near_max + TimeSpec::SECOND, TimeSpec::MAX);
+    assert!(near_max + TimeSpec::SECOND > near_max);
+
+    // 未规格化的纳秒数相加溢出
+    let big_nsec = TimeSpec {
+        tv_sec: 0,
+        tv_nsec: usize::MAX,
+    };
+    assert_eq!(big_nsec.checked_add(TimeSpec::NANOSECOND), None);
+    // 未规格化但不溢出时进位到秒
+    let sum = big_nsec.checked_add(TimeSpec::ZERO).unwrap();
+    assert_eq!(sum.tv_sec, usize::MAX / 1_000_000_000);
+    assert_eq!(sum.tv_nsec, usize::MAX % 1_000_000_000);
+
+    // 正常情况与 Add 一致
+    assert_eq!(
+        TimeSpec::SECOND.checked_add(TimeSpec::MILLSECOND),
+        Some(TimeSpec::SECOND + TimeSpec::MILLSECOND)
+    );
+}
+
+#[test]
+fn test_time_spec_try_from_millis() {
+    // 测试 try_from_millis 与 from_millsecond 一致，大输入也不回绕
+    assert_eq!(TimeSpec::try_from_millis(1500), Some(TimeSpec::from_millsecond(1500)));
+    let max = TimeSpec::try_from_millis(usize::MAX).unwrap();
+    assert_eq!(max.tv_sec, usize::MAX / 1000);
+    assert_eq!(max.tv_nsec, (usize::MAX % 1000) * 1_000_000);
+}
+
+/// 时钟频率是全局状态，修改它的测试需要串行执行
+static CLOCK_FREQ_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[test]
+fn test_clock_freq_ticks() {
+    // 测试同样的 tick 数在不同时钟频率下换算出不同的时间
+    let _guard = CLOCK_FREQ_LOCK.lock().unwrap();
+    let ticks = 30_000_000;
+    set_clock_freq(DEFAULT_CLOCK_FREQ);
+    assert_eq!(TimeSpec::from_ticks(ticks), TimeSpec { tv_sec: 3, tv_nsec: 0 });
+    set_clock_freq(20_000_000);
+    assert_eq!(clock_freq(), 20_000_000);
+    assert_eq!(TimeSpec::from_ticks(ticks), TimeSpec { tv_sec: 1, tv_nsec: 500_000_000 });
+    assert_eq!(TimeSpec::from_ticks(ticks).to_ticks(), ticks);
+    // 0 Hz 被忽略
+    set_clock_freq(0);
+    assert_eq!(clock_freq(), 20_000_000);
+    assert_eq!(TimeSpec::MAX.to_ticks(), u64::MAX);
+    set_clock_freq(DEFAULT_CLOCK_FREQ);
+
+    // 睡眠时长换算为 tick：不足一个 tick 的部分向下取整
+    assert_eq!(TimeSpec::ZERO.to_ticks(), 0);
+    assert_eq!(TimeSpec::SECOND.to_ticks(), DEFAULT_CLOCK_FREQ);
+    assert_eq!(TimeSpec::from_millsecond(50).to_ticks(), DEFAULT_CLOCK_FREQ / 20);
+    assert_eq!(TimeSpec::MICROSECOND.to_ticks(), DEFAULT_CLOCK_FREQ / 1_000_000);
+    assert_eq!(TimeSpec::NANOSECOND.to_ticks(), 0);
+    let req = TimeSpec { tv_sec: 2, tv_nsec: 250_000_000 };
+    assert_eq!(req.to_ticks(), DEFAULT_CLOCK_FREQ * 9 / 4);
+}
+
+#[test]
+fn test_realtime_from_ticks() {
+    // 测试 CLOCK_REALTIME = 启动时的墙上时间 + 单调时间
+    let _guard = CLOCK_FREQ_LOCK.lock().unwrap();
+    set_clock_freq(DEFAULT_CLOCK_FREQ);
+    assert_eq!(boot_realtime(), TimeSpec::ZERO);
+    assert_eq!(realtime_from_ticks(25_000_000), TimeSpec { tv_sec: 2, tv_nsec: 500_000_000 });
+
+    let boot = TimeSpec { tv_sec: 1_700_000_000, tv_nsec: 600_000_000 };
+    set_boot_realtime(boot);
+    assert_eq!(boot_realtime(), boot);
+    assert_eq!(realtime_from_ticks(0), boot);
+    // 纳秒部分进位到秒
+    assert_eq!(
+        realtime_from_ticks(25_000_000),
+        TimeSpec { tv_sec: 1_700_000_003, tv_nsec: 100_000_000 }
+    );
+    assert_eq!(
+        TimeVal::from(realtime_from_ticks(1)),
+        TimeVal { tv_sec: 1_700_000_000, tv_usec: 600_000 }
+    );
+    set_boot_realtime(TimeSpec::ZERO);
+}
+
+#[test]
+fn test_time_spec_nanos() {
+    assert_eq!(TimeSpec::from_nanos(1_500_000_001), TimeSpec { tv_sec: 1, tv_nsec: 500_000_001 });
+    assert_eq!(TimeSpec::from_nanos(1_500_000_001).as_nanos(), 1_500_000_001);
+    assert_eq!(TimeSpec::MAX.as_nanos(), u64::MAX);
+    assert_eq!(core::mem::size_of::<TimeVal>(), 16);
+}
+
 #[test]
 fn test_time_spec_display() {
     // 测试 TimeSpec 的 Display trait
@@ -196,6 +441,12 @@ fn test_open_flags() {
     
     let trunc = OpenFlags::TRUNC;
     assert_eq!(trunc.bits(), 1024);
+
+    let append = OpenFlags::APPEND;
+    assert_eq!(append.bits(), 2048);
+
+    let directory = OpenFlags::DIRECTORY;
+    assert_eq!(directory.bits(), 65536);
     
     // 测试组合标志
     let flags = OpenFlags::WRONLY | OpenFlags::CREATE | OpenFlags::TRUNC;
@@ -216,28 +467,61 @@ fn test_user_api_exists() {
     let _read_fn: fn(usize, &[u8]) -> isize = read;
     let _open_fn: fn(&str, OpenFlags) -> isize = open;
     let _close_fn: fn(usize) -> isize = close;
+    let _rename_fn: fn(&str, &str) -> isize = rename;
+    let _statfs_fn: fn(&str, &mut FsStat) -> isize = statfs;
+    let _getdents_fn: fn(usize, &mut [u8]) -> isize = getdents;
+    let _poll_fn: fn(&mut [PollFd], isize) -> isize = poll;
+    let _ppoll_fn: fn(&mut [PollFd], Option<&TimeSpec>) -> isize = ppoll;
     let _exit_fn: fn(i32) -> isize = exit;
     let _sched_yield_fn: fn() -> isize = sched_yield;
+    let _getcpu_fn: fn(*mut usize, *mut usize) -> isize = getcpu;
+    let _set_priority_fn: fn(isize) -> isize = set_priority;
+    let _get_priority_fn: fn() -> isize = get_priority;
     let _clock_gettime_fn: fn(ClockId, *mut TimeSpec) -> isize = clock_gettime;
+    let _clock_nanosleep_fn: fn(ClockId, usize, &TimeSpec) -> isize = clock_nanosleep;
+    let _nanosleep_fn: fn(&TimeSpec) -> isize = nanosleep;
+    let _gettimeofday_fn: fn(&mut TimeVal) -> isize = gettimeofday;
     let _fork_fn: fn() -> isize = fork;
+    let _vfork_fn: fn() -> isize = vfork;
     let _exec_fn: fn(&str) -> isize = exec;
     let _wait_fn: fn(*mut i32) -> isize = wait;
     let _waitpid_fn: fn(isize, *mut i32) -> isize = waitpid;
     let _getpid_fn: fn() -> isize = getpid;
+    let _setrlimit_fn: fn(usize, usize) -> isize = setrlimit;
+    let _times_fn: fn(&mut Tms) -> isize = times;
+    let _getuid_fn: fn() -> isize = getuid;
+    let _getgid_fn: fn() -> isize = getgid;
+    let _setuid_fn: fn(usize) -> isize = setuid;
+    let _setgid_fn: fn(usize) -> isize = setgid;
+    let _setpgid_fn: fn(usize, usize) -> isize = setpgid;
+    let _getpgid_fn: fn(usize) -> isize = getpgid;
+    let _getsid_fn: fn(usize) -> isize = getsid;
+    let _setsid_fn: fn() -> isize = setsid;
+    let _madvise_fn: fn(usize, usize, usize) -> isize = madvise;
+    let _mmap_fn: fn(usize, usize, usize, usize, isize, usize) -> isize = mmap;
+    let _munmap_fn: fn(usize, usize) -> isize = munmap;
+    let _msync_fn: fn(usize, usize, usize) -> isize = msync;
     let _kill_fn: fn(isize, SignalNo) -> isize = kill;
+    let _tgkill_fn: fn(isize, isize, SignalNo) -> isize = tgkill;
     let _sigaction_fn: fn(SignalNo, *const SignalAction, *const SignalAction) -> isize = sigaction;
-    let _sigprocmask_fn: fn(usize) -> isize = sigprocmask;
+    let _sigprocmask_fn: fn(usize, usize, *mut usize) -> isize = sigprocmask;
+    let _sigwaitinfo_fn: fn(usize, *mut SigInfo) -> isize = sigwaitinfo;
+    let _sigtimedwait_fn: fn(usize, *mut SigInfo, Option<&TimeSpec>) -> isize = sigtimedwait;
     let _sigreturn_fn: fn() -> isize = sigreturn;
     let _thread_create_fn: fn(usize, usize) -> isize = thread_create;
+    let _thread_create_with_tls_fn: fn(usize, usize, usize) -> isize = thread_create_with_tls;
     let _gettid_fn: fn() -> isize = gettid;
     let _waittid_fn: fn(usize) -> isize = waittid;
+    let _prctl_fn: fn(usize, usize) -> isize = prctl;
     let _semaphore_create_fn: fn(usize) -> isize = semaphore_create;
     let _semaphore_up_fn: fn(usize) -> isize = semaphore_up;
     let _semaphore_down_fn: fn(usize) -> isize = semaphore_down;
+    let _semaphore_down_timeout_fn: fn(usize, &TimeSpec) -> isize = semaphore_down_timeout;
     let _mutex_create_fn: fn(bool) -> isize = mutex_create;
     let _mutex_lock_fn: fn(usize) -> isize = mutex_lock;
     let _mutex_unlock_fn: fn(usize) -> isize = mutex_unlock;
     let _condvar_create_fn: fn() -> isize = condvar_create;
     let _condvar_signal_fn: fn(usize) -> isize = condvar_signal;
     let _condvar_wait_fn: fn(usize, usize) -> isize = condvar_wait;
+    let _condvar_wait_timeout_fn: fn(usize, usize, &TimeSpec) -> isize = condvar_wait_timeout;
 }
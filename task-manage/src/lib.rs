@@ -1,35 +1,95 @@
 //! task-manage: 任务标识符类型、任务存储与就绪队列抽象
 //!
-//! 提供 `ProcId`、`ThreadId`、`CoroId` 等单调递增的 ID 类型，
-//! 以及 `Manage`、`Schedule` trait 抽象任务存储与调度。
+//! 提供 `ProcId`、`ThreadId`、`CoroId` 等 ID 类型，每类 ID 都由一个可回收的 `IdAllocator` 分配，
+//! 以及 `Manage`、`Schedule` trait 抽象任务存储与调度，`MapScheduler` 是二者的通用实现，
+//! 调度策略可选先进先出的 `Fifo` 或按优先级的 `PrioritySchedule`。
 
 #![no_std]
 
 extern crate alloc;
 
-use core::cmp::Ordering;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, VecDeque};
+use alloc::vec::Vec;
+use core::cmp::{Ordering, Reverse};
 use core::fmt;
 use core::hash::{Hash, Hasher};
-use core::sync::atomic::{AtomicUsize, Ordering::SeqCst};
 
 // =============================================================================
 // 任务标识符类型 (ProcId, ThreadId, CoroId)
 // =============================================================================
 
+/// 可回收的 ID 分配器
+///
+/// 优先复用最小的已释放 ID，没有可复用的才分配新值。0 保留给 init，不会被分配；
+/// 新值用尽（需要分配 `usize::MAX`）时 panic，不会回绕到已在使用的 ID。
+pub struct IdAllocator {
+    next: usize,
+    free: BTreeSet<usize>,
+}
+
+impl IdAllocator {
+    /// 创建空分配器，第一个分配的 ID 是 1
+    pub const fn new() -> Self {
+        Self {
+            next: 1,
+            free: BTreeSet::new(),
+        }
+    }
+
+    /// 分配一个 ID
+    pub fn alloc(&mut self) -> usize {
+        if let Some(id) = self.free.pop_first() {
+            return id;
+        }
+        self.mint()
+    }
+
+    /// 分配一个从未分配过的 ID，不复用已释放的 ID
+    pub fn mint(&mut self) -> usize {
+        let id = self.next;
+        self.next = id.checked_add(1).expect("IdAllocator: ids exhausted");
+        id
+    }
+
+    /// 释放 ID 以便复用，保留值、未分配过的值和重复释放都会被忽略
+    pub fn free(&mut self, id: usize) {
+        if id != 0 && id < self.next {
+            self.free.insert(id);
+        }
+    }
+}
+
+impl Default for IdAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 macro_rules! impl_id_type {
-    ($name:ident, $counter:ident) => {
-        /// 单调递增的任务标识符
+    ($name:ident, $allocator:ident) => {
+        /// 任务标识符，同一类 ID 都从一个 [`IdAllocator`] 中分配
         #[derive(Clone, Copy)]
         pub struct $name(usize);
 
-        static $counter: AtomicUsize = AtomicUsize::new(0);
+        static $allocator: spin::Mutex<IdAllocator> = spin::Mutex::new(IdAllocator::new());
 
         impl $name {
-            /// 创建新的单调递增 ID
+            /// 创建新的单调递增 ID，不复用 [`free`](Self::free) 归还的 ID
             #[inline]
             pub fn new() -> Self {
-                let v = $counter.fetch_add(1, SeqCst);
-                Self(v)
+                Self($allocator.lock().mint())
+            }
+
+            /// 分配 ID，优先复用 [`free`](Self::free) 归还的 ID
+            ///
+            /// 与 [`new`](Self::new) 共用同一个分配器，两种方式可以混用。
+            pub fn alloc() -> Self {
+                Self($allocator.lock().alloc())
+            }
+
+            /// 把不再使用的 ID 归还到池中，之后由 [`alloc`](Self::alloc) 复用
+            pub fn free(id: Self) {
+                $allocator.lock().free(id.0);
             }
 
             /// 从原始值构造
@@ -43,6 +103,23 @@ macro_rules! impl_id_type {
             pub fn get_usize(self) -> usize {
                 self.0
             }
+
+            /// 把分配器恢复到初始状态：清空 ID 池，下一个新分配的 ID 是 1
+            ///
+            /// 仅供测试使用：分配器是进程全局的，重置后才能断言具体的 ID 值。
+            /// 调用方须保证没有其他线程同时分配同类 ID。
+            #[cfg(feature = "test-utils")]
+            pub fn reset_counters() {
+                *$allocator.lock() = IdAllocator::new();
+            }
+
+            /// 把分配器的下一个新值设为 `next`，下一次 [`new`](Self::new) 返回该值
+            ///
+            /// 仅供测试使用，用于构造 ID 即将用尽等难以自然到达的状态。
+            #[cfg(feature = "test-utils")]
+            pub fn set_counter(next: usize) {
+                $allocator.lock().next = next;
+            }
         }
 
         impl Default for $name {
@@ -90,9 +167,22 @@ macro_rules! impl_id_type {
     };
 }
 
-impl_id_type!(ProcId, PROC_ID_COUNTER);
-impl_id_type!(ThreadId, THREAD_ID_COUNTER);
-impl_id_type!(CoroId, CORO_ID_COUNTER);
+impl_id_type!(ProcId, PROC_ID_ALLOCATOR);
+impl_id_type!(ThreadId, THREAD_ID_ALLOCATOR);
+impl_id_type!(CoroId, CORO_ID_ALLOCATOR);
+
+impl ProcId {
+    /// 创建新的进程 ID，保证不是保留值
+    ///
+    /// 保留值包括：0（init 进程，子进程会被 reparent 到它）、
+    /// `usize::MAX`（`wait` 中表示任意子进程）和 `usize::MAX - 1`（曾用作子进程仍存活的 sentinel）。
+    /// 分配器从 1 开始且不会回绕，因此只剩 `usize::MAX - 1` 需要检查，分配到它说明 ID 已经用尽，直接 panic。
+    pub fn new_nonreserved() -> Self {
+        let pid = Self::new();
+        assert!(pid.0 < usize::MAX - 1, "ProcId: ids exhausted");
+        pid
+    }
+}
 
 // =============================================================================
 // 泛型任务存储接口 Manage
@@ -116,8 +206,406 @@ pub trait Manage<T, I: Copy + Ord> {
 pub trait Schedule<I: Copy + Ord> {
     /// 将 id 加入队列
     fn add(&mut self, id: I);
+    /// 以优先级 `prio` 将 id 加入队列，数值越大越优先；默认忽略优先级
+    fn add_prio(&mut self, id: I, prio: u8) {
+        let _ = prio;
+        self.add(id);
+    }
+    /// 修改 id 的优先级；id 已在队列中时按新优先级重新排队，否则在它下次入队时生效。默认忽略优先级
+    fn set_prio(&mut self, id: I, prio: u8) {
+        let _ = (id, prio);
+    }
+    /// 丢弃为 id 记录的调度信息（如优先级），任务被删除后调用；默认没有需要丢弃的信息
+    fn forget(&mut self, id: I) {
+        let _ = id;
+    }
     /// 从队列取出一个 id
     fn fetch(&mut self) -> Option<I>;
+    /// 取出约一半排队的 id，供工作窃取时迁移到空闲 hart
+    ///
+    /// 应从最晚被调度的一端取，剩下的 id 保持原有顺序；默认不支持迁移，返回空。
+    fn drain_half(&mut self) -> Vec<I> {
+        Vec::new()
+    }
+}
+
+// =============================================================================
+// 通用实现：MapScheduler
+// =============================================================================
+
+/// 先进先出的就绪队列，[`MapScheduler`] 默认的调度策略
+pub struct Fifo<I> {
+    queue: VecDeque<I>,
+}
+
+impl<I> Fifo<I> {
+    /// 创建空队列
+    pub const fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+        }
+    }
+}
+
+impl<I> Default for Fifo<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I: Copy + Ord> Schedule<I> for Fifo<I> {
+    fn add(&mut self, id: I) {
+        self.queue.push_back(id);
+    }
+
+    fn fetch(&mut self) -> Option<I> {
+        self.queue.pop_front()
+    }
+
+    fn drain_half(&mut self) -> Vec<I> {
+        let keep = self.queue.len() - self.queue.len() / 2;
+        self.queue.split_off(keep).into()
+    }
+}
+
+/// 按优先级出队的就绪队列，同优先级先进先出
+///
+/// 记住每个 id 最近一次 [`add_prio`](Schedule::add_prio) 的优先级，之后用 `add` 重新入队
+/// （例如时间片用完被挂起）时沿用它；从未指定过优先级的 id 使用创建时给定的默认优先级。
+pub struct PrioritySchedule<I> {
+    heap: BinaryHeap<(u8, Reverse<usize>, I)>,
+    prios: BTreeMap<I, u8>,
+    default: u8,
+    seq: usize,
+}
+
+impl<I: Ord> PrioritySchedule<I> {
+    /// 未指定优先级时使用的优先级
+    pub const DEFAULT_PRIORITY: u8 = 0;
+
+    /// 创建空队列，默认优先级为 [`Self::DEFAULT_PRIORITY`]
+    pub fn new() -> Self {
+        Self::with_default(Self::DEFAULT_PRIORITY)
+    }
+
+    /// 创建空队列，从未指定过优先级的 id 使用 `prio`
+    pub fn with_default(prio: u8) -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            prios: BTreeMap::new(),
+            default: prio,
+            seq: 0,
+        }
+    }
+
+    fn push(&mut self, id: I, prio: u8) {
+        self.heap.push((prio, Reverse(self.seq), id));
+        self.seq += 1;
+    }
+}
+
+impl<I: Ord> Default for PrioritySchedule<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I: Copy + Ord> Schedule<I> for PrioritySchedule<I> {
+    fn add(&mut self, id: I) {
+        let prio = self
+            .prios
+            .get(&id)
+            .copied()
+            .unwrap_or(self.default);
+        self.push(id, prio);
+    }
+
+    fn add_prio(&mut self, id: I, prio: u8) {
+        self.prios.insert(id, prio);
+        self.push(id, prio);
+    }
+
+    fn set_prio(&mut self, id: I, prio: u8) {
+        self.prios.insert(id, prio);
+        // 已在队列中时移出后按新优先级排到同优先级的末尾
+        if self.heap.iter().any(|&(_, _, queued)| queued == id) {
+            self.heap.retain(|&(_, _, queued)| queued != id);
+            self.push(id, prio);
+        }
+    }
+
+    fn forget(&mut self, id: I) {
+        self.prios.remove(&id);
+    }
+
+    fn fetch(&mut self) -> Option<I> {
+        self.heap.pop().map(|(_, _, id)| id)
+    }
+}
+
+/// 任务存储与就绪队列的组合：`BTreeMap` 按 ID 存放任务，调度策略 `S` 决定出队顺序
+///
+/// 同时实现 [`Manage`] 与 [`Schedule`]，可以直接交给 `PManager`/`PThreadManager`；
+/// 只需要存储时不调用 `Schedule` 的方法即可。
+pub struct MapScheduler<T, I, S = Fifo<I>> {
+    store: BTreeMap<I, T>,
+    ready: S,
+}
+
+impl<T, I: Ord, S: Default> MapScheduler<T, I, S> {
+    /// 创建空存储，调度策略取默认值
+    pub fn new() -> Self {
+        Self::with_policy(S::default())
+    }
+}
+
+impl<T, I: Ord, S> MapScheduler<T, I, S> {
+    /// 创建空存储，使用给定的调度策略
+    pub fn with_policy(ready: S) -> Self {
+        Self {
+            store: BTreeMap::new(),
+            ready,
+        }
+    }
+}
+
+impl<T, I: Ord, S: Default> Default for MapScheduler<T, I, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, I: Copy + Ord, S: Schedule<I>> Manage<T, I> for MapScheduler<T, I, S> {
+    fn insert(&mut self, id: I, item: T) {
+        self.store.insert(id, item);
+    }
+
+    fn delete(&mut self, id: I) {
+        self.store.remove(&id);
+        self.ready.forget(id);
+    }
+
+    fn get_mut(&mut self, id: I) -> Option<&mut T> {
+        self.store.get_mut(&id)
+    }
+}
+
+impl<T, I: Copy + Ord, S: Schedule<I>> Schedule<I> for MapScheduler<T, I, S> {
+    fn add(&mut self, id: I) {
+        self.ready.add(id);
+    }
+
+    fn add_prio(&mut self, id: I, prio: u8) {
+        self.ready.add_prio(id, prio);
+    }
+
+    fn set_prio(&mut self, id: I, prio: u8) {
+        self.ready.set_prio(id, prio);
+    }
+
+    fn forget(&mut self, id: I) {
+        self.ready.forget(id);
+    }
+
+    fn fetch(&mut self) -> Option<I> {
+        self.ready.fetch()
+    }
+
+    fn drain_half(&mut self) -> Vec<I> {
+        self.ready.drain_half()
+    }
+}
+
+// =============================================================================
+// 当前任务槽位
+// =============================================================================
+
+/// 正在运行的任务：地址空间、进程与线程
+///
+/// 内核为每个 hart 放置一个槽位，由该 hart 的调度循环在切换任务前后写入。
+/// 地址空间以指针保存，有效性由 [`set`](Self::set) 的调用方保证。
+pub struct CurrentSlot<S> {
+    inner: spin::Mutex<Option<Current<S>>>,
+}
+
+struct Current<S> {
+    space: *mut S,
+    pid: ProcId,
+    tid: ThreadId,
+}
+
+// 指针只按 `CurrentSlot::set` 的约定解引用
+unsafe impl<S> Send for Current<S> {}
+
+impl<S> CurrentSlot<S> {
+    /// 创建空槽位
+    pub const fn new() -> Self {
+        Self {
+            inner: spin::Mutex::new(None),
+        }
+    }
+
+    /// 记录当前任务，覆盖之前的记录
+    ///
+    /// # Safety
+    ///
+    /// `space` 必须在下一次 `set` 或 [`clear`](Self::clear) 之前保持有效且不被移动。
+    pub unsafe fn set(&self, space: &mut S, pid: ProcId, tid: ThreadId) {
+        *self.inner.lock() = Some(Current { space, pid, tid });
+    }
+
+    /// 清空记录
+    pub fn clear(&self) {
+        *self.inner.lock() = None;
+    }
+
+    /// 当前任务的地址空间
+    ///
+    /// # Safety
+    ///
+    /// 返回的引用只在下一次 [`set`](Self::set) 或 [`clear`](Self::clear) 之前有效，
+    /// 调用方须保证在此之前不再使用它，并且期间没有对该地址空间的可变引用。
+    pub unsafe fn space(&self) -> Option<&S> {
+        self.inner
+            .lock()
+            .as_ref()
+            .map(|current| unsafe { &*current.space })
+    }
+
+    /// 当前任务的地址空间，可修改（如为内核访问补齐缺页）
+    ///
+    /// # Safety
+    ///
+    /// 与 [`space`](Self::space) 相同，并且在返回的引用使用期间不能存在对该地址空间的其他引用。
+    // 可变引用来自 `set` 记录的 `&mut S`，独占由调用方保证
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn space_mut(&self) -> Option<&mut S> {
+        self.inner
+            .lock()
+            .as_ref()
+            .map(|current| unsafe { &mut *current.space })
+    }
+
+    /// 当前进程 ID
+    pub fn pid(&self) -> Option<ProcId> {
+        self.inner.lock().as_ref().map(|current| current.pid)
+    }
+
+    /// 当前线程 ID
+    pub fn tid(&self) -> Option<ThreadId> {
+        self.inner.lock().as_ref().map(|current| current.tid)
+    }
+}
+
+impl<S> Default for CurrentSlot<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =============================================================================
+// 进程闹钟
+// =============================================================================
+
+/// 按进程记录的闹钟（`alarm` 系统调用），到期时刻以时钟周期数表示
+///
+/// 不自带锁，由内核放在自己的锁里使用；到期后由内核向进程发送 SIGALRM。
+pub struct AlarmTable {
+    alarms: BTreeMap<ProcId, u64>,
+}
+
+impl AlarmTable {
+    /// 创建空表
+    pub const fn new() -> Self {
+        Self {
+            alarms: BTreeMap::new(),
+        }
+    }
+
+    /// 把 `pid` 的闹钟设为 `deadline`（`None` 表示取消），返回旧闹钟在 `now` 时的剩余秒数
+    ///
+    /// `freq` 是每秒的时钟周期数。剩余时间向上取整，尚未到期的旧闹钟至少报告 1 秒；没有旧闹钟时返回 0。
+    pub fn replace(&mut self, pid: ProcId, deadline: Option<u64>, now: u64, freq: u64) -> u64 {
+        let old = match deadline {
+            Some(deadline) => self.alarms.insert(pid, deadline),
+            None => self.alarms.remove(&pid),
+        };
+        old.map_or(0, |old| old.saturating_sub(now).div_ceil(freq))
+    }
+
+    /// 移出所有到期时刻不晚于 `now` 的闹钟，返回对应的进程
+    pub fn expire(&mut self, now: u64) -> Vec<ProcId> {
+        let mut expired = Vec::new();
+        self.alarms.retain(|&pid, &mut deadline| {
+            if deadline <= now {
+                expired.push(pid);
+                false
+            } else {
+                true
+            }
+        });
+        expired
+    }
+
+    /// 删除 `pid` 的闹钟（如进程退出时），返回是否确有闹钟
+    pub fn cancel(&mut self, pid: ProcId) -> bool {
+        self.alarms.remove(&pid).is_some()
+    }
+
+    /// `pid` 的闹钟到期时刻
+    pub fn deadline(&self, pid: ProcId) -> Option<u64> {
+        self.alarms.get(&pid).copied()
+    }
+}
+
+impl Default for AlarmTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =============================================================================
+// 等待子进程
+// =============================================================================
+
+/// 等待子进程的结果
+#[cfg(any(feature = "proc", feature = "thread"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitResult {
+    /// 没有符合条件的子进程
+    NoChild,
+    /// 子进程存在但都还没有退出
+    Alive,
+    /// 回收了一个已退出的子进程及其退出码
+    Exited(ProcId, isize),
+}
+
+// =============================================================================
+// 会话与进程组
+// =============================================================================
+
+/// 会话 `sid` 的首进程退出后，找出会话中成为孤儿的进程组的全部成员
+///
+/// `members` 是会话中其余存活进程的 `(pid, pgid, parent)`，`lookup` 返回父进程的 `(pgid, sid)`。
+/// 一个进程组中只要有成员的父进程位于同一会话的另一个进程组，它就仍受作业控制，不是孤儿。
+#[cfg(any(feature = "proc", feature = "thread"))]
+fn orphaned_members(
+    members: &[(ProcId, ProcId, ProcId)],
+    sid: ProcId,
+    lookup: impl Fn(ProcId) -> Option<(ProcId, ProcId)>,
+) -> Vec<ProcId> {
+    let anchored: Vec<ProcId> = members
+        .iter()
+        .filter(|(_, pgid, parent)| {
+            matches!(lookup(*parent), Some((ppgid, psid)) if psid == sid && ppgid != *pgid)
+        })
+        .map(|(_, pgid, _)| *pgid)
+        .collect();
+    members
+        .iter()
+        .filter(|(_, pgid, _)| !anchored.contains(pgid))
+        .map(|(pid, _, _)| *pid)
+        .collect()
 }
 
 // =============================================================================
@@ -135,17 +623,42 @@ mod proc_feature {
         parent: ProcId,
         children: Vec<ProcId>,
         dead_children: Vec<(ProcId, isize)>,
+        pgid: ProcId,
+        sid: ProcId,
     }
 
     impl ProcRel {
+        /// 新建关系，进程组与会话都是 0，由 [`PManager::add`] 改为继承父进程的
         pub fn new(parent_pid: ProcId) -> Self {
             Self {
                 parent: parent_pid,
                 children: Vec::new(),
                 dead_children: Vec::new(),
+                pgid: ProcId::from_usize(0),
+                sid: ProcId::from_usize(0),
             }
         }
 
+        /// 父进程
+        pub fn parent(&self) -> ProcId {
+            self.parent
+        }
+
+        /// 仍存活的子进程
+        pub fn children(&self) -> &[ProcId] {
+            &self.children
+        }
+
+        /// 已退出、尚未被等待回收的子进程及其退出码
+        pub fn dead_children(&self) -> &[(ProcId, isize)] {
+            &self.dead_children
+        }
+
+        /// 仍存活的子进程数
+        pub fn alive_child_count(&self) -> usize {
+            self.children.len()
+        }
+
         pub fn add_child(&mut self, child_pid: ProcId) {
             self.children.push(child_pid);
         }
@@ -157,25 +670,28 @@ mod proc_feature {
             }
         }
 
-        pub fn wait_any_child(&mut self) -> Option<(ProcId, isize)> {
-            if self.children.is_empty() && self.dead_children.is_empty() {
-                return None;
-            }
+        pub fn wait_any_child(&mut self) -> WaitResult {
             if !self.dead_children.is_empty() {
-                return Some(self.dead_children.remove(0));
+                let (pid, code) = self.dead_children.remove(0);
+                return WaitResult::Exited(pid, code);
+            }
+            if self.children.is_empty() {
+                WaitResult::NoChild
+            } else {
+                WaitResult::Alive
             }
-            // 有活跃子进程且无已死子进程：返回 sentinel
-            Some((ProcId::from_usize(usize::MAX - 1), -1))
         }
 
-        pub fn wait_child(&mut self, child_pid: ProcId) -> Option<(ProcId, isize)> {
+        pub fn wait_child(&mut self, child_pid: ProcId) -> WaitResult {
             if let Some(pos) = self.dead_children.iter().position(|(c, _)| *c == child_pid) {
-                return Some(self.dead_children.remove(pos));
+                let (pid, code) = self.dead_children.remove(pos);
+                return WaitResult::Exited(pid, code);
             }
             if self.children.contains(&child_pid) {
-                return Some((ProcId::from_usize(usize::MAX - 1), -1));
+                WaitResult::Alive
+            } else {
+                WaitResult::NoChild
             }
-            None
         }
     }
 
@@ -212,13 +728,65 @@ mod proc_feature {
             let m = self.manager();
             m.insert(id, task);
             m.add(id);
-            self.relations
+            let parent_rel = self
+                .relations
                 .entry(parent)
-                .or_insert_with(|| ProcRel::new(parent))
-                .add_child(id);
-            self.relations
-                .entry(id)
                 .or_insert_with(|| ProcRel::new(parent));
+            parent_rel.add_child(id);
+            let (pgid, sid) = (parent_rel.pgid, parent_rel.sid);
+            self.relations.entry(id).or_insert_with(|| {
+                let mut rel = ProcRel::new(parent);
+                rel.pgid = pgid;
+                rel.sid = sid;
+                rel
+            });
+        }
+
+        /// 进程的父子关系
+        pub fn relation(&self, id: ProcId) -> Option<&ProcRel> {
+            self.relations.get(&id)
+        }
+
+        /// 进程所在的进程组
+        pub fn getpgid(&self, id: ProcId) -> Option<ProcId> {
+            self.relations.get(&id).map(|r| r.pgid)
+        }
+
+        /// 进程所在的会话
+        pub fn getsid(&self, id: ProcId) -> Option<ProcId> {
+            self.relations.get(&id).map(|r| r.sid)
+        }
+
+        /// 把进程移入同一会话中的进程组 `pgid`，`pgid == id` 时新建以它为组长的进程组
+        ///
+        /// 会话首进程不能移动，目标组必须已存在于同一会话中。
+        pub fn setpgid(&mut self, id: ProcId, pgid: ProcId) -> bool {
+            let Some(sid) = self.getsid(id) else {
+                return false;
+            };
+            if sid == id {
+                return false;
+            }
+            let group_exists = self
+                .relations
+                .values()
+                .any(|r| r.pgid == pgid && r.sid == sid);
+            if pgid != id && !group_exists {
+                return false;
+            }
+            self.relations.get_mut(&id).unwrap().pgid = pgid;
+            true
+        }
+
+        /// 以进程为首进程创建新会话和新进程组，返回新会话 id；进程已是进程组组长时失败
+        pub fn setsid(&mut self, id: ProcId) -> Option<ProcId> {
+            if self.relations.values().any(|r| r.pgid == id) {
+                return None;
+            }
+            let rel = self.relations.get_mut(&id)?;
+            rel.pgid = id;
+            rel.sid = id;
+            Some(id)
         }
 
         pub fn find_next(&mut self) -> Option<&mut P> {
@@ -254,10 +822,23 @@ mod proc_feature {
             }
         }
 
-        pub fn make_current_exited(&mut self, exit_code: isize) {
+        /// 阻塞当前进程：不再重新入队，任务仍保留在存储中，由 [`re_enque`](Self::re_enque) 唤醒
+        pub fn make_current_blocked(&mut self) {
+            self.current = None;
+        }
+
+        /// 把被阻塞的进程重新放回就绪队列
+        pub fn re_enque(&mut self, id: ProcId) {
+            self.manager().add(id);
+        }
+
+        /// 结束当前进程并把子进程过继给 init
+        ///
+        /// 返回应收到 SIGHUP 的进程：当前进程是会话首进程时，会话中因此成为孤儿的进程组的成员。
+        pub fn make_current_exited(&mut self, exit_code: isize) -> Vec<ProcId> {
             let exiting_pid = match self.current.take() {
                 Some(id) => id,
-                None => return,
+                None => return Vec::new(),
             };
 
             let m = self.manager();
@@ -287,11 +868,32 @@ mod proc_feature {
             if let Some(rel) = self.relations.get_mut(&exiting_pid) {
                 rel.children.clear();
             }
+
+            // 退出进程的关系记录保留给 wait，因此只统计仍在存储中的会话成员
+            let sid = match self.relations.get(&exiting_pid) {
+                Some(rel) if rel.sid == exiting_pid => exiting_pid,
+                _ => return Vec::new(),
+            };
+            let candidates: Vec<(ProcId, ProcId, ProcId)> = self
+                .relations
+                .iter()
+                .filter(|(&id, rel)| rel.sid == sid && id != exiting_pid)
+                .map(|(&id, rel)| (id, rel.pgid, rel.parent))
+                .collect();
+            let members: Vec<(ProcId, ProcId, ProcId)> = candidates
+                .into_iter()
+                .filter(|&(id, _, _)| self.manager().get_mut(id).is_some())
+                .collect();
+            orphaned_members(&members, sid, |parent| {
+                self.relations.get(&parent).map(|r| (r.pgid, r.sid))
+            })
         }
 
-        pub fn wait(&mut self, child_pid: ProcId) -> Option<(ProcId, isize)> {
-            let current_pid = self.current?;
-            let rel = self.relations.get_mut(&current_pid)?;
+        /// 当前进程等待子进程 `child_pid`，`usize::MAX` 表示任意子进程
+        pub fn wait(&mut self, child_pid: ProcId) -> WaitResult {
+            let Some(rel) = self.current.and_then(|pid| self.relations.get_mut(&pid)) else {
+                return WaitResult::NoChild;
+            };
             if child_pid.get_usize() == usize::MAX {
                 rel.wait_any_child()
             } else {
@@ -330,9 +932,12 @@ mod thread_feature {
         dead_children: Vec<(ProcId, isize)>,
         threads: Vec<ThreadId>,
         dead_threads: Vec<(ThreadId, isize)>,
+        pgid: ProcId,
+        sid: ProcId,
     }
 
     impl ProcThreadRel {
+        /// 新建关系，进程组与会话都是 0，由 [`PThreadManager::add_proc`] 改为继承父进程的
         pub fn new(parent: ProcId) -> Self {
             Self {
                 parent,
@@ -340,9 +945,31 @@ mod thread_feature {
                 dead_children: Vec::new(),
                 threads: Vec::new(),
                 dead_threads: Vec::new(),
+                pgid: ProcId::from_usize(0),
+                sid: ProcId::from_usize(0),
             }
         }
 
+        /// 父进程
+        pub fn parent(&self) -> ProcId {
+            self.parent
+        }
+
+        /// 仍存活的子进程
+        pub fn children(&self) -> &[ProcId] {
+            &self.children
+        }
+
+        /// 已退出、尚未被等待回收的子进程及其退出码
+        pub fn dead_children(&self) -> &[(ProcId, isize)] {
+            &self.dead_children
+        }
+
+        /// 仍存活的子进程数
+        pub fn alive_child_count(&self) -> usize {
+            self.children.len()
+        }
+
         pub fn add_child(&mut self, child_pid: ProcId) {
             self.children.push(child_pid);
         }
@@ -354,24 +981,28 @@ mod thread_feature {
             }
         }
 
-        pub fn wait_any_child(&mut self) -> Option<(ProcId, isize)> {
-            if self.children.is_empty() && self.dead_children.is_empty() {
-                return None;
-            }
+        pub fn wait_any_child(&mut self) -> WaitResult {
             if !self.dead_children.is_empty() {
-                return Some(self.dead_children.remove(0));
+                let (pid, code) = self.dead_children.remove(0);
+                return WaitResult::Exited(pid, code);
+            }
+            if self.children.is_empty() {
+                WaitResult::NoChild
+            } else {
+                WaitResult::Alive
             }
-            Some((ProcId::from_usize(usize::MAX - 1), -1))
         }
 
-        pub fn wait_child(&mut self, child_pid: ProcId) -> Option<(ProcId, isize)> {
+        pub fn wait_child(&mut self, child_pid: ProcId) -> WaitResult {
             if let Some(pos) = self.dead_children.iter().position(|(c, _)| *c == child_pid) {
-                return Some(self.dead_children.remove(pos));
+                let (pid, code) = self.dead_children.remove(pos);
+                return WaitResult::Exited(pid, code);
             }
             if self.children.contains(&child_pid) {
-                return Some((ProcId::from_usize(usize::MAX - 1), -1));
+                WaitResult::Alive
+            } else {
+                WaitResult::NoChild
             }
-            None
         }
 
         pub fn add_thread(&mut self, tid: ThreadId) {
@@ -445,19 +1076,88 @@ mod thread_feature {
         pub fn add_proc(&mut self, id: ProcId, proc: P, parent: ProcId) {
             let pm = self.proc_manager();
             pm.insert(id, proc);
-            self.relations
+            let parent_rel = self
+                .relations
                 .entry(parent)
-                .or_insert_with(|| ProcThreadRel::new(parent))
-                .add_child(id);
-            self.relations
-                .entry(id)
                 .or_insert_with(|| ProcThreadRel::new(parent));
+            parent_rel.add_child(id);
+            let (pgid, sid) = (parent_rel.pgid, parent_rel.sid);
+            self.relations.entry(id).or_insert_with(|| {
+                let mut rel = ProcThreadRel::new(parent);
+                rel.pgid = pgid;
+                rel.sid = sid;
+                rel
+            });
+        }
+
+        /// 进程的父子关系与线程集合
+        pub fn relation(&self, id: ProcId) -> Option<&ProcThreadRel> {
+            self.relations.get(&id)
+        }
+
+        /// 进程所在的进程组
+        pub fn getpgid(&self, id: ProcId) -> Option<ProcId> {
+            self.relations.get(&id).map(|r| r.pgid)
+        }
+
+        /// 进程所在的会话
+        pub fn getsid(&self, id: ProcId) -> Option<ProcId> {
+            self.relations.get(&id).map(|r| r.sid)
+        }
+
+        /// 把进程移入同一会话中的进程组 `pgid`，`pgid == id` 时新建以它为组长的进程组
+        ///
+        /// 会话首进程不能移动，目标组必须已存在于同一会话中。
+        pub fn setpgid(&mut self, id: ProcId, pgid: ProcId) -> bool {
+            let Some(sid) = self.getsid(id) else {
+                return false;
+            };
+            if sid == id {
+                return false;
+            }
+            let group_exists = self
+                .relations
+                .values()
+                .any(|r| r.pgid == pgid && r.sid == sid);
+            if pgid != id && !group_exists {
+                return false;
+            }
+            self.relations.get_mut(&id).unwrap().pgid = pgid;
+            true
+        }
+
+        /// 以进程为首进程创建新会话和新进程组，返回新会话 id；进程已是进程组组长时失败
+        pub fn setsid(&mut self, id: ProcId) -> Option<ProcId> {
+            if self.relations.values().any(|r| r.pgid == id) {
+                return None;
+            }
+            let rel = self.relations.get_mut(&id)?;
+            rel.pgid = id;
+            rel.sid = id;
+            Some(id)
         }
 
         pub fn add(&mut self, id: ThreadId, task: T, pid: ProcId) {
             let tm = self.thread_manager();
             tm.insert(id, task);
             tm.add(id);
+            self.attach_thread(id, pid);
+        }
+
+        /// 以优先级 `prio` 添加线程，调度策略支持时优先级高的线程先被调度
+        pub fn add_with_priority(&mut self, id: ThreadId, task: T, pid: ProcId, prio: u8) {
+            let tm = self.thread_manager();
+            tm.insert(id, task);
+            tm.add_prio(id, prio);
+            self.attach_thread(id, pid);
+        }
+
+        /// 修改线程的调度优先级；线程已就绪时立即按新优先级重新排队，否则在它下次入队时生效
+        pub fn set_priority(&mut self, id: ThreadId, prio: u8) {
+            self.thread_manager().set_prio(id, prio);
+        }
+
+        fn attach_thread(&mut self, id: ThreadId, pid: ProcId) {
             self.tid2pid.insert(id, pid);
             self.relations
                 .entry(pid)
@@ -493,10 +1193,13 @@ mod thread_feature {
             self.current = None;
         }
 
-        pub fn make_current_exited(&mut self, exit_code: isize) {
+        /// 结束当前线程，进程的最后一个线程退出时一并删除进程
+        ///
+        /// 返回值同 [`del_proc`](Self::del_proc)。
+        pub fn make_current_exited(&mut self, exit_code: isize) -> Vec<ProcId> {
             let exiting_tid = match self.current.take() {
                 Some(id) => id,
-                None => return,
+                None => return Vec::new(),
             };
 
             let pid = *self.tid2pid.get(&exiting_tid).expect("tid2pid must have entry");
@@ -509,7 +1212,9 @@ mod thread_feature {
                 rel.threads.len()
             };
             if active_count == 0 {
-                self.del_proc(pid, exit_code);
+                self.del_proc(pid, exit_code)
+            } else {
+                Vec::new()
             }
         }
 
@@ -530,8 +1235,12 @@ mod thread_feature {
             self.proc_manager().get_mut(id)
         }
 
-        pub fn del_proc(&mut self, id: ProcId, exit_code: isize) {
+        /// 删除进程及其关系记录
+        ///
+        /// 返回应收到 SIGHUP 的进程：被删除的是会话首进程时，会话中因此成为孤儿的进程组的成员。
+        pub fn del_proc(&mut self, id: ProcId, exit_code: isize) -> Vec<ProcId> {
             let parent = self.relations.get(&id).map(|r| r.parent);
+            let leader = self.relations.get(&id).is_some_and(|r| r.sid == id);
             let thread_ids: alloc::vec::Vec<ThreadId> = self
                 .relations
                 .get(&id)
@@ -551,12 +1260,30 @@ mod thread_feature {
                     parent_rel.del_child(id, exit_code);
                 }
             }
+
+            if !leader {
+                return Vec::new();
+            }
+            let members: Vec<(ProcId, ProcId, ProcId)> = self
+                .relations
+                .iter()
+                .filter(|(_, rel)| rel.sid == id)
+                .map(|(&pid, rel)| (pid, rel.pgid, rel.parent))
+                .collect();
+            orphaned_members(&members, id, |parent| {
+                self.relations.get(&parent).map(|r| (r.pgid, r.sid))
+            })
         }
 
-        pub fn wait(&mut self, child_pid: ProcId) -> Option<(ProcId, isize)> {
-            let current_tid = self.current?;
-            let pid = *self.tid2pid.get(&current_tid)?;
-            let rel = self.relations.get_mut(&pid)?;
+        /// 当前线程所在进程等待子进程 `child_pid`，`usize::MAX` 表示任意子进程
+        pub fn wait(&mut self, child_pid: ProcId) -> WaitResult {
+            let Some(rel) = self
+                .current
+                .and_then(|tid| self.tid2pid.get(&tid))
+                .and_then(|pid| self.relations.get_mut(pid))
+            else {
+                return WaitResult::NoChild;
+            };
             if child_pid.get_usize() == usize::MAX {
                 rel.wait_any_child()
             } else {
@@ -570,6 +1297,24 @@ mod thread_feature {
             self.relations.get_mut(&pid)?.wait_thread(thread_tid)
         }
 
+        /// `id` 是否仍被某个进程、进程组或会话使用
+        ///
+        /// 回收进程 ID 前应检查：进程组或会话比它的首进程活得久时，这个 ID 还不能复用。
+        pub fn id_in_use(&self, id: ProcId) -> bool {
+            self.relations.contains_key(&id)
+                || self.relations.values().any(|r| r.pgid == id || r.sid == id)
+        }
+
+        /// 进程 `id` 中已退出、尚未被 [`waittid`](Self::waittid) 回收的线程
+        ///
+        /// 进程删除时这些记录一并丢弃，内核可以据此在进程退出前归还它们的 ID。
+        pub fn dead_threads(&self, id: ProcId) -> Vec<ThreadId> {
+            self.relations
+                .get(&id)
+                .map(|r| r.dead_threads.iter().map(|&(tid, _)| tid).collect())
+                .unwrap_or_default()
+        }
+
         pub fn thread_count(&self, id: ProcId) -> usize {
             self.relations
                 .get(&id)
@@ -601,3 +1346,99 @@ mod thread_feature {
 
 #[cfg(feature = "thread")]
 pub use thread_feature::{ProcThreadRel, PThreadManager};
+
+// =============================================================================
+// Feature: coro - 协程管理
+// =============================================================================
+
+#[cfg(feature = "coro")]
+mod coro_feature {
+    use super::*;
+
+    /// 协程管理辅助：结合存储与调度
+    ///
+    /// 协程之间是平级的，没有父子关系，退出时直接从存储中删除。
+    pub struct CoroManager<C, MC> {
+        manager: Option<MC>,
+        current: Option<CoroId>,
+        _phantom: core::marker::PhantomData<C>,
+    }
+
+    impl<C, MC> CoroManager<C, MC>
+    where
+        MC: Manage<C, CoroId> + Schedule<CoroId>,
+    {
+        pub fn new() -> Self {
+            Self {
+                manager: None,
+                current: None,
+                _phantom: core::marker::PhantomData,
+            }
+        }
+
+        pub fn set_manager(&mut self, manager: MC) {
+            self.manager = Some(manager);
+        }
+
+        fn manager(&mut self) -> &mut MC {
+            self.manager.as_mut().expect("must call set_manager first")
+        }
+
+        pub fn add(&mut self, id: CoroId, coro: C) {
+            let m = self.manager();
+            m.insert(id, coro);
+            m.add(id);
+        }
+
+        /// 取出下一个仍在存储中的协程作为当前协程
+        pub fn find_next(&mut self) -> Option<&mut C> {
+            loop {
+                let Some(id) = self.manager().fetch() else {
+                    self.current = None;
+                    return None;
+                };
+                if self.manager().get_mut(id).is_some() {
+                    self.current = Some(id);
+                    return self.manager().get_mut(id);
+                }
+            }
+        }
+
+        pub fn current(&mut self) -> Option<&mut C> {
+            let id = self.current?;
+            self.manager().get_mut(id)
+        }
+
+        pub fn current_id(&self) -> Option<CoroId> {
+            self.current
+        }
+
+        pub fn get_task(&mut self, id: CoroId) -> Option<&mut C> {
+            self.manager().get_mut(id)
+        }
+
+        pub fn make_current_suspend(&mut self) {
+            if let Some(id) = self.current.take() {
+                self.manager().add(id);
+            }
+        }
+
+        pub fn make_current_exited(&mut self) {
+            if let Some(id) = self.current.take() {
+                self.manager().delete(id);
+            }
+        }
+    }
+
+    impl<C, MC> Default for CoroManager<C, MC>
+    where
+        MC: Manage<C, CoroId> + Schedule<CoroId>,
+    {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
+
+#[cfg(feature = "coro")]
+pub use coro_feature::CoroManager;
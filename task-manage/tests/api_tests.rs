@@ -115,6 +115,17 @@ fn test_proc_id_clone_copy() {
     assert_eq!(id1, id3);
 }
 
+#[test]
+fn test_proc_id_new_nonreserved() {
+    // 测试 ProcId::new_nonreserved() 不返回保留值 0、MAX、MAX-1
+    for _ in 0..64 {
+        let pid = ProcId::new_nonreserved().get_usize();
+        assert_ne!(pid, 0);
+        assert_ne!(pid, usize::MAX);
+        assert_ne!(pid, usize::MAX - 1);
+    }
+}
+
 #[test]
 fn test_thread_id_new() {
     // 测试 ThreadId::new()
@@ -315,6 +326,58 @@ fn test_schedule_trait_fifo_order() {
     assert_eq!(scheduler.fetch(), Some(3));
 }
 
+#[test]
+fn test_schedule_drain_half() {
+    // 测试 Fifo::drain_half 取走队尾一半，剩余的保持 FIFO 顺序
+    let mut scheduler: Fifo<usize> = Fifo::new();
+    for i in 0..10 {
+        scheduler.add(i);
+    }
+    assert_eq!(scheduler.drain_half(), vec![5, 6, 7, 8, 9]);
+    for i in 0..5 {
+        assert_eq!(scheduler.fetch(), Some(i));
+    }
+    assert_eq!(scheduler.fetch(), None);
+    assert!(scheduler.drain_half().is_empty());
+
+    // 奇数个时留下多的一半
+    for i in 0..3 {
+        scheduler.add(i);
+    }
+    assert_eq!(scheduler.drain_half(), vec![2]);
+    assert_eq!(scheduler.fetch(), Some(0));
+    assert_eq!(scheduler.fetch(), Some(1));
+
+    // MapScheduler 委托给调度策略，PrioritySchedule 使用默认实现不迁移
+    let mut tasks: MapScheduler<(), usize> = MapScheduler::new();
+    for i in 0..4 {
+        tasks.add(i);
+    }
+    assert_eq!(tasks.drain_half(), vec![2, 3]);
+    assert_eq!(tasks.fetch(), Some(0));
+    let mut prio: MapScheduler<(), usize, PrioritySchedule<usize>> = MapScheduler::new();
+    prio.add_prio(1, 3);
+    prio.add(2);
+    assert!(prio.drain_half().is_empty());
+    assert_eq!(prio.fetch(), Some(1));
+    assert_eq!(prio.fetch(), Some(2));
+
+    // 默认实现不迁移任何 id
+    struct NoSteal(Option<usize>);
+    impl Schedule<usize> for NoSteal {
+        fn add(&mut self, id: usize) {
+            self.0 = Some(id);
+        }
+        fn fetch(&mut self) -> Option<usize> {
+            self.0.take()
+        }
+    }
+    let mut single = NoSteal(None);
+    single.add(1);
+    assert!(single.drain_half().is_empty());
+    assert_eq!(single.fetch(), Some(1));
+}
+
 #[test]
 fn test_id_types_hash() {
     // 测试 ID 类型的 Hash trait
@@ -345,3 +408,614 @@ fn test_id_types_debug() {
     assert!(thread_debug.contains("ThreadId"));
     assert!(coro_debug.contains("CoroId"));
 }
+
+#[cfg(feature = "proc")]
+#[test]
+fn test_pmanager_session_leader_exit_hangs_up_orphans() {
+    // 会话首进程退出后，成为孤儿的进程组成员需要收到 SIGHUP
+    let pid = ProcId::from_usize;
+    let mut manager: PManager<&str, MapScheduler<&str, ProcId>> = PManager::new();
+    manager.set_manager(MapScheduler::new());
+    manager.add(pid(1), "init", pid(0));
+    manager.add(pid(2), "leader", pid(1));
+    assert_eq!(manager.getsid(pid(2)), Some(pid(0)));
+    assert_eq!(manager.setsid(pid(2)), Some(pid(2)));
+    // 已是进程组组长时不能再建会话
+    assert_eq!(manager.setsid(pid(2)), None);
+
+    // 子进程继承会话和进程组，3 另建一组，4 留在首进程组中
+    manager.add(pid(3), "job", pid(2));
+    manager.add(pid(4), "helper", pid(2));
+    manager.add(pid(5), "job-child", pid(3));
+    assert_eq!(manager.getsid(pid(3)), Some(pid(2)));
+    assert_eq!(manager.getpgid(pid(4)), Some(pid(2)));
+    assert!(manager.setpgid(pid(3), pid(3)));
+    assert!(manager.setpgid(pid(5), pid(3)));
+    // 首进程不能离开自己的进程组，也不能加入不存在的组
+    assert!(!manager.setpgid(pid(2), pid(3)));
+    assert!(!manager.setpgid(pid(4), pid(9)));
+
+    // 调度到首进程并让它退出
+    while manager.find_next().copied() != Some("leader") {
+        manager.make_current_suspend();
+    }
+    let mut hangup = manager.make_current_exited(0);
+    hangup.sort();
+    assert_eq!(hangup, vec![pid(3), pid(4), pid(5)]);
+
+    // 非首进程退出不会产生 SIGHUP
+    while manager.find_next().copied() != Some("helper") {
+        manager.make_current_suspend();
+    }
+    assert!(manager.make_current_exited(0).is_empty());
+}
+
+#[cfg(feature = "thread")]
+#[test]
+fn test_pthread_manager_orphaned_group() {
+    // 首进程退出时，父进程仍在同一会话另一组里的进程组不是孤儿
+    let pid = ProcId::from_usize;
+    let tid = ThreadId::from_usize;
+    let mut manager: PThreadManager<(), (), MapScheduler<(), ThreadId>, MapScheduler<(), ProcId>> =
+        PThreadManager::new();
+    manager.set_manager(MapScheduler::new());
+    manager.set_proc_manager(MapScheduler::new());
+    manager.add_proc(pid(1), (), pid(0));
+    manager.add_proc(pid(2), (), pid(1));
+    manager.add(tid(2), (), pid(2));
+    assert_eq!(manager.setsid(pid(2)), Some(pid(2)));
+    manager.add_proc(pid(3), (), pid(2));
+    manager.add_proc(pid(4), (), pid(3));
+    assert!(manager.setpgid(pid(3), pid(3)));
+    // 4 留在首进程组 2 中，它的父进程 3 位于同一会话的组 3，组 2 因此不是孤儿
+
+    manager.find_next();
+    assert_eq!(manager.make_current_exited(0), vec![pid(3)]);
+    assert_eq!(manager.getsid(pid(2)), None);
+    assert_eq!(manager.getsid(pid(4)), Some(pid(2)));
+}
+
+#[cfg(feature = "thread")]
+#[test]
+fn test_pthread_manager_id_in_use() {
+    // 首进程退出后，它的 ID 在进程组解散前仍被占用
+    let pid = ProcId::from_usize;
+    let tid = ThreadId::from_usize;
+    let mut manager: PThreadManager<(), (), MapScheduler<(), ThreadId>, MapScheduler<(), ProcId>> =
+        PThreadManager::new();
+    manager.set_manager(MapScheduler::new());
+    manager.set_proc_manager(MapScheduler::new());
+    manager.add_proc(pid(1), (), pid(0));
+    manager.add_proc(pid(2), (), pid(1));
+    manager.add(tid(2), (), pid(2));
+    assert!(manager.setpgid(pid(2), pid(2)));
+    manager.add_proc(pid(3), (), pid(2));
+    assert!(manager.id_in_use(pid(2)));
+    assert!(!manager.id_in_use(pid(4)));
+
+    manager.find_next();
+    assert!(manager.make_current_exited(0).is_empty());
+    assert_eq!(manager.getpgid(pid(3)), Some(pid(2)));
+    assert!(manager.id_in_use(pid(2)));
+
+    manager.del_proc(pid(3), 0);
+    assert!(!manager.id_in_use(pid(2)));
+}
+
+#[cfg(feature = "thread")]
+#[test]
+fn test_pthread_manager_dead_threads() {
+    // 已退出的线程在被 waittid 回收前都记录在 dead_threads 中
+    let pid = ProcId::from_usize;
+    let tid = ThreadId::from_usize;
+    let mut manager: PThreadManager<(), (), MapScheduler<(), ThreadId>, MapScheduler<(), ProcId>> =
+        PThreadManager::new();
+    manager.set_manager(MapScheduler::new());
+    manager.set_proc_manager(MapScheduler::new());
+    manager.add_proc(pid(1), (), pid(0));
+    manager.add(tid(1), (), pid(1));
+    manager.add(tid(2), (), pid(1));
+    manager.add(tid(3), (), pid(1));
+    assert!(manager.dead_threads(pid(1)).is_empty());
+
+    // 先后结束两个线程，剩下一个线程保持进程存活
+    manager.find_next();
+    assert!(manager.make_current_exited(5).is_empty());
+    manager.find_next();
+    assert!(manager.make_current_exited(5).is_empty());
+    let dead = manager.dead_threads(pid(1));
+    assert_eq!(dead.len(), 2);
+    let live = manager.get_thread(pid(1)).unwrap()[0];
+    assert!(!dead.contains(&live));
+
+    // 存活的线程回收其中一个
+    manager.find_next();
+    assert_eq!(manager.waittid(dead[0]), Some(5));
+    assert_eq!(manager.dead_threads(pid(1)), vec![dead[1]]);
+    assert!(manager.dead_threads(pid(2)).is_empty());
+}
+
+#[test]
+fn test_map_scheduler_manage() {
+    // 测试 MapScheduler 的 insert/get_mut/delete
+    let mut tasks: MapScheduler<&str, ProcId> = MapScheduler::new();
+    tasks.insert(ProcId::from_usize(1), "a");
+    tasks.insert(ProcId::from_usize(2), "b");
+    assert_eq!(tasks.get_mut(ProcId::from_usize(1)), Some(&mut "a"));
+    *tasks.get_mut(ProcId::from_usize(2)).unwrap() = "c";
+    assert_eq!(tasks.get_mut(ProcId::from_usize(2)), Some(&mut "c"));
+    tasks.delete(ProcId::from_usize(1));
+    assert!(tasks.get_mut(ProcId::from_usize(1)).is_none());
+    // 删除不存在的 id 不影响其他任务
+    tasks.delete(ProcId::from_usize(9));
+    assert!(tasks.get_mut(ProcId::from_usize(2)).is_some());
+}
+
+#[test]
+fn test_map_scheduler_delete_forgets_priority() {
+    // 删除任务时一并丢弃它的优先级，复用的 id 回到默认优先级
+    let mut tasks: MapScheduler<&str, usize, PrioritySchedule<usize>> = MapScheduler::new();
+    tasks.insert(1, "old");
+    tasks.add_prio(1, 9);
+    assert_eq!(tasks.fetch(), Some(1));
+    tasks.delete(1);
+
+    tasks.insert(2, "other");
+    tasks.insert(1, "new");
+    tasks.add(2);
+    tasks.add(1);
+    assert_eq!(tasks.fetch(), Some(2));
+    assert_eq!(tasks.fetch(), Some(1));
+}
+
+#[test]
+fn test_map_scheduler_fifo() {
+    // 默认策略先进先出，队列与存储相互独立
+    let mut tasks: MapScheduler<(), ThreadId> = MapScheduler::default();
+    assert_eq!(tasks.fetch(), None);
+    for i in 0..4 {
+        tasks.add(ThreadId::from_usize(i));
+    }
+    assert_eq!(tasks.fetch(), Some(ThreadId::from_usize(0)));
+    tasks.add(ThreadId::from_usize(0));
+    assert_eq!(
+        tasks.drain_half(),
+        vec![ThreadId::from_usize(3), ThreadId::from_usize(0)]
+    );
+    assert_eq!(tasks.fetch(), Some(ThreadId::from_usize(1)));
+    assert_eq!(tasks.fetch(), Some(ThreadId::from_usize(2)));
+    assert_eq!(tasks.fetch(), None);
+    assert!(tasks.get_mut(ThreadId::from_usize(1)).is_none());
+}
+
+#[test]
+fn test_map_scheduler_custom_policy() {
+    // 替换调度策略：后进先出
+    #[derive(Default)]
+    struct Lifo(Vec<usize>);
+    impl Schedule<usize> for Lifo {
+        fn add(&mut self, id: usize) {
+            self.0.push(id);
+        }
+        fn fetch(&mut self) -> Option<usize> {
+            self.0.pop()
+        }
+    }
+    let mut tasks: MapScheduler<char, usize, Lifo> = MapScheduler::with_policy(Lifo(vec![7]));
+    tasks.insert(1, 'x');
+    tasks.add(1);
+    tasks.add(2);
+    assert_eq!(tasks.fetch(), Some(2));
+    assert_eq!(tasks.fetch(), Some(1));
+    assert_eq!(tasks.fetch(), Some(7));
+    assert_eq!(tasks.fetch(), None);
+    assert!(tasks.drain_half().is_empty());
+    assert_eq!(tasks.get_mut(1), Some(&mut 'x'));
+}
+
+#[cfg(feature = "coro")]
+#[test]
+fn test_coro_manager() {
+    // 协程存储与就绪队列合在一起的简单实现
+    struct CoroStore {
+        items: HashMap<CoroId, &'static str>,
+        queue: VecDeque<CoroId>,
+    }
+    impl Manage<&'static str, CoroId> for CoroStore {
+        fn insert(&mut self, id: CoroId, item: &'static str) {
+            self.items.insert(id, item);
+        }
+        fn delete(&mut self, id: CoroId) {
+            self.items.remove(&id);
+        }
+        fn get_mut(&mut self, id: CoroId) -> Option<&mut &'static str> {
+            self.items.get_mut(&id)
+        }
+    }
+    impl Schedule<CoroId> for CoroStore {
+        fn add(&mut self, id: CoroId) {
+            self.queue.push_back(id);
+        }
+        fn fetch(&mut self) -> Option<CoroId> {
+            self.queue.pop_front()
+        }
+    }
+
+    let cid = CoroId::from_usize;
+    let mut manager: CoroManager<&'static str, CoroStore> = CoroManager::new();
+    manager.set_manager(CoroStore {
+        items: HashMap::new(),
+        queue: VecDeque::new(),
+    });
+    assert!(manager.find_next().is_none());
+    manager.add(cid(1), "a");
+    manager.add(cid(2), "b");
+    manager.add(cid(3), "c");
+
+    // 按加入顺序取出，挂起的协程回到队尾
+    assert_eq!(manager.find_next().copied(), Some("a"));
+    assert_eq!(manager.current_id(), Some(cid(1)));
+    manager.make_current_suspend();
+    assert!(manager.current().is_none());
+    assert_eq!(manager.find_next().copied(), Some("b"));
+
+    // 退出的协程从存储中删除，不再被调度
+    manager.make_current_exited();
+    assert!(manager.get_task(cid(2)).is_none());
+    assert_eq!(manager.find_next().copied(), Some("c"));
+    *manager.current().unwrap() = "c2";
+    manager.make_current_suspend();
+    assert_eq!(manager.find_next().copied(), Some("a"));
+    manager.make_current_exited();
+    assert_eq!(manager.find_next().copied(), Some("c2"));
+    manager.make_current_exited();
+    assert!(manager.find_next().is_none());
+    assert!(manager.current().is_none());
+}
+
+#[cfg(feature = "proc")]
+#[test]
+fn test_proc_rel_wait_result() {
+    let pid = ProcId::from_usize;
+    let mut rel = ProcRel::new(pid(0));
+    assert_eq!(rel.wait_any_child(), WaitResult::NoChild);
+    assert_eq!(rel.wait_child(pid(2)), WaitResult::NoChild);
+
+    rel.add_child(pid(2));
+    rel.add_child(pid(3));
+    assert_eq!(rel.wait_any_child(), WaitResult::Alive);
+    assert_eq!(rel.wait_child(pid(3)), WaitResult::Alive);
+    assert_eq!(rel.wait_child(pid(4)), WaitResult::NoChild);
+
+    rel.del_child(pid(3), 7);
+    assert_eq!(rel.wait_child(pid(3)), WaitResult::Exited(pid(3), 7));
+    // 已回收的子进程不能再等待
+    assert_eq!(rel.wait_child(pid(3)), WaitResult::NoChild);
+    rel.del_child(pid(2), -1);
+    assert_eq!(rel.wait_any_child(), WaitResult::Exited(pid(2), -1));
+    assert_eq!(rel.wait_any_child(), WaitResult::NoChild);
+}
+
+#[cfg(feature = "thread")]
+#[test]
+fn test_proc_thread_rel_wait_result() {
+    let pid = ProcId::from_usize;
+    let mut rel = ProcThreadRel::new(pid(0));
+    assert_eq!(rel.wait_any_child(), WaitResult::NoChild);
+
+    rel.add_child(pid(5));
+    assert_eq!(rel.wait_any_child(), WaitResult::Alive);
+    assert_eq!(rel.wait_child(pid(5)), WaitResult::Alive);
+
+    rel.del_child(pid(5), 3);
+    assert_eq!(rel.wait_any_child(), WaitResult::Exited(pid(5), 3));
+    assert_eq!(rel.wait_child(pid(5)), WaitResult::NoChild);
+}
+
+#[cfg(feature = "proc")]
+#[test]
+fn test_pmanager_wait_result() {
+    let pid = ProcId::from_usize;
+    let mut manager: PManager<&str, MapScheduler<&str, ProcId>> = PManager::new();
+    manager.set_manager(MapScheduler::new());
+    // 没有当前进程
+    assert_eq!(manager.wait(pid(usize::MAX)), WaitResult::NoChild);
+    manager.add(pid(1), "parent", pid(0));
+    assert_eq!(manager.find_next().copied(), Some("parent"));
+    assert_eq!(manager.wait(pid(usize::MAX)), WaitResult::NoChild);
+
+    manager.add(pid(2), "child", pid(1));
+    assert_eq!(manager.wait(pid(usize::MAX)), WaitResult::Alive);
+    manager.make_current_suspend();
+    while manager.find_next().copied() != Some("child") {
+        manager.make_current_suspend();
+    }
+    manager.make_current_exited(9);
+    assert_eq!(manager.find_next().copied(), Some("parent"));
+    assert_eq!(manager.wait(pid(2)), WaitResult::Exited(pid(2), 9));
+}
+
+#[test]
+fn test_id_allocator_reuse() {
+    let mut ids = IdAllocator::new();
+    let a = ids.alloc();
+    let b = ids.alloc();
+    let c = ids.alloc();
+    // 0 保留给 init
+    assert_eq!((a, b, c), (1, 2, 3));
+
+    ids.free(b);
+    assert_eq!(ids.alloc(), b);
+    assert_eq!(ids.alloc(), 4);
+
+    // 优先复用最小的 ID，保留值和未分配的值不会进入空闲列表
+    ids.free(c);
+    ids.free(a);
+    ids.free(a);
+    ids.free(0);
+    ids.free(100);
+    assert_eq!(ids.alloc(), a);
+    assert_eq!(ids.alloc(), c);
+    assert_eq!(ids.alloc(), 5);
+}
+
+#[test]
+fn test_proc_id_alloc_free() {
+    let first = ProcId::alloc();
+    let middle = ProcId::alloc();
+    let last = ProcId::alloc();
+    assert_ne!(first.get_usize(), 0);
+    assert!(first < middle && middle < last);
+
+    ProcId::free(middle);
+    assert_eq!(ProcId::alloc(), middle);
+    assert!(ProcId::alloc() > last);
+}
+
+#[cfg(feature = "proc")]
+#[test]
+fn test_proc_rel_queries() {
+    let pid = ProcId::from_usize;
+    let mut rel = ProcRel::new(pid(1));
+    assert_eq!(rel.parent(), pid(1));
+    assert_eq!(rel.alive_child_count(), 0);
+    rel.add_child(pid(2));
+    rel.add_child(pid(3));
+    rel.add_child(pid(4));
+    rel.del_child(pid(3), 5);
+    assert_eq!(rel.children(), &[pid(2), pid(4)]);
+    assert_eq!(rel.dead_children(), &[(pid(3), 5)]);
+    assert_eq!(rel.alive_child_count(), 2);
+
+    // 通过 PManager 查询进程树
+    let mut manager: PManager<(), MapScheduler<(), ProcId>> = PManager::new();
+    manager.set_manager(MapScheduler::new());
+    manager.add(pid(1), (), pid(0));
+    manager.add(pid(2), (), pid(1));
+    manager.add(pid(3), (), pid(1));
+    manager.add(pid(4), (), pid(2));
+    assert_eq!(manager.relation(pid(4)).unwrap().parent(), pid(2));
+    assert_eq!(
+        manager.relation(pid(1)).unwrap().children(),
+        &[pid(2), pid(3)]
+    );
+    assert_eq!(manager.relation(pid(1)).unwrap().alive_child_count(), 2);
+    assert_eq!(manager.relation(pid(3)).unwrap().alive_child_count(), 0);
+    assert!(manager.relation(pid(9)).is_none());
+}
+
+#[cfg(feature = "thread")]
+#[test]
+fn test_proc_thread_rel_queries() {
+    let pid = ProcId::from_usize;
+    let mut manager: PThreadManager<(), (), MapScheduler<(), ThreadId>, MapScheduler<(), ProcId>> =
+        PThreadManager::new();
+    manager.set_manager(MapScheduler::new());
+    manager.set_proc_manager(MapScheduler::new());
+    manager.add_proc(pid(1), (), pid(0));
+    manager.add_proc(pid(2), (), pid(1));
+    manager.add_proc(pid(3), (), pid(1));
+    manager.del_proc(pid(3), 8);
+
+    let rel = manager.relation(pid(1)).unwrap();
+    assert_eq!(rel.parent(), pid(0));
+    assert_eq!(rel.children(), &[pid(2)]);
+    assert_eq!(rel.dead_children(), &[(pid(3), 8)]);
+    assert_eq!(rel.alive_child_count(), 1);
+    assert_eq!(manager.relation(pid(2)).unwrap().parent(), pid(1));
+}
+
+#[test]
+fn test_priority_schedule() {
+    let tid = ThreadId::from_usize;
+    let mut ready: PrioritySchedule<ThreadId> = PrioritySchedule::new();
+    ready.add_prio(tid(1), 1);
+    ready.add(tid(2));
+    ready.add_prio(tid(3), 5);
+    ready.add_prio(tid(4), 1);
+    // 高优先级先出队，同优先级按入队顺序
+    assert_eq!(ready.fetch(), Some(tid(3)));
+    assert_eq!(ready.fetch(), Some(tid(1)));
+    // 重新入队时沿用之前的优先级
+    ready.add(tid(3));
+    assert_eq!(ready.fetch(), Some(tid(3)));
+    assert_eq!(ready.fetch(), Some(tid(4)));
+    assert_eq!(ready.fetch(), Some(tid(2)));
+    assert_eq!(ready.fetch(), None);
+
+    // 已在队列中的 id 修改优先级后立即按新优先级重新排队
+    ready.add_prio(tid(1), 3);
+    ready.add_prio(tid(2), 3);
+    ready.add_prio(tid(3), 1);
+    ready.set_prio(tid(3), 3);
+    ready.set_prio(tid(1), 0);
+    assert_eq!(ready.fetch(), Some(tid(2)));
+    assert_eq!(ready.fetch(), Some(tid(3)));
+    assert_eq!(ready.fetch(), Some(tid(1)));
+    assert_eq!(ready.fetch(), None);
+
+    // 忘记优先级后回到默认优先级
+    ready.forget(tid(2));
+    ready.add(tid(1));
+    ready.add(tid(2));
+    assert_eq!(ready.fetch(), Some(tid(1)));
+    assert_eq!(ready.fetch(), Some(tid(2)));
+
+    // 不支持优先级的策略退化为普通入队
+    let mut fifo: TestScheduler<usize> = TestScheduler::new();
+    fifo.add_prio(1, 0);
+    fifo.add_prio(2, 9);
+    assert_eq!(fifo.fetch(), Some(1));
+}
+
+#[cfg(feature = "thread")]
+#[test]
+fn test_pthread_manager_priority() {
+    let pid = ProcId::from_usize;
+    let tid = ThreadId::from_usize;
+    type Threads = MapScheduler<&'static str, ThreadId, PrioritySchedule<ThreadId>>;
+    let mut manager: PThreadManager<(), &str, Threads, MapScheduler<(), ProcId>> =
+        PThreadManager::new();
+    manager.set_manager(MapScheduler::new());
+    manager.set_proc_manager(MapScheduler::new());
+    manager.add_proc(pid(1), (), pid(0));
+    manager.add_with_priority(tid(1), "low", pid(1), 1);
+    manager.add(tid(2), "default", pid(1));
+    manager.add_with_priority(tid(3), "high", pid(1), 7);
+
+    // 后加入的高优先级线程先被调度，挂起后仍然优先
+    assert_eq!(manager.find_next().copied(), Some("high"));
+    manager.make_current_suspend();
+    assert_eq!(manager.find_next().copied(), Some("high"));
+    manager.make_current_exited(0);
+    assert_eq!(manager.find_next().copied(), Some("low"));
+    manager.make_current_suspend();
+    assert_eq!(manager.find_next().copied(), Some("low"));
+    manager.make_current_exited(0);
+    assert_eq!(manager.find_next().copied(), Some("default"));
+}
+
+#[cfg(feature = "thread")]
+#[test]
+fn test_pthread_manager_set_priority() {
+    let pid = ProcId::from_usize;
+    let tid = ThreadId::from_usize;
+    type Threads = MapScheduler<&'static str, ThreadId, PrioritySchedule<ThreadId>>;
+    let mut manager: PThreadManager<(), &str, Threads, MapScheduler<(), ProcId>> =
+        PThreadManager::new();
+    manager.set_manager(MapScheduler::with_policy(PrioritySchedule::with_default(16)));
+    manager.set_proc_manager(MapScheduler::new());
+    manager.add_proc(pid(1), (), pid(0));
+    manager.add(tid(1), "first", pid(1));
+    manager.add(tid(2), "second", pid(1));
+    manager.add_with_priority(tid(3), "below", pid(1), 1);
+
+    // 未指定优先级的线程使用默认值 16，高于显式的 1
+    assert_eq!(manager.find_next().copied(), Some("first"));
+    // 当前线程降低优先级，挂起后排到同为默认优先级的线程之后
+    manager.set_priority(tid(1), 8);
+    manager.make_current_suspend();
+    assert_eq!(manager.find_next().copied(), Some("second"));
+    manager.make_current_suspend();
+    assert_eq!(manager.find_next().copied(), Some("second"));
+    manager.make_current_exited(0);
+    assert_eq!(manager.find_next().copied(), Some("first"));
+    manager.make_current_exited(0);
+    assert_eq!(manager.find_next().copied(), Some("below"));
+}
+
+#[cfg(feature = "proc")]
+#[test]
+fn test_pmanager_block_and_re_enque() {
+    let pid = ProcId::from_usize;
+    let mut manager: PManager<&str, MapScheduler<&str, ProcId>> = PManager::new();
+    manager.set_manager(MapScheduler::new());
+    manager.add(pid(1), "sleeper", pid(0));
+    manager.add(pid(2), "worker", pid(0));
+
+    assert_eq!(manager.find_next().copied(), Some("sleeper"));
+    manager.make_current_blocked();
+    assert!(manager.current().is_none());
+    // 被阻塞的进程仍在存储中，但不会被调度
+    assert_eq!(manager.get_task(pid(1)).copied(), Some("sleeper"));
+    assert_eq!(manager.find_next().copied(), Some("worker"));
+    manager.make_current_suspend();
+    assert_eq!(manager.find_next().copied(), Some("worker"));
+    manager.make_current_suspend();
+
+    manager.re_enque(pid(1));
+    assert_eq!(manager.find_next().copied(), Some("worker"));
+    manager.make_current_suspend();
+    assert_eq!(manager.find_next().copied(), Some("sleeper"));
+}
+
+#[test]
+fn test_current_slot_set_get_clear() {
+    // 测试 CurrentSlot 的 set / 读取 / clear 循环
+    let slot: CurrentSlot<String> = CurrentSlot::new();
+    assert!(unsafe { slot.space() }.is_none());
+    assert!(slot.pid().is_none());
+    assert!(slot.tid().is_none());
+
+    let mut space = String::from("space-a");
+    unsafe { slot.set(&mut space, ProcId::from_usize(3), ThreadId::from_usize(7)) };
+    assert!(core::ptr::eq(unsafe { slot.space() }.unwrap(), &space));
+    assert_eq!(slot.pid(), Some(ProcId::from_usize(3)));
+    assert_eq!(slot.tid(), Some(ThreadId::from_usize(7)));
+
+    // 再次 set 覆盖之前的记录
+    let mut other = String::from("space-b");
+    unsafe { slot.set(&mut other, ProcId::from_usize(4), ThreadId::from_usize(8)) };
+    assert_eq!(unsafe { slot.space() }.map(String::as_str), Some("space-b"));
+    // 通过 space_mut 的修改作用在记录的地址空间上
+    unsafe { slot.space_mut() }.unwrap().push('!');
+    assert_eq!(unsafe { slot.space() }.map(String::as_str), Some("space-b!"));
+    assert_eq!(slot.pid(), Some(ProcId::from_usize(4)));
+    assert_eq!(slot.tid(), Some(ThreadId::from_usize(8)));
+
+    slot.clear();
+    assert!(unsafe { slot.space() }.is_none());
+    assert!(slot.pid().is_none());
+    assert!(slot.tid().is_none());
+}
+
+#[test]
+fn test_alarm_table_replace_cancel_expire() {
+    // 测试闹钟的设置、替换、取消与到期，以及返回的剩余秒数
+    const FREQ: u64 = 100;
+    let pid = ProcId::from_usize(5);
+    let other = ProcId::from_usize(6);
+    let mut alarms = AlarmTable::new();
+
+    // 没有旧闹钟时返回 0
+    assert_eq!(alarms.replace(pid, Some(300), 0, FREQ), 0);
+    assert_eq!(alarms.deadline(pid), Some(300));
+
+    // 替换返回旧闹钟的剩余秒数，不足 1 秒向上取整
+    assert_eq!(alarms.replace(pid, Some(500), 150, FREQ), 2);
+    assert_eq!(alarms.replace(pid, Some(500), 499, FREQ), 1);
+    assert_eq!(alarms.deadline(pid), Some(500));
+
+    // 取消返回剩余秒数，再次取消返回 0
+    assert_eq!(alarms.replace(pid, None, 200, FREQ), 3);
+    assert_eq!(alarms.deadline(pid), None);
+    assert_eq!(alarms.replace(pid, None, 200, FREQ), 0);
+
+    // 到期只取出不晚于 now 的闹钟，且每个只取一次
+    alarms.replace(pid, Some(100), 0, FREQ);
+    alarms.replace(other, Some(200), 0, FREQ);
+    assert!(alarms.expire(99).is_empty());
+    assert_eq!(alarms.expire(100), vec![pid]);
+    assert!(alarms.expire(150).is_empty());
+    assert_eq!(alarms.expire(u64::MAX), vec![other]);
+
+    // 已过期但尚未取出的旧闹钟剩余 0 秒
+    alarms.replace(pid, Some(100), 0, FREQ);
+    assert_eq!(alarms.replace(pid, None, 120, FREQ), 0);
+
+    // 进程退出时直接删除
+    alarms.replace(pid, Some(100), 0, FREQ);
+    assert!(alarms.cancel(pid));
+    assert!(!alarms.cancel(pid));
+    assert!(alarms.expire(u64::MAX).is_empty());
+}
@@ -0,0 +1,31 @@
+//! ProcId 用尽测试
+//!
+//! 需要把进程全局的分配器推到 `usize::MAX` 附近，为什么单独成一个测试二进制见 `id_reset_tests`。
+//!
+//! ```bash
+//! cargo test -p rcore-task-manage --features test-utils --test id_exhaust_tests
+//! ```
+
+#![cfg(feature = "test-utils")]
+
+use rcore_task_manage::ProcId;
+use std::panic::catch_unwind;
+
+#[test]
+fn test_proc_id_exhaustion_panics() {
+    // 用尽前的最后一个合法值照常返回
+    ProcId::set_counter(usize::MAX - 2);
+    assert_eq!(ProcId::new_nonreserved().get_usize(), usize::MAX - 2);
+    // 下一个值是保留值，不回绕到可能仍在使用的小 ID 而是 panic
+    assert!(catch_unwind(ProcId::new_nonreserved).is_err());
+
+    // new 不检查保留值，但同样不会回绕
+    ProcId::set_counter(usize::MAX - 1);
+    assert_eq!(ProcId::new().get_usize(), usize::MAX - 1);
+    assert!(catch_unwind(ProcId::new).is_err());
+    assert!(catch_unwind(ProcId::alloc).is_err());
+
+    // 用尽后归还的 ID 仍可复用
+    ProcId::free(ProcId::from_usize(7));
+    assert_eq!(ProcId::alloc().get_usize(), 7);
+}
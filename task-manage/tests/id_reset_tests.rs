@@ -0,0 +1,39 @@
+//! ID 分配器重置测试
+//!
+//! ID 分配器是进程全局的，并发运行的其他测试会干扰断言，因此读写分配器状态的测试
+//! （本文件与 `id_exhaust_tests`）各自成一个测试二进制且只包含一个测试。
+//!
+//! ```bash
+//! cargo test -p rcore-task-manage --features test-utils --test id_reset_tests
+//! ```
+
+#![cfg(feature = "test-utils")]
+
+use rcore_task_manage::{CoroId, ProcId, ThreadId};
+
+#[test]
+fn test_reset_counters() {
+    ProcId::new();
+    ProcId::new();
+    ProcId::reset_counters();
+    assert_eq!(ProcId::new().get_usize(), 1);
+    assert_eq!(ProcId::new().get_usize(), 2);
+
+    // new 与 alloc 共用分配器：ID 池同样被清空，alloc 接着 new 的值继续
+    let id = ProcId::alloc();
+    assert_eq!(id.get_usize(), 3);
+    ProcId::free(id);
+    assert_eq!(ProcId::new().get_usize(), 4);
+    ProcId::reset_counters();
+    assert_eq!(ProcId::alloc().get_usize(), 1);
+    assert_eq!(ProcId::new().get_usize(), 2);
+
+    // 各类 ID 的计数器相互独立
+    ThreadId::new();
+    ThreadId::reset_counters();
+    CoroId::new();
+    assert_eq!(ThreadId::new().get_usize(), 1);
+    CoroId::reset_counters();
+    assert_eq!(CoroId::new().get_usize(), 1);
+    assert_eq!(ThreadId::new().get_usize(), 2);
+}
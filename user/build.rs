@@ -5,6 +5,9 @@ fn main() {
     println!("cargo:rerun-if-env-changed=LOG");
     println!("cargo:rerun-if-env-changed=BASE_ADDRESS");
 
+    // pie_simple 链接为位置无关可执行文件，用于验证内核的 `pie` 装载路径
+    println!("cargo:rustc-link-arg-bin=pie_simple=-pie");
+
     if let Some(base) = env::var("BASE_ADDRESS")
         .ok()
         .and_then(|s| s.parse::<u64>().ok())
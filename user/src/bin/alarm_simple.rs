@@ -0,0 +1,51 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use user_lib::{
+    alarm, clock_gettime, sched_yield, sigaction, sigreturn, ClockId, SignalAction, SignalNo,
+    TimeSpec,
+};
+
+static FIRED: AtomicBool = AtomicBool::new(false);
+
+fn on_alarm() {
+    FIRED.store(true, Ordering::SeqCst);
+    sigreturn();
+}
+
+fn now_ms() -> usize {
+    let mut ts = TimeSpec::ZERO;
+    clock_gettime(ClockId::CLOCK_MONOTONIC, &mut ts as *mut TimeSpec);
+    ts.tv_sec * 1000 + ts.tv_nsec / 1_000_000
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let mut action = SignalAction::default();
+    action.handler = on_alarm as usize;
+    let old = SignalAction::default();
+    assert_eq!(sigaction(SignalNo::SIGALRM, &action, &old), 0);
+
+    // 没有闹钟时返回 0；取消时返回剩余秒数
+    assert_eq!(alarm(0), 0);
+    assert_eq!(alarm(5), 0);
+    assert_eq!(alarm(0), 5);
+
+    // 1 秒后收到 SIGALRM
+    let start = now_ms();
+    assert_eq!(alarm(1), 0);
+    while !FIRED.load(Ordering::SeqCst) {
+        assert!(now_ms() - start < 3000, "SIGALRM not delivered");
+        sched_yield();
+    }
+    assert!(now_ms() - start >= 1000);
+    // 闹钟到期后不再挂起
+    assert_eq!(alarm(0), 0);
+
+    println!("alarm_simple passed!");
+    0
+}
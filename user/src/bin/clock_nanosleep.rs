@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+extern crate user_lib;
+
+use user_lib::*;
+
+fn now() -> TimeSpec {
+    let mut ts = TimeSpec::ZERO;
+    clock_gettime(ClockId::CLOCK_MONOTONIC, &mut ts as *mut TimeSpec);
+    ts
+}
+
+fn handler() {
+    // 让信号处理占用一段时间，模拟睡眠前被打断
+    let start = now();
+    while now() < start + TimeSpec::from_millsecond(20) {}
+    sigreturn();
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let mut new = SignalAction::default();
+    let old = SignalAction::default();
+    new.handler = handler as usize;
+    assert!(sigaction(SignalNo::SIGUSR1, &new, &old) >= 0);
+
+    // 绝对截止时间在信号处理之前算好：处理耗时不会推迟唤醒
+    let start = now();
+    let deadline = start + TimeSpec::from_millsecond(50);
+    assert!(kill(getpid(), SignalNo::SIGUSR1) >= 0);
+    assert_eq!(clock_nanosleep(ClockId::CLOCK_MONOTONIC, TIMER_ABSTIME, &deadline), 0);
+    let woke_abs = now();
+    assert!(woke_abs >= deadline);
+
+    // 相对时长从调用时起算：同样的打断会让唤醒时刻整体后移
+    let start = now();
+    assert!(kill(getpid(), SignalNo::SIGUSR1) >= 0);
+    let before_sleep = now();
+    assert_eq!(
+        clock_nanosleep(ClockId::CLOCK_MONOTONIC, 0, &TimeSpec::from_millsecond(50)),
+        0
+    );
+    let woke_rel = now();
+    assert!(woke_rel >= before_sleep + TimeSpec::from_millsecond(50));
+    assert!(woke_rel >= start + TimeSpec::from_millsecond(70));
+    println!("absolute woke at {}, relative woke at {}", woke_abs, woke_rel);
+
+    // 已经过去的绝对时间立即返回
+    assert_eq!(clock_nanosleep(ClockId::CLOCK_MONOTONIC, TIMER_ABSTIME, &start), 0);
+    // 只支持 CLOCK_MONOTONIC
+    assert_eq!(clock_nanosleep(ClockId::CLOCK_REALTIME, 0, &TimeSpec::MILLSECOND), -1);
+
+    println!("clock_nanosleep passed!");
+    0
+}
@@ -0,0 +1,37 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use user_lib::{getpid, sched_yield, thread_create, waittid};
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static STOP: AtomicBool = AtomicBool::new(false);
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn spinner() -> isize {
+    STARTED.store(true, Ordering::SeqCst);
+    // 只做不让出的系统调用；coop 模式下靠预算被强制挂起，主线程才能再次运行
+    while !STOP.load(Ordering::SeqCst) {
+        getpid();
+        CALLS.fetch_add(1, Ordering::SeqCst);
+    }
+    user_lib::exit(0)
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let tid = thread_create(spinner as usize, 0);
+    assert!(tid > 0);
+    while !STARTED.load(Ordering::SeqCst) {
+        sched_yield();
+    }
+    // 能走到这里说明 spinner 已被抢占
+    STOP.store(true, Ordering::SeqCst);
+    assert!(CALLS.load(Ordering::SeqCst) > 0);
+    assert_eq!(waittid(tid as usize), 0);
+    println!("coop_budget passed!");
+    0
+}
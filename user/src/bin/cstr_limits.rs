@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::vec;
+use user_lib::{native, open, OpenFlags, SyscallId};
+
+const ENAMETOOLONG: isize = -36;
+const EFAULT: isize = -14;
+const USER_CSTR_MAX: usize = 4096;
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    // 恰好达到上限：4095 个字符加 '\0' 可以读出，只是文件不存在
+    let mut path = vec![b'a'; USER_CSTR_MAX];
+    path[USER_CSTR_MAX - 1] = 0;
+    let ret = open(core::str::from_utf8(&path).unwrap(), OpenFlags::RDONLY);
+    assert!(ret < 0 && ret != ENAMETOOLONG && ret != EFAULT);
+
+    // 超出一个字节：ENAMETOOLONG
+    let mut path = vec![b'a'; USER_CSTR_MAX + 1];
+    path[USER_CSTR_MAX] = 0;
+    let ret = open(core::str::from_utf8(&path).unwrap(), OpenFlags::RDONLY);
+    assert_eq!(ret, ENAMETOOLONG);
+
+    // 未映射的地址：EFAULT
+    let ret = unsafe { native::syscall2(SyscallId::OPEN, 0, OpenFlags::RDONLY.bits() as usize) };
+    assert_eq!(ret, EFAULT);
+
+    println!("cstr_limits passed!");
+    0
+}
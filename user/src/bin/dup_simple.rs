@@ -0,0 +1,51 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, dup, dup2, open, read, write, OpenFlags};
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let fd = open("dup_tmp\0", OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+    assert_eq!(write(fd as usize, b"hello world"), 11);
+    close(fd as usize);
+
+    // dup 得到的描述符与原描述符共享偏移
+    let fd = open("dup_tmp\0", OpenFlags::RDONLY) as usize;
+    let copy = dup(fd);
+    assert!(copy > 0 && copy as usize != fd);
+    let mut buf = [0u8; 6];
+    assert_eq!(read(fd, &buf), 6);
+    assert_eq!(&buf, b"hello ");
+    assert_eq!(read(copy as usize, &buf[..5]), 5);
+    assert_eq!(&buf[..5], b"world");
+    // 关闭一个不影响另一个
+    close(fd);
+    assert_eq!(read(copy as usize, &buf), 0);
+    close(copy as usize);
+    assert_eq!(dup(fd), -1);
+
+    // dup2 把标准输出重定向到文件，再恢复
+    let out = open("dup_out\0", OpenFlags::CREATE | OpenFlags::WRONLY) as usize;
+    let saved = dup(1) as usize;
+    assert_eq!(dup2(out, 1), 1);
+    print!("redirected");
+    assert_eq!(dup2(saved, 1), 1);
+    close(saved);
+    close(out);
+    let out = open("dup_out\0", OpenFlags::RDONLY) as usize;
+    let mut buf = [0u8; 16];
+    assert_eq!(read(out, &buf), 10);
+    assert_eq!(&buf[..10], b"redirected");
+    close(out);
+    assert_eq!(dup2(out, 5), -1);
+    // 超过描述符上限的目标被拒绝
+    assert_eq!(dup2(1, 1024), -1);
+    assert_eq!(dup2(1, usize::MAX), -1);
+
+    println!("dup_simple passed!");
+    0
+}
@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, getdents, open, Dirent, OpenFlags, DT_REG};
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let fd = open("getdents_tmp\0", OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+    // 普通文件不能列目录
+    let mut buf = [0u8; 2 * Dirent::SIZE];
+    assert_eq!(getdents(fd as usize, &mut buf), -1);
+    close(fd as usize);
+
+    let dir = open("/\0", OpenFlags::RDONLY);
+    assert!(dir > 0);
+    // 放不下一条记录
+    assert_eq!(getdents(dir as usize, &mut buf[..Dirent::SIZE - 1]), -1);
+
+    // 每次至多两条，游标跨调用前进，直到返回 0
+    let mut entries = 0;
+    let mut found = false;
+    loop {
+        let n = getdents(dir as usize, &mut buf);
+        assert!(n >= 0 && n as usize % Dirent::SIZE == 0);
+        if n == 0 {
+            break;
+        }
+        for record in buf[..n as usize].chunks(Dirent::SIZE) {
+            let dirent = unsafe { core::ptr::read_unaligned(record.as_ptr().cast::<Dirent>()) };
+            if dirent.name() == "getdents_tmp" {
+                assert_eq!(dirent.d_type, DT_REG);
+                assert!(!found);
+                found = true;
+            }
+            entries += 1;
+        }
+    }
+    assert!(found);
+    assert!(entries > 2);
+    assert_eq!(getdents(dir as usize, &mut buf), 0);
+    close(dir as usize);
+
+    println!("getdents_simple passed!");
+    0
+}
@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{clock_gettime, gettimeofday, nanosleep, ClockId, TimeSpec, TimeVal};
+
+fn realtime() -> TimeSpec {
+    let mut ts = TimeSpec::ZERO;
+    assert_eq!(
+        clock_gettime(ClockId::CLOCK_REALTIME, &mut ts as *mut TimeSpec),
+        0
+    );
+    ts
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    // QEMU 的 RTC 取宿主机时间，至少晚于 2020 年
+    let start = realtime();
+    assert!(start.tv_sec > 1_577_836_800);
+
+    let mut tv = TimeVal::default();
+    assert_eq!(gettimeofday(&mut tv), 0);
+    assert!(tv.tv_usec < 1_000_000);
+    assert!(tv.tv_sec >= start.tv_sec);
+
+    // 墙上时间与单调时间同步前进
+    assert_eq!(nanosleep(&TimeSpec::from_millsecond(20)), 0);
+    assert!(realtime() >= start + TimeSpec::from_millsecond(20));
+
+    // 不支持的时钟仍然返回 -1
+    let mut ts = TimeSpec::ZERO;
+    assert_eq!(
+        clock_gettime(ClockId::CLOCK_PROCESS_CPUTIME_ID, &mut ts as *mut TimeSpec),
+        -1
+    );
+
+    println!("gettimeofday_simple passed!");
+    0
+}
@@ -0,0 +1,36 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{madvise, MADV_DONTNEED, MADV_NORMAL};
+
+const PAGE_SIZE: usize = 4096;
+
+/// 独占整页的缓冲区，丢弃时不会波及其他数据
+#[repr(C, align(4096))]
+struct Page([u8; PAGE_SIZE]);
+
+static mut BUFFER: Page = Page([0; PAGE_SIZE]);
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let buf = unsafe { &mut *core::ptr::addr_of_mut!(BUFFER) };
+    buf.0.fill(0xab);
+    let addr = buf.0.as_ptr() as usize;
+
+    // 其他建议只是 no-op
+    assert_eq!(madvise(addr, PAGE_SIZE, MADV_NORMAL), 0);
+    assert!(buf.0.iter().all(|b| *b == 0xab));
+
+    assert_eq!(madvise(addr, PAGE_SIZE, MADV_DONTNEED), 0);
+    let buf = unsafe { &*core::ptr::addr_of!(BUFFER) };
+    assert!(buf.0.iter().all(|b| core::hint::black_box(*b) == 0));
+
+    // 未对齐的地址被拒绝
+    assert_eq!(madvise(addr + 1, PAGE_SIZE, MADV_DONTNEED), -1);
+
+    println!("madvise_dontneed passed!");
+    0
+}
@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, mmap, munmap, waitpid, MAP_ANONYMOUS, MAP_PRIVATE, MAP_SHARED, PROT_READ,
+    PROT_WRITE,
+};
+
+const PAGE_SIZE: usize = 4096;
+const ANON: usize = MAP_PRIVATE | MAP_ANONYMOUS;
+const RW: usize = PROT_READ | PROT_WRITE;
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    // 可读写的匿名映射初始为全零
+    let len = 2 * PAGE_SIZE;
+    let addr = mmap(0, len, RW, ANON, -1, 0);
+    assert!(addr > 0 && addr as usize % PAGE_SIZE == 0);
+    let region = unsafe { core::slice::from_raw_parts_mut(addr as *mut u8, len) };
+    assert!(region.iter().all(|&b| b == 0));
+    region[0] = 1;
+    region[len - 1] = 2;
+
+    // 两次映射互不重叠
+    let other = mmap(0, PAGE_SIZE, PROT_READ, ANON, -1, 0);
+    assert!(other > 0);
+    assert!(other as usize + PAGE_SIZE <= addr as usize || addr as usize + len <= other as usize);
+
+    // 只读映射写入会触发缺页，子进程被杀死
+    let pid = fork();
+    if pid == 0 {
+        unsafe { (other as *mut u8).write_volatile(1) };
+        exit(0);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid, &mut exit_code), pid);
+    assert_ne!(exit_code, 0);
+
+    // 只能整段解除，解除后不能再次解除
+    assert_eq!(munmap(addr as usize, PAGE_SIZE), -1);
+    assert_eq!(munmap(addr as usize, len), 0);
+    assert_eq!(munmap(addr as usize, len), -1);
+    assert_eq!(munmap(other as usize, PAGE_SIZE), 0);
+
+    // 长度必须按页对齐且非零，暂不支持共享的匿名映射
+    assert_eq!(mmap(0, 100, RW, ANON, -1, 0), -1);
+    assert_eq!(mmap(0, 0, RW, ANON, -1, 0), -1);
+    let shared = MAP_SHARED | MAP_ANONYMOUS;
+    assert_eq!(mmap(0, PAGE_SIZE, RW, shared, -1, 0), -1);
+
+    println!("mmap_anon passed!");
+    0
+}
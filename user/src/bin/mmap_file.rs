@@ -0,0 +1,63 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    close, mmap, msync, munmap, open, read, write, OpenFlags, MAP_PRIVATE, MAP_SHARED, MS_SYNC,
+    PROT_READ, PROT_WRITE,
+};
+
+const FILE: &str = "mmap_file\0";
+const CONTENT: &[u8] = b"Hello, mmap!";
+
+fn read_file() -> ([u8; 32], usize) {
+    let fd = open(FILE, OpenFlags::RDONLY);
+    assert!(fd > 0);
+    let mut buf = [0u8; 32];
+    let len = read(fd as usize, &mut buf) as usize;
+    close(fd as usize);
+    (buf, len)
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let fd = open(FILE, OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+    write(fd as usize, CONTENT);
+    close(fd as usize);
+
+    // MAP_PRIVATE：拷入文件内容，修改不写回
+    let fd = open(FILE, OpenFlags::RDWR);
+    assert!(fd > 0);
+    let addr = mmap(0, 4096, PROT_READ | PROT_WRITE, MAP_PRIVATE, fd, 0);
+    assert!(addr > 0);
+    let page = unsafe { core::slice::from_raw_parts_mut(addr as *mut u8, 4096) };
+    assert_eq!(&page[..CONTENT.len()], CONTENT);
+    assert_eq!(page[CONTENT.len()], 0);
+    page[0] = b'J';
+    assert_eq!(msync(addr as usize, 4096, MS_SYNC), 0);
+    assert_eq!(munmap(addr as usize, 4096), 0);
+    let (buf, len) = read_file();
+    assert_eq!(&buf[..len], CONTENT);
+
+    // MAP_SHARED：msync 后修改出现在文件里，文件大小不变
+    let addr = mmap(0, 4096, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+    assert!(addr > 0);
+    let page = unsafe { core::slice::from_raw_parts_mut(addr as *mut u8, 4096) };
+    assert_eq!(page[4], b'o');
+    page[0] = b'J';
+    assert_eq!(msync(addr as usize, 4096, MS_SYNC), 0);
+    let (buf, len) = read_file();
+    assert_eq!(len, CONTENT.len());
+    assert_eq!(&buf[..len], b"Jello, mmap!");
+    assert_eq!(munmap(addr as usize, 4096), 0);
+    close(fd as usize);
+
+    // 偏移必须页对齐，不带 MAP_ANONYMOUS 时 fd 必须有效
+    assert_eq!(mmap(0, 4096, PROT_READ, MAP_PRIVATE, fd, 1), -1);
+    assert_eq!(mmap(0, 4096, PROT_READ, MAP_PRIVATE, -1, 0), -1);
+    println!("mmap_file passed!");
+    0
+}
@@ -0,0 +1,40 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{clock_gettime, exit, nanosleep, thread_create, waittid, ClockId, TimeSpec};
+
+fn now() -> TimeSpec {
+    let mut ts = TimeSpec::ZERO;
+    clock_gettime(ClockId::CLOCK_MONOTONIC, &mut ts as *mut TimeSpec);
+    ts
+}
+
+fn sleeper(ms: usize) -> isize {
+    let start = now();
+    assert_eq!(nanosleep(&TimeSpec::from_millsecond(ms)), 0);
+    assert!(now() >= start + TimeSpec::from_millsecond(ms));
+    exit(0)
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let start = now();
+    assert_eq!(nanosleep(&TimeSpec::from_millsecond(50)), 0);
+    assert!(now() >= start + TimeSpec::from_millsecond(50));
+
+    // 零时长立即返回
+    assert_eq!(nanosleep(&TimeSpec::ZERO), 0);
+
+    // 多个线程同时睡眠，各自按自己的时长醒来
+    let long = thread_create(sleeper as usize, 80);
+    let short = thread_create(sleeper as usize, 20);
+    assert!(long > 0 && short > 0);
+    assert_eq!(waittid(short as usize), 0);
+    assert_eq!(waittid(long as usize), 0);
+
+    println!("nanosleep_simple passed!");
+    0
+}
@@ -0,0 +1,20 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+/// 链接为 ET_DYN，需要内核启用 `pie` feature 才能装载
+static TARGET: u64 = 0x5a5a_1234_dead_beef;
+/// 数据中的绝对地址，装载时由 R_RISCV_RELATIVE 重定位修正
+static POINTER: &u64 = &TARGET;
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let ptr = unsafe { core::ptr::read_volatile(&POINTER) };
+    assert_eq!(ptr as *const u64, &TARGET as *const u64);
+    assert_eq!(*ptr, 0x5a5a_1234_dead_beef);
+    println!("pie_simple loaded at {:#x}", main as usize);
+    println!("pie_simple passed!");
+    0
+}
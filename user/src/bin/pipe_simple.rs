@@ -0,0 +1,67 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, exit, fork, pipe, read, waitpid, write};
+
+/// 超过管道缓冲区容量，写端需要等读端取走数据后才能写完
+const TOTAL: usize = 2000;
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let mut fds = [0usize; 2];
+    assert_eq!(pipe(&mut fds), 0);
+    let [read_fd, write_fd] = fds;
+
+    let pid = fork();
+    if pid == 0 {
+        // 子进程：关闭自己的写端，读到 EOF 为止
+        close(write_fd);
+        let mut buf = [0u8; 128];
+        let mut received = 0;
+        loop {
+            let n = read(read_fd, &buf);
+            assert!(n >= 0);
+            if n == 0 {
+                break;
+            }
+            for (i, &byte) in buf[..n as usize].iter().enumerate() {
+                assert_eq!(byte, ((received + i) % 251) as u8);
+            }
+            received += n as usize;
+        }
+        assert_eq!(received, TOTAL);
+        close(read_fd);
+        exit(0);
+    }
+    assert!(pid > 0);
+
+    // 父进程：关闭读端后分块写入，写完关闭写端让子进程看到 EOF
+    close(read_fd);
+    let mut data = [0u8; TOTAL];
+    for (i, byte) in data.iter_mut().enumerate() {
+        *byte = (i % 251) as u8;
+    }
+    let mut sent = 0;
+    while sent < TOTAL {
+        let n = write(write_fd, &data[sent..]);
+        assert!(n > 0);
+        sent += n as usize;
+    }
+    close(write_fd);
+
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    // 读端全部关闭后写入失败
+    assert_eq!(pipe(&mut fds), 0);
+    close(fds[0]);
+    assert_eq!(write(fds[1], b"x"), -1);
+    close(fds[1]);
+
+    println!("pipe_simple passed!");
+    0
+}
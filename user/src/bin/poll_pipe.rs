@@ -0,0 +1,76 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    close, exit, fork, pipe, poll, ppoll, read, sleep, waitpid, write, PollFd, TimeSpec, POLLIN,
+    POLLOUT,
+};
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let mut fds = [0usize; 2];
+    assert_eq!(pipe(&mut fds), 0);
+    let [read_fd, write_fd] = fds;
+
+    // 空管道不可读，写端可写
+    let mut pfds = [
+        PollFd {
+            fd: read_fd as i32,
+            events: POLLIN,
+            revents: 0,
+        },
+        PollFd {
+            fd: write_fd as i32,
+            events: POLLOUT,
+            revents: 0,
+        },
+    ];
+    assert_eq!(ppoll(&mut pfds, Some(&TimeSpec::ZERO)), 1);
+    assert_eq!(pfds[0].revents, 0);
+    assert_eq!(pfds[1].revents, POLLOUT);
+
+    let pid = fork();
+    if pid == 0 {
+        // 子进程：等父进程进入 poll 后再写
+        close(read_fd);
+        sleep(20);
+        assert_eq!(write(write_fd, b"ping"), 4);
+        close(write_fd);
+        exit(0);
+    }
+    assert!(pid > 0);
+
+    // 父进程：不限时等待，直到子进程写入后被唤醒
+    let mut pfds = [PollFd {
+        fd: read_fd as i32,
+        events: POLLIN,
+        revents: 0,
+    }];
+    assert_eq!(poll(&mut pfds, -1), 1);
+    assert_eq!(pfds[0].revents, POLLIN);
+    let mut buf = [0u8; 8];
+    assert_eq!(read(read_fd, &buf), 4);
+    assert_eq!(&buf[..4], b"ping");
+
+    // 管道再次为空且父进程仍持有写端：等到超时
+    pfds[0].revents = 0;
+    assert_eq!(poll(&mut pfds, 10), 0);
+    assert_eq!(pfds[0].revents, 0);
+
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    // 写端全部关闭后读端就绪，read 返回 EOF
+    close(write_fd);
+    assert_eq!(poll(&mut pfds, -1), 1);
+    assert_eq!(pfds[0].revents, POLLIN);
+    assert_eq!(read(read_fd, &buf), 0);
+    close(read_fd);
+
+    println!("poll_pipe passed!");
+    0
+}
@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, open, poll, OpenFlags, PollFd, POLLIN, POLLNVAL, POLLOUT, STDOUT};
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let fd = open("pollfile\0", OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+
+    let mut fds = [
+        PollFd { fd: fd as i32, events: POLLIN | POLLOUT, revents: 0 },
+        PollFd { fd: STDOUT as i32, events: POLLOUT, revents: 0 },
+        PollFd { fd: 99, events: POLLIN, revents: 0 },
+        PollFd { fd: -1, events: POLLIN, revents: 0 },
+    ];
+    // 只写文件：只有 POLLOUT；无效 fd 报 POLLNVAL；负 fd 被忽略
+    assert_eq!(poll(&mut fds, 0), 3);
+    assert_eq!(fds[0].revents, POLLOUT);
+    assert_eq!(fds[1].revents, POLLOUT);
+    assert_eq!(fds[2].revents, POLLNVAL);
+    assert_eq!(fds[3].revents, 0);
+
+    // stdout 永远不可读：等到超时返回 0
+    let mut fds = [PollFd { fd: STDOUT as i32, events: POLLIN, revents: 0 }];
+    assert_eq!(poll(&mut fds, 10), 0);
+    assert_eq!(fds[0].revents, 0);
+
+    close(fd as usize);
+    println!("poll_simple passed!");
+    0
+}
@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{prctl, PR_GET_NAME, PR_SET_NAME, TASK_COMM_LEN};
+
+fn get_name() -> [u8; TASK_COMM_LEN] {
+    let mut buf = [0xffu8; TASK_COMM_LEN];
+    assert_eq!(prctl(PR_GET_NAME, buf.as_mut_ptr() as usize), 0);
+    buf
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    // 默认线程名是程序名
+    assert!(get_name().starts_with(b"prctl_name\0"));
+
+    assert_eq!(prctl(PR_SET_NAME, b"worker\0".as_ptr() as usize), 0);
+    assert!(get_name().starts_with(b"worker\0"));
+
+    // 超过 15 字节的名字被截断
+    let long = b"a-very-long-thread-name\0";
+    assert_eq!(prctl(PR_SET_NAME, long.as_ptr() as usize), 0);
+    let name = get_name();
+    assert_eq!(&name[..15], &long[..15]);
+    assert_eq!(name[15], 0);
+
+    assert_eq!(prctl(0xdead, 0), -1);
+    println!("prctl_name passed!");
+    0
+}
@@ -0,0 +1,24 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_priority, set_priority};
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    // 新线程取内核的默认优先级
+    assert_eq!(get_priority(), 16);
+    for prio in [1, 255, 100] {
+        assert_eq!(set_priority(prio), 0);
+        assert_eq!(get_priority(), prio);
+    }
+    // 越界的优先级被拒绝，原值不变
+    for prio in [0, 256, -1] {
+        assert_eq!(set_priority(prio), -1);
+        assert_eq!(get_priority(), 100);
+    }
+    println!("priority_simple passed!");
+    0
+}
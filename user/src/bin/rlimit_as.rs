@@ -0,0 +1,27 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, setrlimit, thread_create, waittid, RLIMIT_AS, RLIM_INFINITY};
+
+fn child(_arg: usize) -> isize {
+    exit(7)
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    // 限额压到一页以下：任何新的映射都必须失败，而内核继续运行
+    assert_eq!(setrlimit(RLIMIT_AS, 4096), 0);
+    assert_eq!(thread_create(child as usize, 0), -1);
+
+    // 解除限制后恢复正常
+    assert_eq!(setrlimit(RLIMIT_AS, RLIM_INFINITY), 0);
+    let tid = thread_create(child as usize, 0);
+    assert!(tid > 0);
+    assert_eq!(waittid(tid as usize), 7);
+
+    println!("rlimit_as passed!");
+    0
+}
@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{brk, sbrk};
+
+const PAGE_SIZE: usize = 4096;
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let base = brk(0);
+    assert!(base > 0);
+    assert_eq!(sbrk(0), base);
+
+    // 扩大三页多一点，新的堆空间可读写且为全零
+    let len = 3 * PAGE_SIZE + 100;
+    assert_eq!(sbrk(len as isize), base);
+    assert_eq!(brk(0), base + len as isize);
+    let heap = unsafe { core::slice::from_raw_parts_mut(base as usize as *mut u8, len) };
+    assert!(heap.iter().all(|&b| b == 0));
+    for (i, byte) in heap.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+
+    // 收缩后再扩大，释放过的页重新映射为全零
+    assert_eq!(sbrk(-(2 * PAGE_SIZE as isize)), base + len as isize);
+    assert_eq!(brk(0), base + (len - 2 * PAGE_SIZE) as isize);
+    assert_eq!(heap[PAGE_SIZE - 1], (PAGE_SIZE - 1) as u8);
+    assert_eq!(brk(base as usize + len), base + len as isize);
+    let top_page = &heap[3 * PAGE_SIZE..];
+    assert!(top_page.iter().all(|&b| b == 0));
+
+    // 不能收缩到堆起点以下
+    assert_eq!(brk(base as usize - 1), -1);
+    assert_eq!(sbrk(-(len as isize) - 1), -1);
+
+    println!("sbrk_simple passed!");
+    0
+}
@@ -0,0 +1,67 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, getpgid, getpid, getsid, kill, setpgid, setsid, sigprocmask, sigwaitinfo, waitpid,
+    SigInfo, SignalNo, SIG_BLOCK,
+};
+
+const SIGHUP_SET: usize = 1 << SignalNo::SIGHUP as usize;
+const SIGUSR1_SET: usize = 1 << SignalNo::SIGUSR1 as usize;
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    // 屏蔽两个信号，子孙进程继承屏蔽字后用 sigwaitinfo 同步取走
+    assert_eq!(
+        sigprocmask(SIG_BLOCK, SIGHUP_SET | SIGUSR1_SET, core::ptr::null_mut()),
+        0
+    );
+    let main_pid = getpid();
+    let sid = getsid(0);
+    assert!(sid >= 0);
+
+    let leader = fork();
+    if leader == 0 {
+        // 新会话首进程
+        let pid = getpid();
+        assert_eq!(setsid(), pid);
+        assert_eq!(getsid(0), pid);
+        assert_eq!(getpgid(0), pid);
+        // 已是进程组组长，不能再建会话
+        assert_eq!(setsid(), -1);
+
+        let member = fork();
+        if member == 0 {
+            // 首进程退出后本进程组成为孤儿，应收到 SIGHUP
+            let mut info = SigInfo::default();
+            let signum = sigwaitinfo(SIGHUP_SET, &mut info);
+            if signum == SignalNo::SIGHUP as isize {
+                kill(main_pid, SignalNo::SIGUSR1);
+            }
+            exit(0);
+        }
+        assert!(member > 0);
+        // 子进程另建一组，仍在同一会话
+        assert_eq!(setpgid(member as usize, member as usize), 0);
+        assert_eq!(getpgid(member as usize), member);
+        assert_eq!(getsid(member as usize), pid);
+        exit(0);
+    }
+    assert!(leader > 0);
+    // 子进程建立新会话不影响父进程
+    assert_eq!(getsid(0), sid);
+
+    let mut exit_code = -1;
+    assert_eq!(waitpid(leader, &mut exit_code), leader);
+    assert_eq!(exit_code, 0);
+    let mut info = SigInfo::default();
+    assert_eq!(
+        sigwaitinfo(SIGUSR1_SET, &mut info),
+        SignalNo::SIGUSR1 as isize
+    );
+    println!("setsid_hangup passed!");
+    0
+}
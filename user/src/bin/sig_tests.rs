@@ -84,7 +84,7 @@ fn user_sig_test_restore() {
 }
 
 fn kernel_sig_test_ignore() {
-    sigprocmask(1 << SignalNo::SIGSTOP as usize);
+    sigprocmask(SIG_SETMASK, 1 << SignalNo::SIGSTOP as usize, core::ptr::null_mut());
     if kill(getpid(), SignalNo::SIGSTOP) < 0 {
         println!("kill faild\n");
         exit(-1);
@@ -0,0 +1,59 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{
+    getpid, kill, sigaction, sigpending, sigprocmask, sigreturn, SignalAction, SignalNo, SIG_BLOCK,
+    SIG_UNBLOCK,
+};
+
+const SIGUSR1_SET: usize = 1 << SignalNo::SIGUSR1 as usize;
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+fn on_signal(signum: usize) {
+    HANDLED.fetch_or(1 << signum, Ordering::SeqCst);
+    sigreturn();
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let mut action = SignalAction::default();
+    action.handler = on_signal as usize;
+    let old = SignalAction::default();
+    assert_eq!(sigaction(SignalNo::SIGUSR1, &action, &old), 0);
+    assert_eq!(sigaction(SignalNo::SIGUSR2, &action, &old), 0);
+
+    let mut set = usize::MAX;
+    assert_eq!(sigpending(&mut set), 0);
+    assert_eq!(set, 0);
+
+    // 被屏蔽的 SIGUSR1 留在待处理集合中，未屏蔽的 SIGUSR2 直接处理
+    assert_eq!(
+        sigprocmask(SIG_BLOCK, SIGUSR1_SET, core::ptr::null_mut()),
+        0
+    );
+    assert_eq!(kill(getpid(), SignalNo::SIGUSR1), 0);
+    assert_eq!(kill(getpid(), SignalNo::SIGUSR2), 0);
+    assert_eq!(sigpending(&mut set), 0);
+    assert_eq!(set, SIGUSR1_SET);
+    assert_eq!(
+        HANDLED.load(Ordering::SeqCst),
+        1 << SignalNo::SIGUSR2 as usize
+    );
+
+    // 解除屏蔽后 SIGUSR1 被处理，不再待处理
+    assert_eq!(
+        sigprocmask(SIG_UNBLOCK, SIGUSR1_SET, core::ptr::null_mut()),
+        0
+    );
+    assert_eq!(sigpending(&mut set), 0);
+    assert_eq!(set, 0);
+    assert_ne!(HANDLED.load(Ordering::SeqCst) & SIGUSR1_SET, 0);
+
+    println!("sigpending_simple passed!");
+    0
+}
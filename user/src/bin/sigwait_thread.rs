@@ -0,0 +1,76 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, getpid, kill, sched_yield, sigprocmask, sigtimedwait, sigwaitinfo, thread_create,
+    waittid, SigInfo, SignalNo, TimeSpec, SIG_BLOCK,
+};
+
+const SIGUSR1_SET: usize = 1 << SignalNo::SIGUSR1 as usize;
+
+fn sender(pid: usize) -> isize {
+    // 让主线程先进入 sigwaitinfo 再发送
+    for _ in 0..10 {
+        sched_yield();
+    }
+    assert_eq!(kill(pid as isize, SignalNo::SIGUSR1), 0);
+    exit(0)
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    // 屏蔽 SIGUSR1，避免它在 sigwaitinfo 之前被默认动作处理掉
+    assert_eq!(
+        sigprocmask(SIG_BLOCK, SIGUSR1_SET, core::ptr::null_mut()),
+        0
+    );
+
+    // 已经待处理的信号立即返回
+    assert_eq!(kill(getpid(), SignalNo::SIGUSR1), 0);
+    let mut info = SigInfo::default();
+    assert_eq!(
+        sigwaitinfo(SIGUSR1_SET, &mut info),
+        SignalNo::SIGUSR1 as isize
+    );
+    assert_eq!(info.si_signo, SignalNo::SIGUSR1 as i32);
+
+    // 阻塞到另一个线程发来 SIGUSR1
+    let tid = thread_create(sender as usize, getpid() as usize);
+    assert!(tid > 0);
+    let mut info = SigInfo::default();
+    assert_eq!(
+        sigwaitinfo(SIGUSR1_SET, &mut info),
+        SignalNo::SIGUSR1 as isize
+    );
+    assert_eq!(info.si_signo, SignalNo::SIGUSR1 as i32);
+    assert_eq!(waittid(tid as usize), 0);
+
+    // 没有信号到达时超时返回 -1，超时为 0 时只检查一次
+    let timeout = TimeSpec::from_millsecond(10);
+    assert_eq!(sigtimedwait(SIGUSR1_SET, &mut info, Some(&timeout)), -1);
+    assert_eq!(
+        sigtimedwait(SIGUSR1_SET, &mut info, Some(&TimeSpec::ZERO)),
+        -1
+    );
+
+    // 超时前到达的信号照常取出
+    let tid = thread_create(sender as usize, getpid() as usize);
+    assert!(tid > 0);
+    let timeout = TimeSpec::from_millsecond(5000);
+    assert_eq!(
+        sigtimedwait(SIGUSR1_SET, &mut info, Some(&timeout)),
+        SignalNo::SIGUSR1 as isize
+    );
+    assert_eq!(waittid(tid as usize), 0);
+
+    // SIGKILL 不能被等待
+    assert_eq!(
+        sigwaitinfo(1 << SignalNo::SIGKILL as usize, core::ptr::null_mut()),
+        -1
+    );
+    println!("sigwait_thread passed!");
+    0
+}
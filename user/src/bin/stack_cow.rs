@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, waitpid};
+
+static mut VALUE: usize = 1;
+
+fn value() -> usize {
+    unsafe { core::ptr::read_volatile(core::ptr::addr_of!(VALUE)) }
+}
+
+/// 远超初始栈大小的局部数组，访问时栈按需向下增长
+#[inline(never)]
+fn big_frame() -> usize {
+    let mut buf = [0u8; 32 * 1024];
+    for (i, b) in buf.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    core::hint::black_box(&buf)
+        .iter()
+        .map(|&b| b as usize)
+        .sum()
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let expected: usize = (0..32 * 1024).map(|i| (i as u8) as usize).sum();
+    assert_eq!(big_frame(), expected);
+
+    // fork 后父子写时复制共享页面，子进程的写入对父进程不可见
+    let pid = fork();
+    if pid == 0 {
+        unsafe { VALUE = 2 };
+        assert_eq!(value(), 2);
+        assert_eq!(big_frame(), expected);
+        exit(0);
+    }
+    assert!(pid > 0);
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(value(), 1);
+
+    println!("stack_cow passed!");
+    0
+}
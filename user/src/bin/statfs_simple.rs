@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, open, statfs, write, FsStat, OpenFlags};
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    // 先建立空文件（已存在时被截断），之后只有写入会消耗数据块
+    let fd = open("statfs_tmp\0", OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd > 0);
+    let mut before = FsStat::default();
+    assert_eq!(statfs("/", &mut before), 0);
+    assert!(before.free_blocks <= before.total_blocks);
+    assert!(before.free_inodes < before.total_inodes);
+
+    // 写入两个块的数据，数据块应至少减少两个
+    let data = [0x5au8; 1024];
+    assert_eq!(write(fd as usize, &data), data.len() as isize);
+    close(fd as usize);
+
+    let mut after = FsStat::default();
+    assert_eq!(statfs("statfs_tmp", &mut after), 0);
+    assert_eq!(after.total_blocks, before.total_blocks);
+    assert_eq!(after.free_inodes, before.free_inodes);
+    assert!(after.free_blocks + 2 <= before.free_blocks);
+
+    // 不存在的路径
+    assert_eq!(statfs("statfs_missing", &mut after), -1);
+
+    println!("statfs_simple passed!");
+    0
+}
@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    condvar_create, condvar_wait_timeout, exit, mutex_create, mutex_lock, mutex_unlock, nanosleep,
+    semaphore_create, semaphore_down_timeout, semaphore_up, thread_create, waittid, TimeSpec,
+};
+
+const CONDVAR_ID: usize = 0;
+const MUTEX_ID: usize = 0;
+
+/// 拿到主线程等待时释放的锁后一直持有到主线程超时之后
+fn holder() -> isize {
+    mutex_lock(MUTEX_ID);
+    nanosleep(&TimeSpec::from_millsecond(50));
+    mutex_unlock(MUTEX_ID);
+    exit(0)
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let short = TimeSpec::from_millsecond(10);
+
+    // 没有资源时超时返回 -1，有资源时立即成功
+    let sem_id = semaphore_create(0) as usize;
+    assert_eq!(semaphore_down_timeout(sem_id, &short), -1);
+    assert_eq!(semaphore_up(sem_id), 0);
+    assert_eq!(semaphore_down_timeout(sem_id, &short), 0);
+
+    // 无人 signal 时超时返回 -1，并已重新持有互斥锁
+    assert_eq!(condvar_create() as usize, CONDVAR_ID);
+    assert_eq!(mutex_create(true) as usize, MUTEX_ID);
+    assert_eq!(mutex_lock(MUTEX_ID), 0);
+    assert_eq!(condvar_wait_timeout(CONDVAR_ID, MUTEX_ID, &short), -1);
+
+    // 超时时锁被其他线程持有：等到锁后仍然返回 -1
+    let tid = thread_create(holder as usize, 0);
+    assert_eq!(condvar_wait_timeout(CONDVAR_ID, MUTEX_ID, &short), -1);
+    assert_eq!(mutex_unlock(MUTEX_ID), 0);
+    waittid(tid as usize);
+
+    println!("sync_timeout passed!");
+    0
+}
@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    mutex_create, mutex_lock, mutex_trylock, mutex_unlock, semaphore_create, semaphore_down,
+    semaphore_trydown, semaphore_up,
+};
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let mutex_id = mutex_create(true) as usize;
+    assert_eq!(mutex_trylock(mutex_id), 0);
+    // 已被持有时立即失败，不会阻塞
+    assert_eq!(mutex_trylock(mutex_id), -1);
+    assert_eq!(mutex_unlock(mutex_id), 0);
+    assert_eq!(mutex_lock(mutex_id), 0);
+    assert_eq!(mutex_trylock(mutex_id), -1);
+    assert_eq!(mutex_unlock(mutex_id), 0);
+
+    let sem_id = semaphore_create(1) as usize;
+    assert_eq!(semaphore_trydown(sem_id), 0);
+    assert_eq!(semaphore_trydown(sem_id), -1);
+    assert_eq!(semaphore_up(sem_id), 0);
+    assert_eq!(semaphore_down(sem_id), 0);
+    assert_eq!(semaphore_trydown(sem_id), -1);
+    assert_eq!(semaphore_up(sem_id), 0);
+
+    println!("sync_trylock passed!");
+    0
+}
@@ -0,0 +1,79 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use user_lib::*;
+
+const WORKERS: usize = 2;
+
+static TIDS: [AtomicUsize; WORKERS] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+static HITS: [AtomicUsize; WORKERS] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+static STOP: AtomicBool = AtomicBool::new(false);
+
+fn on_usr1() {
+    // 记录信号落在了哪个线程上
+    let tid = gettid() as usize;
+    for (slot_tid, hits) in TIDS.iter().zip(&HITS) {
+        if slot_tid.load(Ordering::SeqCst) == tid {
+            hits.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+    sigreturn();
+}
+
+fn worker(slot: usize) -> isize {
+    TIDS[slot].store(gettid() as usize, Ordering::SeqCst);
+    while !STOP.load(Ordering::SeqCst) {
+        sched_yield();
+    }
+    exit(0)
+}
+
+fn wait_hits(slot: usize, expected: usize) {
+    while HITS[slot].load(Ordering::SeqCst) < expected {
+        sched_yield();
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let mut action = SignalAction::default();
+    action.handler = on_usr1 as usize;
+    let old = SignalAction::default();
+    assert_eq!(sigaction(SignalNo::SIGUSR1, &action, &old), 0);
+
+    let mut tids = [0isize; WORKERS];
+    for (slot, tid) in tids.iter_mut().enumerate() {
+        *tid = thread_create(worker as usize, slot);
+        assert!(*tid > 0);
+    }
+    for slot_tid in &TIDS {
+        while slot_tid.load(Ordering::SeqCst) == 0 {
+            sched_yield();
+        }
+    }
+
+    let pid = getpid();
+    // 只有被点名的线程执行处理函数
+    assert_eq!(tgkill(pid, tids[1], SignalNo::SIGUSR1), 0);
+    wait_hits(1, 1);
+    assert_eq!(HITS[0].load(Ordering::SeqCst), 0);
+
+    assert_eq!(tgkill(pid, tids[0], SignalNo::SIGUSR1), 0);
+    wait_hits(0, 1);
+    assert_eq!(HITS[1].load(Ordering::SeqCst), 1);
+
+    // tid 必须属于 pid
+    assert_eq!(tgkill(pid + 1000, tids[0], SignalNo::SIGUSR1), -1);
+    assert_eq!(tgkill(pid, 100000, SignalNo::SIGUSR1), -1);
+
+    STOP.store(true, Ordering::SeqCst);
+    for tid in tids {
+        assert_eq!(waittid(tid as usize), 0);
+    }
+    println!("tgkill_simple passed!");
+    0
+}
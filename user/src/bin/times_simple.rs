@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{times, Tms};
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    let mut before = Tms::default();
+    assert_eq!(times(&mut before), 0);
+
+    // 纯用户态计算，不发起系统调用
+    let mut acc = 0usize;
+    for i in 0..5_000_000usize {
+        acc = core::hint::black_box(acc.wrapping_mul(31).wrapping_add(i));
+    }
+
+    let mut after = Tms::default();
+    assert_eq!(times(&mut after), 0);
+    assert!(after.tms_utime > before.tms_utime);
+    assert!(after.tms_stime >= before.tms_stime);
+    println!(
+        "acc = {}, utime {} -> {}",
+        acc, before.tms_utime, after.tms_utime
+    );
+    println!("times_simple passed!");
+    0
+}
@@ -0,0 +1,65 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::arch::{asm, global_asm};
+use user_lib::{exit, thread_create_with_tls, waittid};
+
+// stable 工具链不支持 `#[thread_local]`，用汇编在 .tdata 中声明一个线程局部变量，
+// 链接器会据此生成 PT_TLS 段
+global_asm!(
+    ".section .tdata,\"awT\",@progbits",
+    ".balign 8",
+    ".globl TLS_COUNTER",
+    "TLS_COUNTER:",
+    ".dword 0x5a5a",
+    ".section .text",
+);
+
+/// 通过 local-exec 模型读取当前线程的 TLS_COUNTER
+fn tls_counter() -> usize {
+    let value: usize;
+    unsafe {
+        asm!(
+            "lui {tmp}, %tprel_hi(TLS_COUNTER)",
+            "add {tmp}, {tmp}, tp, %tprel_add(TLS_COUNTER)",
+            "ld {value}, %tprel_lo(TLS_COUNTER)({tmp})",
+            tmp = out(reg) _,
+            value = out(reg) value,
+        );
+    }
+    value
+}
+
+fn read_tp() -> usize {
+    let tp: usize;
+    unsafe { asm!("mv {}, tp", out(reg) tp) };
+    tp
+}
+
+fn child(expected_tp: usize) -> isize {
+    // 新线程的 tp 由 thread_create_with_tls 设置
+    if read_tp() != expected_tp {
+        exit(-1);
+    }
+    // 子线程的 TLS 块是 [0x77, ...]，TLS_COUNTER 位于块起始
+    exit(tls_counter() as i32)
+}
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    // 主线程的 tp 由加载器按 PT_TLS 初始化
+    assert_ne!(read_tp(), 0);
+    assert_eq!(tls_counter(), 0x5a5a);
+
+    let block: [usize; 2] = [0x77, 0];
+    let tls = block.as_ptr() as usize;
+    let tid = thread_create_with_tls(child as usize, tls, tls);
+    assert!(tid > 0);
+    assert_eq!(waittid(tid as usize), 0x77);
+
+    println!("tls_simple passed!");
+    0
+}
@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, getgid, getuid, setgid, setuid, waitpid};
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    assert_eq!(setuid(1000), 0);
+    assert_eq!(setgid(100), 0);
+    assert_eq!(getuid(), 1000);
+    assert_eq!(getgid(), 100);
+
+    let pid = fork();
+    if pid == 0 {
+        // 子进程继承父进程的 uid/gid，之后的修改只影响自己
+        if getuid() != 1000 || getgid() != 100 {
+            exit(1);
+        }
+        setuid(2000);
+        exit(if getuid() == 2000 { 0 } else { 2 });
+    }
+    assert!(pid > 0);
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(getuid(), 1000);
+    println!("uid_simple passed!");
+    0
+}
@@ -33,7 +33,7 @@ pub extern "C" fn main() -> i32 {
                         let mut cmd = String::with_capacity(line.len() + 1);
                         cmd.push_str(line.as_str());
                         cmd.push('\0');
-                        if exec(cmd.as_str()) == -1 {
+                        if exec(cmd.as_str()) < 0 {
                             println!("Error when executing!");
                             return -4;
                         }
@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use user_lib::{exec, exit, vfork, waitpid};
+
+/// 子进程写入的标记：父进程能看到说明两者共享同一份页面，没有复制
+static MARK: AtomicUsize = AtomicUsize::new(0);
+
+#[no_mangle]
+pub extern "C" fn main() -> i32 {
+    // vfork 后立即 exit：父进程恢复时 exit 已经发生
+    let pid = vfork();
+    if pid == 0 {
+        MARK.store(1, Ordering::SeqCst);
+        exit(7);
+    }
+    assert!(pid > 0);
+    assert_eq!(MARK.load(Ordering::SeqCst), 1);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid, &mut exit_code), pid);
+    assert_eq!(exit_code, 7);
+
+    // vfork 后 exec：子进程换上新地址空间后父进程才继续
+    let pid = vfork();
+    if pid == 0 {
+        MARK.store(2, Ordering::SeqCst);
+        exec("00hello_world");
+        exit(-1);
+    }
+    assert!(pid > 0);
+    assert_eq!(MARK.load(Ordering::SeqCst), 2);
+    assert_eq!(waitpid(pid, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    println!("vfork_exec passed!");
+    0
+}
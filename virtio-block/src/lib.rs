@@ -0,0 +1,127 @@
+//! virtio-block: 各章节内核共用的 VirtIO 块设备封装
+//!
+//! 提供 virtio-drivers 所需的 [`Hal`] 实现和实现了 easy-fs [`BlockDevice`] 的 [`VirtIOBlock`]。
+//! DMA 缓冲区的虚拟地址到物理地址翻译由内核通过 [`init`] 注入，本 crate 不访问任何内核全局状态。
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::alloc::{alloc_zeroed, dealloc};
+use core::alloc::Layout;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use easy_fs::BlockDevice;
+use spin::{Mutex, Once};
+use virtio_drivers::{Hal, VirtIOBlk, VirtIOHeader};
+
+const PAGE_SIZE: usize = 4096;
+
+/// MMIO 寄存器区域中设备配置空间的偏移，virtio-blk 配置空间的首个字段是以 512 字节扇区计的容量
+const CONFIG_SPACE_OFFSET: usize = 0x100;
+
+/// 内核注入的地址翻译函数：虚拟地址未映射时返回 0
+static VIRT_TO_PHYS: Once<fn(usize) -> usize> = Once::new();
+
+/// 已分配且尚未释放的 DMA 页数
+static DMA_PAGES: AtomicUsize = AtomicUsize::new(0);
+
+/// 注册虚拟地址到物理地址的翻译函数，须在创建 [`VirtIOBlock`] 之前调用。
+///
+/// 重复调用时保留第一次注册的函数。
+pub fn init(virt_to_phys: fn(usize) -> usize) {
+    VIRT_TO_PHYS.call_once(|| virt_to_phys);
+}
+
+/// 当前尚未释放的 DMA 页数，用于检查分配与释放是否配对
+pub fn dma_pages_in_use() -> usize {
+    DMA_PAGES.load(Ordering::Relaxed)
+}
+
+fn dma_layout(pages: usize) -> Layout {
+    Layout::from_size_align(pages * PAGE_SIZE, PAGE_SIZE).unwrap()
+}
+
+/// virtio-drivers 的硬件抽象层，DMA 内存取自内核堆
+pub struct VirtioHal;
+
+impl Hal for VirtioHal {
+    fn dma_alloc(pages: usize) -> usize {
+        let layout = dma_layout(pages);
+        let ptr = unsafe { alloc_zeroed(layout) };
+        if ptr.is_null() {
+            return 0;
+        }
+        let paddr = Self::virt_to_phys(ptr as usize);
+        if paddr == 0 {
+            unsafe { dealloc(ptr, layout) };
+            return 0;
+        }
+        DMA_PAGES.fetch_add(pages, Ordering::Relaxed);
+        paddr
+    }
+
+    fn dma_dealloc(paddr: usize, pages: usize) -> i32 {
+        let vaddr = Self::phys_to_virt(paddr);
+        if vaddr == 0 {
+            return -1;
+        }
+        unsafe { dealloc(vaddr as *mut u8, dma_layout(pages)) };
+        DMA_PAGES.fetch_sub(pages, Ordering::Relaxed);
+        0
+    }
+
+    /// 内核对物理内存恒等映射
+    fn phys_to_virt(paddr: usize) -> usize {
+        paddr
+    }
+
+    /// 委托给 [`init`] 注册的翻译函数，未注册时返回 0
+    fn virt_to_phys(vaddr: usize) -> usize {
+        VIRT_TO_PHYS.get().map_or(0, |translate| translate(vaddr))
+    }
+}
+
+/// VirtIO 块设备
+pub struct VirtIOBlock {
+    blk: Mutex<VirtIOBlk<'static, VirtioHal>>,
+    /// 设备容量（块数），扇区大小与 [`easy_fs::BLOCK_SZ`] 相同
+    capacity: usize,
+}
+
+impl VirtIOBlock {
+    /// 在 MMIO 地址 `base` 上初始化块设备，失败时 panic。
+    ///
+    /// # Safety
+    ///
+    /// `base` 必须是内核可访问的 VirtIO MMIO 寄存器区域，且不被其他驱动使用。
+    pub unsafe fn new(base: usize) -> Self {
+        let capacity =
+            core::ptr::read_volatile((base + CONFIG_SPACE_OFFSET) as *const u64) as usize;
+        let header = &mut *(base as *mut VirtIOHeader);
+        let blk = VirtIOBlk::<VirtioHal>::new(header).expect("failed to init virtio-blk");
+        Self {
+            blk: Mutex::new(blk),
+            capacity,
+        }
+    }
+}
+
+impl BlockDevice for VirtIOBlock {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+        self.blk
+            .lock()
+            .read_block(block_id, buf)
+            .expect("virtio read block failed");
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) {
+        self.blk
+            .lock()
+            .write_block(block_id, buf)
+            .expect("virtio write block failed");
+    }
+
+    fn num_blocks(&self) -> Option<usize> {
+        Some(self.capacity)
+    }
+}
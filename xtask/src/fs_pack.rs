@@ -21,6 +21,11 @@ impl BlockDevice for BlockFile {
             .expect("Error when seeking!");
         assert_eq!(file.write(buf).unwrap(), BLOCK_SZ, "Not a complete block!");
     }
+
+    fn num_blocks(&self) -> Option<usize> {
+        let file = self.0.lock().unwrap();
+        Some(file.metadata().ok()?.len() as usize / BLOCK_SZ)
+    }
 }
 
 pub fn easy_fs_pack(cases: &Vec<String>, target: &str) -> std::io::Result<()> {
@@ -72,7 +72,8 @@ impl BuildArgs {
         let package = match self.ch {
             1 => if self.lab { "ch1-lab" } else { "ch1" }.to_string(),
             2..=8 => {
-                user::build_for(self.ch, false);
+                let features = self.features.as_deref().unwrap_or_default();
+                user::build_for(self.ch, false, features.split_whitespace());
                 env.insert(
                     "APP_ASM",
                     TARGET
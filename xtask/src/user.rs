@@ -8,6 +8,9 @@ struct Cases {
     base: Option<u64>,
     step: Option<u64>,
     pub cases: Option<Vec<String>>,
+    /// 只在内核启用对应 feature 时才打包的用例
+    #[serde(default)]
+    feature_cases: HashMap<String, Vec<String>>,
 }
 
 pub struct CasesInfo {
@@ -69,12 +72,17 @@ fn build_one(name: impl AsRef<OsStr>, release: bool, base_address: u64) -> PathB
     }
 }
 
-pub fn build_for(ch: u8, release: bool) {
+pub fn build_for<'a>(ch: u8, release: bool, features: impl IntoIterator<Item = &'a str>) {
     let cfg = std::fs::read_to_string(PROJECT.join("user/cases.toml")).unwrap();
     let mut cases = toml::from_str::<HashMap<String, Cases>>(&cfg)
         .unwrap()
         .remove(&format!("ch{ch}"))
         .unwrap_or_default();
+    for feature in features {
+        if let Some(extra) = cases.feature_cases.remove(feature) {
+            cases.cases.get_or_insert_with(Vec::new).extend(extra);
+        }
+    }
     let CasesInfo { base, step, bins } = cases.build(release);
     if bins.is_empty() {
         return;